@@ -0,0 +1,340 @@
+use crate::elf::Elf;
+use crate::program_header::phdr_enums::PType;
+use crate::section_header::shdr_enums::{SType, SHF_ALLOC, SHF_TLS};
+
+// A part of the file and the addresses it is loaded at, `index` being
+// the program or section header it comes from
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+struct Range {
+    index: usize,
+    vaddr: u64,
+    memsz: u64,
+    offset: u64,
+    // bytes stored in the file, the rest of memsz is zero filled
+    filesz: u64,
+    // SHF_ALLOC for sections, vaddr means nothing otherwise
+    loaded: bool,
+}
+
+impl Range {
+    fn has_vaddr(&self, vaddr: u64) -> bool {
+        self.loaded && vaddr >= self.vaddr && vaddr - self.vaddr < self.memsz
+    }
+
+    fn has_offset(&self, offset: u64) -> bool {
+        offset >= self.offset && offset - self.offset < self.filesz
+    }
+
+    // a range that would end past the last address or file offset is
+    // corrupt and taken as not mapped
+    fn fits(&self) -> bool {
+        self.vaddr.checked_add(self.memsz).is_some()
+            && self.offset.checked_add(self.filesz).is_some()
+    }
+}
+
+// What holds an address or a file offset
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Location {
+    // None for file offsets in sections that are not loaded
+    pub vaddr: Option<u64>,
+    // None for zero filled memory
+    pub offset: Option<u64>,
+    // index into elf.pheaders of the PT_LOAD segment
+    pub segment: Option<usize>,
+    // index into elf.sheaders of the section
+    pub section: Option<usize>,
+    // past the file part of the segment or in a SHT_NOBITS section,
+    // memory the loader fills with zeros
+    pub bss: bool,
+}
+
+// An index of the PT_LOAD segments and the sections of an object to go
+// between virtual addresses, file offsets, segments and sections.
+//
+// Where ranges overlap the innermost one, starting closest to what is
+// looked up, holds it. A .tbss is left out, its addresses are those of
+// the sections after it. Sections of relocatable objects all start at
+// address 0, in those only file offsets tell them apart.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct AddressMap {
+    // by vaddr
+    segments: Vec<Range>,
+    // sections with an address or file data, by vaddr
+    sections: Vec<Range>,
+}
+
+// the range starting closest below `vaddr` among those holding it
+fn by_vaddr(ranges: &[Range], vaddr: u64) -> Option<&Range> {
+    let end: usize = ranges.partition_point(|r| r.vaddr <= vaddr);
+    ranges[..end].iter().rev().find(|r| r.has_vaddr(vaddr))
+}
+
+// the range starting closest below `offset` among those holding it
+fn by_offset(ranges: &[Range], offset: u64) -> Option<&Range> {
+    ranges.iter().filter(|r| r.has_offset(offset))
+          .fold(None, |best: Option<&Range>, r| match best {
+              Some(best) if best.offset >= r.offset => Some(best),
+              _ => Some(r),
+          })
+}
+
+impl AddressMap {
+    pub fn new(elf: &Elf) -> AddressMap {
+        let mut segments: Vec<Range> = elf.pheaders.iter().enumerate()
+            .filter(|(_, p)| p.ptype == PType::LOAD && p.memsz.max(p.filesz) != 0)
+            .map(|(index, p)| Range {
+                index,
+                vaddr: p.vaddr,
+                memsz: p.memsz.max(p.filesz),
+                offset: p.offset,
+                filesz: p.filesz,
+                loaded: true,
+            })
+            .filter(Range::fits)
+            .collect();
+        let mut sections: Vec<Range> = elf.sheaders.iter().enumerate().skip(1)
+            .filter(|(_, s)| s.size != 0)
+            .filter(|(_, s)| !(s.stype == SType::NOBITS && s.flags & SHF_TLS != 0))
+            .map(|(index, s)| Range {
+                index,
+                vaddr: s.vaddr,
+                memsz: s.size,
+                offset: s.offset,
+                filesz: if s.stype == SType::NOBITS { 0 } else { s.size },
+                loaded: s.flags & SHF_ALLOC != 0,
+            })
+            .filter(|r| r.loaded || r.filesz != 0)
+            .filter(Range::fits)
+            .collect();
+        // stable, equal addresses keep header order
+        segments.sort_by_key(|r| r.vaddr);
+        sections.sort_by_key(|r| r.vaddr);
+        AddressMap { segments, sections }
+    }
+
+    // Where a virtual address is, None when no segment or section is
+    // loaded there
+    pub fn lookup(&self, vaddr: u64) -> Option<Location> {
+        let segment: Option<&Range> = by_vaddr(&self.segments, vaddr);
+        let section: Option<&Range> = by_vaddr(&self.sections, vaddr);
+        // the segment decides where the bytes are, sections of objects
+        // without segments are placed by their own offset
+        let holder: &Range = segment.or(section)?;
+        let delta: u64 = vaddr - holder.vaddr;
+        let bss: bool = delta >= holder.filesz || section.is_some_and(|s| s.filesz == 0);
+        let offset: Option<u64> = match bss {
+            true => None,
+            false => Some(holder.offset.checked_add(delta)?),
+        };
+        Some(Location {
+            vaddr: Some(vaddr),
+            offset,
+            segment: segment.map(|r| r.index),
+            section: section.map(|r| r.index),
+            bss,
+        })
+    }
+
+    // Where a file offset is, None when it is in no segment or section.
+    // An offset in two segments is read at the address given by the
+    // section holding it, or else by the innermost segment.
+    pub fn lookup_offset(&self, offset: u64) -> Option<Location> {
+        let section: Option<&Range> = by_offset(&self.sections, offset);
+        let vaddr: Option<u64> = match section {
+            Some(s) if s.loaded => Some(s.vaddr.checked_add(offset - s.offset)?),
+            Some(_) => None,
+            None => {
+                let segment: &Range = by_offset(&self.segments, offset)?;
+                Some(segment.vaddr.checked_add(offset - segment.offset)?)
+            },
+        };
+        let segment: Option<usize> = vaddr.and_then(|vaddr| by_vaddr(&self.segments, vaddr))
+                                          .map(|r| r.index);
+        Some(Location {
+            vaddr,
+            offset: Some(offset),
+            segment,
+            section: section.map(|r| r.index),
+            bss: false,
+        })
+    }
+
+    // the file offset of the byte loaded at `vaddr`
+    pub fn file_offset(&self, vaddr: u64) -> Option<u64> {
+        self.lookup(vaddr)?.offset
+    }
+
+    // the virtual address the byte at `offset` is loaded at
+    pub fn vaddr(&self, offset: u64) -> Option<u64> {
+        self.lookup_offset(offset)?.vaddr
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::program_header::PHeader;
+    use crate::section_header::SHeader;
+
+    fn load(offset: u64, vaddr: u64, filesz: u64, memsz: u64) -> PHeader {
+        PHeader {
+            ptype: PType::LOAD,
+            type_id: 1,
+            offset,
+            vaddr,
+            paddr: vaddr,
+            filesz,
+            memsz,
+            ..Default::default()
+        }
+    }
+
+    fn section(name: &str, stype: SType, flags: u64, offset: u64, vaddr: u64,
+               size: u64) -> SHeader {
+        SHeader {
+            sname: name.to_string(),
+            stype,
+            flags,
+            offset,
+            vaddr,
+            size,
+            ..Default::default()
+        }
+    }
+
+    fn object(pheaders: Vec<PHeader>, sheaders: Vec<SHeader>) -> Elf {
+        let mut elf: Elf = Elf::default();
+        elf.pheaders = pheaders;
+        elf.sheaders = vec![SHeader::default()];
+        elf.sheaders.extend(sheaders);
+        elf
+    }
+
+    // text at 0x1000, data with a .bss tail at 0x3e10
+    fn executable() -> Elf {
+        object(vec![load(0x1000, 0x1000, 0x200, 0x200), load(0x2e10, 0x3e10, 0x20, 0x50)],
+               vec![section(".text", SType::PROGBITS, SHF_ALLOC | 0x4, 0x1000, 0x1000, 0x200),
+                    section(".data", SType::PROGBITS, SHF_ALLOC | 0x1, 0x2e10, 0x3e10, 0x20),
+                    section(".bss", SType::NOBITS, SHF_ALLOC | 0x1, 0x2e30, 0x3e30, 0x30),
+                    section(".comment", SType::PROGBITS, 0x30, 0x2e30, 0, 0x10)])
+    }
+
+    #[test]
+    fn vaddr_to_offset() {
+        let map: AddressMap = AddressMap::new(&executable());
+        assert_eq!(map.lookup(0x1010), Some(Location {
+            vaddr: Some(0x1010),
+            offset: Some(0x1010),
+            segment: Some(0),
+            section: Some(1),
+            bss: false,
+        }));
+        assert_eq!(map.file_offset(0x3e1f), Some(0x2e1f));
+        assert_eq!(map.lookup(0x11ff).and_then(|l| l.section), Some(1));
+        assert_eq!(map.lookup(0x1200), None);
+        assert_eq!(map.lookup(0xfff), None);
+    }
+
+    #[test]
+    fn bss() {
+        let map: AddressMap = AddressMap::new(&executable());
+        let location: Location = map.lookup(0x3e30).unwrap();
+        assert!(location.bss);
+        assert_eq!(location.offset, None);
+        assert_eq!((location.segment, location.section), (Some(1), Some(3)));
+        assert!(map.lookup(0x3e5f).unwrap().bss);
+        assert_eq!(map.lookup(0x3e60), None);
+        assert!(!map.lookup(0x3e2f).unwrap().bss);
+    }
+
+    #[test]
+    fn offset_to_vaddr() {
+        let map: AddressMap = AddressMap::new(&executable());
+        assert_eq!(map.vaddr(0x2e18), Some(0x3e18));
+        // .comment is in the file but not loaded
+        assert_eq!(map.lookup_offset(0x2e34), Some(Location {
+            vaddr: None,
+            offset: Some(0x2e34),
+            segment: None,
+            section: Some(4),
+            bss: false,
+        }));
+        assert_eq!(map.lookup_offset(0x800), None);
+    }
+
+    #[test]
+    fn unaligned_segments() {
+        // packed segments sharing pages, as with ld -N
+        let elf: Elf = object(vec![load(0x78, 0x400078, 0x123, 0x123),
+                                   load(0x19b, 0x40119b, 0x10, 0x40)],
+                              Vec::new());
+        let map: AddressMap = AddressMap::new(&elf);
+        assert_eq!(map.file_offset(0x400078), Some(0x78));
+        assert_eq!(map.file_offset(0x40019a), Some(0x19a));
+        assert_eq!(map.lookup(0x40019b), None);
+        assert_eq!(map.file_offset(0x40119b), Some(0x19b));
+        assert_eq!(map.vaddr(0x19a), Some(0x40019a));
+        assert_eq!(map.vaddr(0x19b), Some(0x40119b));
+        let location: Location = map.lookup(0x4011ab).unwrap();
+        assert_eq!((location.segment, location.bss), (Some(1), true));
+        assert_eq!(map.lookup(0x4011db), None);
+    }
+
+    #[test]
+    fn overlapping_segments() {
+        // the second segment loads part of the first one's file bytes
+        // again and lies inside its addresses
+        let elf: Elf = object(vec![load(0, 0x10000, 0x2000, 0x2000),
+                                   load(0x1800, 0x8000, 0x400, 0x400),
+                                   load(0x1000, 0x10800, 0x100, 0x100)],
+                              Vec::new());
+        let map: AddressMap = AddressMap::new(&elf);
+        assert_eq!(map.file_offset(0x10900), Some(0x900));
+        assert_eq!(map.lookup(0x10850).and_then(|l| l.segment), Some(2));
+        assert_eq!(map.file_offset(0x10850), Some(0x1050));
+        assert_eq!(map.file_offset(0x8010), Some(0x1810));
+        assert_eq!(map.vaddr(0x1900), Some(0x8100));
+        assert_eq!(map.vaddr(0x1050), Some(0x10850));
+        assert_eq!(map.vaddr(0x500), Some(0x10500));
+    }
+
+    #[test]
+    fn overlapping_segments_follow_sections() {
+        // with a section at the offset its address is the one read
+        let elf: Elf = object(vec![load(0, 0x10000, 0x2000, 0x2000),
+                                   load(0x1800, 0x8000, 0x400, 0x400)],
+                              vec![section(".data", SType::PROGBITS, SHF_ALLOC, 0x1800,
+                                           0x11800, 0x400)]);
+        let map: AddressMap = AddressMap::new(&elf);
+        assert_eq!(map.vaddr(0x1900), Some(0x11900));
+        assert_eq!(map.lookup_offset(0x1900).and_then(|l| l.segment), Some(0));
+    }
+
+    #[test]
+    fn ranges_past_the_end_are_not_mapped() {
+        let elf: Elf = object(vec![load(u64::MAX - 0x8, 0x1000, 0x20, 0x20),
+                                   load(0x2000, u64::MAX - 0x8, 0x20, 0x20)],
+                              vec![section(".data", SType::PROGBITS, SHF_ALLOC | 0x1,
+                                           0x3000, u64::MAX - 0x8, 0x10)]);
+        let map: AddressMap = AddressMap::new(&elf);
+        assert_eq!(map.lookup(0x1010), None);
+        assert_eq!(map.lookup(u64::MAX - 0x8), None);
+        assert_eq!(map.lookup_offset(0x2010), None);
+        assert_eq!(map.lookup_offset(0x300c), None);
+    }
+
+    #[test]
+    fn tbss_is_left_out() {
+        let elf: Elf = object(vec![load(0x2000, 0x3000, 0x20, 0x20)],
+                              vec![section(".tdata", SType::PROGBITS, SHF_ALLOC | SHF_TLS,
+                                           0x2000, 0x3000, 0x8),
+                                   section(".tbss", SType::NOBITS, SHF_ALLOC | SHF_TLS,
+                                           0x2008, 0x3008, 0x10),
+                                   section(".data", SType::PROGBITS, SHF_ALLOC, 0x2008,
+                                           0x3008, 0x18)]);
+        let map: AddressMap = AddressMap::new(&elf);
+        let location: Location = map.lookup(0x3010).unwrap();
+        assert_eq!((location.section, location.offset, location.bss), (Some(3), Some(0x2010), false));
+    }
+}
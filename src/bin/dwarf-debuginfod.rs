@@ -0,0 +1,37 @@
+use clap::Parser;
+use std::path::PathBuf;
+use std::process;
+use dwarf::debuginfod::{Server, Store};
+
+// Serves the ELF files of some directories by build id over the
+// debuginfod protocol
+#[derive(Parser)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    /// Directory scanned for executables and debug files, repeatable
+    #[arg(long, short, required = true)]
+    dir: Vec<String>,
+    /// Address to listen on
+    #[arg(long, short, default_value = "127.0.0.1:8002")]
+    listen: String,
+}
+
+fn main() {
+    let args: Args = Args::parse();
+    let dirs: Vec<PathBuf> = args.dir.iter().map(PathBuf::from).collect();
+    let store: Store = Store::index(&dirs);
+    println!("Indexed {0} build ids.", store.entries.len());
+
+    let server: Server = match Server::bind(&args.listen, store) {
+        Ok(server) => server,
+        Err(e) => {
+            println!("Error: {e}");
+            process::exit(1);
+        },
+    };
+    match server.local_addr() {
+        Ok(addr) => println!("Listening on http://{addr}"),
+        Err(e) => println!("Error: {e}"),
+    }
+    server.run();
+}
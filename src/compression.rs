@@ -0,0 +1,112 @@
+use std::io::Read;
+use crate::{Class, Endian};
+use crate::{BigEndian, LittleEndian, ReadBytesExt};
+
+pub const ELFCOMPRESS_ZLIB: u32 = 1;
+pub const ELFCOMPRESS_ZSTD: u32 = 2;
+
+// Compression header in front of the data of a SHF_COMPRESSED section
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct CHeader {
+    pub ctype: u32,
+    pub size: u64,
+    pub align: u64,
+    // size of the header itself, the compressed stream follows it
+    pub header_size: usize,
+}
+
+pub fn parse_header(data: &[u8], class: Class,
+                    endian: Endian) -> Result<CHeader, &'static str> {
+    let header_size: usize = if class == Class::X32Bit { 0x0c } else { 0x18 };
+    if data.len() < header_size {
+        return Err("Compression header out of bounds.");
+    }
+    let mut buff: &[u8] = data;
+    let read_u32 = |buff: &mut &[u8]| -> Result<u32, &'static str> {
+        match endian {
+            Endian::Little => Ok(buff.read_u32::<LittleEndian>().unwrap()),
+            Endian::Big => Ok(buff.read_u32::<BigEndian>().unwrap()),
+            Endian::NONE => Err("Endianness of the system not defined."),
+        }
+    };
+    let read_u64 = |buff: &mut &[u8]| -> Result<u64, &'static str> {
+        match endian {
+            Endian::Little => Ok(buff.read_u64::<LittleEndian>().unwrap()),
+            Endian::Big => Ok(buff.read_u64::<BigEndian>().unwrap()),
+            Endian::NONE => Err("Endianness of the system not defined."),
+        }
+    };
+
+    let ctype: u32 = read_u32(&mut buff)?;
+    let (size, align): (u64, u64) = if class == Class::X32Bit {
+        (read_u32(&mut buff)? as u64, read_u32(&mut buff)? as u64)
+    }
+    else {
+        // ch_reserved
+        read_u32(&mut buff)?;
+        (read_u64(&mut buff)?, read_u64(&mut buff)?)
+    };
+
+    Ok(CHeader {
+        ctype,
+        size,
+        align,
+        header_size,
+    })
+}
+
+// The decoders stop one byte past the size the header gives, a stream
+// that inflates to more is caught by the size check of the callers
+// without being decompressed in full.
+fn inflate(stream: &[u8], size: u64) -> Result<Vec<u8>, &'static str> {
+    let mut out: Vec<u8> = Vec::with_capacity(size.min(1 << 30) as usize);
+    flate2::read::ZlibDecoder::new(stream).take(size.saturating_add(1))
+                                          .read_to_end(&mut out)
+                                          .map_err(|_| "Invalid zlib compressed section.")?;
+    Ok(out)
+}
+
+fn unzstd(stream: &[u8], size: u64) -> Result<Vec<u8>, &'static str> {
+    let mut out: Vec<u8> = Vec::with_capacity(size.min(1 << 30) as usize);
+    let mut input: &[u8] = stream;
+    // one frame after the other until the stream is consumed
+    while !input.is_empty() && out.len() as u64 <= size {
+        let limit: u64 = size.saturating_add(1) - out.len() as u64;
+        let decoder = ruzstd::decoding::StreamingDecoder::new(&mut input)
+                             .map_err(|_| "Invalid zstd compressed section.")?;
+        decoder.take(limit).read_to_end(&mut out)
+               .map_err(|_| "Invalid zstd compressed section.")?;
+    }
+    Ok(out)
+}
+
+// Decompressed data of a SHF_COMPRESSED section
+pub fn decompress(data: &[u8], class: Class,
+                  endian: Endian) -> Result<Vec<u8>, &'static str> {
+    let header: CHeader = parse_header(data, class, endian)?;
+    let stream: &[u8] = &data[header.header_size..];
+    let out: Vec<u8> = match header.ctype {
+        ELFCOMPRESS_ZLIB => inflate(stream, header.size)?,
+        ELFCOMPRESS_ZSTD => unzstd(stream, header.size)?,
+        _ => return Err("Unsupported section compression."),
+    };
+    if out.len() as u64 != header.size {
+        return Err("Decompressed section size mismatch.");
+    }
+    Ok(out)
+}
+
+// Decompressed data of a legacy .zdebug section: "ZLIB", the size as a
+// 64 bit big endian value and a zlib stream. Data without the magic is
+// returned as is, as binutils does.
+pub fn decompress_zdebug(data: &[u8]) -> Result<Vec<u8>, &'static str> {
+    if data.len() < 12 || &data[..4] != b"ZLIB" {
+        return Ok(data.to_vec());
+    }
+    let size: u64 = (&data[4..12]).read_u64::<BigEndian>().unwrap();
+    let out: Vec<u8> = inflate(&data[12..], size)?;
+    if out.len() as u64 != size {
+        return Err("Decompressed section size mismatch.");
+    }
+    Ok(out)
+}
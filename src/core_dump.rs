@@ -0,0 +1,158 @@
+use std::collections::HashMap;
+use crate::{Class, Endian};
+use crate::debug_info::Reader;
+use crate::debug_info::expression::Provider;
+use crate::elf::{self, Elf};
+use crate::elf_header::header_enums::{BinType, Machine};
+use crate::note::{self, Note};
+use crate::note::note_consts::*;
+use crate::program_header::phdr_enums::PType;
+
+// A core dump used as the machine state for evaluating DWARF expressions:
+// registers of the first thread and the memory saved in PT_LOAD segments
+#[derive(Debug, Default)]
+pub struct CoreDump {
+    pub elf: Elf,
+    pub registers: HashMap<u16, u64>,
+    pub entry: Option<u64>,
+}
+
+// DWARF register number and index into pr_reg of the general purpose
+// registers of x86-64 (struct user_regs_struct)
+const AMD64_REGS: [(u16, usize); 17] = [
+    (0, 10), (1, 12), (2, 11), (3, 5), (4, 13), (5, 14), (6, 4), (7, 19),
+    (8, 9), (9, 8), (10, 7), (11, 6), (12, 3), (13, 2), (14, 1), (15, 0),
+    (16, 16),
+];
+
+// same for i386 (struct user_regs_struct of the 32 bit ABI)
+const X86_REGS: [(u16, usize); 9] = [
+    (0, 6), (1, 1), (2, 2), (3, 0), (4, 15), (5, 5), (6, 3), (7, 4), (8, 12),
+];
+
+fn parse_prstatus(desc: &[u8], class: Class, machine: Machine,
+                  endian: Endian) -> Result<HashMap<u16, u64>, &'static str> {
+    let (regs_offset, width, map): (usize, u8, &[(u16, usize)]) = match machine {
+        Machine::AMD64 if class == Class::X64Bit => (112, 8, &AMD64_REGS),
+        Machine::X86 if class == Class::X32Bit => (72, 4, &X86_REGS),
+        _ => return Err("Registers of this machine are not supported."),
+    };
+
+    let mut registers: HashMap<u16, u64> = HashMap::new();
+    for (dwarf_reg, idx) in map {
+        let mut reader: Reader = Reader::new(desc, regs_offset + idx * width as usize,
+                                             endian);
+        registers.insert(*dwarf_reg, reader.sized(width)?);
+    }
+    Ok(registers)
+}
+
+fn parse_auxv_entry(desc: &[u8], class: Class, endian: Endian) -> Option<u64> {
+    let width: u8 = if class == Class::X32Bit { 4 } else { 8 };
+    let mut reader: Reader = Reader::new(desc, 0, endian);
+    while !reader.is_empty() {
+        let key: u64 = reader.sized(width).ok()?;
+        let value: u64 = reader.sized(width).ok()?;
+        match key {
+            AT_ENTRY => return Some(value),
+            AT_NULL => return None,
+            _ => (),
+        }
+    }
+    None
+}
+
+pub fn parse(content: Vec<u8>) -> Result<CoreDump, &'static str> {
+    let elf: Elf = elf::parse(content)?;
+    if elf.header.bin_type != BinType::CORE {
+        return Err("Not a core dump.");
+    }
+
+    let mut registers: Option<HashMap<u16, u64>> = None;
+    let mut entry: Option<u64> = None;
+    for pheader in elf.pheaders.iter().filter(|p| p.ptype == PType::NOTE) {
+        let notes: Vec<Note> = note::parse(elf.segment_data(pheader)?,
+                                           elf.header.endian, pheader.align)?;
+        for note in notes.iter().filter(|n| n.name == b"CORE") {
+            match note.ntype {
+                // the first NT_PRSTATUS belongs to the thread that crashed
+                NT_PRSTATUS if registers.is_none() => {
+                    registers = Some(parse_prstatus(note.desc, elf.header.class,
+                                                    elf.header.machine,
+                                                    elf.header.endian)?);
+                },
+                NT_AUXV => {
+                    entry = parse_auxv_entry(note.desc, elf.header.class,
+                                             elf.header.endian);
+                },
+                _ => (),
+            }
+        }
+    }
+
+    Ok(CoreDump {
+        elf,
+        registers: registers.ok_or("Core dump has no NT_PRSTATUS note.")?,
+        entry,
+    })
+}
+
+impl CoreDump {
+    // program counter of the crashed thread
+    pub fn pc(&self) -> Option<u64> {
+        let reg: u16 = match self.elf.header.machine {
+            Machine::AMD64 => 16,
+            _ => 8,
+        };
+        self.registers.get(&reg).copied()
+    }
+
+    // difference between run time and link time addresses of the
+    // executable with the given entry point
+    pub fn load_bias(&self, entry_point: u64) -> u64 {
+        match self.entry {
+            Some(entry) => entry.wrapping_sub(entry_point),
+            None => 0,
+        }
+    }
+}
+
+// The core dump seen from a frame other than the crashed one: without
+// unwinding the registers of the crash do not apply there, only memory
+// is read
+pub struct MemoryOnly<'a>(pub &'a CoreDump);
+
+impl Provider for MemoryOnly<'_> {
+    fn read_memory(&self, addr: u64, len: usize) -> Option<Vec<u8>> {
+        self.0.read_memory(addr, len)
+    }
+}
+
+impl Provider for CoreDump {
+    fn register(&self, reg: u16) -> Option<u64> {
+        self.registers.get(&reg).copied()
+    }
+
+    fn read_memory(&self, addr: u64, len: usize) -> Option<Vec<u8>> {
+        // grown as the dump has the bytes, len may come from corrupt DWARF
+        let mut out: Vec<u8> = Vec::new();
+        while out.len() < len {
+            let current: u64 = addr.checked_add(out.len() as u64)?;
+            let pheader = self.elf.pheaders.iter().find(|p| {
+                p.ptype == PType::LOAD && p.vaddr <= current
+                && current - p.vaddr < p.memsz
+            })?;
+            let seg_offset: u64 = current - pheader.vaddr;
+            let wanted: u64 = (len - out.len()) as u64;
+            if seg_offset >= pheader.filesz {
+                // mapped in the process but not saved in the dump
+                return None;
+            }
+            let count: u64 = wanted.min(pheader.filesz - seg_offset);
+            let data: &[u8] = self.elf.segment_data(pheader).ok()?;
+            let start: usize = seg_offset as usize;
+            out.extend_from_slice(&data[start..start + count as usize]);
+        }
+        Some(out)
+    }
+}
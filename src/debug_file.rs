@@ -0,0 +1,235 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::debug_info::Reader;
+use crate::debuginfod::{Artifact, Client};
+use crate::elf::{self, Elf};
+use crate::note;
+use crate::note::note_consts::*;
+
+// Contents of .gnu_debuglink: the file name of the separate debug file
+// and the CRC32 of its contents
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct DebugLink {
+    pub name: String,
+    pub crc: u32,
+}
+
+// Contents of .gnu_debugaltlink: the file name and build id of the dwz
+// supplementary file shared by several debug files
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct AltLink {
+    pub name: String,
+    pub build_id: Vec<u8>,
+}
+
+// How a separate debug file was found
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Method {
+    BuildId,
+    DebugLink,
+    AltLink,
+    Debuginfod,
+}
+
+// A separate debug file found for an object
+#[derive(Debug)]
+pub struct DebugFile {
+    pub path: PathBuf,
+    pub method: Method,
+    pub elf: Elf,
+}
+
+// the NT_GNU_BUILD_ID note, from the note sections or, for objects
+// without section headers, the PT_NOTE segments
+pub fn build_id(elf: &Elf) -> Option<Vec<u8>> {
+    note::all(elf).iter()
+        .find(|n| n.name == b"GNU" && n.ntype == NT_GNU_BUILD_ID && !n.desc.is_empty())
+        .map(|n| n.desc.to_vec())
+}
+
+pub fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+pub fn debug_link(elf: &Elf) -> Result<Option<DebugLink>, &'static str> {
+    let data: &[u8] = match elf.section(".gnu_debuglink") {
+        Some(sheader) => elf.section_data(sheader)?,
+        None => return Ok(None),
+    };
+    let mut reader: Reader = Reader::new(data, 0, elf.header.endian);
+    let name: &[u8] = reader.cstr()?;
+    // the CRC follows the name at the next multiple of 4
+    reader.offset = (reader.offset + 3) & !3;
+    Ok(Some(DebugLink {
+        name: String::from_utf8_lossy(name).into_owned(),
+        crc: reader.u32()?,
+    }))
+}
+
+pub fn alt_link(elf: &Elf) -> Result<Option<AltLink>, &'static str> {
+    let data: &[u8] = match elf.section(".gnu_debugaltlink") {
+        Some(sheader) => elf.section_data(sheader)?,
+        None => return Ok(None),
+    };
+    let mut reader: Reader = Reader::new(data, 0, elf.header.endian);
+    let name: &[u8] = reader.cstr()?;
+    Ok(Some(AltLink {
+        name: String::from_utf8_lossy(name).into_owned(),
+        build_id: data[reader.offset..].to_vec(),
+    }))
+}
+
+// Looks for separate debug files below a list of debug roots, like
+// /usr/lib/debug, the way gdb does, then asks the debuginfod servers
+#[derive(Debug, Clone)]
+pub struct Resolver {
+    pub debug_roots: Vec<PathBuf>,
+    pub debuginfod: Option<Client>,
+}
+
+impl Default for Resolver {
+    fn default() -> Resolver {
+        Resolver {
+            debug_roots: vec![PathBuf::from("/usr/lib/debug")],
+            debuginfod: None,
+        }
+    }
+}
+
+// a parsed candidate file and the CRC32 of its contents
+fn read_elf(path: &Path) -> Option<(u32, Elf)> {
+    let content: Vec<u8> = fs::read(path).ok()?;
+    let crc: u32 = crc32fast::hash(&content);
+    Some((crc, elf::parse(content).ok()?))
+}
+
+impl Resolver {
+    pub fn new(debug_roots: Vec<PathBuf>) -> Resolver {
+        Resolver {
+            debug_roots,
+            debuginfod: None,
+        }
+    }
+
+    // <root>/.build-id/xx/yyyy.debug for every debug root
+    pub fn build_id_paths(&self, build_id: &[u8]) -> Vec<PathBuf> {
+        if build_id.len() < 2 {
+            return Vec::new();
+        }
+        let id: String = hex(build_id);
+        self.debug_roots.iter()
+            .map(|root| root.join(".build-id").join(&id[..2])
+                            .join(format!("{0}.debug", &id[2..])))
+            .collect()
+    }
+
+    // the places a .gnu_debuglink name is looked for: next to the object,
+    // in its .debug directory and below each root under the directory of
+    // the object
+    pub fn debug_link_paths(&self, object: &Path, name: &str) -> Vec<PathBuf> {
+        let dir: &Path = object.parent().filter(|p| !p.as_os_str().is_empty())
+                               .unwrap_or(Path::new("."));
+        let dir: PathBuf = fs::canonicalize(dir).unwrap_or(dir.to_path_buf());
+        let mut paths: Vec<PathBuf> = vec![dir.join(name), dir.join(".debug").join(name)];
+        for root in self.debug_roots.iter() {
+            let relative: &Path = dir.strip_prefix("/").unwrap_or(&dir);
+            paths.push(root.join(relative).join(name));
+        }
+        paths
+    }
+
+    // The separate debug file of the object at `path`: by build id first,
+    // then through .gnu_debuglink. A candidate must have the same build
+    // id or the CRC32 recorded in the link.
+    pub fn find_debug_file(&self, elf: &Elf,
+                           path: &Path) -> Result<Option<DebugFile>, &'static str> {
+        let id: Option<Vec<u8>> = build_id(elf);
+        if let Some(id) = id.as_ref() {
+            for candidate in self.build_id_paths(id) {
+                if let Some((_, debug)) = read_elf(&candidate) {
+                    if build_id(&debug).as_ref() == Some(id) {
+                        return Ok(Some(DebugFile {
+                            path: candidate,
+                            method: Method::BuildId,
+                            elf: debug,
+                        }));
+                    }
+                }
+            }
+        }
+
+        if let Some(link) = debug_link(elf)? {
+            for candidate in self.debug_link_paths(path, &link.name) {
+                // an object can link to a file of the same name next to it
+                if fs::canonicalize(&candidate).ok() == fs::canonicalize(path).ok() {
+                    continue;
+                }
+                if let Some((crc, debug)) = read_elf(&candidate) {
+                    if crc == link.crc {
+                        return Ok(Some(DebugFile {
+                            path: candidate,
+                            method: Method::DebugLink,
+                            elf: debug,
+                        }));
+                    }
+                }
+            }
+        }
+
+        if let (Some(id), Some(client)) = (id.as_ref(), self.debuginfod.as_ref()) {
+            return Ok(self.fetch(client, id, Artifact::Debuginfo));
+        }
+        Ok(None)
+    }
+
+    // an artifact downloaded from debuginfod, which must have the build
+    // id it was asked for
+    fn fetch(&self, client: &Client, id: &[u8], artifact: Artifact) -> Option<DebugFile> {
+        let path: PathBuf = client.fetch(id, artifact)?;
+        let (_, elf) = read_elf(&path)?;
+        if build_id(&elf).as_deref() != Some(id) {
+            return None;
+        }
+        Some(DebugFile {
+            path,
+            method: Method::Debuginfod,
+            elf,
+        })
+    }
+
+    // The dwz supplementary file named by .gnu_debugaltlink of `debug`,
+    // relative names are relative to the directory of `debug_path`. It is
+    // also looked up by its build id, which must match.
+    pub fn find_alt_file(&self, debug: &Elf,
+                         debug_path: &Path) -> Result<Option<DebugFile>, &'static str> {
+        let link: AltLink = match alt_link(debug)? {
+            Some(link) => link,
+            None => return Ok(None),
+        };
+        let mut candidates: Vec<PathBuf> = Vec::new();
+        let name: &Path = Path::new(&link.name);
+        if name.is_absolute() {
+            candidates.push(name.to_path_buf());
+        }
+        else if let Some(dir) = debug_path.parent() {
+            candidates.push(dir.join(name));
+        }
+        candidates.extend(self.build_id_paths(&link.build_id));
+
+        for candidate in candidates {
+            if let Some((_, alt)) = read_elf(&candidate) {
+                if build_id(&alt).is_some_and(|id| id == link.build_id) {
+                    return Ok(Some(DebugFile {
+                        path: candidate,
+                        method: Method::AltLink,
+                        elf: alt,
+                    }));
+                }
+            }
+        }
+        match self.debuginfod.as_ref() {
+            Some(client) => Ok(self.fetch(client, &link.build_id, Artifact::Debuginfo)),
+            None => Ok(None),
+        }
+    }
+}
@@ -0,0 +1,303 @@
+use crate::{BigEndian, LittleEndian, ReadBytesExt};
+use crate::{Class, Endian};
+use crate::elf::Elf;
+pub mod dw_consts;
+pub mod unit;
+pub mod ranges;
+pub mod expression;
+pub mod location;
+pub mod frame;
+pub mod locals;
+pub mod split;
+pub mod names;
+pub mod line;
+pub mod aranges;
+pub mod lint;
+pub mod macros;
+pub mod producer;
+
+const END_OF_DATA: &str = "Unexpected end of DWARF data.";
+
+// A cursor over the bytes of a debug section
+#[derive(Debug, Clone, Copy)]
+pub struct Reader<'a> {
+    pub data: &'a [u8],
+    pub offset: usize,
+    pub endian: Endian,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(data: &'a [u8], offset: usize, endian: Endian) -> Reader<'a> {
+        Reader {
+            data,
+            offset,
+            endian,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.offset >= self.data.len()
+    }
+
+    pub fn bytes(&mut self, len: usize) -> Result<&'a [u8], &'static str> {
+        let end: usize = self.offset.checked_add(len).ok_or(END_OF_DATA)?;
+        if end > self.data.len() {
+            return Err(END_OF_DATA);
+        }
+        let buff: &'a [u8] = &self.data[self.offset..end];
+        self.offset = end;
+        Ok(buff)
+    }
+
+    pub fn skip(&mut self, len: usize) -> Result<(), &'static str> {
+        self.bytes(len)?;
+        Ok(())
+    }
+
+    pub fn u8(&mut self) -> Result<u8, &'static str> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    pub fn u16(&mut self) -> Result<u16, &'static str> {
+        let mut buff: &[u8] = self.bytes(2)?;
+        match self.endian {
+            Endian::Little => Ok(buff.read_u16::<LittleEndian>().unwrap()),
+            Endian::Big => Ok(buff.read_u16::<BigEndian>().unwrap()),
+            Endian::NONE => Err("Endianness of the system not defined."),
+        }
+    }
+
+    pub fn u24(&mut self) -> Result<u32, &'static str> {
+        let mut buff: &[u8] = self.bytes(3)?;
+        match self.endian {
+            Endian::Little => Ok(buff.read_u24::<LittleEndian>().unwrap()),
+            Endian::Big => Ok(buff.read_u24::<BigEndian>().unwrap()),
+            Endian::NONE => Err("Endianness of the system not defined."),
+        }
+    }
+
+    pub fn u32(&mut self) -> Result<u32, &'static str> {
+        let mut buff: &[u8] = self.bytes(4)?;
+        match self.endian {
+            Endian::Little => Ok(buff.read_u32::<LittleEndian>().unwrap()),
+            Endian::Big => Ok(buff.read_u32::<BigEndian>().unwrap()),
+            Endian::NONE => Err("Endianness of the system not defined."),
+        }
+    }
+
+    pub fn u64(&mut self) -> Result<u64, &'static str> {
+        let mut buff: &[u8] = self.bytes(8)?;
+        match self.endian {
+            Endian::Little => Ok(buff.read_u64::<LittleEndian>().unwrap()),
+            Endian::Big => Ok(buff.read_u64::<BigEndian>().unwrap()),
+            Endian::NONE => Err("Endianness of the system not defined."),
+        }
+    }
+
+    // unsigned value of 1, 2, 4 or 8 bytes, used for addresses and offsets
+    pub fn sized(&mut self, size: u8) -> Result<u64, &'static str> {
+        match size {
+            1 => Ok(self.u8()? as u64),
+            2 => Ok(self.u16()? as u64),
+            3 => Ok(self.u24()? as u64),
+            4 => Ok(self.u32()? as u64),
+            8 => self.u64(),
+            _ => Err("Unsupported value size."),
+        }
+    }
+
+    pub fn uleb(&mut self) -> Result<u64, &'static str> {
+        let mut value: u64 = 0;
+        let mut shift: u32 = 0;
+        loop {
+            let byte: u8 = self.u8()?;
+            if shift < 64 {
+                value |= ((byte & 0x7f) as u64) << shift;
+            }
+            shift += 7;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+    }
+
+    pub fn sleb(&mut self) -> Result<i64, &'static str> {
+        let mut value: i64 = 0;
+        let mut shift: u32 = 0;
+        let mut byte: u8;
+        loop {
+            byte = self.u8()?;
+            if shift < 64 {
+                value |= ((byte & 0x7f) as i64) << shift;
+            }
+            shift += 7;
+            if byte & 0x80 == 0 {
+                break;
+            }
+        }
+        if shift < 64 && byte & 0x40 != 0 {
+            value |= -1i64 << shift;
+        }
+        Ok(value)
+    }
+
+    // NUL terminated string, the terminator is consumed but not returned
+    pub fn cstr(&mut self) -> Result<&'a [u8], &'static str> {
+        let rest: &'a [u8] = &self.data[self.offset.min(self.data.len())..];
+        match rest.iter().position(|b| *b == 0x00) {
+            Some(len) => {
+                self.offset += len + 1;
+                Ok(&rest[..len])
+            },
+            None => Err(END_OF_DATA),
+        }
+    }
+
+    // unit_length field, returns the length and the offset size (4 or 8)
+    pub fn initial_length(&mut self) -> Result<(u64, u8), &'static str> {
+        let length: u32 = self.u32()?;
+        if length == 0xffffffff {
+            Ok((self.u64()?, 8))
+        }
+        else if length >= 0xfffffff0 {
+            Err("Reserved unit length value.")
+        }
+        else {
+            Ok((length as u64, 4))
+        }
+    }
+}
+
+// Parameters shared by everything decoded from one unit
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Encoding {
+    pub address_size: u8,
+    pub offset_size: u8,
+    pub version: u16,
+    pub endian: Endian,
+}
+
+impl Encoding {
+    // largest address, also the mask applied to values of the generic type
+    pub fn max_address(&self) -> u64 {
+        if self.address_size >= 8 {
+            u64::MAX
+        }
+        else {
+            (1u64 << (self.address_size as u32 * 8)) - 1
+        }
+    }
+}
+
+// The .debug_addr contribution of a unit, read by the indexed address
+// forms and operations
+#[derive(Debug, Default, Clone, Copy)]
+pub struct AddressTable<'a> {
+    pub data: &'a [u8],
+    pub base: u64,
+    pub address_size: u8,
+    pub endian: Endian,
+}
+
+impl AddressTable<'_> {
+    pub fn get(&self, index: u64) -> Result<u64, &'static str> {
+        let offset: u64 = index.checked_mul(self.address_size as u64)
+                               .and_then(|o| o.checked_add(self.base))
+                               .ok_or("Address index out of bounds.")?;
+        let mut reader: Reader = Reader::new(self.data, offset as usize, self.endian);
+        reader.sized(self.address_size)
+              .map_err(|_| "Address index out of bounds.")
+    }
+}
+
+// The debug sections of one ELF file, empty when a section is absent.
+// Split DWARF files provide the .dwo variants of the same sections.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DwarfSections<'a> {
+    pub endian: Endian,
+    pub address_size: u8,
+    pub info: &'a [u8],
+    pub abbrev: &'a [u8],
+    pub str: &'a [u8],
+    pub str_offsets: &'a [u8],
+    pub addr: &'a [u8],
+    pub line_str: &'a [u8],
+    pub line: &'a [u8],
+    pub aranges: &'a [u8],
+    pub loc: &'a [u8],
+    pub loclists: &'a [u8],
+    pub ranges: &'a [u8],
+    pub rnglists: &'a [u8],
+    pub debug_frame: &'a [u8],
+    pub eh_frame: &'a [u8],
+    pub eh_frame_addr: u64,
+    pub cu_index: &'a [u8],
+    pub tu_index: &'a [u8],
+    pub names: &'a [u8],
+    pub gdb_index: &'a [u8],
+    pub pubnames: &'a [u8],
+    pub pubtypes: &'a [u8],
+    pub gnu_pubnames: &'a [u8],
+    pub gnu_pubtypes: &'a [u8],
+    pub macro_: &'a [u8],
+    pub macinfo: &'a [u8],
+    // .debug_str of the dwz supplementary file, for DW_FORM_strp_sup
+    pub sup_str: &'a [u8],
+}
+
+impl<'a> DwarfSections<'a> {
+    // bytes of a string attribute taken from .debug_str or .debug_line_str
+    pub fn string(&self, value: &unit::AttrValue<'a>) -> Option<&'a [u8]> {
+        let (section, offset): (&'a [u8], u64) = match value {
+            unit::AttrValue::String(s) => return Some(s),
+            unit::AttrValue::Strp(offset) => (self.str, *offset),
+            unit::AttrValue::LineStrp(offset) => (self.line_str, *offset),
+            unit::AttrValue::StrpSup(offset) => (self.sup_str, *offset),
+            _ => return None,
+        };
+        Reader::new(section, offset as usize, self.endian).cstr().ok()
+    }
+}
+
+pub fn load(elf: &Elf) -> DwarfSections<'_> {
+    // .dwo files and .dwp packages name their sections with a suffix
+    let data = |name: &str| -> &[u8] {
+        let sheader = elf.section(name)
+                         .or_else(|| elf.section(&format!("{name}.dwo")));
+        match sheader {
+            Some(sheader) => elf.debug_data(sheader).unwrap_or(&[]),
+            None => &[],
+        }
+    };
+
+    DwarfSections {
+        endian: elf.header.endian,
+        address_size: if elf.header.class == Class::X32Bit { 4 } else { 8 },
+        info: data(".debug_info"),
+        abbrev: data(".debug_abbrev"),
+        str: data(".debug_str"),
+        str_offsets: data(".debug_str_offsets"),
+        addr: data(".debug_addr"),
+        line_str: data(".debug_line_str"),
+        line: data(".debug_line"),
+        aranges: data(".debug_aranges"),
+        loc: data(".debug_loc"),
+        loclists: data(".debug_loclists"),
+        ranges: data(".debug_ranges"),
+        rnglists: data(".debug_rnglists"),
+        debug_frame: data(".debug_frame"),
+        eh_frame: data(".eh_frame"),
+        eh_frame_addr: elf.section(".eh_frame").map_or(0, |s| s.vaddr),
+        cu_index: data(".debug_cu_index"),
+        tu_index: data(".debug_tu_index"),
+        names: data(".debug_names"),
+        gdb_index: data(".gdb_index"),
+        pubnames: data(".debug_pubnames"),
+        pubtypes: data(".debug_pubtypes"),
+        gnu_pubnames: data(".debug_gnu_pubnames"),
+        gnu_pubtypes: data(".debug_gnu_pubtypes"),
+        macro_: data(".debug_macro"),
+        macinfo: data(".debug_macinfo"),
+        sup_str: &[],
+    }
+}
@@ -0,0 +1,84 @@
+use super::{DwarfSections, Reader};
+use super::ranges::Range;
+
+// One address range of a set, `offset` is its position in .debug_aranges
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Arange {
+    pub offset: usize,
+    pub range: Range,
+}
+
+// The address ranges covered by one unit
+#[derive(Debug, Default, Clone)]
+pub struct ArangeSet {
+    pub offset: usize,
+    pub version: u16,
+    pub info_offset: u64,
+    pub address_size: u8,
+    pub segment_size: u8,
+    pub ranges: Vec<Arange>,
+    pub end: usize,
+}
+
+pub fn parse_set(sections: &DwarfSections,
+                 offset: usize) -> Result<ArangeSet, &'static str> {
+    let data: &[u8] = sections.aranges;
+    let mut reader: Reader = Reader::new(data, offset, sections.endian);
+    let mut set: ArangeSet = ArangeSet {
+        offset,
+        ..Default::default()
+    };
+
+    let (unit_length, offset_size) = reader.initial_length()?;
+    set.end = reader.offset.checked_add(unit_length as usize)
+                           .filter(|end| *end <= data.len())
+                           .ok_or("Address range set length out of bounds.")?;
+    reader.data = &data[..set.end];
+    set.version = reader.u16()?;
+    set.info_offset = reader.sized(offset_size)?;
+    set.address_size = reader.u8()?;
+    set.segment_size = reader.u8()?;
+
+    // tuples are aligned to twice the address size from the set start
+    let tuple_size: usize = set.address_size as usize * 2;
+    if tuple_size == 0 {
+        return Err("Address range set has an address size of 0.");
+    }
+    let misalignment: usize = (reader.offset - offset) % tuple_size;
+    if misalignment != 0 {
+        reader.skip(tuple_size - misalignment)?;
+    }
+
+    while !reader.is_empty() {
+        let tuple_offset: usize = reader.offset;
+        if set.segment_size > 0 {
+            reader.sized(set.segment_size)?;
+        }
+        let begin: u64 = reader.sized(set.address_size)?;
+        let length: u64 = reader.sized(set.address_size)?;
+        if begin == 0 && length == 0 {
+            break;
+        }
+        set.ranges.push(Arange {
+            offset: tuple_offset,
+            range: Range {
+                begin,
+                end: begin.wrapping_add(length),
+            },
+        });
+    }
+
+    Ok(set)
+}
+
+// every set of .debug_aranges
+pub fn parse_aranges(sections: &DwarfSections) -> Result<Vec<ArangeSet>, &'static str> {
+    let mut sets: Vec<ArangeSet> = Vec::new();
+    let mut offset: usize = 0;
+    while offset < sections.aranges.len() {
+        let set: ArangeSet = parse_set(sections, offset)?;
+        offset = set.end;
+        sets.push(set);
+    }
+    Ok(sets)
+}
@@ -0,0 +1,606 @@
+// Constants from the DWARF specification (versions 2 to 5) together with
+// the GNU extensions emitted by gcc and clang.
+
+// unit types
+pub const DW_UT_COMPILE: u8 = 0x01;
+pub const DW_UT_TYPE: u8 = 0x02;
+pub const DW_UT_PARTIAL: u8 = 0x03;
+pub const DW_UT_SKELETON: u8 = 0x04;
+pub const DW_UT_SPLIT_COMPILE: u8 = 0x05;
+pub const DW_UT_SPLIT_TYPE: u8 = 0x06;
+
+// tags
+pub const DW_TAG_ARRAY_TYPE: u16 = 0x01;
+pub const DW_TAG_CLASS_TYPE: u16 = 0x02;
+pub const DW_TAG_ENUMERATION_TYPE: u16 = 0x04;
+pub const DW_TAG_FORMAL_PARAMETER: u16 = 0x05;
+pub const DW_TAG_LEXICAL_BLOCK: u16 = 0x0b;
+pub const DW_TAG_MEMBER: u16 = 0x0d;
+pub const DW_TAG_POINTER_TYPE: u16 = 0x0f;
+pub const DW_TAG_REFERENCE_TYPE: u16 = 0x10;
+pub const DW_TAG_COMPILE_UNIT: u16 = 0x11;
+pub const DW_TAG_STRUCTURE_TYPE: u16 = 0x13;
+pub const DW_TAG_SUBROUTINE_TYPE: u16 = 0x15;
+pub const DW_TAG_TYPEDEF: u16 = 0x16;
+pub const DW_TAG_UNION_TYPE: u16 = 0x17;
+pub const DW_TAG_UNSPECIFIED_PARAMETERS: u16 = 0x18;
+pub const DW_TAG_INLINED_SUBROUTINE: u16 = 0x1d;
+pub const DW_TAG_PTR_TO_MEMBER_TYPE: u16 = 0x1f;
+pub const DW_TAG_BASE_TYPE: u16 = 0x24;
+pub const DW_TAG_CONST_TYPE: u16 = 0x26;
+pub const DW_TAG_ENUMERATOR: u16 = 0x28;
+pub const DW_TAG_SUBPROGRAM: u16 = 0x2e;
+pub const DW_TAG_VARIABLE: u16 = 0x34;
+pub const DW_TAG_VOLATILE_TYPE: u16 = 0x35;
+pub const DW_TAG_RESTRICT_TYPE: u16 = 0x37;
+pub const DW_TAG_NAMESPACE: u16 = 0x39;
+pub const DW_TAG_UNSPECIFIED_TYPE: u16 = 0x3b;
+pub const DW_TAG_PARTIAL_UNIT: u16 = 0x3c;
+pub const DW_TAG_RVALUE_REFERENCE_TYPE: u16 = 0x42;
+pub const DW_TAG_TYPE_UNIT: u16 = 0x41;
+pub const DW_TAG_ATOMIC_TYPE: u16 = 0x47;
+pub const DW_TAG_CALL_SITE: u16 = 0x48;
+pub const DW_TAG_CALL_SITE_PARAMETER: u16 = 0x49;
+pub const DW_TAG_SKELETON_UNIT: u16 = 0x4a;
+pub const DW_TAG_GNU_CALL_SITE: u16 = 0x4109;
+pub const DW_TAG_GNU_CALL_SITE_PARAMETER: u16 = 0x410a;
+pub const DW_TAG_IMPORTED_DECLARATION: u16 = 0x08;
+pub const DW_TAG_LABEL: u16 = 0x0a;
+pub const DW_TAG_STRING_TYPE: u16 = 0x12;
+pub const DW_TAG_INHERITANCE: u16 = 0x1c;
+pub const DW_TAG_SUBRANGE_TYPE: u16 = 0x21;
+pub const DW_TAG_TEMPLATE_TYPE_PARAMETER: u16 = 0x2f;
+pub const DW_TAG_TEMPLATE_VALUE_PARAMETER: u16 = 0x30;
+pub const DW_TAG_IMPORTED_MODULE: u16 = 0x3a;
+pub const DW_TAG_IMPORTED_UNIT: u16 = 0x3d;
+pub const DW_TAG_CONDITION: u16 = 0x3f;
+pub const DW_TAG_SHARED_TYPE: u16 = 0x40;
+pub const DW_TAG_DYNAMIC_TYPE: u16 = 0x46;
+pub const DW_TAG_IMMUTABLE_TYPE: u16 = 0x4b;
+
+// attributes
+pub const DW_AT_SIBLING: u16 = 0x01;
+pub const DW_AT_LOCATION: u16 = 0x02;
+pub const DW_AT_NAME: u16 = 0x03;
+pub const DW_AT_BYTE_SIZE: u16 = 0x0b;
+pub const DW_AT_STMT_LIST: u16 = 0x10;
+pub const DW_AT_LOW_PC: u16 = 0x11;
+pub const DW_AT_HIGH_PC: u16 = 0x12;
+pub const DW_AT_LANGUAGE: u16 = 0x13;
+pub const DW_AT_COMP_DIR: u16 = 0x1b;
+pub const DW_AT_CONST_VALUE: u16 = 0x1c;
+pub const DW_AT_INLINE: u16 = 0x20;
+pub const DW_AT_PRODUCER: u16 = 0x25;
+pub const DW_AT_ABSTRACT_ORIGIN: u16 = 0x31;
+pub const DW_AT_COUNT: u16 = 0x37;
+pub const DW_AT_DECLARATION: u16 = 0x3c;
+pub const DW_AT_ENCODING: u16 = 0x3e;
+pub const DW_AT_EXTERNAL: u16 = 0x3f;
+pub const DW_AT_FRAME_BASE: u16 = 0x40;
+pub const DW_AT_SPECIFICATION: u16 = 0x47;
+pub const DW_AT_TYPE: u16 = 0x49;
+pub const DW_AT_DATA_MEMBER_LOCATION: u16 = 0x38;
+pub const DW_AT_RANGES: u16 = 0x55;
+pub const DW_AT_ENTRY_PC: u16 = 0x52;
+pub const DW_AT_MACRO_INFO: u16 = 0x43;
+pub const DW_AT_LINKAGE_NAME: u16 = 0x6e;
+pub const DW_AT_STR_OFFSETS_BASE: u16 = 0x72;
+pub const DW_AT_ADDR_BASE: u16 = 0x73;
+pub const DW_AT_RNGLISTS_BASE: u16 = 0x74;
+pub const DW_AT_DWO_NAME: u16 = 0x76;
+pub const DW_AT_MACROS: u16 = 0x79;
+pub const DW_AT_LOCLISTS_BASE: u16 = 0x8c;
+pub const DW_AT_MIPS_LINKAGE_NAME: u16 = 0x2007;
+pub const DW_AT_GNU_MACROS: u16 = 0x2119;
+pub const DW_AT_GNU_DWO_NAME: u16 = 0x2130;
+pub const DW_AT_GNU_DWO_ID: u16 = 0x2131;
+pub const DW_AT_GNU_RANGES_BASE: u16 = 0x2132;
+pub const DW_AT_GNU_ADDR_BASE: u16 = 0x2133;
+
+// attribute forms
+pub const DW_FORM_ADDR: u16 = 0x01;
+pub const DW_FORM_BLOCK2: u16 = 0x03;
+pub const DW_FORM_BLOCK4: u16 = 0x04;
+pub const DW_FORM_DATA2: u16 = 0x05;
+pub const DW_FORM_DATA4: u16 = 0x06;
+pub const DW_FORM_DATA8: u16 = 0x07;
+pub const DW_FORM_STRING: u16 = 0x08;
+pub const DW_FORM_BLOCK: u16 = 0x09;
+pub const DW_FORM_BLOCK1: u16 = 0x0a;
+pub const DW_FORM_DATA1: u16 = 0x0b;
+pub const DW_FORM_FLAG: u16 = 0x0c;
+pub const DW_FORM_SDATA: u16 = 0x0d;
+pub const DW_FORM_STRP: u16 = 0x0e;
+pub const DW_FORM_UDATA: u16 = 0x0f;
+pub const DW_FORM_REF_ADDR: u16 = 0x10;
+pub const DW_FORM_REF1: u16 = 0x11;
+pub const DW_FORM_REF2: u16 = 0x12;
+pub const DW_FORM_REF4: u16 = 0x13;
+pub const DW_FORM_REF8: u16 = 0x14;
+pub const DW_FORM_REF_UDATA: u16 = 0x15;
+pub const DW_FORM_INDIRECT: u16 = 0x16;
+pub const DW_FORM_SEC_OFFSET: u16 = 0x17;
+pub const DW_FORM_EXPRLOC: u16 = 0x18;
+pub const DW_FORM_FLAG_PRESENT: u16 = 0x19;
+pub const DW_FORM_STRX: u16 = 0x1a;
+pub const DW_FORM_ADDRX: u16 = 0x1b;
+pub const DW_FORM_REF_SUP4: u16 = 0x1c;
+pub const DW_FORM_STRP_SUP: u16 = 0x1d;
+pub const DW_FORM_DATA16: u16 = 0x1e;
+pub const DW_FORM_LINE_STRP: u16 = 0x1f;
+pub const DW_FORM_REF_SIG8: u16 = 0x20;
+pub const DW_FORM_IMPLICIT_CONST: u16 = 0x21;
+pub const DW_FORM_LOCLISTX: u16 = 0x22;
+pub const DW_FORM_RNGLISTX: u16 = 0x23;
+pub const DW_FORM_REF_SUP8: u16 = 0x24;
+pub const DW_FORM_STRX1: u16 = 0x25;
+pub const DW_FORM_STRX2: u16 = 0x26;
+pub const DW_FORM_STRX3: u16 = 0x27;
+pub const DW_FORM_STRX4: u16 = 0x28;
+pub const DW_FORM_ADDRX1: u16 = 0x29;
+pub const DW_FORM_ADDRX2: u16 = 0x2a;
+pub const DW_FORM_ADDRX3: u16 = 0x2b;
+pub const DW_FORM_ADDRX4: u16 = 0x2c;
+pub const DW_FORM_GNU_ADDR_INDEX: u16 = 0x1f01;
+pub const DW_FORM_GNU_STR_INDEX: u16 = 0x1f02;
+pub const DW_FORM_GNU_REF_ALT: u16 = 0x1f20;
+pub const DW_FORM_GNU_STRP_ALT: u16 = 0x1f21;
+
+// expression operations
+pub const DW_OP_ADDR: u8 = 0x03;
+pub const DW_OP_DEREF: u8 = 0x06;
+pub const DW_OP_CONST1U: u8 = 0x08;
+pub const DW_OP_CONST1S: u8 = 0x09;
+pub const DW_OP_CONST2U: u8 = 0x0a;
+pub const DW_OP_CONST2S: u8 = 0x0b;
+pub const DW_OP_CONST4U: u8 = 0x0c;
+pub const DW_OP_CONST4S: u8 = 0x0d;
+pub const DW_OP_CONST8U: u8 = 0x0e;
+pub const DW_OP_CONST8S: u8 = 0x0f;
+pub const DW_OP_CONSTU: u8 = 0x10;
+pub const DW_OP_CONSTS: u8 = 0x11;
+pub const DW_OP_DUP: u8 = 0x12;
+pub const DW_OP_DROP: u8 = 0x13;
+pub const DW_OP_OVER: u8 = 0x14;
+pub const DW_OP_PICK: u8 = 0x15;
+pub const DW_OP_SWAP: u8 = 0x16;
+pub const DW_OP_ROT: u8 = 0x17;
+pub const DW_OP_XDEREF: u8 = 0x18;
+pub const DW_OP_ABS: u8 = 0x19;
+pub const DW_OP_AND: u8 = 0x1a;
+pub const DW_OP_DIV: u8 = 0x1b;
+pub const DW_OP_MINUS: u8 = 0x1c;
+pub const DW_OP_MOD: u8 = 0x1d;
+pub const DW_OP_MUL: u8 = 0x1e;
+pub const DW_OP_NEG: u8 = 0x1f;
+pub const DW_OP_NOT: u8 = 0x20;
+pub const DW_OP_OR: u8 = 0x21;
+pub const DW_OP_PLUS: u8 = 0x22;
+pub const DW_OP_PLUS_UCONST: u8 = 0x23;
+pub const DW_OP_SHL: u8 = 0x24;
+pub const DW_OP_SHR: u8 = 0x25;
+pub const DW_OP_SHRA: u8 = 0x26;
+pub const DW_OP_XOR: u8 = 0x27;
+pub const DW_OP_BRA: u8 = 0x28;
+pub const DW_OP_EQ: u8 = 0x29;
+pub const DW_OP_GE: u8 = 0x2a;
+pub const DW_OP_GT: u8 = 0x2b;
+pub const DW_OP_LE: u8 = 0x2c;
+pub const DW_OP_LT: u8 = 0x2d;
+pub const DW_OP_NE: u8 = 0x2e;
+pub const DW_OP_SKIP: u8 = 0x2f;
+pub const DW_OP_LIT0: u8 = 0x30;
+pub const DW_OP_LIT31: u8 = 0x4f;
+pub const DW_OP_REG0: u8 = 0x50;
+pub const DW_OP_REG31: u8 = 0x6f;
+pub const DW_OP_BREG0: u8 = 0x70;
+pub const DW_OP_BREG31: u8 = 0x8f;
+pub const DW_OP_REGX: u8 = 0x90;
+pub const DW_OP_FBREG: u8 = 0x91;
+pub const DW_OP_BREGX: u8 = 0x92;
+pub const DW_OP_PIECE: u8 = 0x93;
+pub const DW_OP_DEREF_SIZE: u8 = 0x94;
+pub const DW_OP_XDEREF_SIZE: u8 = 0x95;
+pub const DW_OP_NOP: u8 = 0x96;
+pub const DW_OP_PUSH_OBJECT_ADDRESS: u8 = 0x97;
+pub const DW_OP_CALL2: u8 = 0x98;
+pub const DW_OP_CALL4: u8 = 0x99;
+pub const DW_OP_CALL_REF: u8 = 0x9a;
+pub const DW_OP_FORM_TLS_ADDRESS: u8 = 0x9b;
+pub const DW_OP_CALL_FRAME_CFA: u8 = 0x9c;
+pub const DW_OP_BIT_PIECE: u8 = 0x9d;
+pub const DW_OP_IMPLICIT_VALUE: u8 = 0x9e;
+pub const DW_OP_STACK_VALUE: u8 = 0x9f;
+pub const DW_OP_IMPLICIT_POINTER: u8 = 0xa0;
+pub const DW_OP_ADDRX: u8 = 0xa1;
+pub const DW_OP_CONSTX: u8 = 0xa2;
+pub const DW_OP_ENTRY_VALUE: u8 = 0xa3;
+pub const DW_OP_CONST_TYPE: u8 = 0xa4;
+pub const DW_OP_REGVAL_TYPE: u8 = 0xa5;
+pub const DW_OP_DEREF_TYPE: u8 = 0xa6;
+pub const DW_OP_XDEREF_TYPE: u8 = 0xa7;
+pub const DW_OP_CONVERT: u8 = 0xa8;
+pub const DW_OP_REINTERPRET: u8 = 0xa9;
+pub const DW_OP_GNU_PUSH_TLS_ADDRESS: u8 = 0xe0;
+pub const DW_OP_GNU_UNINIT: u8 = 0xf0;
+pub const DW_OP_GNU_IMPLICIT_POINTER: u8 = 0xf2;
+pub const DW_OP_GNU_ENTRY_VALUE: u8 = 0xf3;
+pub const DW_OP_GNU_CONST_TYPE: u8 = 0xf4;
+pub const DW_OP_GNU_REGVAL_TYPE: u8 = 0xf5;
+pub const DW_OP_GNU_DEREF_TYPE: u8 = 0xf6;
+pub const DW_OP_GNU_CONVERT: u8 = 0xf7;
+pub const DW_OP_GNU_REINTERPRET: u8 = 0xf9;
+pub const DW_OP_GNU_PARAMETER_REF: u8 = 0xfa;
+pub const DW_OP_GNU_ADDR_INDEX: u8 = 0xfb;
+pub const DW_OP_GNU_CONST_INDEX: u8 = 0xfc;
+pub const DW_OP_GNU_VARIABLE_VALUE: u8 = 0xfd;
+
+// location list entries (DWARF 5)
+pub const DW_LLE_END_OF_LIST: u8 = 0x00;
+pub const DW_LLE_BASE_ADDRESSX: u8 = 0x01;
+pub const DW_LLE_STARTX_ENDX: u8 = 0x02;
+pub const DW_LLE_STARTX_LENGTH: u8 = 0x03;
+pub const DW_LLE_OFFSET_PAIR: u8 = 0x04;
+pub const DW_LLE_DEFAULT_LOCATION: u8 = 0x05;
+pub const DW_LLE_BASE_ADDRESS: u8 = 0x06;
+pub const DW_LLE_START_END: u8 = 0x07;
+pub const DW_LLE_START_LENGTH: u8 = 0x08;
+
+// range list entries (DWARF 5)
+pub const DW_RLE_END_OF_LIST: u8 = 0x00;
+pub const DW_RLE_BASE_ADDRESSX: u8 = 0x01;
+pub const DW_RLE_STARTX_ENDX: u8 = 0x02;
+pub const DW_RLE_STARTX_LENGTH: u8 = 0x03;
+pub const DW_RLE_OFFSET_PAIR: u8 = 0x04;
+pub const DW_RLE_BASE_ADDRESS: u8 = 0x05;
+pub const DW_RLE_START_END: u8 = 0x06;
+pub const DW_RLE_START_LENGTH: u8 = 0x07;
+
+// section ids of a .dwp package index, 5, 7 and 8 changed meaning
+// between the GNU version 2 format and DWARF 5
+pub const DW_SECT_INFO: u32 = 1;
+pub const DW_SECT_V2_TYPES: u32 = 2;
+pub const DW_SECT_ABBREV: u32 = 3;
+pub const DW_SECT_LINE: u32 = 4;
+pub const DW_SECT_LOCLISTS: u32 = 5;
+pub const DW_SECT_V2_LOC: u32 = 5;
+pub const DW_SECT_STR_OFFSETS: u32 = 6;
+pub const DW_SECT_MACRO: u32 = 7;
+pub const DW_SECT_V2_MACINFO: u32 = 7;
+pub const DW_SECT_RNGLISTS: u32 = 8;
+pub const DW_SECT_V2_MACRO: u32 = 8;
+
+// .debug_names index attributes
+pub const DW_IDX_COMPILE_UNIT: u16 = 1;
+pub const DW_IDX_TYPE_UNIT: u16 = 2;
+pub const DW_IDX_DIE_OFFSET: u16 = 3;
+pub const DW_IDX_PARENT: u16 = 4;
+pub const DW_IDX_TYPE_HASH: u16 = 5;
+
+// line number program opcodes
+pub const DW_LNS_COPY: u8 = 0x01;
+pub const DW_LNS_ADVANCE_PC: u8 = 0x02;
+pub const DW_LNS_ADVANCE_LINE: u8 = 0x03;
+pub const DW_LNS_SET_FILE: u8 = 0x04;
+pub const DW_LNS_SET_COLUMN: u8 = 0x05;
+pub const DW_LNS_NEGATE_STMT: u8 = 0x06;
+pub const DW_LNS_SET_BASIC_BLOCK: u8 = 0x07;
+pub const DW_LNS_CONST_ADD_PC: u8 = 0x08;
+pub const DW_LNS_FIXED_ADVANCE_PC: u8 = 0x09;
+pub const DW_LNS_SET_PROLOGUE_END: u8 = 0x0a;
+pub const DW_LNS_SET_EPILOGUE_BEGIN: u8 = 0x0b;
+pub const DW_LNS_SET_ISA: u8 = 0x0c;
+pub const DW_LNE_END_SEQUENCE: u8 = 0x01;
+pub const DW_LNE_SET_ADDRESS: u8 = 0x02;
+pub const DW_LNE_DEFINE_FILE: u8 = 0x03;
+pub const DW_LNE_SET_DISCRIMINATOR: u8 = 0x04;
+
+// line number header entry formats (DWARF 5)
+pub const DW_LNCT_PATH: u16 = 0x1;
+pub const DW_LNCT_DIRECTORY_INDEX: u16 = 0x2;
+pub const DW_LNCT_TIMESTAMP: u16 = 0x3;
+pub const DW_LNCT_SIZE: u16 = 0x4;
+pub const DW_LNCT_MD5: u16 = 0x5;
+
+// macro information entries, .debug_macro (DWARF 5 and the GNU
+// extension for earlier versions, which uses the same values)
+pub const DW_MACRO_DEFINE: u8 = 0x01;
+pub const DW_MACRO_UNDEF: u8 = 0x02;
+pub const DW_MACRO_START_FILE: u8 = 0x03;
+pub const DW_MACRO_END_FILE: u8 = 0x04;
+pub const DW_MACRO_DEFINE_STRP: u8 = 0x05;
+pub const DW_MACRO_UNDEF_STRP: u8 = 0x06;
+pub const DW_MACRO_IMPORT: u8 = 0x07;
+pub const DW_MACRO_DEFINE_SUP: u8 = 0x08;
+pub const DW_MACRO_UNDEF_SUP: u8 = 0x09;
+pub const DW_MACRO_IMPORT_SUP: u8 = 0x0a;
+pub const DW_MACRO_DEFINE_STRX: u8 = 0x0b;
+pub const DW_MACRO_UNDEF_STRX: u8 = 0x0c;
+
+// macro information entries, .debug_macinfo (DWARF 2 to 4)
+pub const DW_MACINFO_DEFINE: u8 = 0x01;
+pub const DW_MACINFO_UNDEF: u8 = 0x02;
+pub const DW_MACINFO_START_FILE: u8 = 0x03;
+pub const DW_MACINFO_END_FILE: u8 = 0x04;
+pub const DW_MACINFO_VENDOR_EXT: u8 = 0xff;
+
+// source languages
+pub const DW_LANG_C89: u64 = 0x0001;
+pub const DW_LANG_C: u64 = 0x0002;
+pub const DW_LANG_ADA83: u64 = 0x0003;
+pub const DW_LANG_C_PLUS_PLUS: u64 = 0x0004;
+pub const DW_LANG_COBOL74: u64 = 0x0005;
+pub const DW_LANG_COBOL85: u64 = 0x0006;
+pub const DW_LANG_FORTRAN77: u64 = 0x0007;
+pub const DW_LANG_FORTRAN90: u64 = 0x0008;
+pub const DW_LANG_PASCAL83: u64 = 0x0009;
+pub const DW_LANG_MODULA2: u64 = 0x000a;
+pub const DW_LANG_JAVA: u64 = 0x000b;
+pub const DW_LANG_C99: u64 = 0x000c;
+pub const DW_LANG_ADA95: u64 = 0x000d;
+pub const DW_LANG_FORTRAN95: u64 = 0x000e;
+pub const DW_LANG_PLI: u64 = 0x000f;
+pub const DW_LANG_OBJC: u64 = 0x0010;
+pub const DW_LANG_OBJC_PLUS_PLUS: u64 = 0x0011;
+pub const DW_LANG_UPC: u64 = 0x0012;
+pub const DW_LANG_D: u64 = 0x0013;
+pub const DW_LANG_PYTHON: u64 = 0x0014;
+pub const DW_LANG_OPENCL: u64 = 0x0015;
+pub const DW_LANG_GO: u64 = 0x0016;
+pub const DW_LANG_MODULA3: u64 = 0x0017;
+pub const DW_LANG_HASKELL: u64 = 0x0018;
+pub const DW_LANG_C_PLUS_PLUS_03: u64 = 0x0019;
+pub const DW_LANG_C_PLUS_PLUS_11: u64 = 0x001a;
+pub const DW_LANG_OCAML: u64 = 0x001b;
+pub const DW_LANG_RUST: u64 = 0x001c;
+pub const DW_LANG_C11: u64 = 0x001d;
+pub const DW_LANG_SWIFT: u64 = 0x001e;
+pub const DW_LANG_JULIA: u64 = 0x001f;
+pub const DW_LANG_DYLAN: u64 = 0x0020;
+pub const DW_LANG_C_PLUS_PLUS_14: u64 = 0x0021;
+pub const DW_LANG_FORTRAN03: u64 = 0x0022;
+pub const DW_LANG_FORTRAN08: u64 = 0x0023;
+pub const DW_LANG_RENDERSCRIPT: u64 = 0x0024;
+pub const DW_LANG_BLISS: u64 = 0x0025;
+pub const DW_LANG_MIPS_ASSEMBLER: u64 = 0x8001;
+
+// call frame instructions
+pub const DW_CFA_ADVANCE_LOC: u8 = 0x40;
+pub const DW_CFA_OFFSET: u8 = 0x80;
+pub const DW_CFA_RESTORE: u8 = 0xc0;
+pub const DW_CFA_NOP: u8 = 0x00;
+pub const DW_CFA_SET_LOC: u8 = 0x01;
+pub const DW_CFA_ADVANCE_LOC1: u8 = 0x02;
+pub const DW_CFA_ADVANCE_LOC2: u8 = 0x03;
+pub const DW_CFA_ADVANCE_LOC4: u8 = 0x04;
+pub const DW_CFA_OFFSET_EXTENDED: u8 = 0x05;
+pub const DW_CFA_RESTORE_EXTENDED: u8 = 0x06;
+pub const DW_CFA_UNDEFINED: u8 = 0x07;
+pub const DW_CFA_SAME_VALUE: u8 = 0x08;
+pub const DW_CFA_REGISTER: u8 = 0x09;
+pub const DW_CFA_REMEMBER_STATE: u8 = 0x0a;
+pub const DW_CFA_RESTORE_STATE: u8 = 0x0b;
+pub const DW_CFA_DEF_CFA: u8 = 0x0c;
+pub const DW_CFA_DEF_CFA_REGISTER: u8 = 0x0d;
+pub const DW_CFA_DEF_CFA_OFFSET: u8 = 0x0e;
+pub const DW_CFA_DEF_CFA_EXPRESSION: u8 = 0x0f;
+pub const DW_CFA_EXPRESSION: u8 = 0x10;
+pub const DW_CFA_OFFSET_EXTENDED_SF: u8 = 0x11;
+pub const DW_CFA_DEF_CFA_SF: u8 = 0x12;
+pub const DW_CFA_DEF_CFA_OFFSET_SF: u8 = 0x13;
+pub const DW_CFA_VAL_OFFSET: u8 = 0x14;
+pub const DW_CFA_VAL_OFFSET_SF: u8 = 0x15;
+pub const DW_CFA_VAL_EXPRESSION: u8 = 0x16;
+pub const DW_CFA_GNU_ARGS_SIZE: u8 = 0x2e;
+pub const DW_CFA_GNU_NEGATIVE_OFFSET_EXTENDED: u8 = 0x2f;
+
+// pointer encodings used by .eh_frame
+pub const DW_EH_PE_ABSPTR: u8 = 0x00;
+pub const DW_EH_PE_ULEB128: u8 = 0x01;
+pub const DW_EH_PE_UDATA2: u8 = 0x02;
+pub const DW_EH_PE_UDATA4: u8 = 0x03;
+pub const DW_EH_PE_UDATA8: u8 = 0x04;
+pub const DW_EH_PE_SLEB128: u8 = 0x09;
+pub const DW_EH_PE_SDATA2: u8 = 0x0a;
+pub const DW_EH_PE_SDATA4: u8 = 0x0b;
+pub const DW_EH_PE_SDATA8: u8 = 0x0c;
+pub const DW_EH_PE_PCREL: u8 = 0x10;
+pub const DW_EH_PE_DATAREL: u8 = 0x30;
+pub const DW_EH_PE_INDIRECT: u8 = 0x80;
+pub const DW_EH_PE_OMIT: u8 = 0xff;
+
+pub fn op_name(op: u8) -> Option<String> {
+    let name: &str = match op {
+        DW_OP_LIT0..=DW_OP_LIT31 => {
+            return Some(format!("DW_OP_lit{}", op - DW_OP_LIT0));
+        },
+        DW_OP_REG0..=DW_OP_REG31 => {
+            return Some(format!("DW_OP_reg{}", op - DW_OP_REG0));
+        },
+        DW_OP_BREG0..=DW_OP_BREG31 => {
+            return Some(format!("DW_OP_breg{}", op - DW_OP_BREG0));
+        },
+        DW_OP_ADDR => "DW_OP_addr",
+        DW_OP_DEREF => "DW_OP_deref",
+        DW_OP_CONST1U => "DW_OP_const1u",
+        DW_OP_CONST1S => "DW_OP_const1s",
+        DW_OP_CONST2U => "DW_OP_const2u",
+        DW_OP_CONST2S => "DW_OP_const2s",
+        DW_OP_CONST4U => "DW_OP_const4u",
+        DW_OP_CONST4S => "DW_OP_const4s",
+        DW_OP_CONST8U => "DW_OP_const8u",
+        DW_OP_CONST8S => "DW_OP_const8s",
+        DW_OP_CONSTU => "DW_OP_constu",
+        DW_OP_CONSTS => "DW_OP_consts",
+        DW_OP_DUP => "DW_OP_dup",
+        DW_OP_DROP => "DW_OP_drop",
+        DW_OP_OVER => "DW_OP_over",
+        DW_OP_PICK => "DW_OP_pick",
+        DW_OP_SWAP => "DW_OP_swap",
+        DW_OP_ROT => "DW_OP_rot",
+        DW_OP_XDEREF => "DW_OP_xderef",
+        DW_OP_ABS => "DW_OP_abs",
+        DW_OP_AND => "DW_OP_and",
+        DW_OP_DIV => "DW_OP_div",
+        DW_OP_MINUS => "DW_OP_minus",
+        DW_OP_MOD => "DW_OP_mod",
+        DW_OP_MUL => "DW_OP_mul",
+        DW_OP_NEG => "DW_OP_neg",
+        DW_OP_NOT => "DW_OP_not",
+        DW_OP_OR => "DW_OP_or",
+        DW_OP_PLUS => "DW_OP_plus",
+        DW_OP_PLUS_UCONST => "DW_OP_plus_uconst",
+        DW_OP_SHL => "DW_OP_shl",
+        DW_OP_SHR => "DW_OP_shr",
+        DW_OP_SHRA => "DW_OP_shra",
+        DW_OP_XOR => "DW_OP_xor",
+        DW_OP_BRA => "DW_OP_bra",
+        DW_OP_EQ => "DW_OP_eq",
+        DW_OP_GE => "DW_OP_ge",
+        DW_OP_GT => "DW_OP_gt",
+        DW_OP_LE => "DW_OP_le",
+        DW_OP_LT => "DW_OP_lt",
+        DW_OP_NE => "DW_OP_ne",
+        DW_OP_SKIP => "DW_OP_skip",
+        DW_OP_REGX => "DW_OP_regx",
+        DW_OP_FBREG => "DW_OP_fbreg",
+        DW_OP_BREGX => "DW_OP_bregx",
+        DW_OP_PIECE => "DW_OP_piece",
+        DW_OP_DEREF_SIZE => "DW_OP_deref_size",
+        DW_OP_XDEREF_SIZE => "DW_OP_xderef_size",
+        DW_OP_NOP => "DW_OP_nop",
+        DW_OP_PUSH_OBJECT_ADDRESS => "DW_OP_push_object_address",
+        DW_OP_CALL2 => "DW_OP_call2",
+        DW_OP_CALL4 => "DW_OP_call4",
+        DW_OP_CALL_REF => "DW_OP_call_ref",
+        DW_OP_FORM_TLS_ADDRESS => "DW_OP_form_tls_address",
+        DW_OP_CALL_FRAME_CFA => "DW_OP_call_frame_cfa",
+        DW_OP_BIT_PIECE => "DW_OP_bit_piece",
+        DW_OP_IMPLICIT_VALUE => "DW_OP_implicit_value",
+        DW_OP_STACK_VALUE => "DW_OP_stack_value",
+        DW_OP_IMPLICIT_POINTER => "DW_OP_implicit_pointer",
+        DW_OP_ADDRX => "DW_OP_addrx",
+        DW_OP_CONSTX => "DW_OP_constx",
+        DW_OP_ENTRY_VALUE => "DW_OP_entry_value",
+        DW_OP_CONST_TYPE => "DW_OP_const_type",
+        DW_OP_REGVAL_TYPE => "DW_OP_regval_type",
+        DW_OP_DEREF_TYPE => "DW_OP_deref_type",
+        DW_OP_XDEREF_TYPE => "DW_OP_xderef_type",
+        DW_OP_CONVERT => "DW_OP_convert",
+        DW_OP_REINTERPRET => "DW_OP_reinterpret",
+        DW_OP_GNU_PUSH_TLS_ADDRESS => "DW_OP_GNU_push_tls_address",
+        DW_OP_GNU_UNINIT => "DW_OP_GNU_uninit",
+        DW_OP_GNU_IMPLICIT_POINTER => "DW_OP_GNU_implicit_pointer",
+        DW_OP_GNU_ENTRY_VALUE => "DW_OP_GNU_entry_value",
+        DW_OP_GNU_CONST_TYPE => "DW_OP_GNU_const_type",
+        DW_OP_GNU_REGVAL_TYPE => "DW_OP_GNU_regval_type",
+        DW_OP_GNU_DEREF_TYPE => "DW_OP_GNU_deref_type",
+        DW_OP_GNU_CONVERT => "DW_OP_GNU_convert",
+        DW_OP_GNU_REINTERPRET => "DW_OP_GNU_reinterpret",
+        DW_OP_GNU_PARAMETER_REF => "DW_OP_GNU_parameter_ref",
+        DW_OP_GNU_ADDR_INDEX => "DW_OP_GNU_addr_index",
+        DW_OP_GNU_CONST_INDEX => "DW_OP_GNU_const_index",
+        DW_OP_GNU_VARIABLE_VALUE => "DW_OP_GNU_variable_value",
+        _ => return None,
+    };
+    Some(String::from(name))
+}
+
+pub fn tag_name(tag: u16) -> Option<&'static str> {
+    let name: &str = match tag {
+        DW_TAG_ARRAY_TYPE => "DW_TAG_array_type",
+        DW_TAG_CLASS_TYPE => "DW_TAG_class_type",
+        DW_TAG_ENUMERATION_TYPE => "DW_TAG_enumeration_type",
+        DW_TAG_FORMAL_PARAMETER => "DW_TAG_formal_parameter",
+        DW_TAG_IMPORTED_DECLARATION => "DW_TAG_imported_declaration",
+        DW_TAG_LABEL => "DW_TAG_label",
+        DW_TAG_LEXICAL_BLOCK => "DW_TAG_lexical_block",
+        DW_TAG_MEMBER => "DW_TAG_member",
+        DW_TAG_POINTER_TYPE => "DW_TAG_pointer_type",
+        DW_TAG_REFERENCE_TYPE => "DW_TAG_reference_type",
+        DW_TAG_COMPILE_UNIT => "DW_TAG_compile_unit",
+        DW_TAG_STRING_TYPE => "DW_TAG_string_type",
+        DW_TAG_STRUCTURE_TYPE => "DW_TAG_structure_type",
+        DW_TAG_SUBROUTINE_TYPE => "DW_TAG_subroutine_type",
+        DW_TAG_TYPEDEF => "DW_TAG_typedef",
+        DW_TAG_UNION_TYPE => "DW_TAG_union_type",
+        DW_TAG_UNSPECIFIED_PARAMETERS => "DW_TAG_unspecified_parameters",
+        DW_TAG_INHERITANCE => "DW_TAG_inheritance",
+        DW_TAG_INLINED_SUBROUTINE => "DW_TAG_inlined_subroutine",
+        DW_TAG_PTR_TO_MEMBER_TYPE => "DW_TAG_ptr_to_member_type",
+        DW_TAG_SUBRANGE_TYPE => "DW_TAG_subrange_type",
+        DW_TAG_BASE_TYPE => "DW_TAG_base_type",
+        DW_TAG_CONST_TYPE => "DW_TAG_const_type",
+        DW_TAG_ENUMERATOR => "DW_TAG_enumerator",
+        DW_TAG_SUBPROGRAM => "DW_TAG_subprogram",
+        DW_TAG_TEMPLATE_TYPE_PARAMETER => "DW_TAG_template_type_param",
+        DW_TAG_TEMPLATE_VALUE_PARAMETER => "DW_TAG_template_value_param",
+        DW_TAG_VARIABLE => "DW_TAG_variable",
+        DW_TAG_VOLATILE_TYPE => "DW_TAG_volatile_type",
+        DW_TAG_RESTRICT_TYPE => "DW_TAG_restrict_type",
+        DW_TAG_NAMESPACE => "DW_TAG_namespace",
+        DW_TAG_IMPORTED_MODULE => "DW_TAG_imported_module",
+        DW_TAG_UNSPECIFIED_TYPE => "DW_TAG_unspecified_type",
+        DW_TAG_PARTIAL_UNIT => "DW_TAG_partial_unit",
+        DW_TAG_IMPORTED_UNIT => "DW_TAG_imported_unit",
+        DW_TAG_CONDITION => "DW_TAG_condition",
+        DW_TAG_SHARED_TYPE => "DW_TAG_shared_type",
+        DW_TAG_TYPE_UNIT => "DW_TAG_type_unit",
+        DW_TAG_RVALUE_REFERENCE_TYPE => "DW_TAG_rvalue_reference_type",
+        DW_TAG_DYNAMIC_TYPE => "DW_TAG_dynamic_type",
+        DW_TAG_ATOMIC_TYPE => "DW_TAG_atomic_type",
+        DW_TAG_CALL_SITE => "DW_TAG_call_site",
+        DW_TAG_CALL_SITE_PARAMETER => "DW_TAG_call_site_parameter",
+        DW_TAG_SKELETON_UNIT => "DW_TAG_skeleton_unit",
+        DW_TAG_IMMUTABLE_TYPE => "DW_TAG_immutable_type",
+        DW_TAG_GNU_CALL_SITE => "DW_TAG_GNU_call_site",
+        DW_TAG_GNU_CALL_SITE_PARAMETER => "DW_TAG_GNU_call_site_parameter",
+        _ => return None,
+    };
+    Some(name)
+}
+
+pub fn language_name(language: u64) -> Option<&'static str> {
+    let name: &str = match language {
+        DW_LANG_C89 => "DW_LANG_C89",
+        DW_LANG_C => "DW_LANG_C",
+        DW_LANG_ADA83 => "DW_LANG_Ada83",
+        DW_LANG_C_PLUS_PLUS => "DW_LANG_C_plus_plus",
+        DW_LANG_COBOL74 => "DW_LANG_Cobol74",
+        DW_LANG_COBOL85 => "DW_LANG_Cobol85",
+        DW_LANG_FORTRAN77 => "DW_LANG_Fortran77",
+        DW_LANG_FORTRAN90 => "DW_LANG_Fortran90",
+        DW_LANG_PASCAL83 => "DW_LANG_Pascal83",
+        DW_LANG_MODULA2 => "DW_LANG_Modula2",
+        DW_LANG_JAVA => "DW_LANG_Java",
+        DW_LANG_C99 => "DW_LANG_C99",
+        DW_LANG_ADA95 => "DW_LANG_Ada95",
+        DW_LANG_FORTRAN95 => "DW_LANG_Fortran95",
+        DW_LANG_PLI => "DW_LANG_PLI",
+        DW_LANG_OBJC => "DW_LANG_ObjC",
+        DW_LANG_OBJC_PLUS_PLUS => "DW_LANG_ObjC_plus_plus",
+        DW_LANG_UPC => "DW_LANG_UPC",
+        DW_LANG_D => "DW_LANG_D",
+        DW_LANG_PYTHON => "DW_LANG_Python",
+        DW_LANG_OPENCL => "DW_LANG_OpenCL",
+        DW_LANG_GO => "DW_LANG_Go",
+        DW_LANG_MODULA3 => "DW_LANG_Modula3",
+        DW_LANG_HASKELL => "DW_LANG_Haskell",
+        DW_LANG_C_PLUS_PLUS_03 => "DW_LANG_C_plus_plus_03",
+        DW_LANG_C_PLUS_PLUS_11 => "DW_LANG_C_plus_plus_11",
+        DW_LANG_OCAML => "DW_LANG_OCaml",
+        DW_LANG_RUST => "DW_LANG_Rust",
+        DW_LANG_C11 => "DW_LANG_C11",
+        DW_LANG_SWIFT => "DW_LANG_Swift",
+        DW_LANG_JULIA => "DW_LANG_Julia",
+        DW_LANG_DYLAN => "DW_LANG_Dylan",
+        DW_LANG_C_PLUS_PLUS_14 => "DW_LANG_C_plus_plus_14",
+        DW_LANG_FORTRAN03 => "DW_LANG_Fortran03",
+        DW_LANG_FORTRAN08 => "DW_LANG_Fortran08",
+        DW_LANG_RENDERSCRIPT => "DW_LANG_RenderScript",
+        DW_LANG_BLISS => "DW_LANG_BLISS",
+        DW_LANG_MIPS_ASSEMBLER => "DW_LANG_Mips_Assembler",
+        _ => return None,
+    };
+    Some(name)
+}
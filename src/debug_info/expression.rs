@@ -0,0 +1,794 @@
+use super::{AddressTable, Encoding, Reader};
+use super::dw_consts::*;
+use crate::Endian;
+
+// upper bound on executed operations, guards against DW_OP_skip/DW_OP_bra
+// loops in corrupt expressions
+const MAX_STEPS: usize = 0x10000;
+
+// One decoded operation of a DWARF expression
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Op<'a> {
+    Addr(u64),
+    AddrIndex(u64),
+    ConstIndex(u64),
+    Constu(u64),
+    Consts(i64),
+    // any operation without operands, identified by its opcode
+    Simple(u8),
+    Pick(u8),
+    PlusUconst(u64),
+    DerefSize(u8),
+    XderefSize(u8),
+    Skip(i16),
+    Bra(i16),
+    Call(u64),
+    Reg(u16),
+    Breg(u16, i64),
+    Fbreg(i64),
+    Piece(u64),
+    BitPiece(u64, u64),
+    ImplicitValue(&'a [u8]),
+    ImplicitPointer(u64, i64),
+    EntryValue(&'a [u8]),
+    ConstType(u64, &'a [u8]),
+    RegvalType(u16, u64),
+    DerefType(u8, u64),
+    XderefType(u8, u64),
+    Convert(u64),
+    Reinterpret(u64),
+    ParameterRef(u32),
+    VariableValue(u64),
+}
+
+// Where (a piece of) an object lives once an expression has been evaluated
+#[derive(Debug, Clone, PartialEq)]
+pub enum Location {
+    Empty,
+    Address(u64),
+    Register(u16),
+    Value(u64),
+    Bytes(Vec<u8>),
+    ImplicitPointer { die: u64, offset: i64 },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Piece {
+    pub location: Location,
+    pub size_bits: Option<u64>,
+    pub bit_offset: u64,
+}
+
+// Supplies the machine state an expression is evaluated against, e.g. a
+// live process or a core dump. Everything defaults to unavailable.
+pub trait Provider {
+    // value of a DWARF numbered register in the current frame
+    fn register(&self, _reg: u16) -> Option<u64> {
+        None
+    }
+
+    // value the register had on entry to the current function
+    fn entry_register(&self, _reg: u16) -> Option<u64> {
+        None
+    }
+
+    fn read_memory(&self, _addr: u64, _len: usize) -> Option<Vec<u8>> {
+        None
+    }
+
+    // canonical frame address of the current frame
+    fn call_frame_cfa(&self) -> Option<u64> {
+        None
+    }
+
+    // address of a thread local variable given its offset in the module's
+    // TLS block
+    fn tls_address(&self, _offset: u64) -> Option<u64> {
+        None
+    }
+}
+
+// Provider without any machine state, only constant expressions evaluate
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NoProvider;
+
+impl Provider for NoProvider {}
+
+// Values the expression needs from the surrounding DIEs
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Context<'a> {
+    pub frame_base: Option<u64>,
+    pub object_address: Option<u64>,
+    // added to DW_OP_addr operands for position independent objects
+    pub load_bias: u64,
+    // .debug_addr entries of the unit for DW_OP_addrx and DW_OP_constx
+    pub addresses: AddressTable<'a>,
+}
+
+// Evaluates DW_OP_entry_value sub-expressions: register reads return the
+// value the register had on function entry
+struct EntryProvider<'p>(&'p dyn Provider);
+
+impl Provider for EntryProvider<'_> {
+    fn register(&self, reg: u16) -> Option<u64> {
+        self.0.entry_register(reg)
+    }
+
+    fn read_memory(&self, addr: u64, len: usize) -> Option<Vec<u8>> {
+        self.0.read_memory(addr, len)
+    }
+}
+
+pub fn decode<'a>(reader: &mut Reader<'a>,
+                  encoding: Encoding) -> Result<(u8, Op<'a>), &'static str> {
+    let code: u8 = reader.u8()?;
+    let op: Op = match code {
+        DW_OP_ADDR => Op::Addr(reader.sized(encoding.address_size)?),
+        DW_OP_CONST1U => Op::Constu(reader.u8()? as u64),
+        DW_OP_CONST1S => Op::Consts(reader.u8()? as i8 as i64),
+        DW_OP_CONST2U => Op::Constu(reader.u16()? as u64),
+        DW_OP_CONST2S => Op::Consts(reader.u16()? as i16 as i64),
+        DW_OP_CONST4U => Op::Constu(reader.u32()? as u64),
+        DW_OP_CONST4S => Op::Consts(reader.u32()? as i32 as i64),
+        DW_OP_CONST8U => Op::Constu(reader.u64()?),
+        DW_OP_CONST8S => Op::Consts(reader.u64()? as i64),
+        DW_OP_CONSTU => Op::Constu(reader.uleb()?),
+        DW_OP_CONSTS => Op::Consts(reader.sleb()?),
+        DW_OP_LIT0..=DW_OP_LIT31 => Op::Constu((code - DW_OP_LIT0) as u64),
+        DW_OP_PICK => Op::Pick(reader.u8()?),
+        DW_OP_PLUS_UCONST => Op::PlusUconst(reader.uleb()?),
+        DW_OP_DEREF_SIZE => Op::DerefSize(reader.u8()?),
+        DW_OP_XDEREF_SIZE => Op::XderefSize(reader.u8()?),
+        DW_OP_SKIP => Op::Skip(reader.u16()? as i16),
+        DW_OP_BRA => Op::Bra(reader.u16()? as i16),
+        DW_OP_CALL2 => Op::Call(reader.u16()? as u64),
+        DW_OP_CALL4 => Op::Call(reader.u32()? as u64),
+        DW_OP_CALL_REF => Op::Call(reader.sized(encoding.offset_size)?),
+        DW_OP_REG0..=DW_OP_REG31 => Op::Reg((code - DW_OP_REG0) as u16),
+        DW_OP_REGX => Op::Reg(reader.uleb()? as u16),
+        DW_OP_BREG0..=DW_OP_BREG31 => {
+            Op::Breg((code - DW_OP_BREG0) as u16, reader.sleb()?)
+        },
+        DW_OP_BREGX => {
+            let reg: u16 = reader.uleb()? as u16;
+            Op::Breg(reg, reader.sleb()?)
+        },
+        DW_OP_FBREG => Op::Fbreg(reader.sleb()?),
+        DW_OP_PIECE => Op::Piece(reader.uleb()?),
+        DW_OP_BIT_PIECE => {
+            let size: u64 = reader.uleb()?;
+            Op::BitPiece(size, reader.uleb()?)
+        },
+        DW_OP_IMPLICIT_VALUE => {
+            let len: u64 = reader.uleb()?;
+            Op::ImplicitValue(reader.bytes(len as usize)?)
+        },
+        DW_OP_IMPLICIT_POINTER | DW_OP_GNU_IMPLICIT_POINTER => {
+            let die: u64 = if encoding.version == 2 {
+                reader.sized(encoding.address_size)?
+            }
+            else {
+                reader.sized(encoding.offset_size)?
+            };
+            Op::ImplicitPointer(die, reader.sleb()?)
+        },
+        DW_OP_ADDRX | DW_OP_GNU_ADDR_INDEX => Op::AddrIndex(reader.uleb()?),
+        DW_OP_CONSTX | DW_OP_GNU_CONST_INDEX => Op::ConstIndex(reader.uleb()?),
+        DW_OP_ENTRY_VALUE | DW_OP_GNU_ENTRY_VALUE => {
+            let len: u64 = reader.uleb()?;
+            Op::EntryValue(reader.bytes(len as usize)?)
+        },
+        DW_OP_CONST_TYPE | DW_OP_GNU_CONST_TYPE => {
+            let base_type: u64 = reader.uleb()?;
+            let len: u8 = reader.u8()?;
+            Op::ConstType(base_type, reader.bytes(len as usize)?)
+        },
+        DW_OP_REGVAL_TYPE | DW_OP_GNU_REGVAL_TYPE => {
+            let reg: u16 = reader.uleb()? as u16;
+            Op::RegvalType(reg, reader.uleb()?)
+        },
+        DW_OP_DEREF_TYPE | DW_OP_GNU_DEREF_TYPE => {
+            let size: u8 = reader.u8()?;
+            Op::DerefType(size, reader.uleb()?)
+        },
+        DW_OP_XDEREF_TYPE => {
+            let size: u8 = reader.u8()?;
+            Op::XderefType(size, reader.uleb()?)
+        },
+        DW_OP_CONVERT | DW_OP_GNU_CONVERT => Op::Convert(reader.uleb()?),
+        DW_OP_REINTERPRET | DW_OP_GNU_REINTERPRET => {
+            Op::Reinterpret(reader.uleb()?)
+        },
+        DW_OP_GNU_PARAMETER_REF => Op::ParameterRef(reader.u32()?),
+        DW_OP_GNU_VARIABLE_VALUE => {
+            Op::VariableValue(reader.sized(encoding.offset_size)?)
+        },
+        DW_OP_DEREF | DW_OP_DUP | DW_OP_DROP | DW_OP_OVER | DW_OP_SWAP
+        | DW_OP_ROT | DW_OP_XDEREF | DW_OP_ABS | DW_OP_AND | DW_OP_DIV
+        | DW_OP_MINUS | DW_OP_MOD | DW_OP_MUL | DW_OP_NEG | DW_OP_NOT
+        | DW_OP_OR | DW_OP_PLUS | DW_OP_SHL | DW_OP_SHR | DW_OP_SHRA
+        | DW_OP_XOR | DW_OP_EQ | DW_OP_GE | DW_OP_GT | DW_OP_LE | DW_OP_LT
+        | DW_OP_NE | DW_OP_NOP | DW_OP_PUSH_OBJECT_ADDRESS
+        | DW_OP_FORM_TLS_ADDRESS | DW_OP_GNU_PUSH_TLS_ADDRESS
+        | DW_OP_CALL_FRAME_CFA | DW_OP_STACK_VALUE | DW_OP_GNU_UNINIT => {
+            Op::Simple(code)
+        },
+        _ => return Err("Unknown DWARF expression operation."),
+    };
+    Ok((code, op))
+}
+
+// every operation of an expression together with its byte offset
+pub fn decode_all<'a>(expr: &'a [u8], encoding: Encoding)
+                      -> Result<Vec<(usize, u8, Op<'a>)>, &'static str> {
+    let mut reader: Reader<'a> = Reader::new(expr, 0, encoding.endian);
+    let mut ops: Vec<(usize, u8, Op<'a>)> = Vec::new();
+    while !reader.is_empty() {
+        let offset: usize = reader.offset;
+        let (code, op) = decode(&mut reader, encoding)?;
+        ops.push((offset, code, op));
+    }
+    Ok(ops)
+}
+
+// readable form of an expression, e.g. "DW_OP_fbreg: -20; DW_OP_piece: 4"
+pub fn disassemble(expr: &[u8], encoding: Encoding) -> Result<String, &'static str> {
+    let mut out: Vec<String> = Vec::new();
+    for (_, code, op) in decode_all(expr, encoding)? {
+        let name: String = op_name(code).unwrap_or_else(|| format!("DW_OP_{code:#x}"));
+        let operands: String = match op {
+            Op::Addr(addr) => format!("{addr:#x}"),
+            Op::AddrIndex(idx) | Op::ConstIndex(idx) => format!("{idx}"),
+            Op::Constu(value) if !(DW_OP_LIT0..=DW_OP_LIT31).contains(&code) => {
+                format!("{value}")
+            },
+            Op::Consts(value) => format!("{value}"),
+            Op::Pick(idx) => format!("{idx}"),
+            Op::PlusUconst(value) | Op::Piece(value) => format!("{value}"),
+            Op::DerefSize(size) | Op::XderefSize(size) => format!("{size}"),
+            Op::Skip(delta) | Op::Bra(delta) => format!("{delta}"),
+            Op::Call(die) => format!("<{die:#x}>"),
+            Op::Reg(reg) if code == DW_OP_REGX => format!("{reg}"),
+            Op::Breg(reg, offset) if code == DW_OP_BREGX => format!("{reg} {offset}"),
+            Op::Breg(_, offset) | Op::Fbreg(offset) => format!("{offset}"),
+            Op::BitPiece(size, offset) => format!("size {size} offset {offset}"),
+            Op::ImplicitValue(bytes) => {
+                let hex: Vec<String> = bytes.iter().map(|b| format!("{b:02x}")).collect();
+                format!("{} byte block: {}", bytes.len(), hex.join(" "))
+            },
+            Op::ImplicitPointer(die, offset) => format!("<{die:#x}> {offset}"),
+            Op::EntryValue(sub) => format!("({})", disassemble(sub, encoding)?),
+            Op::ConstType(base_type, bytes) => {
+                let hex: Vec<String> = bytes.iter().map(|b| format!("{b:02x}")).collect();
+                format!("<{base_type:#x}> {}", hex.join(" "))
+            },
+            Op::RegvalType(reg, base_type) => format!("{reg} <{base_type:#x}>"),
+            Op::DerefType(size, base_type) | Op::XderefType(size, base_type) => {
+                format!("{size} <{base_type:#x}>")
+            },
+            Op::Convert(base_type) | Op::Reinterpret(base_type) => {
+                format!("<{base_type:#x}>")
+            },
+            Op::ParameterRef(die) => format!("<{die:#x}>"),
+            Op::VariableValue(die) => format!("<{die:#x}>"),
+            _ => String::new(),
+        };
+        if operands.is_empty() {
+            out.push(name);
+        }
+        else {
+            out.push(format!("{name}: {operands}"));
+        }
+    }
+    Ok(out.join("; "))
+}
+
+// interpret a value of the generic type as signed
+fn signed(value: u64, encoding: Encoding) -> i64 {
+    let bits: u32 = encoding.address_size as u32 * 8;
+    if bits >= 64 {
+        value as i64
+    }
+    else {
+        ((value << (64 - bits)) as i64) >> (64 - bits)
+    }
+}
+
+// integer stored in target byte order
+pub fn bytes_to_value(bytes: &[u8], endian: Endian) -> u64 {
+    let mut value: u64 = 0;
+    match endian {
+        Endian::Big => {
+            for byte in bytes.iter().take(8) {
+                value = (value << 8) | *byte as u64;
+            }
+        },
+        _ => {
+            for (i, byte) in bytes.iter().take(8).enumerate() {
+                value |= (*byte as u64) << (i * 8);
+            }
+        },
+    }
+    value
+}
+
+// the low `len` bytes of a value in target byte order
+pub fn value_to_bytes(value: u64, len: usize, endian: Endian) -> Vec<u8> {
+    let len: usize = len.min(8);
+    match endian {
+        Endian::Big => value.to_be_bytes()[8 - len..].to_vec(),
+        _ => value.to_le_bytes()[..len].to_vec(),
+    }
+}
+
+fn pop(stack: &mut Vec<u64>) -> Result<u64, &'static str> {
+    stack.pop().ok_or("DWARF expression stack underflow.")
+}
+
+fn read_value(provider: &dyn Provider, addr: u64, size: u8,
+              encoding: Encoding) -> Result<u64, &'static str> {
+    if size == 0 || size > 8 {
+        return Err("Unsupported dereference size.");
+    }
+    let bytes: Vec<u8> = provider.read_memory(addr, size as usize)
+                                 .ok_or("Memory is not available.")?;
+    if bytes.len() < size as usize {
+        return Err("Memory is not available.");
+    }
+    Ok(bytes_to_value(&bytes, encoding.endian))
+}
+
+// Evaluate a DWARF expression or location description. The result is
+// one piece per DW_OP_piece/DW_OP_bit_piece, or a single piece without
+// a size for a location that is not composite.
+pub fn evaluate(expr: &[u8], encoding: Encoding, context: &Context,
+                provider: &dyn Provider) -> Result<Vec<Piece>, &'static str> {
+    let mask: u64 = encoding.max_address();
+    let mut reader: Reader = Reader::new(expr, 0, encoding.endian);
+    let mut stack: Vec<u64> = Vec::new();
+    let mut pieces: Vec<Piece> = Vec::new();
+    // location set by a register, stack value or implicit operation,
+    // only DW_OP_piece may follow it
+    let mut pending: Option<Location> = None;
+    let mut steps: usize = 0;
+
+    while !reader.is_empty() {
+        steps += 1;
+        if steps > MAX_STEPS {
+            return Err("DWARF expression does not terminate.");
+        }
+        let (_, op) = decode(&mut reader, encoding)?;
+        match op {
+            Op::Addr(addr) => stack.push(addr.wrapping_add(context.load_bias) & mask),
+            Op::Constu(value) => stack.push(value & mask),
+            Op::Consts(value) => stack.push(value as u64 & mask),
+            Op::AddrIndex(idx) => {
+                let addr: u64 = context.addresses.get(idx)?;
+                stack.push(addr.wrapping_add(context.load_bias) & mask);
+            },
+            Op::ConstIndex(idx) => stack.push(context.addresses.get(idx)? & mask),
+            Op::Pick(idx) => {
+                if idx as usize >= stack.len() {
+                    return Err("DWARF expression stack underflow.");
+                }
+                stack.push(stack[stack.len() - 1 - idx as usize]);
+            },
+            Op::PlusUconst(value) => {
+                let top: u64 = pop(&mut stack)?;
+                stack.push(top.wrapping_add(value) & mask);
+            },
+            Op::DerefSize(size) => {
+                let addr: u64 = pop(&mut stack)?;
+                stack.push(read_value(provider, addr, size, encoding)?);
+            },
+            Op::XderefSize(size) => {
+                let addr: u64 = pop(&mut stack)?;
+                // the address space identifier is not modelled
+                pop(&mut stack)?;
+                stack.push(read_value(provider, addr, size, encoding)?);
+            },
+            Op::DerefType(size, _) => {
+                let addr: u64 = pop(&mut stack)?;
+                stack.push(read_value(provider, addr, size, encoding)?);
+            },
+            Op::XderefType(size, _) => {
+                let addr: u64 = pop(&mut stack)?;
+                pop(&mut stack)?;
+                stack.push(read_value(provider, addr, size, encoding)?);
+            },
+            Op::Skip(delta) | Op::Bra(delta) => {
+                let jump: bool = match op {
+                    Op::Bra(_) => pop(&mut stack)? != 0,
+                    _ => true,
+                };
+                if jump {
+                    let target: i64 = reader.offset as i64 + delta as i64;
+                    if target < 0 || target as usize > expr.len() {
+                        return Err("DWARF expression branch out of bounds.");
+                    }
+                    reader.offset = target as usize;
+                }
+            },
+            Op::Call(_) => {
+                return Err("DW_OP_call2, DW_OP_call4 and DW_OP_call_ref are not supported.");
+            },
+            Op::Reg(reg) => pending = Some(Location::Register(reg)),
+            Op::Breg(reg, offset) => {
+                let value: u64 = provider.register(reg)
+                                         .ok_or("Register is not available.")?;
+                stack.push(value.wrapping_add(offset as u64) & mask);
+            },
+            Op::RegvalType(reg, _) => {
+                let value: u64 = provider.register(reg)
+                                         .ok_or("Register is not available.")?;
+                stack.push(value);
+            },
+            Op::Fbreg(offset) => {
+                let base: u64 = context.frame_base
+                                       .ok_or("Frame base is not available.")?;
+                stack.push(base.wrapping_add(offset as u64) & mask);
+            },
+            Op::Piece(size) | Op::BitPiece(size, _) => {
+                let (size_bits, bit_offset) = match op {
+                    Op::BitPiece(size, offset) => (size, offset),
+                    _ => (size.checked_mul(8).ok_or("DW_OP_piece size out of range.")?, 0),
+                };
+                let location: Location = match pending.take() {
+                    Some(location) => location,
+                    None => match stack.pop() {
+                        Some(addr) => Location::Address(addr),
+                        None => Location::Empty,
+                    },
+                };
+                pieces.push(Piece {
+                    location,
+                    size_bits: Some(size_bits),
+                    bit_offset,
+                });
+            },
+            Op::ImplicitValue(bytes) => pending = Some(Location::Bytes(bytes.to_vec())),
+            Op::ImplicitPointer(die, offset) => {
+                pending = Some(Location::ImplicitPointer { die, offset });
+            },
+            Op::EntryValue(sub) => {
+                let entry: EntryProvider = EntryProvider(provider);
+                let result: Vec<Piece> = evaluate(sub, encoding, context, &entry)?;
+                let value: u64 = match result.first().map(|p| &p.location) {
+                    Some(Location::Register(reg)) => {
+                        provider.entry_register(*reg)
+                                .ok_or("Entry value is not available.")?
+                    },
+                    Some(Location::Address(value)) | Some(Location::Value(value)) => *value,
+                    _ => return Err("Unsupported DW_OP_entry_value expression."),
+                };
+                stack.push(value);
+            },
+            Op::ConstType(_, bytes) => {
+                if bytes.len() > 8 {
+                    return Err("Typed constants wider than 8 bytes are not supported.");
+                }
+                stack.push(bytes_to_value(bytes, encoding.endian));
+            },
+            // values are kept untyped, conversions leave them unchanged
+            Op::Convert(_) | Op::Reinterpret(_) => (),
+            Op::ParameterRef(_) | Op::VariableValue(_) => {
+                return Err("DW_OP_GNU_parameter_ref and DW_OP_GNU_variable_value are not supported.");
+            },
+            Op::Simple(code) => {
+                evaluate_simple(code, &mut stack, &mut pending, encoding,
+                                context, provider)?;
+            },
+        }
+        if pending.is_some() && !reader.is_empty() {
+            // a location description may only be followed by a piece
+            let next: u8 = expr[reader.offset];
+            if next != DW_OP_PIECE && next != DW_OP_BIT_PIECE {
+                return Err("Location description not followed by DW_OP_piece.");
+            }
+        }
+    }
+
+    if pieces.is_empty() {
+        let location: Location = match pending {
+            Some(location) => location,
+            None => match stack.last() {
+                Some(addr) => Location::Address(*addr),
+                None => Location::Empty,
+            },
+        };
+        pieces.push(Piece {
+            location,
+            size_bits: None,
+            bit_offset: 0,
+        });
+    }
+
+    Ok(pieces)
+}
+
+fn evaluate_simple(code: u8, stack: &mut Vec<u64>,
+                   pending: &mut Option<Location>, encoding: Encoding,
+                   context: &Context,
+                   provider: &dyn Provider) -> Result<(), &'static str> {
+    let mask: u64 = encoding.max_address();
+    match code {
+        DW_OP_DEREF => {
+            let addr: u64 = pop(stack)?;
+            stack.push(read_value(provider, addr, encoding.address_size, encoding)?);
+        },
+        DW_OP_XDEREF => {
+            let addr: u64 = pop(stack)?;
+            pop(stack)?;
+            stack.push(read_value(provider, addr, encoding.address_size, encoding)?);
+        },
+        DW_OP_DUP => {
+            let top: u64 = *stack.last().ok_or("DWARF expression stack underflow.")?;
+            stack.push(top);
+        },
+        DW_OP_DROP => {
+            pop(stack)?;
+        },
+        DW_OP_OVER => {
+            if stack.len() < 2 {
+                return Err("DWARF expression stack underflow.");
+            }
+            stack.push(stack[stack.len() - 2]);
+        },
+        DW_OP_SWAP => {
+            let first: u64 = pop(stack)?;
+            let second: u64 = pop(stack)?;
+            stack.push(first);
+            stack.push(second);
+        },
+        DW_OP_ROT => {
+            let first: u64 = pop(stack)?;
+            let second: u64 = pop(stack)?;
+            let third: u64 = pop(stack)?;
+            stack.push(first);
+            stack.push(third);
+            stack.push(second);
+        },
+        DW_OP_ABS => {
+            let top: i64 = signed(pop(stack)?, encoding);
+            stack.push(top.unsigned_abs() & mask);
+        },
+        DW_OP_NEG => {
+            let top: i64 = signed(pop(stack)?, encoding);
+            stack.push(top.wrapping_neg() as u64 & mask);
+        },
+        DW_OP_NOT => {
+            let top: u64 = pop(stack)?;
+            stack.push(!top & mask);
+        },
+        DW_OP_AND | DW_OP_DIV | DW_OP_MINUS | DW_OP_MOD | DW_OP_MUL | DW_OP_OR
+        | DW_OP_PLUS | DW_OP_SHL | DW_OP_SHR | DW_OP_SHRA | DW_OP_XOR
+        | DW_OP_EQ | DW_OP_GE | DW_OP_GT | DW_OP_LE | DW_OP_LT | DW_OP_NE => {
+            let rhs: u64 = pop(stack)?;
+            let lhs: u64 = pop(stack)?;
+            let (slhs, srhs) = (signed(lhs, encoding), signed(rhs, encoding));
+            let value: u64 = match code {
+                DW_OP_AND => lhs & rhs,
+                DW_OP_OR => lhs | rhs,
+                DW_OP_XOR => lhs ^ rhs,
+                DW_OP_PLUS => lhs.wrapping_add(rhs),
+                DW_OP_MINUS => lhs.wrapping_sub(rhs),
+                DW_OP_MUL => lhs.wrapping_mul(rhs),
+                DW_OP_DIV => {
+                    if srhs == 0 {
+                        return Err("Division by zero in DWARF expression.");
+                    }
+                    slhs.wrapping_div(srhs) as u64
+                },
+                DW_OP_MOD => {
+                    if rhs == 0 {
+                        return Err("Division by zero in DWARF expression.");
+                    }
+                    lhs % rhs
+                },
+                DW_OP_SHL => if rhs >= 64 { 0 } else { lhs << rhs },
+                DW_OP_SHR => if rhs >= 64 { 0 } else { lhs >> rhs },
+                DW_OP_SHRA => (slhs >> rhs.min(63)) as u64,
+                DW_OP_EQ => (slhs == srhs) as u64,
+                DW_OP_GE => (slhs >= srhs) as u64,
+                DW_OP_GT => (slhs > srhs) as u64,
+                DW_OP_LE => (slhs <= srhs) as u64,
+                DW_OP_LT => (slhs < srhs) as u64,
+                _ => (slhs != srhs) as u64,
+            };
+            stack.push(value & mask);
+        },
+        DW_OP_NOP | DW_OP_GNU_UNINIT => (),
+        DW_OP_PUSH_OBJECT_ADDRESS => {
+            stack.push(context.object_address
+                              .ok_or("Object address is not available.")?);
+        },
+        DW_OP_FORM_TLS_ADDRESS | DW_OP_GNU_PUSH_TLS_ADDRESS => {
+            let offset: u64 = pop(stack)?;
+            stack.push(provider.tls_address(offset)
+                               .ok_or("Thread local storage is not available.")?);
+        },
+        DW_OP_CALL_FRAME_CFA => {
+            stack.push(provider.call_frame_cfa()
+                               .ok_or("Call frame address is not available.")?);
+        },
+        DW_OP_STACK_VALUE => {
+            *pending = Some(Location::Value(pop(stack)?));
+        },
+        _ => return Err("Unknown DWARF expression operation."),
+    }
+    Ok(())
+}
+
+// Read the bytes of an object described by evaluated pieces, `size` is
+// the object size used for a location that is not composite
+pub fn read_pieces(pieces: &[Piece], size: usize, encoding: Encoding,
+                   provider: &dyn Provider) -> Result<Vec<u8>, &'static str> {
+    let mut out: Vec<u8> = Vec::new();
+    for piece in pieces {
+        let len: usize = match piece.size_bits {
+            Some(bits) => bits.div_ceil(8) as usize,
+            None => size,
+        };
+        let bytes: Vec<u8> = match &piece.location {
+            Location::Empty => return Err("Value is optimized out."),
+            Location::Address(addr) => {
+                provider.read_memory(*addr, len).ok_or("Memory is not available.")?
+            },
+            Location::Register(reg) => {
+                let value: u64 = provider.register(*reg)
+                                         .ok_or("Register is not available.")?;
+                // bit pieces of a register count from its least significant bit
+                let value: u64 = value >> piece.bit_offset.min(63);
+                value_to_bytes(value, len, encoding.endian)
+            },
+            Location::Value(value) => value_to_bytes(*value, len, encoding.endian),
+            Location::Bytes(bytes) => bytes.iter().copied().take(len).collect(),
+            Location::ImplicitPointer { .. } => {
+                return Err("Value is an implicit pointer.");
+            },
+        };
+        out.extend_from_slice(&bytes);
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    const ENCODING: Encoding = Encoding {
+        address_size: 8,
+        offset_size: 4,
+        version: 5,
+        endian: Endian::Little,
+    };
+
+    // registers, 32 bytes of memory at 0xff0 holding 0, 1, 2, ... and a CFA
+    struct Machine {
+        registers: HashMap<u16, u64>,
+    }
+
+    impl Provider for Machine {
+        fn register(&self, reg: u16) -> Option<u64> {
+            self.registers.get(&reg).copied()
+        }
+
+        fn read_memory(&self, addr: u64, len: usize) -> Option<Vec<u8>> {
+            let memory: Vec<u8> = (0..32).collect();
+            let start: usize = addr.checked_sub(0xff0)? as usize;
+            memory.get(start..start.checked_add(len)?).map(|bytes| bytes.to_vec())
+        }
+
+        fn call_frame_cfa(&self) -> Option<u64> {
+            Some(0x1010)
+        }
+    }
+
+    fn machine() -> Machine {
+        Machine {
+            registers: HashMap::from([(0, 0x1122334455667788), (6, 0x1000)]),
+        }
+    }
+
+    fn run(expr: &[u8], encoding: Encoding) -> Result<Vec<Piece>, &'static str> {
+        let context: Context = Context {
+            frame_base: Some(0x1000),
+            ..Default::default()
+        };
+        evaluate(expr, encoding, &context, &machine())
+    }
+
+    // the value left by an expression ending in DW_OP_stack_value
+    fn value(expr: &[u8], encoding: Encoding) -> u64 {
+        match run(expr, encoding).unwrap().as_slice() {
+            [Piece { location: Location::Value(value), size_bits: None, .. }] => *value,
+            pieces => panic!("not a value: {pieces:?}"),
+        }
+    }
+
+    #[test]
+    fn stack_operations() {
+        // lit1 lit2 lit3 rot: 3 1 2, minus: 3 -1, mul: -3
+        assert_eq!(value(&[0x31, 0x32, 0x33, 0x17, 0x1c, 0x1e, 0x9f], ENCODING), -3i64 as u64);
+        // lit4 lit5 over swap: 4 4 5, pick 2: 4 4 5 4, drop minus plus: 3
+        assert_eq!(value(&[0x34, 0x35, 0x14, 0x16, 0x15, 0x02, 0x13, 0x1c, 0x22, 0x9f],
+                         ENCODING), 3);
+        // consts -7 lit2 div is signed, lit7 lit3 mod, consts -16 lit2 shra
+        assert_eq!(value(&[0x11, 0x79, 0x32, 0x1b, 0x9f], ENCODING), -3i64 as u64);
+        assert_eq!(value(&[0x37, 0x33, 0x1d, 0x9f], ENCODING), 1);
+        assert_eq!(value(&[0x11, 0x70, 0x32, 0x26, 0x9f], ENCODING), -4i64 as u64);
+        // plus_uconst and a comparison
+        assert_eq!(value(&[0x35, 0x23, 0x80, 0x01, 0x10, 0x85, 0x01, 0x29, 0x9f], ENCODING), 1);
+
+        // values of the generic type wrap at the address size
+        let encoding: Encoding = Encoding { address_size: 4, ..ENCODING };
+        assert_eq!(value(&[0x11, 0x7f, 0x9f], encoding), 0xffffffff);
+        assert_eq!(value(&[0x11, 0x7f, 0x31, 0x22, 0x9f], encoding), 0);
+        assert_eq!(value(&[0x11, 0x7f, 0x30, 0x2d, 0x9f], encoding), 1);
+
+        assert_eq!(run(&[0x12], ENCODING), Err("DWARF expression stack underflow."));
+        assert_eq!(run(&[0x31, 0x30, 0x1b], ENCODING),
+                   Err("Division by zero in DWARF expression."));
+    }
+
+    #[test]
+    fn branches_and_step_limit() {
+        // lit5, then lit1 minus dup bra -6 until the counter is 0
+        assert_eq!(value(&[0x35, 0x31, 0x1c, 0x12, 0x28, 0xfa, 0xff, 0x9f], ENCODING), 0);
+        // skip over lit1 to lit2
+        assert_eq!(value(&[0x2f, 0x01, 0x00, 0x31, 0x32, 0x9f], ENCODING), 2);
+        assert_eq!(run(&[0x2f, 0x10, 0x00], ENCODING),
+                   Err("DWARF expression branch out of bounds."));
+
+        // 1 + 0x3fff * 4 + 1 operations are within MAX_STEPS, counting
+        // down from 0x4000 is not
+        let mut expr: Vec<u8> = vec![0x10, 0xff, 0x7f, 0x31, 0x1c, 0x12, 0x28, 0xfa, 0xff, 0x9f];
+        assert_eq!(value(&expr, ENCODING), 0);
+        expr.splice(1..3, [0x80, 0x80, 0x01]);
+        assert_eq!(run(&expr, ENCODING), Err("DWARF expression does not terminate."));
+        assert_eq!(run(&[0x2f, 0xfd, 0xff], ENCODING),
+                   Err("DWARF expression does not terminate."));
+    }
+
+    #[test]
+    fn pieces() {
+        // reg0 piece 4, fbreg -16 piece 2, lit9 stack_value piece 2, piece 1
+        let expr: [u8; 13] = [0x50, 0x93, 0x04, 0x91, 0x70, 0x93, 0x02, 0x39, 0x9f, 0x93, 0x02,
+                              0x93, 0x01];
+        let pieces: Vec<Piece> = run(&expr, ENCODING).unwrap();
+        let locations: Vec<(Location, Option<u64>)> = pieces.iter()
+            .map(|p| (p.location.clone(), p.size_bits)).collect();
+        assert_eq!(locations, [(Location::Register(0), Some(32)),
+                               (Location::Address(0xff0), Some(16)),
+                               (Location::Value(9), Some(16)),
+                               (Location::Empty, Some(8))]);
+        assert_eq!(read_pieces(&pieces[..3], 0, ENCODING, &machine()),
+                   Ok(vec![0x88, 0x77, 0x66, 0x55, 0x00, 0x01, 0x09, 0x00]));
+        assert_eq!(read_pieces(&pieces, 0, ENCODING, &machine()),
+                   Err("Value is optimized out."));
+
+        assert_eq!(run(&[0x50, 0x31], ENCODING),
+                   Err("Location description not followed by DW_OP_piece."));
+        // a piece of 2^61 bytes has no size in bits
+        let mut huge: Vec<u8> = vec![0x93];
+        huge.extend_from_slice(&[0x80; 8]);
+        huge.push(0x20);
+        assert_eq!(run(&huge, ENCODING), Err("DW_OP_piece size out of range."));
+    }
+
+    #[test]
+    fn registers_and_frame_base() {
+        let address = |expr: &[u8]| -> Result<Location, &'static str> {
+            Ok(run(expr, ENCODING)?.remove(0).location)
+        };
+        // fbreg -16, breg6 8 deref, call_frame_cfa
+        assert_eq!(address(&[0x91, 0x70]), Ok(Location::Address(0xff0)));
+        assert_eq!(address(&[0x76, 0x08, 0x06]), Ok(Location::Address(0x1f1e1d1c1b1a1918)));
+        assert_eq!(address(&[0x9c]), Ok(Location::Address(0x1010)));
+        assert_eq!(address(&[0x73, 0x00]), Err("Register is not available."));
+        assert_eq!(address(&[0x76, 0x40, 0x06]), Err("Memory is not available."));
+        assert_eq!(evaluate(&[0x91, 0x70], ENCODING, &Context::default(), &machine()),
+                   Err("Frame base is not available."));
+    }
+}
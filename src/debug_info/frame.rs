@@ -0,0 +1,315 @@
+use super::{DwarfSections, Encoding, Reader};
+use super::dw_consts::*;
+use super::expression::{self, Context, Location, Provider};
+
+// How the canonical frame address is computed at some address
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CfaRule<'a> {
+    RegOffset(u16, i64),
+    Expression(&'a [u8]),
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Cie<'a> {
+    code_align: u64,
+    data_align: i64,
+    fde_encoding: u8,
+    has_augmentation_data: bool,
+    address_size: u8,
+    instructions: &'a [u8],
+}
+
+// Which call frame section is being read, they differ in CIE ids,
+// CIE pointers and address encodings
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    EhFrame,
+    DebugFrame,
+}
+
+// read a pointer encoded with a DW_EH_PE_* value, `section_addr` is the
+// virtual address of the start of the section for pc relative pointers
+fn read_encoded(reader: &mut Reader, encoding: u8, address_size: u8,
+                section_addr: u64) -> Result<u64, &'static str> {
+    if encoding == DW_EH_PE_OMIT {
+        return Ok(0);
+    }
+    let field_addr: u64 = section_addr.wrapping_add(reader.offset as u64);
+    let value: u64 = match encoding & 0x0f {
+        DW_EH_PE_ABSPTR => reader.sized(address_size)?,
+        DW_EH_PE_ULEB128 => reader.uleb()?,
+        DW_EH_PE_UDATA2 => reader.u16()? as u64,
+        DW_EH_PE_UDATA4 => reader.u32()? as u64,
+        DW_EH_PE_UDATA8 => reader.u64()?,
+        DW_EH_PE_SLEB128 => reader.sleb()? as u64,
+        DW_EH_PE_SDATA2 => reader.u16()? as i16 as i64 as u64,
+        DW_EH_PE_SDATA4 => reader.u32()? as i32 as i64 as u64,
+        DW_EH_PE_SDATA8 => reader.u64()?,
+        _ => return Err("Unknown pointer encoding."),
+    };
+    match encoding & 0x70 {
+        0 => Ok(value),
+        DW_EH_PE_PCREL => Ok(field_addr.wrapping_add(value)),
+        _ => Err("Unsupported pointer encoding."),
+    }
+}
+
+fn parse_cie<'a>(data: &'a [u8], offset: usize, kind: Kind,
+                 sections: &DwarfSections) -> Result<Cie<'a>, &'static str> {
+    let mut reader: Reader<'a> = Reader::new(data, offset, sections.endian);
+    let (length, offset_size) = reader.initial_length()?;
+    let end: usize = reader.offset + length as usize;
+    if end > data.len() {
+        return Err("Call frame entry out of bounds.");
+    }
+    reader.skip(offset_size as usize)?;
+
+    let mut cie: Cie = Cie {
+        address_size: sections.address_size,
+        fde_encoding: DW_EH_PE_ABSPTR,
+        ..Default::default()
+    };
+    let version: u8 = reader.u8()?;
+    let augmentation: &[u8] = reader.cstr()?;
+    if augmentation.starts_with(b"eh") {
+        reader.skip(sections.address_size as usize)?;
+    }
+    if kind == Kind::DebugFrame && version >= 4 {
+        cie.address_size = reader.u8()?;
+        reader.u8()?;
+    }
+    cie.code_align = reader.uleb()?;
+    cie.data_align = reader.sleb()?;
+    if version == 1 {
+        reader.u8()?;
+    }
+    else {
+        reader.uleb()?;
+    }
+
+    if augmentation.first() == Some(&b'z') {
+        cie.has_augmentation_data = true;
+        let len: u64 = reader.uleb()?;
+        let aug_end: usize = reader.offset + len as usize;
+        for byte in &augmentation[1..] {
+            match byte {
+                b'L' => {
+                    reader.u8()?;
+                },
+                b'R' => cie.fde_encoding = reader.u8()?,
+                b'P' => {
+                    let encoding: u8 = reader.u8()?;
+                    read_encoded(&mut reader, encoding & !DW_EH_PE_INDIRECT,
+                                 cie.address_size, 0)?;
+                },
+                _ => break,
+            }
+        }
+        reader.offset = aug_end;
+    }
+
+    cie.instructions = reader.data.get(reader.offset..end)
+                                  .ok_or("Call frame entry out of bounds.")?;
+    Ok(cie)
+}
+
+struct State<'a> {
+    rule: Option<CfaRule<'a>>,
+    saved: Vec<Option<CfaRule<'a>>>,
+}
+
+// run call frame instructions until the location passes `pc`, returns
+// false once that happened
+fn execute<'a>(instructions: &'a [u8], cie: &Cie, state: &mut State<'a>,
+               loc: &mut u64, pc: u64, sections: &DwarfSections,
+               section_addr: u64) -> Result<bool, &'static str> {
+    let mut reader: Reader<'a> = Reader::new(instructions, 0, sections.endian);
+    while !reader.is_empty() {
+        let byte: u8 = reader.u8()?;
+        let mut advance: u64 = 0;
+        match byte & 0xc0 {
+            DW_CFA_ADVANCE_LOC => advance = (byte & 0x3f) as u64 * cie.code_align,
+            DW_CFA_OFFSET => {
+                reader.uleb()?;
+            },
+            DW_CFA_RESTORE => (),
+            _ => match byte {
+                DW_CFA_NOP => (),
+                DW_CFA_REMEMBER_STATE => state.saved.push(state.rule),
+                DW_CFA_RESTORE_STATE => {
+                    state.rule = state.saved.pop()
+                                      .ok_or("Unbalanced DW_CFA_restore_state.")?;
+                },
+                DW_CFA_SET_LOC => {
+                    let target: u64 = read_encoded(&mut reader, cie.fde_encoding,
+                                                   cie.address_size, section_addr)?;
+                    if target > pc {
+                        return Ok(false);
+                    }
+                    *loc = target;
+                },
+                DW_CFA_ADVANCE_LOC1 => advance = reader.u8()? as u64 * cie.code_align,
+                DW_CFA_ADVANCE_LOC2 => advance = reader.u16()? as u64 * cie.code_align,
+                DW_CFA_ADVANCE_LOC4 => advance = reader.u32()? as u64 * cie.code_align,
+                DW_CFA_OFFSET_EXTENDED | DW_CFA_REGISTER | DW_CFA_VAL_OFFSET
+                | DW_CFA_GNU_NEGATIVE_OFFSET_EXTENDED => {
+                    reader.uleb()?;
+                    reader.uleb()?;
+                },
+                DW_CFA_OFFSET_EXTENDED_SF | DW_CFA_VAL_OFFSET_SF => {
+                    reader.uleb()?;
+                    reader.sleb()?;
+                },
+                DW_CFA_RESTORE_EXTENDED | DW_CFA_UNDEFINED | DW_CFA_SAME_VALUE
+                | DW_CFA_GNU_ARGS_SIZE => {
+                    reader.uleb()?;
+                },
+                DW_CFA_EXPRESSION | DW_CFA_VAL_EXPRESSION => {
+                    reader.uleb()?;
+                    let len: u64 = reader.uleb()?;
+                    reader.skip(len as usize)?;
+                },
+                DW_CFA_DEF_CFA => {
+                    let reg: u16 = reader.uleb()? as u16;
+                    state.rule = Some(CfaRule::RegOffset(reg, reader.uleb()? as i64));
+                },
+                DW_CFA_DEF_CFA_SF => {
+                    let reg: u16 = reader.uleb()? as u16;
+                    let offset: i64 = reader.sleb()? * cie.data_align;
+                    state.rule = Some(CfaRule::RegOffset(reg, offset));
+                },
+                DW_CFA_DEF_CFA_REGISTER => {
+                    let reg: u16 = reader.uleb()? as u16;
+                    state.rule = match state.rule {
+                        Some(CfaRule::RegOffset(_, offset)) => Some(CfaRule::RegOffset(reg, offset)),
+                        _ => Some(CfaRule::RegOffset(reg, 0)),
+                    };
+                },
+                DW_CFA_DEF_CFA_OFFSET | DW_CFA_DEF_CFA_OFFSET_SF => {
+                    let offset: i64 = if byte == DW_CFA_DEF_CFA_OFFSET {
+                        reader.uleb()? as i64
+                    }
+                    else {
+                        reader.sleb()? * cie.data_align
+                    };
+                    if let Some(CfaRule::RegOffset(reg, _)) = state.rule {
+                        state.rule = Some(CfaRule::RegOffset(reg, offset));
+                    }
+                },
+                DW_CFA_DEF_CFA_EXPRESSION => {
+                    let len: u64 = reader.uleb()?;
+                    state.rule = Some(CfaRule::Expression(reader.bytes(len as usize)?));
+                },
+                _ => return Err("Unknown call frame instruction."),
+            },
+        }
+        if advance != 0 {
+            if loc.wrapping_add(advance) > pc {
+                return Ok(false);
+            }
+            *loc = loc.wrapping_add(advance);
+        }
+    }
+    Ok(true)
+}
+
+fn search<'a>(data: &'a [u8], kind: Kind, section_addr: u64,
+              sections: &DwarfSections, pc: u64) -> Result<Option<CfaRule<'a>>, &'static str> {
+    let mut reader: Reader<'a> = Reader::new(data, 0, sections.endian);
+
+    while !reader.is_empty() {
+        let (length, offset_size) = reader.initial_length()?;
+        if length == 0 {
+            if kind == Kind::EhFrame {
+                break;
+            }
+            continue;
+        }
+        let end: usize = reader.offset + length as usize;
+        if end > data.len() {
+            return Err("Call frame entry out of bounds.");
+        }
+        let id_offset: usize = reader.offset;
+        let id: u64 = reader.sized(offset_size)?;
+        let is_cie: bool = match kind {
+            Kind::EhFrame => id == 0,
+            Kind::DebugFrame => id == 0xffffffff || id == u64::MAX,
+        };
+        if is_cie {
+            reader.offset = end;
+            continue;
+        }
+
+        let cie_offset: usize = match kind {
+            Kind::EhFrame => id_offset.checked_sub(id as usize)
+                                      .ok_or("Invalid CIE pointer.")?,
+            Kind::DebugFrame => id as usize,
+        };
+        let cie: Cie = parse_cie(data, cie_offset, kind, sections)?;
+        let (begin, range): (u64, u64) = match kind {
+            Kind::EhFrame => {
+                let begin: u64 = read_encoded(&mut reader, cie.fde_encoding,
+                                              cie.address_size, section_addr)?;
+                let range: u64 = read_encoded(&mut reader, cie.fde_encoding & 0x0f,
+                                              cie.address_size, 0)?;
+                (begin, range)
+            },
+            Kind::DebugFrame => {
+                let begin: u64 = reader.sized(cie.address_size)?;
+                (begin, reader.sized(cie.address_size)?)
+            },
+        };
+
+        if begin <= pc && pc < begin.wrapping_add(range) {
+            if cie.has_augmentation_data {
+                let len: u64 = reader.uleb()?;
+                reader.skip(len as usize)?;
+            }
+            let instructions: &'a [u8] = data.get(reader.offset..end)
+                                             .ok_or("Call frame entry out of bounds.")?;
+            let mut state: State = State {
+                rule: None,
+                saved: Vec::new(),
+            };
+            let mut loc: u64 = begin;
+            execute(cie.instructions, &cie, &mut state, &mut loc, u64::MAX,
+                    sections, section_addr)?;
+            execute(instructions, &cie, &mut state, &mut loc, pc,
+                    sections, section_addr)?;
+            return Ok(state.rule);
+        }
+        reader.offset = end;
+    }
+
+    Ok(None)
+}
+
+// CFA rule in effect at `pc`, looked up in .eh_frame and then .debug_frame
+pub fn cfa_rule<'a>(sections: &DwarfSections<'a>,
+                    pc: u64) -> Result<Option<CfaRule<'a>>, &'static str> {
+    if let Some(rule) = search(sections.eh_frame, Kind::EhFrame,
+                               sections.eh_frame_addr, sections, pc)? {
+        return Ok(Some(rule));
+    }
+    search(sections.debug_frame, Kind::DebugFrame, 0, sections, pc)
+}
+
+// evaluate a CFA rule against the registers of a provider
+pub fn evaluate_cfa(rule: CfaRule, encoding: Encoding,
+                    provider: &dyn Provider) -> Result<u64, &'static str> {
+    match rule {
+        CfaRule::RegOffset(reg, offset) => {
+            let value: u64 = provider.register(reg)
+                                     .ok_or("Register is not available.")?;
+            Ok(value.wrapping_add(offset as u64))
+        },
+        CfaRule::Expression(expr) => {
+            let pieces = expression::evaluate(expr, encoding, &Context::default(),
+                                              provider)?;
+            match pieces.first().map(|p| &p.location) {
+                Some(Location::Address(addr)) => Ok(*addr),
+                _ => Err("Unsupported CFA expression."),
+            }
+        },
+    }
+}
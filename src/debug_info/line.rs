@@ -0,0 +1,388 @@
+use super::{DwarfSections, Reader};
+use super::dw_consts::*;
+use super::unit::{self, AttrSpec, AttrValue, UnitHeader};
+
+// A file of the line number program header
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FileEntry {
+    pub path: String,
+    pub dir_index: u64,
+    pub timestamp: u64,
+    pub size: u64,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct LineHeader {
+    pub offset: usize,
+    pub unit_length: u64,
+    pub offset_size: u8,
+    pub version: u16,
+    pub address_size: u8,
+    pub min_inst_length: u8,
+    pub max_ops_per_inst: u8,
+    pub default_is_stmt: bool,
+    pub line_base: i8,
+    pub line_range: u8,
+    pub opcode_base: u8,
+    pub standard_opcode_lengths: Vec<u8>,
+    pub include_directories: Vec<String>,
+    pub file_names: Vec<FileEntry>,
+    pub program_offset: usize,
+    pub end: usize,
+}
+
+// One row of the line number matrix. `offset` is the position in
+// .debug_line of the opcode that appended the row.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct LineRow {
+    pub offset: usize,
+    pub address: u64,
+    pub file: u64,
+    pub line: u64,
+    pub column: u64,
+    pub is_stmt: bool,
+    pub basic_block: bool,
+    pub end_sequence: bool,
+    pub prologue_end: bool,
+    pub epilogue_begin: bool,
+    pub isa: u64,
+    pub discriminator: u64,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct LineProgram {
+    pub header: LineHeader,
+    pub rows: Vec<LineRow>,
+}
+
+// DWARF 5 directory and file name entries, described by a list of
+// (content type, form) pairs
+fn parse_entries(reader: &mut Reader, header: &LineHeader,
+                 sections: &DwarfSections) -> Result<Vec<FileEntry>, &'static str> {
+    let format_count: u8 = reader.u8()?;
+    let mut formats: Vec<AttrSpec> = Vec::with_capacity(format_count as usize);
+    for _ in 0..format_count {
+        formats.push(AttrSpec {
+            name: reader.uleb()? as u16,
+            form: reader.uleb()? as u16,
+            implicit_const: 0,
+        });
+    }
+    // the forms are read like those of a unit with the same encoding
+    let unit_header: UnitHeader = UnitHeader {
+        offset_size: header.offset_size,
+        address_size: header.address_size,
+        version: header.version,
+        ..Default::default()
+    };
+
+    let count: u64 = reader.uleb()?;
+    let mut entries: Vec<FileEntry> = Vec::new();
+    for _ in 0..count {
+        let mut entry: FileEntry = FileEntry {
+            ..Default::default()
+        };
+        for spec in formats.iter() {
+            let value: AttrValue = unit::parse_value(reader, *spec, &unit_header)?;
+            match spec.name {
+                DW_LNCT_PATH => {
+                    let path: &[u8] = sections.string(&value)
+                                              .ok_or("Invalid line table path.")?;
+                    entry.path = String::from_utf8_lossy(path).into_owned();
+                },
+                DW_LNCT_DIRECTORY_INDEX => entry.dir_index = value.udata().unwrap_or(0),
+                DW_LNCT_TIMESTAMP => entry.timestamp = value.udata().unwrap_or(0),
+                DW_LNCT_SIZE => entry.size = value.udata().unwrap_or(0),
+                _ => (),
+            }
+        }
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+pub fn parse_header(sections: &DwarfSections,
+                    offset: usize) -> Result<LineHeader, &'static str> {
+    let mut reader: Reader = Reader::new(sections.line, offset, sections.endian);
+    let mut header: LineHeader = LineHeader {
+        offset,
+        address_size: sections.address_size,
+        ..Default::default()
+    };
+
+    let (unit_length, offset_size) = reader.initial_length()?;
+    header.unit_length = unit_length;
+    header.offset_size = offset_size;
+    header.end = reader.offset.checked_add(unit_length as usize)
+                               .ok_or("Line table length out of bounds.")?;
+    if header.end > sections.line.len() {
+        return Err("Line table length out of bounds.");
+    }
+    reader.data = &sections.line[..header.end];
+
+    header.version = reader.u16()?;
+    if !(2..=5).contains(&header.version) {
+        return Err("Unsupported line table version.");
+    }
+    if header.version >= 5 {
+        header.address_size = reader.u8()?;
+        // segment selector size
+        reader.u8()?;
+    }
+    let header_length: u64 = reader.sized(offset_size)?;
+    header.program_offset = reader.offset.checked_add(header_length as usize)
+                                         .filter(|o| *o <= header.end)
+                                         .ok_or("Line table header length out of bounds.")?;
+    header.min_inst_length = reader.u8()?;
+    header.max_ops_per_inst = if header.version >= 4 { reader.u8()? } else { 1 };
+    header.default_is_stmt = reader.u8()? != 0;
+    header.line_base = reader.u8()? as i8;
+    header.line_range = reader.u8()?;
+    header.opcode_base = reader.u8()?;
+    if header.line_range == 0 {
+        return Err("Line table has a line range of 0.");
+    }
+    for _ in 1..header.opcode_base {
+        header.standard_opcode_lengths.push(reader.u8()?);
+    }
+
+    if header.version >= 5 {
+        header.include_directories = parse_entries(&mut reader, &header, sections)?
+            .into_iter().map(|entry| entry.path).collect();
+        header.file_names = parse_entries(&mut reader, &header, sections)?;
+    }
+    else {
+        loop {
+            let dir: &[u8] = reader.cstr()?;
+            if dir.is_empty() {
+                break;
+            }
+            header.include_directories.push(String::from_utf8_lossy(dir).into_owned());
+        }
+        loop {
+            let path: &[u8] = reader.cstr()?;
+            if path.is_empty() {
+                break;
+            }
+            header.file_names.push(FileEntry {
+                path: String::from_utf8_lossy(path).into_owned(),
+                dir_index: reader.uleb()?,
+                timestamp: reader.uleb()?,
+                size: reader.uleb()?,
+            });
+        }
+    }
+
+    Ok(header)
+}
+
+// run the line number program of the table at `offset`
+pub fn parse_program(sections: &DwarfSections,
+                     offset: usize) -> Result<LineProgram, &'static str> {
+    let mut header: LineHeader = parse_header(sections, offset)?;
+    let mut reader: Reader = Reader::new(&sections.line[..header.end],
+                                         header.program_offset, sections.endian);
+    let mut rows: Vec<LineRow> = Vec::new();
+    let initial: LineRow = LineRow {
+        file: 1,
+        line: 1,
+        is_stmt: header.default_is_stmt,
+        ..Default::default()
+    };
+    let mut row: LineRow = initial;
+    let mut op_index: u64 = 0;
+    let max_ops: u64 = header.max_ops_per_inst.max(1) as u64;
+    let min_inst: u64 = header.min_inst_length as u64;
+
+    // advance the address by a number of operations, VLIW targets count
+    // several operations per instruction
+    let advance = |row: &mut LineRow, op_index: &mut u64, operations: u64| {
+        let total: u64 = op_index.wrapping_add(operations);
+        row.address = row.address.wrapping_add(min_inst.wrapping_mul(total / max_ops));
+        *op_index = total % max_ops;
+    };
+
+    while !reader.is_empty() {
+        let opcode_offset: usize = reader.offset;
+        let opcode: u8 = reader.u8()?;
+        if opcode >= header.opcode_base {
+            // special opcode, advances address and line and appends a row
+            let adjusted: u8 = opcode - header.opcode_base;
+            advance(&mut row, &mut op_index, (adjusted / header.line_range) as u64);
+            let delta: i64 = header.line_base as i64 + (adjusted % header.line_range) as i64;
+            row.line = row.line.wrapping_add(delta as u64);
+            row.offset = opcode_offset;
+            rows.push(row);
+            row.basic_block = false;
+            row.prologue_end = false;
+            row.epilogue_begin = false;
+            row.discriminator = 0;
+            continue;
+        }
+        match opcode {
+            0x00 => {
+                let len: u64 = reader.uleb()?;
+                let body: &[u8] = reader.bytes(len as usize)?;
+                let mut ext: Reader = Reader::new(body, 0, sections.endian);
+                match ext.u8()? {
+                    DW_LNE_END_SEQUENCE => {
+                        row.end_sequence = true;
+                        row.offset = opcode_offset;
+                        rows.push(row);
+                        row = initial;
+                        op_index = 0;
+                    },
+                    DW_LNE_SET_ADDRESS => {
+                        row.address = ext.sized((len - 1) as u8)?;
+                        op_index = 0;
+                    },
+                    DW_LNE_DEFINE_FILE => {
+                        let path: &[u8] = ext.cstr()?;
+                        header.file_names.push(FileEntry {
+                            path: String::from_utf8_lossy(path).into_owned(),
+                            dir_index: ext.uleb()?,
+                            timestamp: ext.uleb()?,
+                            size: ext.uleb()?,
+                        });
+                    },
+                    DW_LNE_SET_DISCRIMINATOR => row.discriminator = ext.uleb()?,
+                    // unknown extended opcodes are skipped by their length
+                    _ => (),
+                }
+            },
+            DW_LNS_COPY => {
+                row.offset = opcode_offset;
+                rows.push(row);
+                row.basic_block = false;
+                row.prologue_end = false;
+                row.epilogue_begin = false;
+                row.discriminator = 0;
+            },
+            DW_LNS_ADVANCE_PC => {
+                let operations: u64 = reader.uleb()?;
+                advance(&mut row, &mut op_index, operations);
+            },
+            DW_LNS_ADVANCE_LINE => {
+                let delta: i64 = reader.sleb()?;
+                row.line = row.line.wrapping_add(delta as u64);
+            },
+            DW_LNS_SET_FILE => row.file = reader.uleb()?,
+            DW_LNS_SET_COLUMN => row.column = reader.uleb()?,
+            DW_LNS_NEGATE_STMT => row.is_stmt = !row.is_stmt,
+            DW_LNS_SET_BASIC_BLOCK => row.basic_block = true,
+            DW_LNS_CONST_ADD_PC => {
+                let adjusted: u8 = 255 - header.opcode_base;
+                advance(&mut row, &mut op_index, (adjusted / header.line_range) as u64);
+            },
+            DW_LNS_FIXED_ADVANCE_PC => {
+                row.address = row.address.wrapping_add(reader.u16()? as u64);
+                op_index = 0;
+            },
+            DW_LNS_SET_PROLOGUE_END => row.prologue_end = true,
+            DW_LNS_SET_EPILOGUE_BEGIN => row.epilogue_begin = true,
+            DW_LNS_SET_ISA => row.isa = reader.uleb()?,
+            _ => {
+                // unknown standard opcode, skip its uleb operands
+                let count: u8 = header.standard_opcode_lengths
+                                      .get(opcode as usize - 1).copied().unwrap_or(0);
+                for _ in 0..count {
+                    reader.uleb()?;
+                }
+            },
+        }
+    }
+
+    Ok(LineProgram {
+        header,
+        rows,
+    })
+}
+
+impl LineHeader {
+    // path of a file of the table joined with its directory, the file
+    // numbers start at 1 before DWARF 5 and at 0 from then on
+    pub fn file_path(&self, file: u64) -> Option<String> {
+        let idx: usize = if self.version >= 5 {
+            file as usize
+        }
+        else {
+            (file as usize).checked_sub(1)?
+        };
+        let entry: &FileEntry = self.file_names.get(idx)?;
+        if entry.path.starts_with('/') {
+            return Some(entry.path.clone());
+        }
+        let dir_idx: Option<usize> = if self.version >= 5 {
+            Some(entry.dir_index as usize)
+        }
+        else {
+            // directory 0 is the compilation directory, not in the list
+            (entry.dir_index as usize).checked_sub(1)
+        };
+        match dir_idx.and_then(|i| self.include_directories.get(i)) {
+            Some(dir) => Some(format!("{dir}/{0}", entry.path)),
+            None => Some(entry.path.clone()),
+        }
+    }
+}
+
+impl LineProgram {
+    pub fn file_path(&self, file: u64) -> Option<String> {
+        self.header.file_path(file)
+    }
+
+    // the row describing `addr`: the last row at or below it within a
+    // sequence that covers it
+    pub fn row_for(&self, addr: u64) -> Option<&LineRow> {
+        let mut best: Option<&LineRow> = None;
+        let mut candidate: Option<&LineRow> = None;
+        for row in self.rows.iter() {
+            if row.end_sequence {
+                if let Some(found) = candidate {
+                    if addr < row.address
+                       && best.is_none_or(|b| found.address > b.address) {
+                        best = Some(found);
+                    }
+                }
+                candidate = None;
+                continue;
+            }
+            if row.address <= addr {
+                candidate = Some(row);
+            }
+        }
+        best
+    }
+}
+
+// A position in the source, as the line tables give it
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SourceLine {
+    pub path: String,
+    pub line: u64,
+    pub column: u64,
+}
+
+// The source line of `addr` from the line tables of .debug_line, the one
+// whose row lies closest below it when several cover it
+pub fn source_line(sections: &DwarfSections,
+                   addr: u64) -> Result<Option<SourceLine>, &'static str> {
+    let mut best: Option<(u64, SourceLine)> = None;
+    let mut offset: usize = 0;
+    while offset < sections.line.len() {
+        let program: LineProgram = parse_program(sections, offset)?;
+        if let Some(row) = program.row_for(addr) {
+            if best.as_ref().is_none_or(|(address, _)| row.address > *address) {
+                let path: String = program.file_path(row.file)
+                                          .unwrap_or_else(|| format!("file {0}", row.file));
+                best = Some((row.address, SourceLine {
+                    path,
+                    line: row.line,
+                    column: row.column,
+                }));
+            }
+        }
+        offset = program.header.end;
+    }
+    Ok(best.map(|(_, source)| source))
+}
@@ -0,0 +1,455 @@
+use std::collections::{HashMap, HashSet};
+use super::{DwarfSections, Reader};
+use super::aranges::{self, Arange, ArangeSet};
+use super::dw_consts::*;
+use super::line::{self, LineProgram, LineRow};
+use super::location;
+use super::ranges;
+use super::unit::{self, Abbrev, AttrValue, Unit, UnitHeader};
+
+// A problem found in the debug information, `offset` is relative to the
+// start of `section`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Problem {
+    pub check: &'static str,
+    pub section: &'static str,
+    pub offset: usize,
+    pub message: String,
+}
+
+// the names problems are reported under
+pub const CHECKS: [&str; 7] = [
+    "abbrev", "aranges", "die-tree", "line-sequence", "range-offset", "reference",
+    "string-offset",
+];
+
+#[derive(Debug, Default)]
+struct Linter {
+    problems: Vec<Problem>,
+}
+
+impl Linter {
+    fn report(&mut self, check: &'static str, section: &'static str, offset: usize,
+              message: String) {
+        self.problems.push(Problem {
+            check,
+            section,
+            offset,
+            message,
+        });
+    }
+}
+
+fn is_unit_tag(tag: u16) -> bool {
+    matches!(tag, DW_TAG_COMPILE_UNIT | DW_TAG_PARTIAL_UNIT | DW_TAG_TYPE_UNIT
+                  | DW_TAG_SKELETON_UNIT)
+}
+
+// Validate the abbreviation table at `offset`, every problem is reported.
+// Returns false when the table cannot be used to read entries.
+fn check_abbrevs(linter: &mut Linter, sections: &DwarfSections, offset: u64) -> bool {
+    let mut reader: Reader = Reader::new(sections.abbrev, offset as usize, sections.endian);
+    let mut codes: HashSet<u64> = HashSet::new();
+    let mut usable: bool = true;
+
+    loop {
+        let entry_offset: usize = reader.offset;
+        let code: u64 = match reader.uleb() {
+            Ok(code) => code,
+            Err(_) => {
+                linter.report("abbrev", ".debug_abbrev", entry_offset,
+                              String::from("abbreviation table is not terminated"));
+                return false;
+            },
+        };
+        if code == 0 {
+            break;
+        }
+        if !codes.insert(code) {
+            linter.report("abbrev", ".debug_abbrev", entry_offset,
+                          format!("duplicate abbreviation code {code}"));
+        }
+        let tag: u64 = reader.uleb().unwrap_or(0);
+        if tag == 0 {
+            linter.report("abbrev", ".debug_abbrev", entry_offset,
+                          format!("abbreviation {code} has tag 0"));
+        }
+        match reader.u8() {
+            Ok(0) | Ok(1) => (),
+            Ok(value) => {
+                linter.report("abbrev", ".debug_abbrev", entry_offset,
+                              format!("abbreviation {code} has children flag {value}"));
+            },
+            Err(_) => return false,
+        }
+        loop {
+            let spec_offset: usize = reader.offset;
+            let (name, form) = match (reader.uleb(), reader.uleb()) {
+                (Ok(name), Ok(form)) => (name, form),
+                _ => {
+                    linter.report("abbrev", ".debug_abbrev", spec_offset,
+                                  format!("attributes of abbreviation {code} are not terminated"));
+                    return false;
+                },
+            };
+            if name == 0 && form == 0 {
+                break;
+            }
+            if name == 0 || form == 0 {
+                linter.report("abbrev", ".debug_abbrev", spec_offset,
+                              format!("abbreviation {code} has attribute {name:#x} with form {form:#x}"));
+            }
+            if form == DW_FORM_IMPLICIT_CONST as u64 && reader.sleb().is_err() {
+                return false;
+            }
+            if !is_known_form(form) {
+                linter.report("abbrev", ".debug_abbrev", spec_offset,
+                              format!("abbreviation {code} uses unknown form {form:#x}"));
+                usable = false;
+            }
+        }
+    }
+
+    usable
+}
+
+fn is_known_form(form: u64) -> bool {
+    ((DW_FORM_ADDR as u64..=DW_FORM_ADDRX4 as u64).contains(&form) && form != 0x02)
+    || [DW_FORM_GNU_ADDR_INDEX, DW_FORM_GNU_STR_INDEX, DW_FORM_GNU_REF_ALT,
+        DW_FORM_GNU_STRP_ALT].iter().any(|f| *f as u64 == form)
+}
+
+// Walk the entries of a unit without building it, checking that the
+// tree is well formed. Returns false when the entries cannot be read.
+fn check_tree(linter: &mut Linter, sections: &DwarfSections, header: &UnitHeader,
+              abbrevs: &HashMap<u64, Abbrev>) -> bool {
+    let mut reader: Reader = Reader::new(&sections.info[..header.end],
+                                         header.entries_offset, sections.endian);
+    let mut depth: usize = 0;
+    let mut top_level: usize = 0;
+
+    while !reader.is_empty() {
+        let offset: usize = reader.offset;
+        let code: u64 = match reader.uleb() {
+            Ok(code) => code,
+            Err(_) => {
+                linter.report("die-tree", ".debug_info", offset,
+                              String::from("entry runs past the end of its unit"));
+                return false;
+            },
+        };
+        if code == 0 {
+            if depth == 0 {
+                linter.report("die-tree", ".debug_info", offset,
+                              String::from("null entry does not close a list of children"));
+            }
+            depth = depth.saturating_sub(1);
+            continue;
+        }
+        let abbrev: &Abbrev = match abbrevs.get(&code) {
+            Some(abbrev) => abbrev,
+            None => {
+                linter.report("abbrev", ".debug_info", offset,
+                              format!("entry uses undefined abbreviation code {code}"));
+                return false;
+            },
+        };
+        if depth == 0 {
+            top_level += 1;
+            if top_level == 1 && !is_unit_tag(abbrev.tag) {
+                linter.report("die-tree", ".debug_info", offset,
+                              format!("unit entry has tag {:#x}", abbrev.tag));
+            }
+            if top_level == 2 {
+                linter.report("die-tree", ".debug_info", offset,
+                              String::from("unit has more than one top level entry"));
+            }
+        }
+        else if is_unit_tag(abbrev.tag) {
+            linter.report("die-tree", ".debug_info", offset,
+                          String::from("unit entry nested inside another entry"));
+        }
+        for spec in abbrev.attrs.iter() {
+            if spec.form == DW_FORM_IMPLICIT_CONST && header.version < 5 {
+                linter.report("abbrev", ".debug_info", offset,
+                              String::from("DW_FORM_implicit_const used before DWARF 5"));
+            }
+            if let Err(e) = unit::parse_value(&mut reader, *spec, header) {
+                linter.report("die-tree", ".debug_info", offset,
+                              format!("attribute {0:#x}: {e}", spec.name));
+                return false;
+            }
+        }
+        if abbrev.has_children {
+            depth += 1;
+        }
+    }
+
+    if depth > 0 {
+        linter.report("die-tree", ".debug_info", header.end,
+                      format!("unit ends with {depth} unterminated lists of children"));
+    }
+    true
+}
+
+// check that an offset attribute points inside its section
+fn check_offset(linter: &mut Linter, check: &'static str, die_offset: usize,
+                name: &str, section: &str, data: &[u8], offset: u64) -> bool {
+    if offset as usize >= data.len() {
+        linter.report(check, ".debug_info", die_offset,
+                      format!("{name} offset {offset:#x} is outside of {section}"));
+        return false;
+    }
+    true
+}
+
+fn check_attributes(linter: &mut Linter, unit: &Unit, die_offsets: &HashSet<usize>) {
+    let sections: &DwarfSections = &unit.sections;
+    // a .dwo file checked on its own has no .debug_addr, addresses are
+    // only known together with the skeleton
+    let has_addresses: bool = !(unit.is_split() && sections.addr.is_empty());
+    let list_sections: [(&str, &[u8]); 2] = if unit.header.version >= 5 {
+        [(".debug_rnglists", sections.rnglists), (".debug_loclists", sections.loclists)]
+    }
+    else {
+        [(".debug_ranges", sections.ranges), (".debug_loc", sections.loc)]
+    };
+
+    for (idx, die) in unit.dies.iter().enumerate() {
+        for attr in die.attrs.iter() {
+            match attr.value {
+                AttrValue::UnitRef(offset) => {
+                    let target: usize = unit.header.offset + offset as usize;
+                    if unit.index_of(target).is_none() {
+                        linter.report("reference", ".debug_info", die.offset,
+                                      format!("reference {target:#x} of attribute {0:#x} is not an entry of its unit",
+                                              attr.name));
+                    }
+                    else if attr.name == DW_AT_SIBLING {
+                        let next: Option<usize> = unit.dies.get(unit.subtree(idx).end)
+                                                       .map(|d| d.offset);
+                        if next.is_some_and(|next| next != target) {
+                            linter.report("reference", ".debug_info", die.offset,
+                                          format!("DW_AT_sibling points to {target:#x} instead of the next sibling"));
+                        }
+                    }
+                },
+                AttrValue::RefAddr(offset) if !die_offsets.contains(&(offset as usize)) => {
+                    linter.report("reference", ".debug_info", die.offset,
+                                  format!("reference {offset:#x} of attribute {0:#x} is not an entry",
+                                          attr.name));
+                },
+                AttrValue::Strp(offset) => {
+                    check_offset(linter, "string-offset", die.offset, "string",
+                                 ".debug_str", sections.str, offset);
+                },
+                AttrValue::LineStrp(offset) => {
+                    check_offset(linter, "string-offset", die.offset, "string",
+                                 ".debug_line_str", sections.line_str, offset);
+                },
+                AttrValue::StrIndex(index) => {
+                    linter.report("string-offset", ".debug_info", die.offset,
+                                  format!("string index {index} is outside of .debug_str_offsets"));
+                },
+                AttrValue::AddrIndex(index) if has_addresses => {
+                    linter.report("range-offset", ".debug_info", die.offset,
+                                  format!("address index {index} is outside of .debug_addr"));
+                },
+                AttrValue::RnglistIndex(index) | AttrValue::LoclistIndex(index) => {
+                    linter.report("range-offset", ".debug_info", die.offset,
+                                  format!("list index {index} is outside of its offsets table"));
+                },
+                AttrValue::SecOffset(offset) if attr.name == DW_AT_RANGES => {
+                    let (section, data) = list_sections[0];
+                    if check_offset(linter, "range-offset", die.offset, "range list",
+                                    section, data, offset) && has_addresses {
+                        match ranges::die_ranges(unit, die) {
+                            Ok(ranges) => {
+                                for range in ranges.iter().filter(|r| r.begin > r.end) {
+                                    linter.report("range-offset", ".debug_info", die.offset,
+                                                  format!("range [{0:#x}, {1:#x}) ends before it begins",
+                                                          range.begin, range.end));
+                                }
+                            },
+                            Err(e) => {
+                                linter.report("range-offset", section, offset as usize,
+                                              format!("range list: {e}"));
+                            },
+                        }
+                    }
+                },
+                AttrValue::SecOffset(offset) | AttrValue::Data(offset)
+                    if matches!(attr.name, DW_AT_LOCATION | DW_AT_FRAME_BASE)
+                       && matches!(attr.form, DW_FORM_SEC_OFFSET | DW_FORM_DATA4
+                                              | DW_FORM_DATA8) => {
+                    let (section, data) = list_sections[1];
+                    if check_offset(linter, "range-offset", die.offset, "location list",
+                                    section, data, offset) && has_addresses {
+                        if let Err(e) = location::parse_attr(unit, attr.value) {
+                            linter.report("range-offset", section, offset as usize,
+                                          format!("location list: {e}"));
+                        }
+                    }
+                },
+                AttrValue::SecOffset(offset) | AttrValue::Data(offset)
+                    if attr.name == DW_AT_STMT_LIST => {
+                    check_offset(linter, "range-offset", die.offset, "line table",
+                                 ".debug_line", sections.line, offset);
+                },
+                _ => (),
+            }
+        }
+        if die.tag == DW_TAG_SUBPROGRAM || die.tag == DW_TAG_LEXICAL_BLOCK {
+            if let (Some(AttrValue::Addr(low)), Some(AttrValue::Addr(high)))
+                   = (die.attr(DW_AT_LOW_PC), die.attr(DW_AT_HIGH_PC)) {
+                if high < low {
+                    linter.report("range-offset", ".debug_info", die.offset,
+                                  format!("DW_AT_high_pc {high:#x} is below DW_AT_low_pc {low:#x}"));
+                }
+            }
+        }
+    }
+}
+
+fn check_units(linter: &mut Linter, sections: &DwarfSections) -> Vec<usize> {
+    let mut units: Vec<Unit> = Vec::new();
+    let mut unit_offsets: Vec<usize> = Vec::new();
+    let mut offset: usize = 0;
+
+    while offset < sections.info.len() {
+        let header: UnitHeader = match unit::parse_header(sections.info, offset, sections) {
+            Ok(header) => header,
+            Err(e) => {
+                // the following units cannot be found without this length
+                linter.report("die-tree", ".debug_info", offset,
+                              format!("unit header: {e}"));
+                break;
+            },
+        };
+        offset = header.end;
+        unit_offsets.push(header.offset);
+        if ![2, 4, 8].contains(&header.address_size) {
+            linter.report("die-tree", ".debug_info", header.offset,
+                          format!("unit has address size {0}", header.address_size));
+            continue;
+        }
+        if header.abbrev_offset as usize >= sections.abbrev.len() {
+            linter.report("abbrev", ".debug_info", header.offset,
+                          format!("abbreviation offset {0:#x} is outside of .debug_abbrev",
+                                  header.abbrev_offset));
+            continue;
+        }
+        if !check_abbrevs(linter, sections, header.abbrev_offset) {
+            continue;
+        }
+        let abbrevs = match unit::parse_abbrevs(sections.abbrev, header.abbrev_offset,
+                                                sections) {
+            Ok(abbrevs) => abbrevs,
+            Err(_) => continue,
+        };
+        if !check_tree(linter, sections, &header, &abbrevs) {
+            continue;
+        }
+        if let Ok(mut unit) = unit::parse_unit(sections, sections.info, header, &abbrevs) {
+            unit.resolve_indexes();
+            units.push(unit);
+        }
+    }
+
+    let die_offsets: HashSet<usize> = units.iter()
+        .flat_map(|unit| unit.dies.iter().map(|die| die.offset))
+        .collect();
+    for unit in units.iter() {
+        check_attributes(linter, unit, &die_offsets);
+    }
+    unit_offsets
+}
+
+fn check_aranges(linter: &mut Linter, sections: &DwarfSections, unit_offsets: &[usize]) {
+    let mut all: Vec<Arange> = Vec::new();
+    let mut offset: usize = 0;
+    while offset < sections.aranges.len() {
+        let set: ArangeSet = match aranges::parse_set(sections, offset) {
+            Ok(set) => set,
+            Err(e) => {
+                linter.report("aranges", ".debug_aranges", offset, e.to_string());
+                break;
+            },
+        };
+        if !unit_offsets.contains(&(set.info_offset as usize)) {
+            linter.report("aranges", ".debug_aranges", set.offset,
+                          format!("set refers to {0:#x}, which is not a unit in .debug_info",
+                                  set.info_offset));
+        }
+        // linkers move ranges of discarded functions to 0 or to a
+        // tombstone at the end of the address space
+        let max_address: u64 = if set.address_size >= 8 {
+            u64::MAX
+        }
+        else {
+            (1u64 << (set.address_size as u32 * 8)) - 1
+        };
+        all.extend(set.ranges.iter().filter(|r| {
+            r.range.begin < r.range.end && r.range.begin != 0
+            && r.range.begin < max_address - 1
+        }));
+        offset = set.end;
+    }
+
+    all.sort_by_key(|r| (r.range.begin, r.offset));
+    let mut previous: Option<Arange> = None;
+    for current in all.iter() {
+        if let Some(prev) = previous {
+            if current.range.begin < prev.range.end {
+                linter.report("aranges", ".debug_aranges", current.offset,
+                              format!("range [{0:#x}, {1:#x}) overlaps [{2:#x}, {3:#x}) at {4:#x}",
+                                      current.range.begin, current.range.end,
+                                      prev.range.begin, prev.range.end, prev.offset));
+            }
+        }
+        if previous.is_none_or(|prev| current.range.end > prev.range.end) {
+            previous = Some(*current);
+        }
+    }
+}
+
+fn check_lines(linter: &mut Linter, sections: &DwarfSections) {
+    let mut offset: usize = 0;
+    while offset < sections.line.len() {
+        let program: LineProgram = match line::parse_program(sections, offset) {
+            Ok(program) => program,
+            Err(e) => {
+                linter.report("line-sequence", ".debug_line", offset, e.to_string());
+                break;
+            },
+        };
+        let mut previous: Option<&LineRow> = None;
+        for row in program.rows.iter() {
+            if let Some(prev) = previous {
+                if row.address < prev.address {
+                    linter.report("line-sequence", ".debug_line", row.offset,
+                                  format!("sequence goes backwards from {0:#x} to {1:#x}",
+                                          prev.address, row.address));
+                }
+            }
+            previous = if row.end_sequence { None } else { Some(row) };
+        }
+        if previous.is_some() {
+            linter.report("line-sequence", ".debug_line", program.header.end,
+                          String::from("line table ends inside a sequence"));
+        }
+        offset = program.header.end;
+    }
+}
+
+// Check the debug information of an object and report every problem
+// found, grouped by the section they were found in
+pub fn check(sections: &DwarfSections) -> Vec<Problem> {
+    let mut linter: Linter = Linter {
+        ..Default::default()
+    };
+    let unit_offsets: Vec<usize> = check_units(&mut linter, sections);
+    check_aranges(&mut linter, sections, &unit_offsets);
+    check_lines(&mut linter, sections);
+    linter.problems
+}
@@ -0,0 +1,265 @@
+use super::{AddressTable, DwarfSections, Encoding};
+use super::dw_consts::*;
+use super::expression::{self, Context, Piece, Provider};
+use super::frame;
+use super::location::{self, LocationAttr};
+use super::ranges;
+use super::unit::{AttrValue, Die, Unit};
+
+// A parameter or local variable visible at some pc
+#[derive(Debug, Clone)]
+pub struct Variable<'a> {
+    pub name: String,
+    pub is_parameter: bool,
+    pub offset: usize,
+    pub byte_size: Option<u64>,
+    pub location: Option<LocationAttr<'a>>,
+    pub const_value: Option<AttrValue<'a>>,
+}
+
+// The innermost function containing a pc with the variables in scope
+#[derive(Debug, Clone)]
+pub struct Frame<'a> {
+    pub function: String,
+    pub pc: u64,
+    pub encoding: Encoding,
+    // sections of the unit, those of the .dwo file for split DWARF
+    pub sections: DwarfSections<'a>,
+    pub addresses: AddressTable<'a>,
+    pub frame_base: Option<LocationAttr<'a>>,
+    pub variables: Vec<Variable<'a>>,
+}
+
+// Provider wrapper that falls back to the call frame information of the
+// object when the provider cannot compute the CFA itself
+struct CfaProvider<'p, 's> {
+    provider: &'p dyn Provider,
+    sections: &'p DwarfSections<'s>,
+    encoding: Encoding,
+    pc: u64,
+}
+
+impl Provider for CfaProvider<'_, '_> {
+    fn register(&self, reg: u16) -> Option<u64> {
+        self.provider.register(reg)
+    }
+
+    fn entry_register(&self, reg: u16) -> Option<u64> {
+        self.provider.entry_register(reg)
+    }
+
+    fn read_memory(&self, addr: u64, len: usize) -> Option<Vec<u8>> {
+        self.provider.read_memory(addr, len)
+    }
+
+    fn call_frame_cfa(&self) -> Option<u64> {
+        if let Some(cfa) = self.provider.call_frame_cfa() {
+            return Some(cfa);
+        }
+        let rule = frame::cfa_rule(self.sections, self.pc).ok()??;
+        frame::evaluate_cfa(rule, self.encoding, self.provider).ok()
+    }
+
+    fn tls_address(&self, offset: u64) -> Option<u64> {
+        self.provider.tls_address(offset)
+    }
+}
+
+// size in bytes of the type an entry refers to, following typedefs and
+// qualifiers
+fn type_size(unit: &Unit, die: &Die) -> Option<u64> {
+    let mut current: &Die = unit.reference(die.attr(DW_AT_TYPE)?)?;
+    for _ in 0..16 {
+        if let Some(size) = current.attr(DW_AT_BYTE_SIZE).and_then(|v| v.udata()) {
+            return Some(size);
+        }
+        match current.tag {
+            DW_TAG_POINTER_TYPE | DW_TAG_REFERENCE_TYPE
+            | DW_TAG_RVALUE_REFERENCE_TYPE => {
+                return Some(unit.header.address_size as u64);
+            },
+            DW_TAG_TYPEDEF | DW_TAG_CONST_TYPE | DW_TAG_VOLATILE_TYPE
+            | DW_TAG_RESTRICT_TYPE | DW_TAG_ATOMIC_TYPE => {
+                current = unit.reference(current.attr(DW_AT_TYPE)?)?;
+            },
+            _ => return None,
+        }
+    }
+    None
+}
+
+// attribute of an entry or of its abstract origin
+fn inherited<'a>(unit: &Unit<'a>, die: &Die<'a>, name: u16) -> Option<AttrValue<'a>> {
+    if let Some(value) = die.attr(name) {
+        return Some(value);
+    }
+    let origin: &Die<'a> = unit.reference(die.attr(DW_AT_ABSTRACT_ORIGIN)?)?;
+    origin.attr(name)
+}
+
+fn in_scope(unit: &Unit, die: &Die, pc: u64) -> Result<bool, &'static str> {
+    let ranges: Vec<ranges::Range> = ranges::die_ranges(unit, die)?;
+    // blocks without addresses cover their whole parent
+    Ok(ranges.is_empty() || ranges.iter().any(|r| r.contains(pc)))
+}
+
+fn variables<'a>(unit: &Unit<'a>, idx: usize, pc: u64) -> Result<Vec<Variable<'a>>, &'static str> {
+    let mut variables: Vec<Variable<'a>> = Vec::new();
+    let subtree = unit.subtree(idx);
+    let mut i: usize = subtree.start;
+
+    while i < subtree.end {
+        let die: &Die<'a> = &unit.dies[i];
+        match die.tag {
+            DW_TAG_LEXICAL_BLOCK | DW_TAG_INLINED_SUBROUTINE
+            | DW_TAG_SUBPROGRAM => {
+                // descend only into scopes that contain the pc, nested
+                // functions are never part of the frame
+                if die.tag == DW_TAG_SUBPROGRAM
+                   || !in_scope(unit, die, pc)? {
+                    i = unit.subtree(i).end;
+                    continue;
+                }
+            },
+            DW_TAG_VARIABLE | DW_TAG_FORMAL_PARAMETER => {
+                let location: Option<LocationAttr<'a>> = match die.attr(DW_AT_LOCATION) {
+                    Some(value) => Some(location::parse_attr(unit, value)?),
+                    None => None,
+                };
+                let origin: &Die<'a> = die.attr(DW_AT_ABSTRACT_ORIGIN)
+                                          .and_then(|v| unit.reference(v))
+                                          .unwrap_or(die);
+                variables.push(Variable {
+                    name: unit.name(die).unwrap_or_default(),
+                    is_parameter: die.tag == DW_TAG_FORMAL_PARAMETER,
+                    offset: die.offset,
+                    byte_size: type_size(unit, die).or_else(|| type_size(unit, origin)),
+                    location,
+                    const_value: inherited(unit, die, DW_AT_CONST_VALUE),
+                });
+                i = unit.subtree(i).end;
+                continue;
+            },
+            _ => {
+                i = unit.subtree(i).end;
+                continue;
+            },
+        }
+        i += 1;
+    }
+
+    Ok(variables)
+}
+
+// find the function containing `pc` and the variables in scope there,
+// `units` come from unit::parse_units or split::units
+pub fn frame_at<'a>(units: &[Unit<'a>],
+                    pc: u64) -> Result<Option<Frame<'a>>, &'static str> {
+    for unit in units.iter() {
+        let root_in_scope: bool = match unit.dies.first() {
+            Some(root) => in_scope(unit, root, pc)?,
+            None => false,
+        };
+        if !root_in_scope {
+            continue;
+        }
+        for (idx, die) in unit.dies.iter().enumerate() {
+            if die.tag != DW_TAG_SUBPROGRAM {
+                continue;
+            }
+            let ranges: Vec<ranges::Range> = ranges::die_ranges(unit, die)?;
+            if !ranges.iter().any(|r| r.contains(pc)) {
+                continue;
+            }
+            let frame_base: Option<LocationAttr<'a>> = match die.attr(DW_AT_FRAME_BASE) {
+                Some(value) => Some(location::parse_attr(unit, value)?),
+                None => None,
+            };
+            return Ok(Some(Frame {
+                function: unit.name(die).unwrap_or_default(),
+                pc,
+                encoding: unit.encoding(),
+                sections: unit.sections,
+                addresses: unit.addresses(),
+                frame_base,
+                variables: variables(unit, idx, pc)?,
+            }));
+        }
+    }
+    Ok(None)
+}
+
+impl<'a> Frame<'a> {
+    // the caller's context completed with the address table of the unit
+    fn context<'c>(&'c self, context: &Context<'c>) -> Context<'c> {
+        let mut context: Context<'c> = *context;
+        if context.addresses.data.is_empty() {
+            context.addresses = self.addresses;
+        }
+        context
+    }
+
+    // value of the frame base at the frame's pc
+    pub fn frame_base(&self, context: &Context,
+                      provider: &dyn Provider) -> Result<u64, &'static str> {
+        let expr: &[u8] = self.frame_base.as_ref()
+                              .and_then(|attr| attr.at(self.pc))
+                              .ok_or("Function has no frame base.")?;
+        let context: Context = self.context(context);
+        let provider = CfaProvider {
+            provider,
+            sections: &self.sections,
+            encoding: self.encoding,
+            pc: self.pc,
+        };
+        let pieces: Vec<Piece> = expression::evaluate(expr, self.encoding,
+                                                      &context, &provider)?;
+        match pieces.first().map(|p| &p.location) {
+            Some(expression::Location::Address(addr)) => Ok(*addr),
+            // a register location means the frame base is that register
+            Some(expression::Location::Register(reg)) => {
+                provider.register(*reg).ok_or("Register is not available.")
+            },
+            _ => Err("Unsupported frame base."),
+        }
+    }
+
+    // evaluate the location of a variable at the frame's pc
+    pub fn locate(&self, variable: &Variable, context: &Context,
+                  provider: &dyn Provider) -> Result<Vec<Piece>, &'static str> {
+        let expr: &[u8] = variable.location.as_ref()
+                                  .and_then(|attr| attr.at(self.pc))
+                                  .ok_or("Value is optimized out.")?;
+        let mut context: Context = self.context(context);
+        if context.frame_base.is_none() {
+            context.frame_base = self.frame_base(&context, provider).ok();
+        }
+        let provider = CfaProvider {
+            provider,
+            sections: &self.sections,
+            encoding: self.encoding,
+            pc: self.pc,
+        };
+        expression::evaluate(expr, self.encoding, &context, &provider)
+    }
+
+    // read the bytes of a variable's value
+    pub fn read(&self, variable: &Variable, context: &Context,
+                provider: &dyn Provider) -> Result<Vec<u8>, &'static str> {
+        if variable.location.is_none() {
+            if let Some(value) = variable.const_value {
+                let size: usize = variable.byte_size.unwrap_or(8) as usize;
+                return match value {
+                    AttrValue::Block(bytes) => Ok(bytes.to_vec()),
+                    _ => {
+                        let value: u64 = value.udata().ok_or("Unsupported constant value.")?;
+                        Ok(expression::value_to_bytes(value, size, self.encoding.endian))
+                    },
+                };
+            }
+        }
+        let pieces: Vec<Piece> = self.locate(variable, context, provider)?;
+        let size: usize = variable.byte_size.ok_or("Variable size is unknown.")? as usize;
+        expression::read_pieces(&pieces, size, self.encoding, provider)
+    }
+}
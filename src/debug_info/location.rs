@@ -0,0 +1,187 @@
+use super::{AddressTable, Encoding, Reader};
+use super::dw_consts::*;
+use super::ranges::unit_base;
+use super::unit::{AttrValue, Unit};
+
+// One entry of a location list, the expression applies to [begin, end)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LocationEntry<'a> {
+    pub begin: u64,
+    pub end: u64,
+    // DW_LLE_default_location, applies wherever no other entry does
+    pub default: bool,
+    pub expr: &'a [u8],
+}
+
+// A DW_AT_location or DW_AT_frame_base attribute: either a single
+// expression or a location list
+#[derive(Debug, Clone, PartialEq)]
+pub enum LocationAttr<'a> {
+    Expr(&'a [u8]),
+    List(Vec<LocationEntry<'a>>),
+}
+
+impl<'a> LocationAttr<'a> {
+    // expression in effect at `pc`, None when the object has no location
+    // there (optimized out)
+    pub fn at(&self, pc: u64) -> Option<&'a [u8]> {
+        match self {
+            LocationAttr::Expr(expr) => Some(expr),
+            LocationAttr::List(entries) => {
+                entries.iter()
+                       .find(|e| !e.default && e.begin <= pc && pc < e.end)
+                       .or_else(|| entries.iter().find(|e| e.default))
+                       .map(|e| e.expr)
+            },
+        }
+    }
+}
+
+// pre DWARF 5 location list in .debug_loc
+pub fn parse_loc<'a>(data: &'a [u8], offset: u64, encoding: Encoding,
+                     base: u64) -> Result<Vec<LocationEntry<'a>>, &'static str> {
+    let mut reader: Reader<'a> = Reader::new(data, offset as usize, encoding.endian);
+    let mut entries: Vec<LocationEntry<'a>> = Vec::new();
+    let mut base: u64 = base;
+
+    loop {
+        let begin: u64 = reader.sized(encoding.address_size)?;
+        let end: u64 = reader.sized(encoding.address_size)?;
+        if begin == 0 && end == 0 {
+            break;
+        }
+        if begin == encoding.max_address() {
+            // base address selection entry
+            base = end;
+            continue;
+        }
+        let len: u16 = reader.u16()?;
+        entries.push(LocationEntry {
+            begin: base.wrapping_add(begin),
+            end: base.wrapping_add(end),
+            default: false,
+            expr: reader.bytes(len as usize)?,
+        });
+    }
+
+    Ok(entries)
+}
+
+// DWARF 5 location list in .debug_loclists
+pub fn parse_loclists<'a>(data: &'a [u8], offset: u64, encoding: Encoding, base: u64,
+                          addresses: &AddressTable) -> Result<Vec<LocationEntry<'a>>, &'static str> {
+    let mut reader: Reader<'a> = Reader::new(data, offset as usize, encoding.endian);
+    let mut entries: Vec<LocationEntry<'a>> = Vec::new();
+    let mut base: u64 = base;
+
+    loop {
+        let kind: u8 = reader.u8()?;
+        let (begin, end, default): (u64, u64, bool) = match kind {
+            DW_LLE_END_OF_LIST => break,
+            DW_LLE_BASE_ADDRESS => {
+                base = reader.sized(encoding.address_size)?;
+                continue;
+            },
+            DW_LLE_OFFSET_PAIR => {
+                let begin: u64 = reader.uleb()?;
+                let end: u64 = reader.uleb()?;
+                (base.wrapping_add(begin), base.wrapping_add(end), false)
+            },
+            DW_LLE_START_END => {
+                let begin: u64 = reader.sized(encoding.address_size)?;
+                (begin, reader.sized(encoding.address_size)?, false)
+            },
+            DW_LLE_START_LENGTH => {
+                let begin: u64 = reader.sized(encoding.address_size)?;
+                (begin, begin.wrapping_add(reader.uleb()?), false)
+            },
+            DW_LLE_DEFAULT_LOCATION => (0, 0, true),
+            DW_LLE_BASE_ADDRESSX => {
+                base = addresses.get(reader.uleb()?)?;
+                continue;
+            },
+            DW_LLE_STARTX_ENDX => {
+                let begin: u64 = addresses.get(reader.uleb()?)?;
+                (begin, addresses.get(reader.uleb()?)?, false)
+            },
+            DW_LLE_STARTX_LENGTH => {
+                let begin: u64 = addresses.get(reader.uleb()?)?;
+                (begin, begin.wrapping_add(reader.uleb()?), false)
+            },
+            _ => return Err("Unknown location list entry."),
+        };
+        let len: u64 = reader.uleb()?;
+        entries.push(LocationEntry {
+            begin,
+            end,
+            default,
+            expr: reader.bytes(len as usize)?,
+        });
+    }
+
+    Ok(entries)
+}
+
+// location list of a GNU split unit in .debug_loc.dwo, the entry kinds
+// match the DW_LLE values of DWARF 5 and lengths are 4 bytes
+pub fn parse_loc_dwo<'a>(data: &'a [u8], offset: u64, encoding: Encoding,
+                         addresses: &AddressTable) -> Result<Vec<LocationEntry<'a>>, &'static str> {
+    let mut reader: Reader<'a> = Reader::new(data, offset as usize, encoding.endian);
+    let mut entries: Vec<LocationEntry<'a>> = Vec::new();
+
+    loop {
+        let kind: u8 = reader.u8()?;
+        let (begin, end): (u64, u64) = match kind {
+            DW_LLE_END_OF_LIST => break,
+            // the base address is not used by the other kinds
+            DW_LLE_BASE_ADDRESSX => {
+                reader.uleb()?;
+                continue;
+            },
+            DW_LLE_STARTX_ENDX => {
+                let begin: u64 = addresses.get(reader.uleb()?)?;
+                (begin, addresses.get(reader.uleb()?)?)
+            },
+            DW_LLE_STARTX_LENGTH => {
+                let begin: u64 = addresses.get(reader.uleb()?)?;
+                (begin, begin.wrapping_add(reader.u32()? as u64))
+            },
+            _ => return Err("Unknown location list entry."),
+        };
+        let len: u16 = reader.u16()?;
+        entries.push(LocationEntry {
+            begin,
+            end,
+            default: false,
+            expr: reader.bytes(len as usize)?,
+        });
+    }
+
+    Ok(entries)
+}
+
+// decode a DW_AT_location or DW_AT_frame_base value of a unit's entry
+pub fn parse_attr<'a>(unit: &Unit<'a>,
+                      value: AttrValue<'a>) -> Result<LocationAttr<'a>, &'static str> {
+    let encoding: Encoding = unit.encoding();
+    let offset: u64 = match value {
+        AttrValue::Exprloc(expr) | AttrValue::Block(expr) => {
+            return Ok(LocationAttr::Expr(expr));
+        },
+        // DWARF 2 and 3 use data4/data8 for location list offsets
+        AttrValue::SecOffset(offset) | AttrValue::Data(offset) => offset,
+        _ => return Err("Unsupported location form."),
+    };
+
+    let entries: Vec<LocationEntry<'a>> = if encoding.version >= 5 {
+        parse_loclists(unit.sections.loclists, offset, encoding, unit_base(unit),
+                       &unit.addresses())?
+    }
+    else if unit.is_split() {
+        parse_loc_dwo(unit.sections.loc, offset, encoding, &unit.addresses())?
+    }
+    else {
+        parse_loc(unit.sections.loc, offset, encoding, unit_base(unit))?
+    };
+    Ok(LocationAttr::List(entries))
+}
@@ -0,0 +1,412 @@
+use std::collections::HashMap;
+use super::{DwarfSections, Reader};
+use super::dw_consts::*;
+use super::unit::{self, AttrSpec, AttrValue, Unit, UnitHeader};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MacroEntry {
+    Define { line: u64, text: String },
+    Undef { line: u64, text: String },
+    // a file of the line table entered at `line` of the including file
+    StartFile { line: u64, file: u64 },
+    EndFile,
+    // entries of another .debug_macro unit take the place of this one
+    Import { offset: u64 },
+    // entries whose string or import is in the supplementary object file
+    // (.gnu_debugaltlink), which is not read here
+    DefineSup { line: u64, offset: u64 },
+    UndefSup { line: u64, offset: u64 },
+    ImportSup { offset: u64 },
+    // vendor extension, .debug_macinfo gives a constant and a string
+    Vendor { opcode: u8, constant: u64, text: String },
+}
+
+// An entry and its position in .debug_macro or .debug_macinfo
+#[derive(Debug, Clone, PartialEq)]
+pub struct Macro {
+    pub offset: usize,
+    pub entry: MacroEntry,
+}
+
+// One unit of .debug_macro, a header followed by the entries up to the
+// terminating 0 opcode
+#[derive(Debug, Default, Clone)]
+pub struct MacroUnit {
+    pub offset: usize,
+    pub version: u16,
+    pub offset_size: u8,
+    pub line_offset: Option<u64>,
+    // operand forms of vendor opcodes, by opcode
+    pub opcode_forms: HashMap<u8, Vec<u16>>,
+    pub entries: Vec<Macro>,
+    pub end: usize,
+}
+
+fn string(reader: &mut Reader) -> Result<String, &'static str> {
+    Ok(String::from_utf8_lossy(reader.cstr()?).into_owned())
+}
+
+fn str_at(sections: &DwarfSections, offset: u64) -> Result<String, &'static str> {
+    let text: &[u8] = sections.string(&AttrValue::Strp(offset))
+                              .ok_or("Invalid macro string offset.")?;
+    Ok(String::from_utf8_lossy(text).into_owned())
+}
+
+// Parse the .debug_macro unit at `offset`. The unit provides the string
+// sections and the string offsets base of the strx entries.
+pub fn parse_macro(unit: &Unit, offset: usize) -> Result<MacroUnit, &'static str> {
+    let sections: &DwarfSections = &unit.sections;
+    let mut reader: Reader = Reader::new(sections.macro_, offset, sections.endian);
+    let mut macros: MacroUnit = MacroUnit {
+        offset,
+        ..Default::default()
+    };
+
+    // version 4 is the GNU extension, version 5 the standard
+    macros.version = reader.u16()?;
+    if !(4..=5).contains(&macros.version) {
+        return Err("Unsupported macro unit version.");
+    }
+    let flags: u8 = reader.u8()?;
+    macros.offset_size = if flags & 0x1 != 0 { 8 } else { 4 };
+    if flags & 0x2 != 0 {
+        macros.line_offset = Some(reader.sized(macros.offset_size)?);
+    }
+    if flags & 0x4 != 0 {
+        let count: u8 = reader.u8()?;
+        for _ in 0..count {
+            let opcode: u8 = reader.u8()?;
+            let form_count: u64 = reader.uleb()?;
+            let mut forms: Vec<u16> = Vec::new();
+            for _ in 0..form_count {
+                forms.push(reader.u8()? as u16);
+            }
+            macros.opcode_forms.insert(opcode, forms);
+        }
+    }
+
+    // operands of vendor opcodes are read like attributes of a unit with
+    // the offset size of the macro unit
+    let value_header: UnitHeader = UnitHeader {
+        offset_size: macros.offset_size,
+        address_size: unit.header.address_size,
+        version: unit.header.version,
+        ..Default::default()
+    };
+
+    loop {
+        let entry_offset: usize = reader.offset;
+        let opcode: u8 = reader.u8()?;
+        let entry: MacroEntry = match opcode {
+            0x00 => break,
+            DW_MACRO_DEFINE => MacroEntry::Define {
+                line: reader.uleb()?,
+                text: string(&mut reader)?,
+            },
+            DW_MACRO_UNDEF => MacroEntry::Undef {
+                line: reader.uleb()?,
+                text: string(&mut reader)?,
+            },
+            DW_MACRO_START_FILE => MacroEntry::StartFile {
+                line: reader.uleb()?,
+                file: reader.uleb()?,
+            },
+            DW_MACRO_END_FILE => MacroEntry::EndFile,
+            DW_MACRO_DEFINE_STRP => MacroEntry::Define {
+                line: reader.uleb()?,
+                text: str_at(sections, reader.sized(macros.offset_size)?)?,
+            },
+            DW_MACRO_UNDEF_STRP => MacroEntry::Undef {
+                line: reader.uleb()?,
+                text: str_at(sections, reader.sized(macros.offset_size)?)?,
+            },
+            DW_MACRO_IMPORT => MacroEntry::Import {
+                offset: reader.sized(macros.offset_size)?,
+            },
+            DW_MACRO_DEFINE_SUP => MacroEntry::DefineSup {
+                line: reader.uleb()?,
+                offset: reader.sized(macros.offset_size)?,
+            },
+            DW_MACRO_UNDEF_SUP => MacroEntry::UndefSup {
+                line: reader.uleb()?,
+                offset: reader.sized(macros.offset_size)?,
+            },
+            DW_MACRO_IMPORT_SUP => MacroEntry::ImportSup {
+                offset: reader.sized(macros.offset_size)?,
+            },
+            DW_MACRO_DEFINE_STRX | DW_MACRO_UNDEF_STRX => {
+                let line: u64 = reader.uleb()?;
+                let index: u64 = reader.uleb()?;
+                let text: String = unit.string_offset(index)
+                                       .ok_or("Invalid macro string index.")
+                                       .and_then(|offset| str_at(sections, offset))?;
+                if opcode == DW_MACRO_DEFINE_STRX {
+                    MacroEntry::Define { line, text }
+                }
+                else {
+                    MacroEntry::Undef { line, text }
+                }
+            },
+            _ => {
+                // unknown opcodes can only be skipped with the operand
+                // forms of the header
+                let forms: &Vec<u16> = macros.opcode_forms.get(&opcode)
+                                             .ok_or("Unknown macro opcode.")?;
+                for form in forms.iter() {
+                    let spec: AttrSpec = AttrSpec {
+                        form: *form,
+                        ..Default::default()
+                    };
+                    unit::parse_value(&mut reader, spec, &value_header)?;
+                }
+                MacroEntry::Vendor {
+                    opcode,
+                    constant: 0,
+                    text: String::new(),
+                }
+            },
+        };
+        macros.entries.push(Macro {
+            offset: entry_offset,
+            entry,
+        });
+    }
+
+    macros.end = reader.offset;
+    Ok(macros)
+}
+
+// Parse the .debug_macinfo entries at `offset`, up to the terminating 0
+pub fn parse_macinfo(sections: &DwarfSections,
+                     offset: usize) -> Result<Vec<Macro>, &'static str> {
+    let mut reader: Reader = Reader::new(sections.macinfo, offset, sections.endian);
+    let mut entries: Vec<Macro> = Vec::new();
+    loop {
+        let entry_offset: usize = reader.offset;
+        let entry: MacroEntry = match reader.u8()? {
+            0x00 => break,
+            DW_MACINFO_DEFINE => MacroEntry::Define {
+                line: reader.uleb()?,
+                text: string(&mut reader)?,
+            },
+            DW_MACINFO_UNDEF => MacroEntry::Undef {
+                line: reader.uleb()?,
+                text: string(&mut reader)?,
+            },
+            DW_MACINFO_START_FILE => MacroEntry::StartFile {
+                line: reader.uleb()?,
+                file: reader.uleb()?,
+            },
+            DW_MACINFO_END_FILE => MacroEntry::EndFile,
+            DW_MACINFO_VENDOR_EXT => MacroEntry::Vendor {
+                opcode: DW_MACINFO_VENDOR_EXT,
+                constant: reader.uleb()?,
+                text: string(&mut reader)?,
+            },
+            _ => return Err("Unknown macinfo type."),
+        };
+        entries.push(Macro {
+            offset: entry_offset,
+            entry,
+        });
+    }
+    Ok(entries)
+}
+
+// The macro information of a unit
+#[derive(Debug, Default, Clone)]
+pub struct UnitMacros {
+    // .debug_macro or .debug_macinfo
+    pub section: &'static str,
+    pub offset: usize,
+    pub version: u16,
+    // line table the file numbers of DW_MACRO_start_file refer to
+    pub line_offset: Option<u64>,
+    // entries with the imported units expanded in place
+    pub entries: Vec<Macro>,
+}
+
+fn expand(unit: &Unit, offset: usize, stack: &mut Vec<usize>,
+          entries: &mut Vec<Macro>) -> Result<MacroUnit, &'static str> {
+    if stack.contains(&offset) {
+        return Err("Macro unit imports itself.");
+    }
+    stack.push(offset);
+    let mut macros: MacroUnit = parse_macro(unit, offset)?;
+    for entry in std::mem::take(&mut macros.entries) {
+        match entry.entry {
+            MacroEntry::Import { offset } => {
+                expand(unit, offset as usize, stack, entries)?;
+            },
+            _ => entries.push(entry),
+        }
+    }
+    stack.pop();
+    Ok(macros)
+}
+
+// Macros of a unit from DW_AT_macros, DW_AT_GNU_macros or
+// DW_AT_macro_info of its unit entry, None when it has no macro information
+pub fn unit_macros(unit: &Unit) -> Result<Option<UnitMacros>, &'static str> {
+    let root = match unit.dies.first() {
+        Some(root) => root,
+        None => return Ok(None),
+    };
+    let offset_of = |value: AttrValue| -> Option<usize> {
+        match value {
+            AttrValue::SecOffset(v) | AttrValue::Data(v) => Some(v as usize),
+            _ => None,
+        }
+    };
+    let stmt_list: Option<u64> = root.attr(DW_AT_STMT_LIST).and_then(offset_of)
+                                     .map(|v| v as u64);
+
+    let macro_offset: Option<usize> = root.attr(DW_AT_MACROS)
+                                          .or_else(|| root.attr(DW_AT_GNU_MACROS))
+                                          .and_then(offset_of);
+    if let Some(offset) = macro_offset {
+        let mut entries: Vec<Macro> = Vec::new();
+        let macros: MacroUnit = expand(unit, offset, &mut Vec::new(), &mut entries)?;
+        return Ok(Some(UnitMacros {
+            section: ".debug_macro",
+            offset,
+            version: macros.version,
+            line_offset: macros.line_offset.or(stmt_list),
+            entries,
+        }));
+    }
+
+    match root.attr(DW_AT_MACRO_INFO).and_then(offset_of) {
+        Some(offset) => Ok(Some(UnitMacros {
+            section: ".debug_macinfo",
+            offset,
+            version: unit.header.version,
+            line_offset: stmt_list,
+            entries: parse_macinfo(&unit.sections, offset)?,
+        })),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Endian;
+
+    const STR: &[u8] = b"\0B\0C 3\0";
+    // a DWARF 5 header followed by the .debug_str offsets of "B" and "C 3"
+    const STR_OFFSETS: &[u8] = &[12, 0, 0, 0, 5, 0, 0, 0, 1, 0, 0, 0, 3, 0, 0, 0];
+
+    fn unit(macro_: &'static [u8]) -> Unit<'static> {
+        let mut unit: Unit = Unit::default();
+        unit.header.version = 5;
+        unit.header.offset_size = 4;
+        unit.header.address_size = 8;
+        unit.bases.str_offsets = 8;
+        unit.sections = DwarfSections {
+            endian: Endian::Little,
+            address_size: 8,
+            str: STR,
+            str_offsets: STR_OFFSETS,
+            macro_,
+            ..Default::default()
+        };
+        unit
+    }
+
+    fn define(line: u64, text: &str) -> MacroEntry {
+        MacroEntry::Define { line, text: text.to_string() }
+    }
+
+    #[test]
+    fn macro_units() {
+        let data: &'static [u8] = &[
+            // version 5 with a line offset and an opcode table, 0xe0 takes
+            // a udata and a string
+            5, 0, 0x6, 0x10, 0, 0, 0, 1, 0xe0, 2, DW_FORM_UDATA as u8, DW_FORM_STRING as u8,
+            DW_MACRO_DEFINE, 1, b'A', b' ', b'1', 0,
+            DW_MACRO_UNDEF_STRP, 2, 1, 0, 0, 0,
+            DW_MACRO_START_FILE, 0, 1,
+            DW_MACRO_DEFINE_STRX, 3, 1,
+            DW_MACRO_END_FILE,
+            0xe0, 0x80, 0x01, b'x', 0,
+            DW_MACRO_IMPORT, 59, 0, 0, 0,
+            DW_MACRO_DEFINE_SUP, 4, 0x20, 0, 0, 0,
+            DW_MACRO_UNDEF_SUP, 5, 0x24, 0, 0, 0,
+            DW_MACRO_IMPORT_SUP, 0x28, 0, 0, 0,
+            0,
+            // the imported GNU unit at 59
+            4, 0, 0, DW_MACRO_DEFINE, 7, b'D', 0, 0,
+        ];
+        let unit: Unit = unit(data);
+        let macros: MacroUnit = parse_macro(&unit, 0).unwrap();
+        assert_eq!((macros.version, macros.offset_size, macros.line_offset), (5, 4, Some(0x10)));
+        assert_eq!(macros.end, 59);
+        let entries: Vec<MacroEntry> = macros.entries.iter().map(|m| m.entry.clone()).collect();
+        assert_eq!(entries, [
+            define(1, "A 1"),
+            MacroEntry::Undef { line: 2, text: "B".to_string() },
+            MacroEntry::StartFile { line: 0, file: 1 },
+            define(3, "C 3"),
+            MacroEntry::EndFile,
+            MacroEntry::Vendor { opcode: 0xe0, constant: 0, text: String::new() },
+            MacroEntry::Import { offset: 59 },
+            MacroEntry::DefineSup { line: 4, offset: 0x20 },
+            MacroEntry::UndefSup { line: 5, offset: 0x24 },
+            MacroEntry::ImportSup { offset: 0x28 },
+        ]);
+        assert_eq!(macros.entries[1].offset, 18);
+
+        // imports are expanded in place
+        let mut expanded: Vec<Macro> = Vec::new();
+        expand(&unit, 0, &mut Vec::new(), &mut expanded).unwrap();
+        assert_eq!(expanded.len(), 10);
+        assert_eq!(expanded[6], Macro { offset: 62, entry: define(7, "D") });
+    }
+
+    #[test]
+    fn invalid_macro_units() {
+        let parse = |data: &'static [u8]| parse_macro(&unit(data), 0).map(|m| m.entries.len());
+        assert_eq!(parse(&[3, 0, 0, 0]), Err("Unsupported macro unit version."));
+        assert_eq!(parse(&[5, 0, 0, 0xe0, 0]), Err("Unknown macro opcode."));
+        assert_eq!(parse(&[5, 0, 0, DW_MACRO_DEFINE_STRP, 1, 0x40, 0, 0, 0, 0]),
+                   Err("Invalid macro string offset."));
+        assert_eq!(parse(&[5, 0, 0, DW_MACRO_DEFINE_STRX, 1, 9, 0]),
+                   Err("Invalid macro string index."));
+        assert!(parse(&[5, 0, 0, DW_MACRO_DEFINE, 1, b'A']).is_err());
+        // 64-bit offsets
+        assert_eq!(parse(&[5, 0, 0x1, DW_MACRO_IMPORT, 0, 0, 0, 0, 0, 0, 0, 0, 0]), Ok(1));
+
+        // a unit importing itself, through another one
+        let data: &'static [u8] = &[5, 0, 0, DW_MACRO_IMPORT, 9, 0, 0, 0, 0,
+                                    5, 0, 0, DW_MACRO_IMPORT, 0, 0, 0, 0, 0];
+        let mut entries: Vec<Macro> = Vec::new();
+        assert_eq!(expand(&unit(data), 0, &mut Vec::new(), &mut entries).map(|m| m.offset),
+                   Err("Macro unit imports itself."));
+    }
+
+    #[test]
+    fn macinfo() {
+        let sections: DwarfSections = DwarfSections {
+            endian: Endian::Little,
+            macinfo: &[DW_MACINFO_START_FILE, 0, 1,
+                       DW_MACINFO_DEFINE, 1, b'A', 0,
+                       DW_MACINFO_UNDEF, 2, b'A', 0,
+                       DW_MACINFO_VENDOR_EXT, 7, b'v', 0,
+                       DW_MACINFO_END_FILE,
+                       0,
+                       0x05, 0],
+            ..Default::default()
+        };
+        let entries: Vec<MacroEntry> = parse_macinfo(&sections, 0).unwrap().into_iter()
+                                                                   .map(|m| m.entry).collect();
+        assert_eq!(entries, [
+            MacroEntry::StartFile { line: 0, file: 1 },
+            define(1, "A"),
+            MacroEntry::Undef { line: 2, text: "A".to_string() },
+            MacroEntry::Vendor { opcode: DW_MACINFO_VENDOR_EXT, constant: 7, text: "v".to_string() },
+            MacroEntry::EndFile,
+        ]);
+        assert_eq!(parse_macinfo(&sections, 17), Err("Unknown macinfo type."));
+    }
+}
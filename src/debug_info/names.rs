@@ -0,0 +1,725 @@
+use std::collections::HashMap;
+use super::{DwarfSections, Reader};
+use super::dw_consts::*;
+use super::split::{self, Package};
+use super::unit::{self, AttrSpec, AttrValue, Unit, UnitHeader};
+use crate::Endian;
+
+// What a name stands for
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NameKind {
+    Function,
+    Variable,
+    Type,
+    Other,
+}
+
+impl NameKind {
+    pub fn from_tag(tag: u16) -> NameKind {
+        match tag {
+            DW_TAG_SUBPROGRAM | DW_TAG_INLINED_SUBROUTINE => NameKind::Function,
+            DW_TAG_VARIABLE | DW_TAG_FORMAL_PARAMETER
+            | DW_TAG_ENUMERATOR => NameKind::Variable,
+            DW_TAG_BASE_TYPE | DW_TAG_STRUCTURE_TYPE | DW_TAG_CLASS_TYPE
+            | DW_TAG_UNION_TYPE | DW_TAG_ENUMERATION_TYPE | DW_TAG_TYPEDEF
+            | DW_TAG_ARRAY_TYPE | DW_TAG_POINTER_TYPE | DW_TAG_SUBROUTINE_TYPE
+            | DW_TAG_UNSPECIFIED_TYPE | DW_TAG_STRING_TYPE
+            | DW_TAG_SUBRANGE_TYPE => NameKind::Type,
+            _ => NameKind::Other,
+        }
+    }
+
+    // symbol kind of .gdb_index and .debug_gnu_pubnames
+    fn from_gdb_index(kind: u32) -> NameKind {
+        match kind {
+            1 => NameKind::Type,
+            2 => NameKind::Variable,
+            3 => NameKind::Function,
+            _ => NameKind::Other,
+        }
+    }
+}
+
+// A name found in an index. `unit_offset` is the unit in .debug_info (the
+// skeleton for split DWARF), `die_offset` is relative to the start of the
+// unit holding the entry (the split unit for a skeleton) and unknown for
+// .gdb_index, which only records the unit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NameEntry {
+    pub name: String,
+    pub kind: NameKind,
+    pub tag: Option<u16>,
+    pub unit_offset: usize,
+    pub die_offset: Option<u64>,
+}
+
+#[derive(Debug, Default, Clone)]
+struct NamesAbbrev {
+    tag: u16,
+    attrs: Vec<AttrSpec>,
+}
+
+// One name table of .debug_names
+#[derive(Debug, Default, Clone)]
+pub struct DebugNames<'a> {
+    data: &'a [u8],
+    str: &'a [u8],
+    endian: Endian,
+    offset_size: u8,
+    address_size: u8,
+    cus: Vec<u64>,
+    tus: Vec<u64>,
+    bucket_count: u32,
+    name_count: u32,
+    buckets: usize,
+    hashes: usize,
+    str_offsets: usize,
+    entry_offsets: usize,
+    pool: usize,
+    abbrevs: HashMap<u64, NamesAbbrev>,
+}
+
+// The .gdb_index section, always little endian
+#[derive(Debug, Default, Clone)]
+pub struct GdbIndex<'a> {
+    data: &'a [u8],
+    version: u32,
+    cus: Vec<u64>,
+    symtab: usize,
+    slot_count: usize,
+    pool: usize,
+}
+
+// Where the names of a NameIndex come from
+#[derive(Debug, Clone)]
+pub enum NameIndex<'a> {
+    DebugNames(Vec<DebugNames<'a>>),
+    GdbIndex(GdbIndex<'a>),
+    // .debug_pubnames and .debug_pubtypes, or their GNU variants
+    PubNames(HashMap<String, Vec<NameEntry>>),
+    // built from the units when the object has no index
+    Memory(HashMap<String, Vec<NameEntry>>),
+}
+
+// hash of .debug_names, the DJB hash of the case folded name
+pub fn djb_hash(name: &[u8]) -> u32 {
+    let mut hash: u32 = 5381;
+    for c in name.iter() {
+        hash = hash.wrapping_mul(33).wrapping_add(c.to_ascii_lowercase() as u32);
+    }
+    hash
+}
+
+// hash of the .gdb_index symbol table, case insensitive from version 5
+pub fn gdb_index_hash(name: &[u8], version: u32) -> u32 {
+    let mut hash: u32 = 0;
+    for c in name.iter() {
+        let c: u8 = if version >= 5 { c.to_ascii_lowercase() } else { *c };
+        hash = hash.wrapping_mul(67).wrapping_add(c as u32).wrapping_sub(113);
+    }
+    hash
+}
+
+fn parse_debug_names_table<'a>(sections: &DwarfSections<'a>, offset: usize)
+                               -> Result<(DebugNames<'a>, usize), &'static str> {
+    let mut reader: Reader<'a> = Reader::new(sections.names, offset, sections.endian);
+    let (unit_length, offset_size) = reader.initial_length()?;
+    let end: usize = reader.offset.checked_add(unit_length as usize)
+                                  .filter(|end| *end <= sections.names.len())
+                                  .ok_or("Name table length out of bounds.")?;
+    let data: &'a [u8] = &sections.names[..end];
+    reader.data = data;
+
+    let version: u16 = reader.u16()?;
+    if version != 5 {
+        return Err("Unsupported .debug_names version.");
+    }
+    reader.skip(2)?;
+    let cu_count: u32 = reader.u32()?;
+    let local_tu_count: u32 = reader.u32()?;
+    let foreign_tu_count: u32 = reader.u32()?;
+    let bucket_count: u32 = reader.u32()?;
+    let name_count: u32 = reader.u32()?;
+    let abbrev_size: u32 = reader.u32()?;
+    let augmentation_size: u32 = reader.u32()?;
+    reader.skip(augmentation_size as usize)?;
+
+    let mut table: DebugNames<'a> = DebugNames {
+        data,
+        str: sections.str,
+        endian: sections.endian,
+        offset_size,
+        address_size: sections.address_size,
+        bucket_count,
+        name_count,
+        ..Default::default()
+    };
+    for _ in 0..cu_count {
+        table.cus.push(reader.sized(offset_size)?);
+    }
+    for _ in 0..local_tu_count {
+        table.tus.push(reader.sized(offset_size)?);
+    }
+    // type units in .dwo files are only known by their signature
+    reader.skip(foreign_tu_count as usize * 8)?;
+
+    table.buckets = reader.offset;
+    reader.skip(bucket_count as usize * 4)?;
+    table.hashes = reader.offset;
+    if bucket_count > 0 {
+        reader.skip(name_count as usize * 4)?;
+    }
+    table.str_offsets = reader.offset;
+    reader.skip(name_count as usize * offset_size as usize)?;
+    table.entry_offsets = reader.offset;
+    reader.skip(name_count as usize * offset_size as usize)?;
+
+    let abbrev_data: &'a [u8] = reader.bytes(abbrev_size as usize)?;
+    table.pool = reader.offset;
+    let mut abbrevs: Reader<'a> = Reader::new(abbrev_data, 0, sections.endian);
+    loop {
+        let code: u64 = abbrevs.uleb()?;
+        if code == 0 {
+            break;
+        }
+        let tag: u16 = abbrevs.uleb()? as u16;
+        let mut attrs: Vec<AttrSpec> = Vec::new();
+        loop {
+            let name: u16 = abbrevs.uleb()? as u16;
+            let form: u16 = abbrevs.uleb()? as u16;
+            if name == 0 && form == 0 {
+                break;
+            }
+            let implicit_const: i64 = if form == DW_FORM_IMPLICIT_CONST {
+                abbrevs.sleb()?
+            }
+            else {
+                0
+            };
+            attrs.push(AttrSpec {
+                name,
+                form,
+                implicit_const,
+            });
+        }
+        table.abbrevs.insert(code, NamesAbbrev {
+            tag,
+            attrs,
+        });
+    }
+
+    Ok((table, end))
+}
+
+pub fn parse_debug_names<'a>(sections: &DwarfSections<'a>)
+                             -> Result<Vec<DebugNames<'a>>, &'static str> {
+    let mut tables: Vec<DebugNames<'a>> = Vec::new();
+    let mut offset: usize = 0;
+    while offset < sections.names.len() {
+        let (table, end) = parse_debug_names_table(sections, offset)?;
+        tables.push(table);
+        offset = end;
+    }
+    Ok(tables)
+}
+
+impl<'a> DebugNames<'a> {
+    fn reader(&self, offset: usize) -> Reader<'a> {
+        Reader::new(self.data, offset, self.endian)
+    }
+
+    fn name(&self, idx: u32) -> Result<&'a [u8], &'static str> {
+        let offset: u64 = self.reader(self.str_offsets + idx as usize * self.offset_size as usize)
+                              .sized(self.offset_size)?;
+        Reader::new(self.str, offset as usize, self.endian).cstr()
+    }
+
+    // the entries in the pool for the name at `idx`
+    fn entries(&self, idx: u32, name: &str) -> Result<Vec<NameEntry>, &'static str> {
+        let offset: u64 = self.reader(self.entry_offsets + idx as usize * self.offset_size as usize)
+                              .sized(self.offset_size)?;
+        let mut reader: Reader<'a> = self.reader(self.pool + offset as usize);
+        // the forms of the index attributes are read like unit attributes
+        let header: UnitHeader = UnitHeader {
+            offset_size: self.offset_size,
+            address_size: self.address_size,
+            version: 5,
+            ..Default::default()
+        };
+        let mut entries: Vec<NameEntry> = Vec::new();
+
+        loop {
+            let code: u64 = reader.uleb()?;
+            if code == 0 {
+                break;
+            }
+            let abbrev: &NamesAbbrev = self.abbrevs.get(&code)
+                                           .ok_or("Unknown name index abbreviation.")?;
+            let mut cu: Option<u64> = None;
+            let mut tu: Option<u64> = None;
+            let mut die_offset: Option<u64> = None;
+            for spec in abbrev.attrs.iter() {
+                let value: u64 = match unit::parse_value(&mut reader, *spec, &header)? {
+                    AttrValue::Data(v) | AttrValue::Udata(v) | AttrValue::UnitRef(v)
+                    | AttrValue::SecOffset(v) | AttrValue::RefSig8(v) => v,
+                    AttrValue::Sdata(v) => v as u64,
+                    _ => continue,
+                };
+                match spec.name {
+                    DW_IDX_COMPILE_UNIT => cu = Some(value),
+                    DW_IDX_TYPE_UNIT => tu = Some(value),
+                    DW_IDX_DIE_OFFSET => die_offset = Some(value),
+                    _ => (),
+                }
+            }
+            let unit_offset: Option<u64> = match (cu, tu) {
+                (_, Some(tu)) => self.tus.get(tu as usize).copied(),
+                (Some(cu), None) => self.cus.get(cu as usize).copied(),
+                // a table of a single unit may leave it implicit
+                (None, None) if self.cus.len() == 1 => Some(self.cus[0]),
+                (None, None) => None,
+            };
+            // entries of foreign type units have no unit in this file
+            if let Some(unit_offset) = unit_offset {
+                entries.push(NameEntry {
+                    name: String::from(name),
+                    kind: NameKind::from_tag(abbrev.tag),
+                    tag: Some(abbrev.tag),
+                    unit_offset: unit_offset as usize,
+                    die_offset,
+                });
+            }
+        }
+
+        Ok(entries)
+    }
+
+    pub fn lookup(&self, name: &str) -> Result<Vec<NameEntry>, &'static str> {
+        // without a hash table the names have to be searched in order
+        if self.bucket_count == 0 {
+            for idx in 0..self.name_count {
+                if self.name(idx)? == name.as_bytes() {
+                    return self.entries(idx, name);
+                }
+            }
+            return Ok(Vec::new());
+        }
+
+        let hash: u32 = djb_hash(name.as_bytes());
+        let bucket: u32 = hash % self.bucket_count;
+        // indexes into the name table start at 1, 0 is an empty bucket
+        let mut idx: u32 = self.reader(self.buckets + bucket as usize * 4).u32()?;
+        if idx == 0 {
+            return Ok(Vec::new());
+        }
+        while idx <= self.name_count {
+            let name_hash: u32 = self.reader(self.hashes + (idx as usize - 1) * 4).u32()?;
+            if name_hash % self.bucket_count != bucket {
+                break;
+            }
+            if name_hash == hash && self.name(idx - 1)? == name.as_bytes() {
+                return self.entries(idx - 1, name);
+            }
+            idx += 1;
+        }
+        Ok(Vec::new())
+    }
+}
+
+pub fn parse_gdb_index(data: &[u8]) -> Result<GdbIndex<'_>, &'static str> {
+    let mut reader: Reader = Reader::new(data, 0, Endian::Little);
+    let version: u32 = reader.u32()?;
+    // older versions hash names differently or lack symbol kinds
+    if !(7..=9).contains(&version) {
+        return Err("Unsupported .gdb_index version.");
+    }
+    let cu_list: u32 = reader.u32()?;
+    let tu_list: u32 = reader.u32()?;
+    reader.u32()?;
+    let symtab: u32 = reader.u32()?;
+    // version 9 adds a shortcut table in front of the constant pool
+    if version >= 9 {
+        reader.u32()?;
+    }
+    let pool: u32 = reader.u32()?;
+    if symtab > pool || pool as usize > data.len() || cu_list > tu_list {
+        return Err("Malformed .gdb_index header.");
+    }
+
+    let mut index: GdbIndex = GdbIndex {
+        data,
+        version,
+        symtab: symtab as usize,
+        slot_count: (pool - symtab) as usize / 8,
+        pool: pool as usize,
+        ..Default::default()
+    };
+    let mut cus: Reader = Reader::new(data, cu_list as usize, Endian::Little);
+    while cus.offset + 16 <= tu_list as usize {
+        index.cus.push(cus.u64()?);
+        cus.u64()?;
+    }
+
+    Ok(index)
+}
+
+impl GdbIndex<'_> {
+    pub fn lookup(&self, name: &str) -> Result<Vec<NameEntry>, &'static str> {
+        let mut entries: Vec<NameEntry> = Vec::new();
+        if self.slot_count == 0 {
+            return Ok(entries);
+        }
+        let mask: u32 = self.slot_count as u32 - 1;
+        let hash: u32 = gdb_index_hash(name.as_bytes(), self.version);
+        let step: u32 = (hash.wrapping_mul(17) & mask) | 1;
+        let mut slot: u32 = hash & mask;
+
+        for _ in 0..self.slot_count {
+            let mut reader: Reader = Reader::new(self.data, self.symtab + slot as usize * 8,
+                                                 Endian::Little);
+            let name_offset: u32 = reader.u32()?;
+            let vector_offset: u32 = reader.u32()?;
+            if name_offset == 0 && vector_offset == 0 {
+                break;
+            }
+            let slot_name: &[u8] = Reader::new(self.data, self.pool + name_offset as usize,
+                                               Endian::Little).cstr()?;
+            if slot_name == name.as_bytes() {
+                let mut vector: Reader = Reader::new(self.data,
+                                                     self.pool + vector_offset as usize,
+                                                     Endian::Little);
+                let count: u32 = vector.u32()?;
+                for _ in 0..count {
+                    let value: u32 = vector.u32()?;
+                    let cu: usize = (value & 0xffffff) as usize;
+                    // type units are listed after the compile units and
+                    // refer to .debug_types, they are not resolved here
+                    if let Some(offset) = self.cus.get(cu) {
+                        entries.push(NameEntry {
+                            name: String::from(name),
+                            kind: NameKind::from_gdb_index((value >> 28) & 0x7),
+                            tag: None,
+                            unit_offset: *offset as usize,
+                            die_offset: None,
+                        });
+                    }
+                }
+                break;
+            }
+            slot = (slot + step) & mask;
+        }
+
+        Ok(entries)
+    }
+}
+
+// Read a .debug_pubnames or .debug_pubtypes section into `names`. The
+// GNU variants store a symbol kind byte after each offset.
+pub fn parse_pubnames(data: &[u8], sections: &DwarfSections, gnu: bool,
+                      names: &mut HashMap<String, Vec<NameEntry>>) -> Result<(), &'static str> {
+    let mut reader: Reader = Reader::new(data, 0, sections.endian);
+    while !reader.is_empty() {
+        let (unit_length, offset_size) = reader.initial_length()?;
+        let end: usize = reader.offset + unit_length as usize;
+        reader.u16()?;
+        let unit_offset: u64 = reader.sized(offset_size)?;
+        reader.sized(offset_size)?;
+        while reader.offset < end {
+            let die_offset: u64 = reader.sized(offset_size)?;
+            if die_offset == 0 {
+                break;
+            }
+            let kind: NameKind = if gnu {
+                NameKind::from_gdb_index((reader.u8()? as u32 >> 4) & 0x7)
+            }
+            else {
+                NameKind::Other
+            };
+            let name: String = String::from_utf8_lossy(reader.cstr()?).into_owned();
+            let entries: &mut Vec<NameEntry> = names.entry(name.clone()).or_default();
+            // gcc can list a name of split units twice
+            if entries.iter().any(|e| e.unit_offset == unit_offset as usize
+                                      && e.die_offset == Some(die_offset)) {
+                continue;
+            }
+            entries.push(NameEntry {
+                name,
+                kind,
+                tag: None,
+                unit_offset: unit_offset as usize,
+                die_offset: Some(die_offset),
+            });
+        }
+        reader.offset = end;
+    }
+    Ok(())
+}
+
+// Index every named function, variable and type outside of a function
+pub fn build(units: &[Unit]) -> HashMap<String, Vec<NameEntry>> {
+    let mut names: HashMap<String, Vec<NameEntry>> = HashMap::new();
+    for unit in units.iter() {
+        // depth of the innermost enclosing function, its locals are not
+        // indexed
+        let mut function_depth: Option<usize> = None;
+        for die in unit.dies.iter() {
+            if function_depth.is_some_and(|depth| die.depth > depth) {
+                continue;
+            }
+            function_depth = None;
+            if die.tag == DW_TAG_SUBPROGRAM {
+                function_depth = Some(die.depth);
+            }
+            if die.depth == 0 || die.attr(DW_AT_DECLARATION).is_some() {
+                continue;
+            }
+            let kind: NameKind = NameKind::from_tag(die.tag);
+            if kind == NameKind::Other && die.tag != DW_TAG_NAMESPACE {
+                continue;
+            }
+            let name: String = match die.attr(DW_AT_NAME).and_then(|v| unit.sections.string(&v)) {
+                Some(name) => String::from_utf8_lossy(name).into_owned(),
+                None => continue,
+            };
+            names.entry(name.clone()).or_default().push(NameEntry {
+                name,
+                kind,
+                tag: Some(die.tag),
+                unit_offset: unit.skeleton_offset.unwrap_or(unit.header.offset),
+                die_offset: Some((die.offset - unit.header.offset) as u64),
+            });
+        }
+    }
+    names
+}
+
+// The best index of an object: .debug_names, then .gdb_index, then the
+// pubnames sections, and finally one built from the units
+pub fn load<'a>(sections: &DwarfSections<'a>, dwos: &[DwarfSections<'a>],
+                package: Option<&Package<'a>>) -> Result<NameIndex<'a>, &'static str> {
+    if !sections.names.is_empty() {
+        return Ok(NameIndex::DebugNames(parse_debug_names(sections)?));
+    }
+    if !sections.gdb_index.is_empty() {
+        return Ok(NameIndex::GdbIndex(parse_gdb_index(sections.gdb_index)?));
+    }
+    if !sections.gnu_pubnames.is_empty() || !sections.gnu_pubtypes.is_empty() {
+        let mut names: HashMap<String, Vec<NameEntry>> = HashMap::new();
+        parse_pubnames(sections.gnu_pubnames, sections, true, &mut names)?;
+        parse_pubnames(sections.gnu_pubtypes, sections, true, &mut names)?;
+        return Ok(NameIndex::PubNames(names));
+    }
+    if !sections.pubnames.is_empty() || !sections.pubtypes.is_empty() {
+        let mut names: HashMap<String, Vec<NameEntry>> = HashMap::new();
+        parse_pubnames(sections.pubnames, sections, false, &mut names)?;
+        parse_pubnames(sections.pubtypes, sections, false, &mut names)?;
+        return Ok(NameIndex::PubNames(names));
+    }
+    let units: Vec<Unit<'a>> = split::units(sections, dwos, package)?;
+    Ok(NameIndex::Memory(build(&units)))
+}
+
+impl NameIndex<'_> {
+    // section the index was read from
+    pub fn source(&self) -> &'static str {
+        match self {
+            NameIndex::DebugNames(_) => ".debug_names",
+            NameIndex::GdbIndex(_) => ".gdb_index",
+            NameIndex::PubNames(_) => ".debug_pubnames",
+            NameIndex::Memory(_) => "in-memory index",
+        }
+    }
+
+    pub fn lookup(&self, name: &str) -> Result<Vec<NameEntry>, &'static str> {
+        match self {
+            NameIndex::DebugNames(tables) => {
+                let mut entries: Vec<NameEntry> = Vec::new();
+                for table in tables.iter() {
+                    entries.extend(table.lookup(name)?);
+                }
+                Ok(entries)
+            },
+            NameIndex::GdbIndex(index) => index.lookup(name),
+            NameIndex::PubNames(names) | NameIndex::Memory(names) => {
+                Ok(names.get(name).cloned().unwrap_or_default())
+            },
+        }
+    }
+}
+
+// Parse only the unit an entry points to and find the entry in it,
+// returns the unit (the split unit for a skeleton) and the index of the
+// entry in its `dies`
+pub fn resolve<'a>(sections: &DwarfSections<'a>, dwos: &[DwarfSections<'a>],
+                   package: Option<&Package<'a>>,
+                   entry: &NameEntry) -> Result<Option<(Unit<'a>, usize)>, &'static str> {
+    let header: UnitHeader = unit::parse_header(sections.info, entry.unit_offset, sections)?;
+    let abbrevs = unit::parse_abbrevs(sections.abbrev, header.abbrev_offset, sections)?;
+    let mut unit: Unit<'a> = unit::parse_unit(sections, sections.info, header, &abbrevs)?;
+    unit.resolve_indexes();
+    if unit.is_skeleton() {
+        match split::split_unit(&unit, dwos, package)? {
+            Some(split) => unit = split,
+            None => return Ok(None),
+        }
+    }
+
+    let idx: Option<usize> = match entry.die_offset {
+        Some(offset) => unit.index_of(unit.header.offset + offset as usize),
+        // .gdb_index only knows the unit, search it by name
+        None => unit.dies.iter().position(|die| {
+            die.depth > 0
+            && die.attr(DW_AT_DECLARATION).is_none()
+            && (entry.kind == NameKind::Other || NameKind::from_tag(die.tag) == entry.kind)
+            && die.attr(DW_AT_NAME).and_then(|v| unit.sections.string(&v))
+                  == Some(entry.name.as_bytes())
+        }),
+    };
+    Ok(idx.map(|idx| (unit, idx)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // an entry of the pool: abbreviation code and DW_IDX values
+    type Entry = (u8, Vec<u8>);
+
+    // A 32-bit .debug_names table of the compile and type units at `cus`
+    // and `tus` plus one foreign type unit, with `bucket_count` buckets.
+    // The names are added to `strings`, the .debug_str. Abbreviation 1 is
+    // a function with its unit, 2 a variable of the only unit and 3 a
+    // structure of a type unit.
+    fn debug_names(cus: &[u32], tus: &[u32], bucket_count: u32,
+                   names: &[(&str, Vec<Entry>)], strings: &mut Vec<u8>) -> Vec<u8> {
+        let mut names: Vec<&(&str, Vec<Entry>)> = names.iter().collect();
+        if bucket_count > 0 {
+            names.sort_by_key(|(name, _)| djb_hash(name.as_bytes()) % bucket_count);
+        }
+        let (mut str_offsets, mut entry_offsets, mut pool): (Vec<u8>, Vec<u8>, Vec<u8>) =
+            (Vec::new(), Vec::new(), Vec::new());
+        for (name, entries) in names.iter() {
+            str_offsets.extend((strings.len() as u32).to_le_bytes());
+            strings.extend(name.as_bytes());
+            strings.push(0);
+            entry_offsets.extend((pool.len() as u32).to_le_bytes());
+            for (code, values) in entries.iter() {
+                pool.push(*code);
+                pool.extend(values);
+            }
+            pool.push(0);
+        }
+        let abbrevs: [u8; 23] = [1, 0x2e, 1, 0x0b, 3, 0x13, 0, 0,
+                                 2, 0x34, 3, 0x13, 0, 0,
+                                 3, 0x13, 2, 0x0b, 3, 0x13, 0, 0,
+                                 0];
+
+        let mut body: Vec<u8> = vec![5, 0, 0, 0];
+        for value in [cus.len() as u32, tus.len() as u32, 1, bucket_count, names.len() as u32,
+                      abbrevs.len() as u32, 0] {
+            body.extend(value.to_le_bytes());
+        }
+        for offset in cus.iter().chain(tus.iter()) {
+            body.extend(offset.to_le_bytes());
+        }
+        body.extend(0x1122334455667788u64.to_le_bytes());
+        for bucket in 0..bucket_count {
+            let first: Option<usize> = names.iter().position(|(name, _)| {
+                djb_hash(name.as_bytes()) % bucket_count == bucket
+            });
+            body.extend(first.map_or(0, |idx| idx as u32 + 1).to_le_bytes());
+        }
+        if bucket_count > 0 {
+            for (name, _) in names.iter() {
+                body.extend(djb_hash(name.as_bytes()).to_le_bytes());
+            }
+        }
+        body.extend(str_offsets);
+        body.extend(entry_offsets);
+        body.extend(abbrevs);
+        body.extend(pool);
+
+        let mut table: Vec<u8> = (body.len() as u32).to_le_bytes().to_vec();
+        table.extend(body);
+        table
+    }
+
+    fn entry(kind: NameKind, tag: u16, unit_offset: usize, die_offset: u64) -> NameEntry {
+        NameEntry {
+            name: String::new(),
+            kind,
+            tag: Some(tag),
+            unit_offset,
+            die_offset: Some(die_offset),
+        }
+    }
+
+    fn lookup(index: &NameIndex, name: &str) -> Vec<NameEntry> {
+        index.lookup(name).unwrap().into_iter()
+             .map(|e| NameEntry { name: String::new(), ..e })
+             .collect()
+    }
+
+    #[test]
+    fn hashes() {
+        assert_eq!(djb_hash(b""), 5381);
+        assert_eq!(djb_hash(b"main"), 0x7c9a7f6a);
+        assert_eq!(djb_hash(b"MAIN"), djb_hash(b"main"));
+        assert_eq!(gdb_index_hash(b"", 7), 0);
+        assert_eq!(gdb_index_hash(b"main", 7), 0xffec89e9);
+        assert_eq!(gdb_index_hash(b"Main", 7), gdb_index_hash(b"main", 7));
+        assert_ne!(gdb_index_hash(b"Main", 4), gdb_index_hash(b"main", 4));
+    }
+
+    #[test]
+    fn name_tables() {
+        let mut strings: Vec<u8> = vec![0];
+        // a hashed table of two compile units and a type unit, then one of
+        // a single unit searched in order
+        let mut data: Vec<u8> = debug_names(&[0x0, 0x80], &[0x200], 3, &[
+            ("main", vec![(1, vec![1, 0x20, 0, 0, 0])]),
+            ("helper", vec![(1, vec![0, 0x30, 0, 0, 0]), (1, vec![1, 0x40, 0, 0, 0])]),
+            // the second one is in the foreign type unit
+            ("point", vec![(3, vec![0, 0x1e, 0, 0, 0]), (3, vec![1, 0x1e, 0, 0, 0])]),
+            ("a", vec![(1, vec![0, 0x50, 0, 0, 0])]),
+            ("b", vec![(1, vec![0, 0x60, 0, 0, 0])]),
+        ], &mut strings);
+        data.extend(debug_names(&[0x300], &[], 0, &[
+            ("counter", vec![(2, vec![0x18, 0, 0, 0])]),
+            ("main", vec![(2, vec![0x28, 0, 0, 0])]),
+        ], &mut strings));
+        let sections: DwarfSections = DwarfSections {
+            endian: Endian::Little,
+            address_size: 8,
+            names: &data,
+            str: &strings,
+            ..Default::default()
+        };
+
+        let index: NameIndex = load(&sections, &[], None).unwrap();
+        assert_eq!(index.source(), ".debug_names");
+        assert_eq!(lookup(&index, "main"),
+                   [entry(NameKind::Function, DW_TAG_SUBPROGRAM, 0x80, 0x20),
+                    entry(NameKind::Variable, DW_TAG_VARIABLE, 0x300, 0x28)]);
+        assert_eq!(index.lookup("main").unwrap()[0].name, "main");
+        assert_eq!(lookup(&index, "helper"),
+                   [entry(NameKind::Function, DW_TAG_SUBPROGRAM, 0x0, 0x30),
+                    entry(NameKind::Function, DW_TAG_SUBPROGRAM, 0x80, 0x40)]);
+        assert_eq!(lookup(&index, "point"),
+                   [entry(NameKind::Type, DW_TAG_STRUCTURE_TYPE, 0x200, 0x1e)]);
+        assert_eq!(lookup(&index, "counter"),
+                   [entry(NameKind::Variable, DW_TAG_VARIABLE, 0x300, 0x18)]);
+        for name in ["a", "b"] {
+            assert_eq!(lookup(&index, name).len(), 1, "{name}");
+        }
+        // the hash ignores case, the names do not
+        assert!(lookup(&index, "MAIN").is_empty());
+        assert!(lookup(&index, "missing").is_empty());
+
+        let mut version: Vec<u8> = data.clone();
+        version[4] = 4;
+        assert!(parse_debug_names(&DwarfSections { names: &version, ..sections }).is_err());
+        let truncated: &[u8] = &data[..data.len() - 1];
+        assert!(parse_debug_names(&DwarfSections { names: truncated, ..sections }).is_err());
+    }
+}
@@ -0,0 +1,70 @@
+use super::dw_consts::*;
+use super::unit::{AttrValue, Unit};
+
+// What the unit entry of a compilation unit says about how it was built
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct UnitSummary {
+    pub offset: usize,
+    pub name: Option<String>,
+    pub comp_dir: Option<String>,
+    pub producer: Option<String>,
+    pub language: Option<u64>,
+}
+
+impl UnitSummary {
+    // the producer without the command line options GCC appends to it
+    // with -grecord-gcc-switches (the default)
+    pub fn compiler(&self) -> Option<&str> {
+        let producer: &str = self.producer.as_deref()?;
+        match producer.find(" -") {
+            Some(idx) => Some(producer[..idx].trim_end()),
+            None => Some(producer),
+        }
+    }
+
+    // options recorded in the producer, each starting with '-'. An option
+    // with a separate argument keeps it, as in "-I dir".
+    pub fn flags(&self) -> Vec<&str> {
+        let producer: &str = match self.producer.as_deref() {
+            Some(producer) => producer,
+            None => return Vec::new(),
+        };
+        let start: usize = match producer.find(" -") {
+            Some(idx) => idx + 1,
+            None => return Vec::new(),
+        };
+        let mut flags: Vec<&str> = Vec::new();
+        let mut rest: &str = &producer[start..];
+        while !rest.is_empty() {
+            let end: usize = rest.find(" -").unwrap_or(rest.len());
+            flags.push(rest[..end].trim());
+            rest = rest[end..].trim_start();
+        }
+        flags
+    }
+
+    pub fn language_name(&self) -> Option<&'static str> {
+        language_name(self.language?)
+    }
+}
+
+pub fn summarize(unit: &Unit) -> UnitSummary {
+    let mut summary: UnitSummary = UnitSummary {
+        offset: unit.header.offset,
+        ..Default::default()
+    };
+    let root = match unit.dies.first() {
+        Some(root) => root,
+        None => return summary,
+    };
+    let string = |name: u16| -> Option<String> {
+        let value: AttrValue = root.attr(name)?;
+        unit.sections.string(&value).map(|s| String::from_utf8_lossy(s).into_owned())
+    };
+
+    summary.name = string(DW_AT_NAME);
+    summary.comp_dir = string(DW_AT_COMP_DIR);
+    summary.producer = string(DW_AT_PRODUCER);
+    summary.language = root.attr(DW_AT_LANGUAGE).and_then(|v| v.udata());
+    summary
+}
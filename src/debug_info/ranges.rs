@@ -0,0 +1,148 @@
+use super::{AddressTable, Encoding, Reader};
+use super::dw_consts::*;
+use super::unit::{AttrValue, Die, Unit};
+
+// half open address range [begin, end)
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Range {
+    pub begin: u64,
+    pub end: u64,
+}
+
+impl Range {
+    pub fn contains(&self, addr: u64) -> bool {
+        self.begin <= addr && addr < self.end
+    }
+}
+
+// pre DWARF 5 range list in .debug_ranges
+pub fn parse_ranges(data: &[u8], offset: u64, encoding: Encoding,
+                    base: u64) -> Result<Vec<Range>, &'static str> {
+    let mut reader: Reader = Reader::new(data, offset as usize, encoding.endian);
+    let mut ranges: Vec<Range> = Vec::new();
+    let mut base: u64 = base;
+
+    loop {
+        let begin: u64 = reader.sized(encoding.address_size)?;
+        let end: u64 = reader.sized(encoding.address_size)?;
+        if begin == 0 && end == 0 {
+            break;
+        }
+        if begin == encoding.max_address() {
+            // base address selection entry
+            base = end;
+            continue;
+        }
+        ranges.push(Range {
+            begin: base.wrapping_add(begin),
+            end: base.wrapping_add(end),
+        });
+    }
+
+    Ok(ranges)
+}
+
+// DWARF 5 range list in .debug_rnglists
+pub fn parse_rnglists(data: &[u8], offset: u64, encoding: Encoding, base: u64,
+                      addresses: &AddressTable) -> Result<Vec<Range>, &'static str> {
+    let mut reader: Reader = Reader::new(data, offset as usize, encoding.endian);
+    let mut ranges: Vec<Range> = Vec::new();
+    let mut base: u64 = base;
+
+    loop {
+        let kind: u8 = reader.u8()?;
+        match kind {
+            DW_RLE_END_OF_LIST => break,
+            DW_RLE_BASE_ADDRESS => {
+                base = reader.sized(encoding.address_size)?;
+            },
+            DW_RLE_OFFSET_PAIR => {
+                let begin: u64 = reader.uleb()?;
+                let end: u64 = reader.uleb()?;
+                ranges.push(Range {
+                    begin: base.wrapping_add(begin),
+                    end: base.wrapping_add(end),
+                });
+            },
+            DW_RLE_START_END => {
+                let begin: u64 = reader.sized(encoding.address_size)?;
+                let end: u64 = reader.sized(encoding.address_size)?;
+                ranges.push(Range { begin, end });
+            },
+            DW_RLE_START_LENGTH => {
+                let begin: u64 = reader.sized(encoding.address_size)?;
+                let length: u64 = reader.uleb()?;
+                ranges.push(Range {
+                    begin,
+                    end: begin.wrapping_add(length),
+                });
+            },
+            DW_RLE_BASE_ADDRESSX => {
+                base = addresses.get(reader.uleb()?)?;
+            },
+            DW_RLE_STARTX_ENDX => {
+                let begin: u64 = addresses.get(reader.uleb()?)?;
+                let end: u64 = addresses.get(reader.uleb()?)?;
+                ranges.push(Range { begin, end });
+            },
+            DW_RLE_STARTX_LENGTH => {
+                let begin: u64 = addresses.get(reader.uleb()?)?;
+                let length: u64 = reader.uleb()?;
+                ranges.push(Range {
+                    begin,
+                    end: begin.wrapping_add(length),
+                });
+            },
+            _ => return Err("Unknown range list entry."),
+        }
+    }
+
+    Ok(ranges)
+}
+
+// base address of a unit used by its range and location lists, split
+// units take it from their skeleton
+pub fn unit_base(unit: &Unit) -> u64 {
+    match unit.dies.first().and_then(|die| die.attr(DW_AT_LOW_PC)) {
+        Some(AttrValue::Addr(addr)) => addr,
+        _ => unit.bases.address,
+    }
+}
+
+// address ranges covered by an entry, from DW_AT_low_pc/DW_AT_high_pc
+// or DW_AT_ranges
+pub fn die_ranges(unit: &Unit, die: &Die) -> Result<Vec<Range>, &'static str> {
+    let encoding: Encoding = unit.encoding();
+
+    if let Some(value) = die.attr(DW_AT_RANGES) {
+        let offset: u64 = match value {
+            AttrValue::SecOffset(offset) | AttrValue::Data(offset) => offset,
+            _ => return Err("Unsupported DW_AT_ranges form."),
+        };
+        if encoding.version >= 5 {
+            return parse_rnglists(unit.sections.rnglists, offset, encoding,
+                                  unit_base(unit), &unit.addresses());
+        }
+        return parse_ranges(unit.sections.ranges, offset, encoding,
+                            unit_base(unit));
+    }
+
+    let low: u64 = match die.attr(DW_AT_LOW_PC) {
+        Some(AttrValue::Addr(addr)) => addr,
+        _ => return Ok(Vec::new()),
+    };
+    let high: u64 = match die.attr(DW_AT_HIGH_PC) {
+        Some(AttrValue::Addr(addr)) => addr,
+        // the constant class encodes the length of the range
+        Some(value) => match value.udata() {
+            Some(length) => low.wrapping_add(length),
+            None => return Err("Unsupported DW_AT_high_pc form."),
+        },
+        None => low.wrapping_add(1),
+    };
+
+    Ok(vec![Range {
+        begin: low,
+        end: high,
+    }])
+}
@@ -0,0 +1,339 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use super::{DwarfSections, Reader};
+use super::dw_consts::*;
+use super::ranges::unit_base;
+use super::unit::{self, Unit};
+use crate::elf::{self, Elf};
+
+// Contribution of one unit to a section of a .dwp package
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Contribution {
+    pub section: u32,
+    pub offset: u64,
+    pub size: u64,
+}
+
+// A .debug_cu_index or .debug_tu_index section, maps the dwo id or type
+// signature of a unit to its contributions
+#[derive(Debug, Default, Clone)]
+pub struct PackageIndex {
+    pub version: u16,
+    pub entries: HashMap<u64, Vec<Contribution>>,
+}
+
+pub fn parse_index(data: &[u8],
+                   sections: &DwarfSections) -> Result<PackageIndex, &'static str> {
+    let mut reader: Reader = Reader::new(data, 0, sections.endian);
+    let mut index: PackageIndex = PackageIndex {
+        ..Default::default()
+    };
+
+    // the GNU format has a 4 byte version, DWARF 5 a 2 byte version
+    // followed by padding
+    let version: u32 = reader.u32()?;
+    index.version = if version == 2 {
+        2
+    }
+    else if version & 0xffff == 5 || version >> 16 == 5 {
+        5
+    }
+    else {
+        return Err("Unsupported package index version.");
+    };
+    let section_count: usize = reader.u32()? as usize;
+    let unit_count: usize = reader.u32()? as usize;
+    let slot_count: usize = reader.u32()? as usize;
+
+    let mut signatures: Vec<u64> = Vec::with_capacity(slot_count);
+    for _ in 0..slot_count {
+        signatures.push(reader.u64()?);
+    }
+    let mut rows: Vec<u32> = Vec::with_capacity(slot_count);
+    for _ in 0..slot_count {
+        rows.push(reader.u32()?);
+    }
+
+    let mut ids: Vec<u32> = Vec::with_capacity(section_count);
+    for _ in 0..section_count {
+        ids.push(reader.u32()?);
+    }
+    let offsets_start: usize = reader.offset;
+    let sizes_start: usize = unit_count.checked_mul(section_count * 4)
+                                       .and_then(|len| len.checked_add(offsets_start))
+                                       .ok_or("Package index out of bounds.")?;
+
+    for (signature, row) in signatures.iter().zip(rows.iter()) {
+        // row 0 marks an empty slot, rows are numbered from 1
+        if *row == 0 {
+            continue;
+        }
+        if *row as usize > unit_count {
+            return Err("Package index row out of bounds.");
+        }
+        let row_offset: usize = (*row as usize - 1) * section_count * 4;
+        let mut offsets: Reader = Reader::new(data, offsets_start + row_offset,
+                                              sections.endian);
+        let mut sizes: Reader = Reader::new(data, sizes_start + row_offset,
+                                            sections.endian);
+        let mut contributions: Vec<Contribution> = Vec::with_capacity(section_count);
+        for id in ids.iter() {
+            contributions.push(Contribution {
+                section: *id,
+                offset: offsets.u32()? as u64,
+                size: sizes.u32()? as u64,
+            });
+        }
+        index.entries.insert(*signature, contributions);
+    }
+
+    Ok(index)
+}
+
+fn slice<'a>(data: &'a [u8], contribution: &Contribution) -> &'a [u8] {
+    let start: usize = (contribution.offset as usize).min(data.len());
+    let end: usize = start.saturating_add(contribution.size as usize).min(data.len());
+    &data[start..end]
+}
+
+// sections of one unit of a package, each cut down to the contribution
+// of that unit so offsets inside the unit need no adjustment
+pub fn package_sections<'a>(package: &DwarfSections<'a>, version: u16,
+                            contributions: &[Contribution]) -> DwarfSections<'a> {
+    let mut sections: DwarfSections<'a> = *package;
+    for contribution in contributions.iter() {
+        match (contribution.section, version) {
+            (DW_SECT_INFO, _) => sections.info = slice(package.info, contribution),
+            (DW_SECT_ABBREV, _) => sections.abbrev = slice(package.abbrev, contribution),
+            (DW_SECT_LINE, _) => sections.line = slice(package.line, contribution),
+            (DW_SECT_STR_OFFSETS, _) => {
+                sections.str_offsets = slice(package.str_offsets, contribution);
+            },
+            (DW_SECT_V2_LOC, 2) => sections.loc = slice(package.loc, contribution),
+            (DW_SECT_LOCLISTS, _) => {
+                sections.loclists = slice(package.loclists, contribution);
+            },
+            (DW_SECT_MACRO, 5) | (DW_SECT_V2_MACRO, 2) => {
+                sections.macro_ = slice(package.macro_, contribution);
+            },
+            (DW_SECT_V2_MACINFO, 2) => {
+                sections.macinfo = slice(package.macinfo, contribution);
+            },
+            (DW_SECT_RNGLISTS, 5) => {
+                sections.rnglists = slice(package.rnglists, contribution);
+            },
+            _ => (),
+        }
+    }
+    sections
+}
+
+// sections of a .dwo file or package, completed with those of the
+// executable that split DWARF leaves there
+pub fn dwo_sections<'a>(dwo: &DwarfSections<'a>,
+                        parent: &DwarfSections<'a>) -> DwarfSections<'a> {
+    DwarfSections {
+        addr: parent.addr,
+        line_str: parent.line_str,
+        ranges: parent.ranges,
+        debug_frame: parent.debug_frame,
+        eh_frame: parent.eh_frame,
+        eh_frame_addr: parent.eh_frame_addr,
+        ..*dwo
+    }
+}
+
+// path of the .dwo file named by a skeleton unit, relative names are
+// relative to its DW_AT_comp_dir
+pub fn dwo_path(skeleton: &Unit) -> Option<PathBuf> {
+    let root = skeleton.dies.first()?;
+    let name = root.attr(DW_AT_DWO_NAME).or_else(|| root.attr(DW_AT_GNU_DWO_NAME))?;
+    let name: PathBuf = PathBuf::from(String::from_utf8_lossy(skeleton.sections.string(&name)?)
+                                          .into_owned());
+    match root.attr(DW_AT_COMP_DIR).and_then(|v| skeleton.sections.string(&v)) {
+        Some(dir) if name.is_relative() => {
+            Some(Path::new(&*String::from_utf8_lossy(dir)).join(name))
+        },
+        _ => Some(name),
+    }
+}
+
+// A .dwo file that was read but is not a valid ELF file
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvalidDwo {
+    pub path: PathBuf,
+    pub reason: &'static str,
+}
+
+// The .dwo files of the skeleton units, and those that were skipped
+#[derive(Debug, Default)]
+pub struct DwoFiles {
+    pub files: Vec<Elf>,
+    pub invalid: Vec<InvalidDwo>,
+}
+
+// Read the .dwo files of the skeleton units in `sections`. A file that is
+// not found at its recorded path is looked up by name in `search_dirs`,
+// files that cannot be found at all are skipped and so are invalid ones,
+// the next candidate is tried instead.
+pub fn load_dwos(sections: &DwarfSections,
+                 search_dirs: &[PathBuf]) -> Result<DwoFiles, &'static str> {
+    let mut dwos: DwoFiles = DwoFiles::default();
+    for skeleton in unit::parse_units(sections)? {
+        if !skeleton.is_skeleton() {
+            continue;
+        }
+        let path: PathBuf = match dwo_path(&skeleton) {
+            Some(path) => path,
+            None => continue,
+        };
+        let mut candidates: Vec<PathBuf> = vec![path.clone()];
+        if let Some(name) = path.file_name() {
+            candidates.extend(search_dirs.iter().map(|dir| dir.join(name)));
+        }
+        for candidate in candidates {
+            let content: Vec<u8> = match fs::read(&candidate) {
+                Ok(content) => content,
+                Err(_) => continue,
+            };
+            match elf::parse(content) {
+                Ok(elf) => {
+                    dwos.files.push(elf);
+                    break;
+                },
+                Err(reason) => dwos.invalid.push(InvalidDwo {
+                    path: candidate,
+                    reason,
+                }),
+            }
+        }
+    }
+    Ok(dwos)
+}
+
+// A .dwp package with its unit indexes, parsed once for all the units
+// looked up in it
+#[derive(Debug, Default, Clone)]
+pub struct Package<'a> {
+    pub sections: DwarfSections<'a>,
+    pub cu_index: Option<PackageIndex>,
+    pub tu_index: Option<PackageIndex>,
+}
+
+impl<'a> Package<'a> {
+    pub fn load(sections: DwarfSections<'a>) -> Result<Package<'a>, &'static str> {
+        let index = |data: &[u8]| -> Result<Option<PackageIndex>, &'static str> {
+            match data.is_empty() {
+                true => Ok(None),
+                false => parse_index(data, &sections).map(Some),
+            }
+        };
+        Ok(Package {
+            cu_index: index(sections.cu_index)?,
+            tu_index: index(sections.tu_index)?,
+            sections,
+        })
+    }
+}
+
+// `<executable>.dwp`, the package dwp writes next to an executable
+pub fn package_path(executable: &Path) -> PathBuf {
+    let mut name = executable.as_os_str().to_owned();
+    name.push(".dwp");
+    PathBuf::from(name)
+}
+
+// the unit of `sections` with a dwo id or type signature
+fn find_unit<'a>(sections: &DwarfSections<'a>, id: u64,
+                 parent: &Unit<'a>) -> Result<Option<Unit<'a>>, &'static str> {
+    for header in unit::parse_headers(sections.info, sections)? {
+        if header.dwo_id.is_some_and(|dwo_id| dwo_id != id)
+           || header.type_signature.is_some_and(|sig| sig != id) {
+            continue;
+        }
+        let abbrevs = unit::parse_abbrevs(sections.abbrev, header.abbrev_offset,
+                                          sections)?;
+        let mut unit: Unit<'a> = unit::parse_unit(sections, sections.info,
+                                                  header, &abbrevs)?;
+        if unit.dwo_id() != Some(id) && unit.header.type_signature != Some(id) {
+            continue;
+        }
+        // the skeleton provides the .debug_addr base and the base
+        // address, GNU split units also take the range list base from it
+        unit.bases.addr = parent.bases.addr;
+        unit.bases.address = unit_base(parent);
+        if unit.header.version < 5 {
+            unit.bases.rnglists = parent.bases.rnglists;
+        }
+        if parent.is_skeleton() {
+            unit.skeleton_offset = Some(parent.header.offset);
+        }
+        unit.resolve_indexes();
+        return Ok(Some(unit));
+    }
+    Ok(None)
+}
+
+// The split unit of a skeleton, from a .dwp package when one is given,
+// otherwise from the matching .dwo file
+pub fn split_unit<'a>(skeleton: &Unit<'a>, dwos: &[DwarfSections<'a>],
+                      package: Option<&Package<'a>>)
+                      -> Result<Option<Unit<'a>>, &'static str> {
+    let id: u64 = match skeleton.dwo_id() {
+        Some(id) => id,
+        None => return Ok(None),
+    };
+    if let Some(package) = package {
+        if let Some(index) = package.cu_index.as_ref() {
+            if let Some(contributions) = index.entries.get(&id) {
+                let sections: DwarfSections<'a> = package_sections(&package.sections,
+                                                                   index.version,
+                                                                   contributions);
+                return find_unit(&dwo_sections(&sections, &skeleton.sections), id,
+                                 skeleton);
+            }
+        }
+    }
+    for dwo in dwos.iter() {
+        if let Some(unit) = find_unit(&dwo_sections(dwo, &skeleton.sections), id,
+                                      skeleton)? {
+            return Ok(Some(unit));
+        }
+    }
+    Ok(None)
+}
+
+// A type unit of a package by its signature, from .debug_tu_index
+pub fn package_type_unit<'a>(package: &Package<'a>, parent: &Unit<'a>,
+                             signature: u64) -> Result<Option<Unit<'a>>, &'static str> {
+    let index: &PackageIndex = match package.tu_index.as_ref() {
+        Some(index) => index,
+        None => return Ok(None),
+    };
+    match index.entries.get(&signature) {
+        Some(contributions) => {
+            let sections: DwarfSections<'a> = package_sections(&package.sections, index.version,
+                                                               contributions);
+            find_unit(&dwo_sections(&sections, &parent.sections), signature, parent)
+        },
+        None => Ok(None),
+    }
+}
+
+// Every unit of `sections` with skeleton units replaced by their split
+// unit where it can be found in `dwos` or `package`
+pub fn units<'a>(sections: &DwarfSections<'a>, dwos: &[DwarfSections<'a>],
+                 package: Option<&Package<'a>>) -> Result<Vec<Unit<'a>>, &'static str> {
+    let mut units: Vec<Unit<'a>> = Vec::new();
+    for unit in unit::parse_units(sections)? {
+        if unit.is_skeleton() {
+            if let Some(split) = split_unit(&unit, dwos, package)? {
+                units.push(split);
+                continue;
+            }
+        }
+        units.push(unit);
+    }
+    Ok(units)
+}
@@ -0,0 +1,593 @@
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use super::{AddressTable, DwarfSections, Encoding, Reader};
+use super::dw_consts::*;
+
+#[derive(Debug, Default, Clone)]
+pub struct UnitHeader {
+    pub offset: usize,
+    pub unit_length: u64,
+    pub offset_size: u8,
+    pub version: u16,
+    pub unit_type: u8,
+    pub abbrev_offset: u64,
+    pub address_size: u8,
+    pub dwo_id: Option<u64>,
+    pub type_signature: Option<u64>,
+    pub type_offset: Option<u64>,
+    pub entries_offset: usize,
+    pub end: usize,
+}
+
+impl UnitHeader {
+    pub fn encoding(&self, sections: &DwarfSections) -> Encoding {
+        Encoding {
+            address_size: self.address_size,
+            offset_size: self.offset_size,
+            version: self.version,
+            endian: sections.endian,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct AttrSpec {
+    pub name: u16,
+    pub form: u16,
+    pub implicit_const: i64,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct Abbrev {
+    pub code: u64,
+    pub tag: u16,
+    pub has_children: bool,
+    pub attrs: Vec<AttrSpec>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AttrValue<'a> {
+    Addr(u64),
+    AddrIndex(u64),
+    Block(&'a [u8]),
+    Exprloc(&'a [u8]),
+    Data(u64),
+    Data16(&'a [u8]),
+    Sdata(i64),
+    Udata(u64),
+    Flag(bool),
+    String(&'a [u8]),
+    Strp(u64),
+    LineStrp(u64),
+    StrIndex(u64),
+    StrpSup(u64),
+    UnitRef(u64),
+    RefAddr(u64),
+    RefSig8(u64),
+    RefSup(u64),
+    SecOffset(u64),
+    LoclistIndex(u64),
+    RnglistIndex(u64),
+}
+
+impl AttrValue<'_> {
+    // value of a constant class attribute
+    pub fn udata(&self) -> Option<u64> {
+        match *self {
+            AttrValue::Data(v) | AttrValue::Udata(v) => Some(v),
+            AttrValue::Sdata(v) => Some(v as u64),
+            _ => None,
+        }
+    }
+
+    pub fn sdata(&self) -> Option<i64> {
+        match *self {
+            AttrValue::Data(v) | AttrValue::Udata(v) => Some(v as i64),
+            AttrValue::Sdata(v) => Some(v),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Attr<'a> {
+    pub name: u16,
+    pub form: u16,
+    pub value: AttrValue<'a>,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct Die<'a> {
+    pub offset: usize,
+    pub depth: usize,
+    pub code: u64,
+    pub tag: u16,
+    pub has_children: bool,
+    pub attrs: Vec<Attr<'a>>,
+}
+
+impl<'a> Die<'a> {
+    pub fn attr(&self, name: u16) -> Option<AttrValue<'a>> {
+        self.attrs.iter().find(|a| a.name == name).map(|a| a.value)
+    }
+}
+
+// Offsets the indexed forms of a unit are relative to, in
+// .debug_str_offsets, .debug_addr, .debug_rnglists and .debug_loclists
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct UnitBases {
+    // base address of the range and location lists
+    pub address: u64,
+    pub str_offsets: u64,
+    pub addr: u64,
+    pub rnglists: u64,
+    pub loclists: u64,
+}
+
+// A unit with all of its entries, children follow their parent with a
+// depth one greater. `sections` are the sections its attributes refer
+// to, those of the .dwo file for a split unit.
+#[derive(Debug, Default, Clone)]
+pub struct Unit<'a> {
+    pub header: UnitHeader,
+    pub sections: DwarfSections<'a>,
+    pub bases: UnitBases,
+    // offset in .debug_info of the skeleton of a split unit
+    pub skeleton_offset: Option<usize>,
+    pub dies: Vec<Die<'a>>,
+    index: HashMap<usize, usize>,
+}
+
+impl<'a> Unit<'a> {
+    pub fn encoding(&self) -> Encoding {
+        self.header.encoding(&self.sections)
+    }
+
+    // skeleton unit of a split DWARF object, its entries are in a .dwo
+    // file or .dwp package
+    pub fn is_skeleton(&self) -> bool {
+        match self.header.unit_type {
+            DW_UT_SKELETON => true,
+            _ => self.header.version < 5 && self.dies.first()
+                .is_some_and(|root| root.attr(DW_AT_GNU_DWO_NAME).is_some()),
+        }
+    }
+
+    // unit read from a .dwo file or .dwp package
+    pub fn is_split(&self) -> bool {
+        match self.header.unit_type {
+            DW_UT_SPLIT_COMPILE | DW_UT_SPLIT_TYPE => true,
+            _ => self.header.version < 5 && !self.is_skeleton() && self.dies.first()
+                .is_some_and(|root| root.attr(DW_AT_GNU_DWO_ID).is_some()),
+        }
+    }
+
+    // id shared by a skeleton unit and its split unit
+    pub fn dwo_id(&self) -> Option<u64> {
+        self.header.dwo_id.or_else(|| {
+            match self.dies.first()?.attr(DW_AT_GNU_DWO_ID)? {
+                AttrValue::Data(id) | AttrValue::Udata(id) => Some(id),
+                _ => None,
+            }
+        })
+    }
+
+    pub fn addresses(&self) -> AddressTable<'a> {
+        AddressTable {
+            data: self.sections.addr,
+            base: self.bases.addr,
+            address_size: self.header.address_size,
+            endian: self.sections.endian,
+        }
+    }
+
+    // entry at a section offset
+    pub fn die_at(&self, offset: usize) -> Option<&Die<'a>> {
+        self.index.get(&offset).map(|i| &self.dies[*i])
+    }
+
+    // index into `dies` of the entry at a section offset
+    pub fn index_of(&self, offset: usize) -> Option<usize> {
+        self.index.get(&offset).copied()
+    }
+
+    // entry referenced by a unit relative or section relative reference
+    pub fn reference(&self, value: AttrValue) -> Option<&Die<'a>> {
+        match value {
+            AttrValue::UnitRef(offset) => {
+                self.die_at(self.header.offset + offset as usize)
+            },
+            AttrValue::RefAddr(offset) => self.die_at(offset as usize),
+            _ => None,
+        }
+    }
+
+    // range of indexes into `dies` holding the children (and deeper
+    // descendants) of the entry at `idx`
+    pub fn subtree(&self, idx: usize) -> std::ops::Range<usize> {
+        let depth: usize = self.dies[idx].depth;
+        let mut end: usize = idx + 1;
+        while end < self.dies.len() && self.dies[end].depth > depth {
+            end += 1;
+        }
+        (idx + 1)..end
+    }
+
+    // name of an entry, following DW_AT_abstract_origin and
+    // DW_AT_specification when the entry has none of its own
+    pub fn name(&self, die: &Die<'a>) -> Option<String> {
+        let mut current: &Die<'a> = die;
+        for _ in 0..8 {
+            if let Some(value) = current.attr(DW_AT_NAME) {
+                return self.sections.string(&value)
+                    .map(|s| String::from_utf8_lossy(s).into_owned());
+            }
+            let origin = current.attr(DW_AT_ABSTRACT_ORIGIN)
+                .or_else(|| current.attr(DW_AT_SPECIFICATION))?;
+            current = self.reference(origin)?;
+        }
+        None
+    }
+
+    // .debug_str offset stored at `index` of the string offsets of the unit
+    pub fn string_offset(&self, index: u64) -> Option<u64> {
+        table_entry(self.sections.str_offsets, self.bases.str_offsets, index,
+                    self.header.offset_size, &self.sections)
+    }
+
+    // replace the indexed forms of every attribute by the string offset,
+    // address or list offset they stand for, indexes that cannot be
+    // resolved are kept
+    pub fn resolve_indexes(&mut self) {
+        let split: bool = self.is_split();
+        let addresses: AddressTable<'a> = self.addresses();
+        let header: &UnitHeader = &self.header;
+        let sections: &DwarfSections<'a> = &self.sections;
+        let bases: UnitBases = self.bases;
+        for die in self.dies.iter_mut() {
+            for attr in die.attrs.iter_mut() {
+                let resolved: Option<AttrValue<'a>> = match attr.value {
+                    AttrValue::StrIndex(index) => {
+                        table_entry(sections.str_offsets, bases.str_offsets, index,
+                                    header.offset_size, sections)
+                            .map(AttrValue::Strp)
+                    },
+                    AttrValue::AddrIndex(index) => {
+                        addresses.get(index).ok().map(AttrValue::Addr)
+                    },
+                    // list offsets are relative to the base of the unit
+                    AttrValue::RnglistIndex(index) => {
+                        table_entry(sections.rnglists, bases.rnglists, index,
+                                    header.offset_size, sections)
+                            .map(|offset| AttrValue::SecOffset(bases.rnglists + offset))
+                    },
+                    AttrValue::LoclistIndex(index) => {
+                        table_entry(sections.loclists, bases.loclists, index,
+                                    header.offset_size, sections)
+                            .map(|offset| AttrValue::SecOffset(bases.loclists + offset))
+                    },
+                    // GNU split units add DW_AT_GNU_ranges_base of their
+                    // skeleton to range list offsets
+                    AttrValue::SecOffset(offset) if split && header.version < 5
+                                                    && attr.name == DW_AT_RANGES => {
+                        Some(AttrValue::SecOffset(offset + bases.rnglists))
+                    },
+                    _ => None,
+                };
+                if let Some(value) = resolved {
+                    attr.value = value;
+                }
+            }
+        }
+    }
+}
+
+// offset stored at `index` of an offsets table starting at `base`
+fn table_entry(data: &[u8], base: u64, index: u64, offset_size: u8,
+               sections: &DwarfSections) -> Option<u64> {
+    let offset: u64 = index.checked_mul(offset_size as u64)?.checked_add(base)?;
+    Reader::new(data, offset as usize, sections.endian).sized(offset_size).ok()
+}
+
+// size of the header in front of the offsets of a .debug_str_offsets
+// contribution, and of a .debug_rnglists or .debug_loclists one
+fn offsets_header_size(offset_size: u8) -> (u64, u64) {
+    if offset_size == 8 {
+        (16, 20)
+    }
+    else {
+        (8, 12)
+    }
+}
+
+// bases given by the attributes of the unit entry. Split units have
+// none, their tables start after the header of the section and their
+// .debug_addr base comes from the skeleton.
+fn unit_bases(header: &UnitHeader, root: Option<&Die>) -> UnitBases {
+    let attr = |names: &[u16]| -> Option<u64> {
+        let root: &Die = root?;
+        names.iter().find_map(|name| match root.attr(*name)? {
+            AttrValue::SecOffset(v) | AttrValue::Data(v) | AttrValue::Udata(v) => Some(v),
+            _ => None,
+        })
+    };
+    let (str_header, list_header): (u64, u64) = if header.version >= 5 {
+        offsets_header_size(header.offset_size)
+    }
+    else {
+        (0, 0)
+    };
+    let split: bool = matches!(header.unit_type, DW_UT_SPLIT_COMPILE | DW_UT_SPLIT_TYPE);
+
+    UnitBases {
+        address: match root.and_then(|root| root.attr(DW_AT_LOW_PC)) {
+            Some(AttrValue::Addr(addr)) => addr,
+            _ => 0,
+        },
+        str_offsets: attr(&[DW_AT_STR_OFFSETS_BASE]).unwrap_or(str_header),
+        addr: attr(&[DW_AT_ADDR_BASE, DW_AT_GNU_ADDR_BASE]).unwrap_or(0),
+        rnglists: attr(&[DW_AT_RNGLISTS_BASE, DW_AT_GNU_RANGES_BASE])
+            .unwrap_or(if split { list_header } else { 0 }),
+        loclists: attr(&[DW_AT_LOCLISTS_BASE])
+            .unwrap_or(if split { list_header } else { 0 }),
+    }
+}
+
+pub fn parse_header(info: &[u8], offset: usize,
+                    sections: &DwarfSections) -> Result<UnitHeader, &'static str> {
+    let mut reader: Reader = Reader::new(info, offset, sections.endian);
+    let mut header: UnitHeader = UnitHeader {
+        offset,
+        ..Default::default()
+    };
+
+    let (unit_length, offset_size) = reader.initial_length()?;
+    header.unit_length = unit_length;
+    header.offset_size = offset_size;
+    header.end = reader.offset.checked_add(unit_length as usize)
+                               .ok_or("Unit length out of bounds.")?;
+    if header.end > info.len() {
+        return Err("Unit length out of bounds.");
+    }
+
+    header.version = reader.u16()?;
+    match header.version {
+        2..=4 => {
+            header.unit_type = DW_UT_COMPILE;
+            header.abbrev_offset = reader.sized(offset_size)?;
+            header.address_size = reader.u8()?;
+        },
+        5 => {
+            header.unit_type = reader.u8()?;
+            header.address_size = reader.u8()?;
+            header.abbrev_offset = reader.sized(offset_size)?;
+            match header.unit_type {
+                DW_UT_SKELETON | DW_UT_SPLIT_COMPILE => {
+                    header.dwo_id = Some(reader.u64()?);
+                },
+                DW_UT_TYPE | DW_UT_SPLIT_TYPE => {
+                    header.type_signature = Some(reader.u64()?);
+                    header.type_offset = Some(reader.sized(offset_size)?);
+                },
+                _ => (),
+            }
+        },
+        _ => return Err("Unsupported DWARF version."),
+    }
+    header.entries_offset = reader.offset;
+
+    Ok(header)
+}
+
+// headers of every unit in a .debug_info section
+pub fn parse_headers(info: &[u8],
+                     sections: &DwarfSections) -> Result<Vec<UnitHeader>, &'static str> {
+    let mut headers: Vec<UnitHeader> = Vec::new();
+    let mut offset: usize = 0;
+    while offset < info.len() {
+        let header: UnitHeader = parse_header(info, offset, sections)?;
+        offset = header.end;
+        headers.push(header);
+    }
+    Ok(headers)
+}
+
+pub fn parse_abbrevs(abbrev: &[u8], offset: u64,
+                     sections: &DwarfSections) -> Result<HashMap<u64, Abbrev>, &'static str> {
+    let mut reader: Reader = Reader::new(abbrev, offset as usize, sections.endian);
+    let mut abbrevs: HashMap<u64, Abbrev> = HashMap::new();
+
+    loop {
+        let code: u64 = reader.uleb()?;
+        if code == 0 {
+            break;
+        }
+        let tag: u64 = reader.uleb()?;
+        let has_children: bool = reader.u8()? != 0;
+        let mut attrs: Vec<AttrSpec> = Vec::new();
+        loop {
+            let name: u64 = reader.uleb()?;
+            let form: u64 = reader.uleb()?;
+            if name == 0 && form == 0 {
+                break;
+            }
+            let implicit_const: i64 = if form == DW_FORM_IMPLICIT_CONST as u64 {
+                reader.sleb()?
+            }
+            else {
+                0
+            };
+            attrs.push(AttrSpec {
+                name: name as u16,
+                form: form as u16,
+                implicit_const,
+            });
+        }
+        abbrevs.insert(code, Abbrev {
+            code,
+            tag: tag as u16,
+            has_children,
+            attrs,
+        });
+    }
+
+    Ok(abbrevs)
+}
+
+pub fn parse_value<'a>(reader: &mut Reader<'a>, spec: AttrSpec,
+                       header: &UnitHeader) -> Result<AttrValue<'a>, &'static str> {
+    let mut form: u16 = spec.form;
+    // DW_FORM_indirect stores the real form in front of the value
+    while form == DW_FORM_INDIRECT {
+        form = reader.uleb()? as u16;
+    }
+
+    let value: AttrValue = match form {
+        DW_FORM_ADDR => AttrValue::Addr(reader.sized(header.address_size)?),
+        DW_FORM_BLOCK1 => {
+            let len: u8 = reader.u8()?;
+            AttrValue::Block(reader.bytes(len as usize)?)
+        },
+        DW_FORM_BLOCK2 => {
+            let len: u16 = reader.u16()?;
+            AttrValue::Block(reader.bytes(len as usize)?)
+        },
+        DW_FORM_BLOCK4 => {
+            let len: u32 = reader.u32()?;
+            AttrValue::Block(reader.bytes(len as usize)?)
+        },
+        DW_FORM_BLOCK => {
+            let len: u64 = reader.uleb()?;
+            AttrValue::Block(reader.bytes(len as usize)?)
+        },
+        DW_FORM_EXPRLOC => {
+            let len: u64 = reader.uleb()?;
+            AttrValue::Exprloc(reader.bytes(len as usize)?)
+        },
+        DW_FORM_DATA1 => AttrValue::Data(reader.u8()? as u64),
+        DW_FORM_DATA2 => AttrValue::Data(reader.u16()? as u64),
+        DW_FORM_DATA4 => AttrValue::Data(reader.u32()? as u64),
+        DW_FORM_DATA8 => AttrValue::Data(reader.u64()?),
+        DW_FORM_DATA16 => AttrValue::Data16(reader.bytes(16)?),
+        DW_FORM_SDATA => AttrValue::Sdata(reader.sleb()?),
+        DW_FORM_UDATA => AttrValue::Udata(reader.uleb()?),
+        DW_FORM_IMPLICIT_CONST => AttrValue::Sdata(spec.implicit_const),
+        DW_FORM_FLAG => AttrValue::Flag(reader.u8()? != 0),
+        DW_FORM_FLAG_PRESENT => AttrValue::Flag(true),
+        DW_FORM_STRING => AttrValue::String(reader.cstr()?),
+        DW_FORM_STRP => AttrValue::Strp(reader.sized(header.offset_size)?),
+        DW_FORM_LINE_STRP => AttrValue::LineStrp(reader.sized(header.offset_size)?),
+        DW_FORM_STRP_SUP | DW_FORM_GNU_STRP_ALT => {
+            AttrValue::StrpSup(reader.sized(header.offset_size)?)
+        },
+        DW_FORM_STRX | DW_FORM_GNU_STR_INDEX => AttrValue::StrIndex(reader.uleb()?),
+        DW_FORM_STRX1 => AttrValue::StrIndex(reader.u8()? as u64),
+        DW_FORM_STRX2 => AttrValue::StrIndex(reader.u16()? as u64),
+        DW_FORM_STRX3 => AttrValue::StrIndex(reader.u24()? as u64),
+        DW_FORM_STRX4 => AttrValue::StrIndex(reader.u32()? as u64),
+        DW_FORM_ADDRX | DW_FORM_GNU_ADDR_INDEX => AttrValue::AddrIndex(reader.uleb()?),
+        DW_FORM_ADDRX1 => AttrValue::AddrIndex(reader.u8()? as u64),
+        DW_FORM_ADDRX2 => AttrValue::AddrIndex(reader.u16()? as u64),
+        DW_FORM_ADDRX3 => AttrValue::AddrIndex(reader.u24()? as u64),
+        DW_FORM_ADDRX4 => AttrValue::AddrIndex(reader.u32()? as u64),
+        DW_FORM_REF1 => AttrValue::UnitRef(reader.u8()? as u64),
+        DW_FORM_REF2 => AttrValue::UnitRef(reader.u16()? as u64),
+        DW_FORM_REF4 => AttrValue::UnitRef(reader.u32()? as u64),
+        DW_FORM_REF8 => AttrValue::UnitRef(reader.u64()?),
+        DW_FORM_REF_UDATA => AttrValue::UnitRef(reader.uleb()?),
+        DW_FORM_REF_ADDR => {
+            // DWARF 2 sized this like an address, later versions like an
+            // offset
+            if header.version == 2 {
+                AttrValue::RefAddr(reader.sized(header.address_size)?)
+            }
+            else {
+                AttrValue::RefAddr(reader.sized(header.offset_size)?)
+            }
+        },
+        DW_FORM_REF_SIG8 => AttrValue::RefSig8(reader.u64()?),
+        DW_FORM_REF_SUP4 => AttrValue::RefSup(reader.u32()? as u64),
+        DW_FORM_REF_SUP8 => AttrValue::RefSup(reader.u64()?),
+        DW_FORM_GNU_REF_ALT => AttrValue::RefSup(reader.sized(header.offset_size)?),
+        DW_FORM_SEC_OFFSET => AttrValue::SecOffset(reader.sized(header.offset_size)?),
+        DW_FORM_LOCLISTX => AttrValue::LoclistIndex(reader.uleb()?),
+        DW_FORM_RNGLISTX => AttrValue::RnglistIndex(reader.uleb()?),
+        _ => return Err("Unknown attribute form."),
+    };
+
+    Ok(value)
+}
+
+// parse every entry of a unit
+pub fn parse_unit<'a>(sections: &DwarfSections<'a>, info: &'a [u8],
+                      header: UnitHeader,
+                      abbrevs: &HashMap<u64, Abbrev>) -> Result<Unit<'a>, &'static str> {
+    let mut reader: Reader<'a> = Reader::new(&info[..header.end],
+                                             header.entries_offset,
+                                             sections.endian);
+    let mut dies: Vec<Die<'a>> = Vec::new();
+    let mut index: HashMap<usize, usize> = HashMap::new();
+    let mut depth: usize = 0;
+
+    while !reader.is_empty() {
+        let offset: usize = reader.offset;
+        let code: u64 = reader.uleb()?;
+        if code == 0 {
+            // null entry closes the current list of siblings
+            depth = depth.saturating_sub(1);
+            continue;
+        }
+        let abbrev: &Abbrev = abbrevs.get(&code)
+                                     .ok_or("Unknown abbreviation code.")?;
+        let mut attrs: Vec<Attr<'a>> = Vec::with_capacity(abbrev.attrs.len());
+        for spec in abbrev.attrs.iter() {
+            attrs.push(Attr {
+                name: spec.name,
+                form: spec.form,
+                value: parse_value(&mut reader, *spec, &header)?,
+            });
+        }
+        index.insert(offset, dies.len());
+        dies.push(Die {
+            offset,
+            depth,
+            code,
+            tag: abbrev.tag,
+            has_children: abbrev.has_children,
+            attrs,
+        });
+        if abbrev.has_children {
+            depth += 1;
+        }
+    }
+
+    Ok(Unit {
+        bases: unit_bases(&header, dies.first()),
+        header,
+        sections: *sections,
+        skeleton_offset: None,
+        dies,
+        index,
+    })
+}
+
+// parse every unit of .debug_info and resolve their indexed forms
+pub fn parse_units<'a>(sections: &DwarfSections<'a>) -> Result<Vec<Unit<'a>>, &'static str> {
+    let mut units: Vec<Unit<'a>> = Vec::new();
+    let mut abbrev_cache: HashMap<u64, HashMap<u64, Abbrev>> = HashMap::new();
+
+    for header in parse_headers(sections.info, sections)? {
+        let abbrevs: &HashMap<u64, Abbrev> = match abbrev_cache.entry(header.abbrev_offset) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                entry.insert(parse_abbrevs(sections.abbrev, header.abbrev_offset,
+                                           sections)?)
+            },
+        };
+        let mut unit: Unit<'a> = parse_unit(sections, sections.info, header, abbrevs)?;
+        unit.resolve_indexes();
+        units.push(unit);
+    }
+
+    Ok(units)
+}
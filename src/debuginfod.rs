@@ -0,0 +1,719 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use crate::debug_file;
+use crate::debug_info::{self, DwarfSections};
+use crate::debug_info::dw_consts::*;
+use crate::debug_info::line::{self, LineHeader};
+use crate::debug_info::unit::{self, AttrValue, Unit};
+use crate::elf::{self, Elf};
+use crate::program_header::phdr_enums::PType;
+use crate::section_header::shdr_enums::SType;
+
+// how long a connection may stay silent, on the server and the client
+const TIMEOUT: Duration = Duration::from_secs(30);
+// largest artifact the client downloads into its cache
+pub const MAX_DOWNLOAD: u64 = 4 << 30;
+
+// Files a debuginfod server provides for a build id
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Artifact {
+    Debuginfo,
+    Executable,
+}
+
+impl Artifact {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Artifact::Debuginfo => "debuginfo",
+            Artifact::Executable => "executable",
+        }
+    }
+}
+
+// The files indexed for one build id
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct StoreEntry {
+    pub executable: Option<PathBuf>,
+    pub debuginfo: Option<PathBuf>,
+    // the sources named by the debuginfo, see source_files
+    pub sources: HashSet<String>,
+}
+
+// ELF files of a set of directories by build id (lower case hex)
+#[derive(Debug, Default, Clone)]
+pub struct Store {
+    pub entries: HashMap<String, StoreEntry>,
+}
+
+// object with loadable code, not a debug file whose code sections were
+// turned into NOBITS by objcopy --only-keep-debug
+fn has_code(elf: &Elf) -> bool {
+    elf.pheaders.iter().any(|p| p.ptype == PType::LOAD)
+        && elf.sheaders.iter().any(|s| s.flags & 0x4 != 0 && s.stype != SType::NOBITS)
+}
+
+fn has_debuginfo(elf: &Elf) -> bool {
+    elf.section(".debug_info").is_some_and(|s| s.stype != SType::NOBITS)
+}
+
+fn walk(dir: &Path, files: &mut Vec<PathBuf>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    for entry in entries.flatten() {
+        let path: PathBuf = entry.path();
+        match entry.file_type() {
+            Ok(kind) if kind.is_dir() => walk(&path, files),
+            Ok(kind) if kind.is_file() => files.push(path),
+            _ => (),
+        }
+    }
+}
+
+impl Store {
+    // Index every ELF file with a build id below `dirs`, files that are
+    // not ELF are skipped. An unstripped binary provides both artifacts.
+    pub fn index(dirs: &[PathBuf]) -> Store {
+        let mut files: Vec<PathBuf> = Vec::new();
+        for dir in dirs.iter() {
+            walk(dir, &mut files);
+        }
+        files.sort();
+
+        let mut store: Store = Store::default();
+        for path in files {
+            let elf: Elf = match fs::read(&path).ok().and_then(|c| elf::parse(c).ok()) {
+                Some(elf) => elf,
+                None => continue,
+            };
+            let id: String = match debug_file::build_id(&elf) {
+                Some(id) => debug_file::hex(&id),
+                None => continue,
+            };
+            let entry: &mut StoreEntry = store.entries.entry(id).or_default();
+            if has_code(&elf) && entry.executable.is_none() {
+                entry.executable = Some(path.clone());
+            }
+            if has_debuginfo(&elf) && entry.debuginfo.is_none() {
+                entry.debuginfo = Some(path);
+                entry.sources = source_files(&elf);
+            }
+        }
+        store
+    }
+
+    pub fn get(&self, build_id: &str, artifact: Artifact) -> Option<&PathBuf> {
+        let entry: &StoreEntry = self.entries.get(&build_id.to_ascii_lowercase())?;
+        match artifact {
+            Artifact::Debuginfo => entry.debuginfo.as_ref(),
+            Artifact::Executable => entry.executable.as_ref(),
+        }
+    }
+
+    // the sources /source requests of a build id may ask for
+    pub fn sources(&self, build_id: &str) -> Option<&HashSet<String>> {
+        Some(&self.entries.get(&build_id.to_ascii_lowercase())?.sources)
+    }
+}
+
+// An absolute path with `.`, `..` and repeated slashes resolved without
+// looking at the file system, None when it is relative or `..` goes above
+// the root
+fn normalize(path: &str) -> Option<String> {
+    if !path.starts_with('/') {
+        return None;
+    }
+    let mut parts: Vec<&str> = Vec::new();
+    for part in path.split('/') {
+        match part {
+            "" | "." => (),
+            ".." => {
+                parts.pop()?;
+            },
+            _ => parts.push(part),
+        }
+    }
+    Some(format!("/{0}", parts.join("/")))
+}
+
+// The source files named by the line tables of the debug information,
+// relative names joined with the unit directory and normalized. Only
+// these are served by /source requests.
+fn source_files(elf: &Elf) -> HashSet<String> {
+    let mut sources: HashSet<String> = HashSet::new();
+    let sections: DwarfSections = debug_info::load(elf);
+    let units: Vec<Unit> = unit::parse_units(&sections).unwrap_or_default();
+    for unit in units.iter() {
+        let root = match unit.dies.first() {
+            Some(root) => root,
+            None => continue,
+        };
+        let comp_dir: String = root.attr(DW_AT_COMP_DIR)
+            .and_then(|v| unit.sections.string(&v))
+            .map(|s| String::from_utf8_lossy(s).into_owned())
+            .unwrap_or_default();
+        let header: LineHeader = match root.attr(DW_AT_STMT_LIST) {
+            Some(AttrValue::SecOffset(offset)) | Some(AttrValue::Data(offset)) => {
+                match line::parse_header(&unit.sections, offset as usize) {
+                    Ok(header) => header,
+                    Err(_) => continue,
+                }
+            },
+            _ => continue,
+        };
+        let first: u64 = if header.version >= 5 { 0 } else { 1 };
+        for file in first..first + header.file_names.len() as u64 {
+            if let Some(path) = header.file_path(file) {
+                let path: String = match path.starts_with('/') {
+                    true => path,
+                    false => format!("{comp_dir}/{path}"),
+                };
+                sources.extend(normalize(&path));
+            }
+        }
+    }
+    sources
+}
+
+// Status code and body of a response
+#[derive(Debug, Clone, PartialEq)]
+pub struct Response {
+    pub status: u16,
+    pub body: Vec<u8>,
+    pub file: Option<PathBuf>,
+}
+
+fn not_found() -> Response {
+    Response {
+        status: 404,
+        body: b"Not found\n".to_vec(),
+        file: None,
+    }
+}
+
+fn file_response(path: &Path) -> Response {
+    match fs::read(path) {
+        Ok(body) => Response {
+            status: 200,
+            body,
+            file: Some(path.to_path_buf()),
+        },
+        Err(_) => not_found(),
+    }
+}
+
+// decode %XX escapes of a request path
+fn percent_decode(path: &str) -> String {
+    let bytes: &[u8] = path.as_bytes();
+    let mut out: Vec<u8> = Vec::with_capacity(bytes.len());
+    let mut i: usize = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            if let Some(byte) = path.get(i + 1..i + 3)
+                                    .and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                out.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+// Answer a request path of the debuginfod protocol:
+// /buildid/<id>/debuginfo, /buildid/<id>/executable and
+// /buildid/<id>/source/<absolute path>
+pub fn respond(store: &Store, path: &str) -> Response {
+    let path: String = percent_decode(path.split('?').next().unwrap_or(""));
+    let rest: &str = match path.strip_prefix("/buildid/") {
+        Some(rest) => rest,
+        None => return not_found(),
+    };
+    let (id, request): (&str, &str) = match rest.split_once('/') {
+        Some(parts) => parts,
+        None => return not_found(),
+    };
+    if id.is_empty() || !id.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return not_found();
+    }
+    let artifact: Option<Artifact> = match request {
+        "debuginfo" => Some(Artifact::Debuginfo),
+        "executable" => Some(Artifact::Executable),
+        _ => None,
+    };
+    if let Some(artifact) = artifact {
+        return match store.get(id, artifact) {
+            Some(path) => file_response(path),
+            None => not_found(),
+        };
+    }
+    match request.strip_prefix("source") {
+        // the source path is absolute, so it keeps its leading '/'
+        // compared once normalized, a `..` cannot step out of a path the
+        // debug information names
+        Some(source) => match (normalize(source), store.sources(id)) {
+            (Some(source), Some(sources)) if sources.contains(&source) => {
+                file_response(Path::new(&source))
+            },
+            _ => not_found(),
+        },
+        _ => not_found(),
+    }
+}
+
+fn handle(store: &Store, stream: TcpStream) -> io::Result<()> {
+    // an idle client would otherwise keep its thread forever
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    let mut reader: BufReader<&TcpStream> = BufReader::new(&stream);
+    let mut request_line: String = String::new();
+    reader.read_line(&mut request_line)?;
+    // the headers are read and ignored
+    loop {
+        let mut header: String = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let method: &str = parts.next().unwrap_or("");
+    let target: &str = parts.next().unwrap_or("");
+    let response: Response = match method {
+        "GET" | "HEAD" => respond(store, target),
+        _ => Response {
+            status: 405,
+            body: Vec::new(),
+            file: None,
+        },
+    };
+    let reason: &str = match response.status {
+        200 => "OK",
+        404 => "Not Found",
+        _ => "Method Not Allowed",
+    };
+
+    let mut writer: &TcpStream = &stream;
+    write!(writer, "HTTP/1.1 {0} {reason}\r\n", response.status)?;
+    write!(writer, "Content-Length: {0}\r\n", response.body.len())?;
+    write!(writer, "Content-Type: application/octet-stream\r\n")?;
+    if let Some(file) = response.file.as_ref() {
+        write!(writer, "X-DEBUGINFOD-FILE: {0}\r\n", file.display())?;
+        write!(writer, "X-DEBUGINFOD-SIZE: {0}\r\n", response.body.len())?;
+    }
+    write!(writer, "Connection: close\r\n\r\n")?;
+    if method != "HEAD" {
+        writer.write_all(&response.body)?;
+    }
+    writer.flush()
+}
+
+// A debuginfod server over a store, each connection is answered on its
+// own thread
+pub struct Server {
+    listener: TcpListener,
+    store: Arc<Store>,
+}
+
+impl Server {
+    pub fn bind(addr: &str, store: Store) -> Result<Server, &'static str> {
+        Ok(Server {
+            listener: TcpListener::bind(addr).map_err(|_| "Cannot listen on the address.")?,
+            store: Arc::new(store),
+        })
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr, &'static str> {
+        self.listener.local_addr().map_err(|_| "Server address not available.")
+    }
+
+    // serve until the process ends
+    pub fn run(&self) {
+        for stream in self.listener.incoming().flatten() {
+            let store: Arc<Store> = Arc::clone(&self.store);
+            thread::spawn(move || {
+                let _ = handle(&store, stream);
+            });
+        }
+    }
+
+    // serve on a background thread
+    pub fn spawn(self) -> thread::JoinHandle<()> {
+        thread::spawn(move || self.run())
+    }
+}
+
+// A client of one or more debuginfod servers, with a cache directory
+// laid out like the one of the elfutils client:
+// <cache>/<build id>/debuginfo
+#[derive(Debug, Clone)]
+pub struct Client {
+    pub urls: Vec<String>,
+    pub cache_dir: PathBuf,
+}
+
+// host:port and path prefix of an http:// URL
+fn split_url(url: &str) -> Option<(String, String)> {
+    let rest: &str = url.strip_prefix("http://")?;
+    let (host, prefix): (&str, &str) = match rest.find('/') {
+        Some(idx) => (&rest[..idx], rest[idx..].trim_end_matches('/')),
+        None => (rest, ""),
+    };
+    let host: String = if host.contains(':') {
+        String::from(host)
+    }
+    else {
+        format!("{host}:80")
+    };
+    Some((host, String::from(prefix)))
+}
+
+fn connect(host: &str) -> Option<TcpStream> {
+    for addr in host.to_socket_addrs().ok()? {
+        if let Ok(stream) = TcpStream::connect_timeout(&addr, TIMEOUT) {
+            stream.set_read_timeout(Some(TIMEOUT)).ok()?;
+            stream.set_write_timeout(Some(TIMEOUT)).ok()?;
+            return Some(stream);
+        }
+    }
+    None
+}
+
+// GET a path from a server and write the body of a 200 response to
+// `out`, bodies over MAX_DOWNLOAD bytes are refused
+fn get(host: &str, path: &str, out: &mut impl Write) -> Option<()> {
+    let mut stream: TcpStream = connect(host)?;
+    write!(stream, "GET {path} HTTP/1.1\r\nHost: {host}\r\nConnection: close\r\n\r\n").ok()?;
+    let mut reader: BufReader<TcpStream> = BufReader::new(stream);
+
+    let mut status_line: String = String::new();
+    reader.read_line(&mut status_line).ok()?;
+    let status: &str = status_line.split_whitespace().nth(1)?;
+    let mut length: Option<u64> = None;
+    loop {
+        let mut header: String = String::new();
+        if reader.read_line(&mut header).ok()? == 0 || header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                length = value.trim().parse().ok();
+            }
+        }
+    }
+    if status != "200" || length.is_some_and(|length| length > MAX_DOWNLOAD) {
+        return None;
+    }
+    // without a length the body ends with the connection
+    let limit: u64 = length.unwrap_or(MAX_DOWNLOAD + 1);
+    let copied: u64 = io::copy(&mut reader.take(limit), out).ok()?;
+    match length {
+        Some(length) if copied != length => None,
+        None if copied > MAX_DOWNLOAD => None,
+        _ => Some(()),
+    }
+}
+
+impl Client {
+    pub fn new(urls: Vec<String>, cache_dir: PathBuf) -> Client {
+        Client {
+            urls,
+            cache_dir,
+        }
+    }
+
+    // $DEBUGINFOD_CACHE_PATH or ~/.cache/debuginfod_client
+    pub fn default_cache_dir() -> Option<PathBuf> {
+        match std::env::var_os("DEBUGINFOD_CACHE_PATH") {
+            Some(path) => Some(PathBuf::from(path)),
+            None => Some(PathBuf::from(std::env::var_os("HOME")?).join(".cache")
+                                                                  .join("debuginfod_client")),
+        }
+    }
+
+    // client for the servers of $DEBUGINFOD_URLS
+    pub fn from_env() -> Option<Client> {
+        let urls: Vec<String> = std::env::var("DEBUGINFOD_URLS").ok()?
+            .split_whitespace().map(String::from).collect();
+        if urls.is_empty() {
+            return None;
+        }
+        Some(Client::new(urls, Client::default_cache_dir()?))
+    }
+
+    // Path of the artifact in the cache, downloaded from the first server
+    // that has it when it is not cached yet. Only http:// servers are
+    // supported.
+    pub fn fetch(&self, build_id: &[u8], artifact: Artifact) -> Option<PathBuf> {
+        let id: String = debug_file::hex(build_id);
+        let cached: PathBuf = self.cache_dir.join(&id).join(artifact.name());
+        if cached.is_file() {
+            return Some(cached);
+        }
+        for url in self.urls.iter() {
+            let (host, prefix) = match split_url(url) {
+                Some(parts) => parts,
+                None => continue,
+            };
+            let path: String = format!("{prefix}/buildid/{id}/{0}", artifact.name());
+            fs::create_dir_all(cached.parent()?).ok()?;
+            // written aside and renamed so a partial download is never
+            // taken from the cache
+            let partial: PathBuf = cached.with_extension("tmp");
+            let mut file: fs::File = fs::File::create(&partial).ok()?;
+            if get(&host, &path, &mut file).is_some() {
+                drop(file);
+                fs::rename(&partial, &cached).ok()?;
+                return Some(cached);
+            }
+            drop(file);
+            let _ = fs::remove_file(&partial);
+            let _ = fs::remove_dir(cached.parent()?);
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::debug_file::Resolver;
+    use crate::note::note_consts::NT_GNU_BUILD_ID;
+
+    fn push_u16(out: &mut Vec<u8>, value: u16) {
+        out.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn push_u32(out: &mut Vec<u8>, value: u32) {
+        out.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn push_u64(out: &mut Vec<u8>, value: u64) {
+        out.extend_from_slice(&value.to_le_bytes());
+    }
+
+    // A little endian ELF64 file with a build id note, a .text section
+    // that holds code or, like in a debug file, is NOBITS, and optionally
+    // a .debug_info section
+    fn elf_file(build_id: &[u8], code: bool, debug: bool) -> Vec<u8> {
+        let mut note: Vec<u8> = Vec::new();
+        push_u32(&mut note, 4);
+        push_u32(&mut note, build_id.len() as u32);
+        push_u32(&mut note, NT_GNU_BUILD_ID);
+        note.extend_from_slice(b"GNU\0");
+        note.extend_from_slice(build_id);
+        while !note.len().is_multiple_of(4) {
+            note.push(0);
+        }
+        let text: &[u8] = &[0xc3; 4];
+        let debug_info: &[u8] = b"not parsed by the store";
+        let shstrtab: &[u8] = b"\0.note.gnu.build-id\0.text\0.debug_info\0.shstrtab\0";
+
+        let phnum: u16 = if code { 1 } else { 0 };
+        let note_offset: u64 = 64 + 56 * phnum as u64;
+        let text_offset: u64 = note_offset + note.len() as u64;
+        let debug_offset: u64 = text_offset + text.len() as u64;
+        let strtab_offset: u64 = debug_offset + if debug { debug_info.len() as u64 } else { 0 };
+        let shoff: u64 = strtab_offset + shstrtab.len() as u64;
+        // (name, type, flags, offset, size, align)
+        let mut sections: Vec<(u32, u32, u64, u64, u64, u64)> = vec![
+            (0, 0, 0, 0, 0, 0),
+            (1, 7, 2, note_offset, note.len() as u64, 4),
+            (20, if code { 1 } else { 8 }, 6, text_offset, text.len() as u64, 16),
+        ];
+        if debug {
+            sections.push((26, 1, 0, debug_offset, debug_info.len() as u64, 1));
+        }
+        sections.push((38, 3, 0, strtab_offset, shstrtab.len() as u64, 1));
+
+        let mut out: Vec<u8> = vec![0x7f, b'E', b'L', b'F', 2, 1, 1, 0];
+        out.resize(16, 0);
+        push_u16(&mut out, 3);
+        push_u16(&mut out, 0x3e);
+        push_u32(&mut out, 1);
+        push_u64(&mut out, 0);
+        push_u64(&mut out, if code { 64 } else { 0 });
+        push_u64(&mut out, shoff);
+        push_u32(&mut out, 0);
+        push_u16(&mut out, 64);
+        push_u16(&mut out, 56);
+        push_u16(&mut out, phnum);
+        push_u16(&mut out, 64);
+        push_u16(&mut out, sections.len() as u16);
+        push_u16(&mut out, sections.len() as u16 - 1);
+        if code {
+            push_u32(&mut out, 1);
+            push_u32(&mut out, 5);
+            push_u64(&mut out, 0);
+            push_u64(&mut out, 0);
+            push_u64(&mut out, 0);
+            push_u64(&mut out, debug_offset);
+            push_u64(&mut out, debug_offset);
+            push_u64(&mut out, 0x1000);
+        }
+        out.extend_from_slice(&note);
+        out.extend_from_slice(text);
+        if debug {
+            out.extend_from_slice(debug_info);
+        }
+        out.extend_from_slice(shstrtab);
+        for (name, stype, flags, offset, size, align) in sections {
+            push_u32(&mut out, name);
+            push_u32(&mut out, stype);
+            push_u64(&mut out, flags);
+            push_u64(&mut out, 0);
+            push_u64(&mut out, offset);
+            push_u64(&mut out, size);
+            push_u32(&mut out, 0);
+            push_u32(&mut out, 0);
+            push_u64(&mut out, align);
+            push_u64(&mut out, 0);
+        }
+        out
+    }
+
+    const FULL_ID: [u8; 4] = [0x12, 0x34, 0x56, 0x78];
+    const SPLIT_ID: [u8; 4] = [0xab, 0xcd, 0xef, 0x01];
+
+    // a store directory with an unstripped binary, a stripped binary
+    // with its debug file in a subdirectory and a file that is not ELF
+    fn store_dir() -> tempfile::TempDir {
+        let dir: tempfile::TempDir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("full"), elf_file(&FULL_ID, true, true)).unwrap();
+        fs::write(dir.path().join("stripped"), elf_file(&SPLIT_ID, true, false)).unwrap();
+        fs::create_dir(dir.path().join("debug")).unwrap();
+        fs::write(dir.path().join("debug").join("stripped.debug"),
+                  elf_file(&SPLIT_ID, false, true)).unwrap();
+        fs::write(dir.path().join("README"), b"not an object").unwrap();
+        dir
+    }
+
+    #[test]
+    fn index_by_build_id() {
+        let dir: tempfile::TempDir = store_dir();
+        let store: Store = Store::index(&[dir.path().to_path_buf()]);
+        assert_eq!(store.entries.len(), 2);
+
+        let full: &StoreEntry = &store.entries["12345678"];
+        assert_eq!(full.executable, Some(dir.path().join("full")));
+        assert_eq!(full.debuginfo, Some(dir.path().join("full")));
+
+        let split: &StoreEntry = &store.entries["abcdef01"];
+        assert_eq!(split.executable, Some(dir.path().join("stripped")));
+        assert_eq!(split.debuginfo, Some(dir.path().join("debug").join("stripped.debug")));
+        assert_eq!(store.get("ABCDEF01", Artifact::Executable),
+                   Some(&dir.path().join("stripped")));
+    }
+
+    #[test]
+    fn respond_to_requests() {
+        let dir: tempfile::TempDir = store_dir();
+        let store: Store = Store::index(&[dir.path().to_path_buf()]);
+
+        let response: Response = respond(&store, "/buildid/abcdef01/debuginfo");
+        assert_eq!(response.status, 200);
+        assert_eq!(response.body, elf_file(&SPLIT_ID, false, true));
+        assert_eq!(respond(&store, "/buildid/abcdef01/executable").body,
+                   elf_file(&SPLIT_ID, true, false));
+
+        assert_eq!(respond(&store, "/buildid/00000000/debuginfo").status, 404);
+        assert_eq!(respond(&store, "/buildid/abcdef01/other").status, 404);
+        assert_eq!(respond(&store, "/buildid/../debuginfo").status, 404);
+        assert_eq!(respond(&store, "/metrics").status, 404);
+        // only sources named by the debug information are served
+        assert_eq!(respond(&store, "/buildid/12345678/source/etc/passwd").status, 404);
+        assert_eq!(respond(&store, "/buildid/12345678/source%2Fetc%2Fpasswd").status, 404);
+    }
+
+    #[test]
+    fn respond_to_source_requests() {
+        let dir: tempfile::TempDir = store_dir();
+        let mut store: Store = Store::index(&[dir.path().to_path_buf()]);
+        let source: String = format!("{0}/README", dir.path().display());
+        store.entries.get_mut("12345678").unwrap().sources.insert(source.clone());
+
+        assert_eq!(respond(&store, &format!("/buildid/12345678/source{source}")).body,
+                   b"not an object");
+        let indirect: String = format!("{0}/debug/.././/README", dir.path().display());
+        assert_eq!(respond(&store, &format!("/buildid/12345678/source{indirect}")).status, 200);
+        assert_eq!(respond(&store, &format!("/buildid/abcdef01/source{source}")).status, 404);
+        let escape: String = format!("{source}/../../../../../../../../etc/passwd");
+        assert_eq!(respond(&store, &format!("/buildid/12345678/source{escape}")).status, 404);
+
+        assert_eq!(normalize("/usr/./src//a/../b.c"), Some(String::from("/usr/src/b.c")));
+        assert_eq!(normalize("/usr/../../etc/passwd"), None);
+        assert_eq!(normalize("src/b.c"), None);
+    }
+
+    #[test]
+    fn serve_on_localhost() {
+        let dir: tempfile::TempDir = store_dir();
+        let cache: tempfile::TempDir = tempfile::tempdir().unwrap();
+        let server: Server = Server::bind("127.0.0.1:0",
+                                          Store::index(&[dir.path().to_path_buf()])).unwrap();
+        let url: String = format!("http://{0}", server.local_addr().unwrap());
+        server.spawn();
+
+        let client: Client = Client::new(vec![url], cache.path().to_path_buf());
+        let path: PathBuf = client.fetch(&SPLIT_ID, Artifact::Debuginfo).unwrap();
+        assert_eq!(path, cache.path().join("abcdef01").join("debuginfo"));
+        assert_eq!(fs::read(&path).unwrap(), elf_file(&SPLIT_ID, false, true));
+        let path: PathBuf = client.fetch(&FULL_ID, Artifact::Executable).unwrap();
+        assert_eq!(fs::read(&path).unwrap(), elf_file(&FULL_ID, true, true));
+        assert_eq!(client.fetch(&[0x99; 4], Artifact::Debuginfo), None);
+
+        // cached artifacts are not downloaded again
+        let offline: Client = Client::new(vec![String::from("http://127.0.0.1:1")],
+                                          cache.path().to_path_buf());
+        assert!(offline.fetch(&SPLIT_ID, Artifact::Debuginfo).is_some());
+        assert_eq!(offline.fetch(&FULL_ID, Artifact::Debuginfo), None);
+    }
+
+    #[test]
+    fn refuse_oversized_downloads() {
+        let listener: TcpListener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url: String = format!("http://{0}", listener.local_addr().unwrap());
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let mut line: String = String::new();
+                let _ = BufReader::new(&stream).read_line(&mut line);
+                let mut writer: &TcpStream = &stream;
+                let _ = write!(writer, "HTTP/1.1 200 OK\r\nContent-Length: {0}\r\n\r\nELF",
+                               MAX_DOWNLOAD + 1);
+            }
+        });
+        let cache: tempfile::TempDir = tempfile::tempdir().unwrap();
+        let client: Client = Client::new(vec![url], cache.path().to_path_buf());
+        assert_eq!(client.fetch(&SPLIT_ID, Artifact::Debuginfo), None);
+        assert!(!cache.path().join("abcdef01").exists());
+    }
+
+    #[test]
+    fn resolver_queries_debuginfod() {
+        let dir: tempfile::TempDir = store_dir();
+        let cache: tempfile::TempDir = tempfile::tempdir().unwrap();
+        let server: Server = Server::bind("127.0.0.1:0",
+                                          Store::index(&[dir.path().to_path_buf()])).unwrap();
+        let url: String = format!("http://{0}", server.local_addr().unwrap());
+        server.spawn();
+
+        let stripped: Elf = elf::parse(elf_file(&SPLIT_ID, true, false)).unwrap();
+        let mut resolver: Resolver = Resolver::new(vec![cache.path().join("none")]);
+        let object: PathBuf = cache.path().join("stripped");
+        assert!(resolver.find_debug_file(&stripped, &object).unwrap().is_none());
+
+        resolver.debuginfod = Some(Client::new(vec![url], cache.path().to_path_buf()));
+        let found = resolver.find_debug_file(&stripped, &object).unwrap().unwrap();
+        assert_eq!(found.method, debug_file::Method::Debuginfod);
+        assert_eq!(debug_file::build_id(&found.elf), Some(SPLIT_ID.to_vec()));
+    }
+}
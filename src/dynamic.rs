@@ -0,0 +1,210 @@
+use crate::Class;
+use crate::debug_info::Reader;
+use crate::elf::Elf;
+use crate::program_header::phdr_enums::PType;
+use crate::section_header::SHeader;
+use crate::section_header::shdr_enums::SType;
+pub mod dyn_consts;
+use self::dyn_consts::*;
+
+// One entry of the dynamic section, the value is an address, a size or
+// an offset into the dynamic string table depending on the tag
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct DynEntry {
+    pub tag: u64,
+    pub value: u64,
+}
+
+// The dynamic section of an object with the entries the dynamic loader
+// needs to find its dependencies
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Dynamic {
+    pub entries: Vec<DynEntry>,
+    pub needed: Vec<String>,
+    pub soname: Option<String>,
+    // DT_RPATH and DT_RUNPATH as stored, colon separated lists
+    pub rpath: Option<String>,
+    pub runpath: Option<String>,
+    pub flags: u64,
+    pub flags_1: u64,
+    // file offset of the table and the size of one entry
+    pub offset: u64,
+    pub entry_size: u64,
+}
+
+impl Dynamic {
+    // value of the first entry with `tag`
+    pub fn get(&self, tag: u64) -> Option<u64> {
+        self.entries.iter().find(|e| e.tag == tag).map(|e| e.value)
+    }
+
+    // file offset of the first entry with `tag`
+    pub fn entry_offset(&self, tag: u64) -> Option<u64> {
+        self.entries.iter().position(|e| e.tag == tag)
+            .map(|idx| self.offset + idx as u64 * self.entry_size)
+    }
+}
+
+// the name of a dynamic tag, None for tags without a constant here
+pub fn tag_name(tag: u64) -> Option<&'static str> {
+    let name: &str = match tag {
+        DT_NULL => "DT_NULL",
+        DT_NEEDED => "DT_NEEDED",
+        DT_PLTRELSZ => "DT_PLTRELSZ",
+        DT_PLTGOT => "DT_PLTGOT",
+        DT_HASH => "DT_HASH",
+        DT_STRTAB => "DT_STRTAB",
+        DT_SYMTAB => "DT_SYMTAB",
+        DT_RELA => "DT_RELA",
+        DT_RELASZ => "DT_RELASZ",
+        DT_RELAENT => "DT_RELAENT",
+        DT_STRSZ => "DT_STRSZ",
+        DT_SYMENT => "DT_SYMENT",
+        DT_INIT => "DT_INIT",
+        DT_FINI => "DT_FINI",
+        DT_SONAME => "DT_SONAME",
+        DT_RPATH => "DT_RPATH",
+        DT_SYMBOLIC => "DT_SYMBOLIC",
+        DT_REL => "DT_REL",
+        DT_RELSZ => "DT_RELSZ",
+        DT_RELENT => "DT_RELENT",
+        DT_PLTREL => "DT_PLTREL",
+        DT_DEBUG => "DT_DEBUG",
+        DT_TEXTREL => "DT_TEXTREL",
+        DT_JMPREL => "DT_JMPREL",
+        DT_BIND_NOW => "DT_BIND_NOW",
+        DT_INIT_ARRAY => "DT_INIT_ARRAY",
+        DT_FINI_ARRAY => "DT_FINI_ARRAY",
+        DT_INIT_ARRAYSZ => "DT_INIT_ARRAYSZ",
+        DT_FINI_ARRAYSZ => "DT_FINI_ARRAYSZ",
+        DT_RUNPATH => "DT_RUNPATH",
+        DT_FLAGS => "DT_FLAGS",
+        DT_PREINIT_ARRAY => "DT_PREINIT_ARRAY",
+        DT_PREINIT_ARRAYSZ => "DT_PREINIT_ARRAYSZ",
+        DT_GNU_HASH => "DT_GNU_HASH",
+        DT_VERSYM => "DT_VERSYM",
+        DT_RELACOUNT => "DT_RELACOUNT",
+        DT_RELCOUNT => "DT_RELCOUNT",
+        DT_FLAGS_1 => "DT_FLAGS_1",
+        DT_VERDEF => "DT_VERDEF",
+        DT_VERDEFNUM => "DT_VERDEFNUM",
+        DT_VERNEED => "DT_VERNEED",
+        DT_VERNEEDNUM => "DT_VERNEEDNUM",
+        DT_AUXILIARY => "DT_AUXILIARY",
+        DT_FILTER => "DT_FILTER",
+        _ => return None,
+    };
+    Some(name)
+}
+
+// whether the value of a tag is an offset into the dynamic string table
+pub fn is_string_tag(tag: u64) -> bool {
+    matches!(tag, DT_NEEDED | DT_SONAME | DT_RPATH | DT_RUNPATH | DT_AUXILIARY | DT_FILTER)
+}
+
+// the file offset of a virtual address inside a PT_LOAD segment
+pub fn file_offset(elf: &Elf, vaddr: u64) -> Option<u64> {
+    elf.pheaders.iter()
+       .filter(|p| p.ptype == PType::LOAD)
+       .find(|p| vaddr >= p.vaddr && vaddr - p.vaddr < p.filesz)
+       .map(|p| p.offset + (vaddr - p.vaddr))
+}
+
+// the bytes from a virtual address to the end of its PT_LOAD segment in
+// the file, for tables the dynamic section only gives the address of
+pub fn data_at(elf: &Elf, vaddr: u64) -> Option<&[u8]> {
+    let pheader = elf.pheaders.iter()
+                     .filter(|p| p.ptype == PType::LOAD)
+                     .find(|p| vaddr >= p.vaddr && vaddr - p.vaddr < p.filesz)?;
+    let data: &[u8] = elf.segment_data(pheader).ok()?;
+    data.get((vaddr - pheader.vaddr) as usize..)
+}
+
+// the dynamic string table, from the section linked to .dynamic or,
+// without section headers, at DT_STRTAB in the loaded segments
+fn string_table<'a>(elf: &'a Elf, section: Option<&SHeader>,
+                    entries: &[DynEntry]) -> Result<&'a [u8], &'static str> {
+    if let Some(strtab) = section.and_then(|s| elf.sheaders.get(s.link as usize)) {
+        return elf.section_data(strtab);
+    }
+    let find = |tag: u64| entries.iter().find(|e| e.tag == tag).map(|e| e.value);
+    let (addr, size): (u64, u64) = match (find(DT_STRTAB), find(DT_STRSZ)) {
+        (Some(addr), Some(size)) => (addr, size),
+        _ => return Ok(&[]),
+    };
+    let start: usize = file_offset(elf, addr).ok_or("DT_STRTAB outside the loaded segments.")?
+                       as usize;
+    let end: usize = start.checked_add(size as usize).ok_or("DT_STRSZ out of bounds.")?;
+    elf.content.get(start..end).ok_or("DT_STRSZ out of bounds.")
+}
+
+// the dynamic string table of `elf` whose dynamic section is `dynamic`
+pub fn strtab<'a>(elf: &'a Elf, dynamic: &Dynamic) -> Result<&'a [u8], &'static str> {
+    let section: Option<&SHeader> = elf.sheaders.iter().find(|s| s.stype == SType::DYNAMIC);
+    string_table(elf, section, &dynamic.entries)
+}
+
+// The dynamic section of `elf`, None for objects without one like
+// static executables and relocatable files
+pub fn parse(elf: &Elf) -> Result<Option<Dynamic>, &'static str> {
+    let section: Option<&SHeader> = elf.sheaders.iter().find(|s| s.stype == SType::DYNAMIC);
+    let (data, offset): (&[u8], u64) = match section {
+        Some(sheader) => (elf.section_data(sheader)?, sheader.offset),
+        None => match elf.pheaders.iter().find(|p| p.ptype == PType::DYNAMIC) {
+            Some(pheader) => (elf.segment_data(pheader)?, pheader.offset),
+            None => return Ok(None),
+        },
+    };
+
+    let is_64: bool = elf.header.class != Class::X32Bit;
+    let entry_size: usize = if is_64 { 16 } else { 8 };
+    let mut entries: Vec<DynEntry> = Vec::new();
+    let mut reader: Reader = Reader::new(data, 0, elf.header.endian);
+    while reader.offset + entry_size <= data.len() {
+        let (tag, value): (u64, u64) = if is_64 {
+            (reader.u64()?, reader.u64()?)
+        }
+        else {
+            (reader.u32()? as u64, reader.u32()? as u64)
+        };
+        if tag == DT_NULL {
+            break;
+        }
+        entries.push(DynEntry { tag, value });
+    }
+
+    let strtab: &[u8] = string_table(elf, section, &entries)?;
+    let string = |offset: u64| -> String {
+        let name: &[u8] = Reader::new(strtab, offset as usize, elf.header.endian)
+                                 .cstr().unwrap_or(&[]);
+        String::from_utf8_lossy(name).into_owned()
+    };
+
+    let mut dynamic: Dynamic = Dynamic {
+        entries: Vec::new(),
+        offset,
+        entry_size: entry_size as u64,
+        ..Default::default()
+    };
+    for entry in entries.iter() {
+        match entry.tag {
+            DT_NEEDED => dynamic.needed.push(string(entry.value)),
+            DT_SONAME => dynamic.soname = Some(string(entry.value)),
+            DT_RPATH => dynamic.rpath = Some(string(entry.value)),
+            DT_RUNPATH => dynamic.runpath = Some(string(entry.value)),
+            DT_FLAGS => dynamic.flags = entry.value,
+            DT_FLAGS_1 => dynamic.flags_1 = entry.value,
+            _ => (),
+        }
+    }
+    dynamic.entries = entries;
+    Ok(Some(dynamic))
+}
+
+// the program interpreter named by PT_INTERP
+pub fn interpreter(elf: &Elf) -> Option<String> {
+    let pheader = elf.pheaders.iter().find(|p| p.ptype == PType::INTERP)?;
+    let data: &[u8] = elf.segment_data(pheader).ok()?;
+    let name: &[u8] = data.split(|b| *b == 0).next()?;
+    Some(String::from_utf8_lossy(name).into_owned())
+}
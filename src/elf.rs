@@ -0,0 +1,198 @@
+use std::collections::HashMap;
+use crate::compression;
+use crate::elf_header::{self, Header};
+use crate::elf_header::header_enums::BinType;
+use crate::program_header::{self, PHeader};
+use crate::relocation;
+use crate::section_header::{self, SHeader};
+use crate::section_header::shdr_enums::{SType, SHF_COMPRESSED};
+
+// A fully parsed ELF file: the raw bytes together with the ELF header
+// and every program and section header found in them.
+#[derive(Debug, Default)]
+pub struct Elf {
+    pub content: Vec<u8>,
+    pub header: Header,
+    pub pheaders: Vec<PHeader>,
+    pub sheaders: Vec<SHeader>,
+    // data of the compressed sections, by section offset
+    decompressed: HashMap<u64, Result<Vec<u8>, &'static str>>,
+    // data of the debug sections of a relocatable file with their
+    // relocations applied, by section offset
+    relocated: HashMap<u64, Result<Vec<u8>, &'static str>>,
+}
+
+impl Elf {
+    // first section with the given name, a .debug_* section is also
+    // found under the legacy compressed name .zdebug_*
+    pub fn section(&self, name: &str) -> Option<&SHeader> {
+        self.sheaders.iter().find(|s| s.sname == name).or_else(|| {
+            let rest: &str = name.strip_prefix(".debug_")?;
+            let zname: String = format!(".zdebug_{rest}");
+            self.sheaders.iter().find(|s| s.sname == zname)
+        })
+    }
+
+    // indexes of the sections a user asked for, by index when `spec` is
+    // a number and else every section named `spec`
+    pub fn select_sections(&self, spec: &str) -> Vec<usize> {
+        match spec.parse::<usize>() {
+            Ok(idx) if idx < self.sheaders.len() => vec![idx],
+            Ok(_) => Vec::new(),
+            Err(_) => self.sheaders.iter().enumerate().filter(|(_, s)| s.sname == spec)
+                          .map(|(idx, _)| idx).collect(),
+        }
+    }
+
+    // section compressed with SHF_COMPRESSED or named .zdebug_*
+    pub fn is_compressed(&self, sheader: &SHeader) -> bool {
+        sheader.stype != SType::NOBITS
+            && (sheader.flags & SHF_COMPRESSED != 0 || sheader.sname.starts_with(".zdebug"))
+    }
+
+    // bytes of a section, decompressed when the section is compressed.
+    // NOBITS sections are empty.
+    pub fn section_data(&self, sheader: &SHeader)
+                        -> Result<&[u8], &'static str> {
+        if sheader.stype == SType::NOBITS {
+            return Ok(&[]);
+        }
+        if self.is_compressed(sheader) {
+            if let Some(data) = self.decompressed.get(&sheader.offset) {
+                return data.as_deref().map_err(|e| *e);
+            }
+        }
+        self.file_data(sheader)
+    }
+
+    // bytes of a debug section to decode, with the relocations against it
+    // applied in a relocatable file. Other readers see them unrelocated.
+    pub fn debug_data(&self, sheader: &SHeader)
+                      -> Result<&[u8], &'static str> {
+        match self.relocated.get(&sheader.offset) {
+            Some(data) => data.as_deref().map_err(|e| *e),
+            None => self.section_data(sheader),
+        }
+    }
+
+    // bytes of a section as stored in the file, compressed or not
+    pub fn file_data(&self, sheader: &SHeader)
+                     -> Result<&[u8], &'static str> {
+        if sheader.stype == SType::NOBITS {
+            return Ok(&[]);
+        }
+        let start: usize = sheader.offset as usize;
+        let end: usize = start.checked_add(sheader.size as usize)
+                              .ok_or("Section out of bounds.")?;
+        if end > self.content.len() {
+            return Err("Section out of bounds.");
+        }
+        Ok(&self.content[start..end])
+    }
+
+    // bytes of a segment as stored in the file (p_filesz bytes)
+    pub fn segment_data(&self, pheader: &PHeader)
+                        -> Result<&[u8], &'static str> {
+        let start: usize = pheader.offset as usize;
+        let end: usize = start.checked_add(pheader.filesz as usize)
+                              .ok_or("Segment out of bounds.")?;
+        if end > self.content.len() {
+            return Err("Segment out of bounds.");
+        }
+        Ok(&self.content[start..end])
+    }
+}
+
+pub fn parse(content: Vec<u8>) -> Result<Elf, &'static str> {
+    let mut header: Header = Header {
+        ..Default::default()
+    };
+    elf_header::parse(&content, &mut header)?;
+
+    let mut pheaders: Vec<PHeader> = Vec::new();
+    let mut phdr_offset: u64 = header.phdr_offset;
+    for _ in 0..header.phdr_entries {
+        pheaders.push(program_header::parse(&content, phdr_offset,
+                                            header.class, header.endian)?);
+        phdr_offset = phdr_offset.checked_add(header.phdr_entry_sz as u64)
+                                 .ok_or("Program header out of bounds.")?;
+    }
+
+    let mut sheaders: Vec<SHeader> = Vec::new();
+    if header.shdr_entries > 0 {
+        // locate the section name string table first, names are
+        // resolved against it while parsing the other headers
+        let strtab_start: u64 = if header.shstr_idx != 0
+                                   && header.shstr_idx < header.shdr_entries {
+            let strtab_header_start: u64 = (header.shstr_idx as u64)
+                                .checked_mul(header.shdr_entry_sz as u64)
+                                .and_then(|o| o.checked_add(header.shdr_offset))
+                                .ok_or("Section header out of bounds.")?;
+            section_header::parse(&content, strtab_header_start,
+                                  header.class, header.endian,
+                                  content.len() as u64)?.offset
+        }
+        else {
+            content.len() as u64
+        };
+
+        let mut shdr_offset: u64 = header.shdr_offset;
+        for _ in 0..header.shdr_entries {
+            sheaders.push(section_header::parse(&content, shdr_offset,
+                                                header.class, header.endian,
+                                                strtab_start)?);
+            shdr_offset = shdr_offset.checked_add(header.shdr_entry_sz as u64)
+                                     .ok_or("Section header out of bounds.")?;
+        }
+    }
+
+    let mut elf: Elf = Elf {
+        content,
+        header,
+        pheaders,
+        sheaders,
+        ..Default::default()
+    };
+
+    // compressed sections are decompressed once here so that every
+    // reader of section_data sees the plain bytes
+    let mut decompressed: HashMap<u64, Result<Vec<u8>, &'static str>> = HashMap::new();
+    for sheader in elf.sheaders.iter().filter(|s| elf.is_compressed(s)) {
+        let data: Result<Vec<u8>, &'static str> = elf.file_data(sheader).and_then(|data| {
+            if sheader.flags & SHF_COMPRESSED != 0 {
+                compression::decompress(data, elf.header.class, elf.header.endian)
+            }
+            else {
+                compression::decompress_zdebug(data)
+            }
+        });
+        decompressed.insert(sheader.offset, data);
+    }
+    elf.decompressed = decompressed;
+
+    // debug sections of object files refer to each other and to the code
+    // through relocations, without them every offset into .debug_str,
+    // .debug_line or .debug_rnglists is 0
+    if elf.header.bin_type == BinType::REL {
+        let mut relocated: HashMap<u64, Result<Vec<u8>, &'static str>> = HashMap::new();
+        for (idx, sheader) in elf.sheaders.iter().enumerate() {
+            let is_debug: bool = sheader.sname.starts_with(".debug_")
+                                 || sheader.sname.starts_with(".zdebug_");
+            let has_relocations: bool = elf.sheaders.iter().any(|s| {
+                matches!(s.stype, SType::REL | SType::RELA) && s.info as usize == idx
+            });
+            if !is_debug || !has_relocations || sheader.stype == SType::NOBITS {
+                continue;
+            }
+            let data: Result<Vec<u8>, &'static str> = elf.section_data(sheader).and_then(|data| {
+                let mut data: Vec<u8> = data.to_vec();
+                relocation::apply(&elf, idx, &mut data)?;
+                Ok(data)
+            });
+            relocated.insert(sheader.offset, data);
+        }
+        elf.relocated = relocated;
+    }
+
+    Ok(elf)
+}
@@ -2,15 +2,22 @@ use crate::{BigEndian, LittleEndian, ReadBytesExt};
 pub mod header_enums;
 use self::header_enums::{BinType, Class, Endian, Abi, Machine};
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub struct Header {
     pub class: Class,
     pub endian: Endian,
     pub version: u8,
     pub abi: Abi,
+    // EI_OSABI as stored
+    pub abi_id: u8,
     pub abi_version: u8,
     pub bin_type: BinType,
+    // e_type as stored
+    pub type_id: u16,
     pub machine: Machine,
+    // e_machine as stored, machines without a Machine variant differ
+    // only here
+    pub machine_id: u16,
     pub misc_version: u32,
     pub entry_point: u64,
     pub phdr_offset: u64,
@@ -24,16 +31,18 @@ pub struct Header {
     pub shstr_idx: u16,
 }
 
-pub fn parse(content: &Vec<u8>,
+pub fn parse(content: &[u8],
                 header: &mut Header) -> Result<(), &'static str> {
     // first we check whether it a valid ELF file or not
     const MAGIC: [u8; 4] = [0x7f, 0x45, 0x4c, 0x46];
 
+    if content.len() < 0x34 {
+        return Err("Not an ELF binary.");
+    }
     let magic: &[u8] = &content[0..4];
     if MAGIC != magic {
         return Err("Not an ELF binary.");
     }
-    println!("Valid ELF binary.");
 
     // cursor
     let mut cursor: usize = 0x04;
@@ -49,6 +58,9 @@ pub fn parse(content: &Vec<u8>,
         0x02 => header.class = Class::X64Bit,
         _ => header.class = Class::NONE,
     }
+    if header.class == Class::X64Bit && content.len() < 0x40 {
+        return Err("Not an ELF binary.");
+    }
     cursor += 0x01;
 
     // reading endianness
@@ -85,6 +97,7 @@ pub fn parse(content: &Vec<u8>,
         0x12 => header.abi = Abi::Status,
         _ => header.abi = Abi::NONE,
     }
+    header.abi_id = content[cursor];
     cursor += 0x01;
 
     // ABI version
@@ -113,6 +126,7 @@ pub fn parse(content: &Vec<u8>,
         0xFFFF => header.bin_type = BinType::HIPROC,
         _ => header.bin_type = BinType::NONE,
     }
+    header.type_id = bin_type;
     cursor += 0x02;
 
     // machine
@@ -126,8 +140,19 @@ pub fn parse(content: &Vec<u8>,
     match machine {
         0x03 => header.machine = Machine::X86,
         0x3E => header.machine = Machine::AMD64,
+        0x08 => header.machine = Machine::MIPS,
+        0x14 => header.machine = Machine::PPC,
+        0x15 => header.machine = Machine::PPC64,
+        0x16 => header.machine = Machine::S390,
+        0x28 => header.machine = Machine::ARM,
+        0x2B => header.machine = Machine::SPARCV9,
+        0x32 => header.machine = Machine::IA_64,
+        0xB7 => header.machine = Machine::AARCH64,
+        0xF3 => header.machine = Machine::RISCV,
+        0x102 => header.machine = Machine::LOONGARCH,
         _ =>  header.machine = Machine::NONE,
     }
+    header.machine_id = machine;
     cursor += 0x02;
 
     // Another Version
@@ -285,6 +310,5 @@ pub fn parse(content: &Vec<u8>,
     };
 
     // end of ELF header
-    dbg!(header);
     Ok(())
 }
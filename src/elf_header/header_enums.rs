@@ -1,4 +1,7 @@
-#[derive(Debug, Default)]
+// Variant names mirror the constants from the ELF specification.
+#![allow(non_camel_case_types)]
+
+#[derive(Debug, Default, PartialEq, Copy, Clone)]
 pub enum BinType {
     #[default]
     NONE,
@@ -28,7 +31,7 @@ pub enum Endian {
     Big,
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, PartialEq, Copy, Clone)]
 pub enum Abi {
     #[default]
     NONE,
@@ -52,11 +55,21 @@ pub enum Abi {
     Status,
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, PartialEq, Copy, Clone)]
 pub enum Machine {
     #[default]
     NONE,
     X86,
     AMD64,
+    MIPS,
+    PPC,
+    PPC64,
+    S390,
+    ARM,
+    SPARCV9,
+    IA_64,
+    AARCH64,
+    RISCV,
+    LOONGARCH,
 }
 
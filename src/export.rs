@@ -0,0 +1,249 @@
+use serde_json::{json, Value};
+use crate::debug_file;
+use crate::debug_info::Reader;
+use crate::dynamic::{self, Dynamic};
+use crate::elf::Elf;
+use crate::elf_header::Header;
+use crate::elf_header::header_enums::{Abi, BinType, Class, Endian, Machine};
+use crate::hardening::{Hardening, Pie, Relro};
+use crate::mapping;
+use crate::note;
+use crate::program_header::phdr_enums::{PType, PF_R, PF_W, PF_X};
+use crate::relocation::{self, Relocation};
+use crate::section_header::shdr_enums::SType;
+use crate::symbol::{self, Symbol};
+
+// The parsed tables as JSON values, also written as YAML. The schema is
+// versioned by SCHEMA_VERSION, which changes only when a field changes
+// or goes away; new fields may be added at any time.
+//
+// Raw values with a decoded name are objects {"value": 62, "name":
+// "AMD64"}, the name being null for values not known here. Flag words
+// are {"value": 5, "names": ["R", "X"]}. Addresses, offsets and sizes
+// are plain numbers, note contents hex strings.
+//
+//   header: class, data, version, osabi, abi_version, type, machine,
+//     entry, phoff, shoff, flags, ehsize, phentsize, phnum, shentsize,
+//     shnum, shstrndx
+//   program_headers[]: index, type, flags, offset, vaddr, paddr, filesz,
+//     memsz, align, sections[] (names, as readelf maps them)
+//   section_headers[]: index, name, type, flags, addr, offset, size,
+//     link, info, addralign, entsize
+//   symbols[]: table (.symtab or .dynsym), index, name, value, size,
+//     type, bind, visibility, shndx
+//   relocations[]: section, entries[]: offset, type, symbol_index,
+//     symbol, addend (null for SHT_REL)
+//   dynamic[]: tag, value, string (for tags naming a string, else null)
+//   notes[]: owner, type, desc
+//   hardening: pie (yes, no or dso), nx, relro (none, partial or full),
+//     bind_now, stack_protector, fortified[], rpath, runpath, textrel,
+//     ibt, shstk, bti, pac, rwx_segments[]
+pub const SCHEMA_VERSION: u32 = 1;
+
+// sh_flags bits and their names
+const SECTION_FLAGS: [(u64, &str); 12] = [
+    (0x1, "WRITE"), (0x2, "ALLOC"), (0x4, "EXECINSTR"), (0x10, "MERGE"), (0x20, "STRINGS"),
+    (0x40, "INFO_LINK"), (0x80, "LINK_ORDER"), (0x100, "OS_NONCONFORMING"), (0x200, "GROUP"),
+    (0x400, "TLS"), (0x800, "COMPRESSED"), (0x8000_0000, "EXCLUDE"),
+];
+
+fn named(value: u64, name: Option<String>) -> Value {
+    json!({ "value": value, "name": name })
+}
+
+fn flags(value: u64, bits: &[(u64, &str)]) -> Value {
+    let names: Vec<&str> = bits.iter().filter(|(bit, _)| value & bit != 0)
+                                      .map(|(_, name)| *name).collect();
+    json!({ "value": value, "names": names })
+}
+
+pub fn header(header: &Header) -> Value {
+    let class: (u64, Option<&str>) = match header.class {
+        Class::X32Bit => (1, Some("ELF32")),
+        Class::X64Bit => (2, Some("ELF64")),
+        Class::NONE => (0, None),
+    };
+    let data: (u64, Option<&str>) = match header.endian {
+        Endian::Little => (1, Some("little")),
+        Endian::Big => (2, Some("big")),
+        Endian::NONE => (0, None),
+    };
+    let osabi: Option<String> = match header.abi {
+        Abi::NONE => None,
+        abi => Some(format!("{abi:?}")),
+    };
+    // BinType::NONE also stands for the values it has no variant for
+    let btype: Option<String> = match header.bin_type {
+        BinType::NONE if header.type_id != 0 => None,
+        btype => Some(format!("{btype:?}")),
+    };
+    let machine: Option<String> = match header.machine {
+        Machine::NONE => None,
+        machine => Some(format!("{machine:?}")),
+    };
+    json!({
+        "class": named(class.0, class.1.map(String::from)),
+        "data": named(data.0, data.1.map(String::from)),
+        "version": header.version,
+        "osabi": named(header.abi_id as u64, osabi),
+        "abi_version": header.abi_version,
+        "type": named(header.type_id as u64, btype),
+        "machine": named(header.machine_id as u64, machine),
+        "entry": header.entry_point,
+        "phoff": header.phdr_offset,
+        "shoff": header.shdr_offset,
+        "flags": header.flags,
+        "ehsize": header.hdr_sz,
+        "phentsize": header.phdr_entry_sz,
+        "phnum": header.phdr_entries,
+        "shentsize": header.shdr_entry_sz,
+        "shnum": header.shdr_entries,
+        "shstrndx": header.shstr_idx,
+    })
+}
+
+pub fn program_headers(elf: &Elf) -> Value {
+    let bits: [(u64, &str); 3] = [(PF_R as u64, "R"), (PF_W as u64, "W"), (PF_X as u64, "X")];
+    let mapping: Vec<Vec<usize>> = mapping::segment_sections(elf);
+    elf.pheaders.iter().enumerate().map(|(idx, p)| {
+        let sections: Vec<&str> = mapping[idx].iter().map(|s| elf.sheaders[*s].sname.as_str())
+                                              .collect();
+        let ptype: Option<String> = match p.ptype {
+            PType::NONE => None,
+            ptype => Some(format!("{ptype:?}")),
+        };
+        json!({
+            "index": idx,
+            "type": named(p.type_id as u64, ptype),
+            "flags": flags(p.flags as u64, &bits),
+            "offset": p.offset,
+            "vaddr": p.vaddr,
+            "paddr": p.paddr,
+            "filesz": p.filesz,
+            "memsz": p.memsz,
+            "align": p.align,
+            "sections": sections,
+        })
+    }).collect()
+}
+
+pub fn section_headers(elf: &Elf) -> Value {
+    elf.sheaders.iter().enumerate().map(|(idx, s)| {
+        let stype: Option<String> = match s.stype {
+            SType::NONE => None,
+            stype => Some(format!("{stype:?}")),
+        };
+        json!({
+            "index": idx,
+            "name": s.sname,
+            "type": named(s.type_id as u64, stype),
+            "flags": flags(s.flags, &SECTION_FLAGS),
+            "addr": s.vaddr,
+            "offset": s.offset,
+            "size": s.size,
+            "link": s.link,
+            "info": s.info,
+            "addralign": s.align,
+            "entsize": s.entsize,
+        })
+    }).collect()
+}
+
+fn symbol_values(symbols: &[Symbol]) -> Vec<Value> {
+    symbols.iter().enumerate().map(|(idx, sym)| json!({
+        "table": sym.table,
+        "index": idx,
+        "name": sym.name,
+        "value": sym.value,
+        "size": sym.size,
+        "type": named(sym.stype as u64, symbol::type_name(sym.stype).map(String::from)),
+        "bind": named(sym.bind as u64, symbol::bind_name(sym.bind).map(String::from)),
+        "visibility": named(sym.visibility as u64,
+                            symbol::visibility_name(sym.visibility).map(String::from)),
+        "shndx": sym.shndx,
+    })).collect()
+}
+
+// the symbols of .symtab and .dynsym, each numbered within its table
+pub fn symbols(elf: &Elf) -> Result<Value, &'static str> {
+    let mut values: Vec<Value> = Vec::new();
+    for sheader in elf.sheaders.iter().filter(|s| s.stype == SType::SYMTAB) {
+        values.extend(symbol_values(&symbol::parse(elf, sheader, ".symtab")?));
+    }
+    values.extend(symbol_values(&symbol::dynamic_symbols(elf)?));
+    Ok(Value::Array(values))
+}
+
+pub fn relocations(elf: &Elf) -> Result<Value, &'static str> {
+    let mut sections: Vec<Value> = Vec::new();
+    for sheader in elf.sheaders.iter().filter(|s| matches!(s.stype, SType::REL | SType::RELA)) {
+        let entries: Vec<Relocation> = relocation::parse(elf, sheader)?;
+        let entries: Vec<Value> = entries.iter().map(|r| json!({
+            "offset": r.offset,
+            "type": named(r.rtype as u64,
+                          relocation::type_name(elf.header.machine, r.rtype).map(String::from)),
+            "symbol_index": r.sym,
+            "symbol": r.symbol,
+            "addend": r.addend,
+        })).collect();
+        sections.push(json!({ "section": sheader.sname, "entries": entries }));
+    }
+    Ok(Value::Array(sections))
+}
+
+pub fn dynamic(elf: &Elf) -> Result<Value, &'static str> {
+    let dynamic: Dynamic = match dynamic::parse(elf)? {
+        Some(dynamic) => dynamic,
+        None => return Ok(Value::Array(Vec::new())),
+    };
+    let strtab: &[u8] = dynamic::strtab(elf, &dynamic)?;
+    Ok(dynamic.entries.iter().map(|e| {
+        let string: Option<String> = match dynamic::is_string_tag(e.tag) {
+            true => Reader::new(strtab, e.value as usize, elf.header.endian).cstr().ok()
+                           .map(|s| String::from_utf8_lossy(s).into_owned()),
+            false => None,
+        };
+        json!({
+            "tag": named(e.tag, dynamic::tag_name(e.tag).map(String::from)),
+            "value": e.value,
+            "string": string,
+        })
+    }).collect())
+}
+
+pub fn notes(elf: &Elf) -> Value {
+    note::all(elf).iter().map(|n| json!({
+        "owner": String::from_utf8_lossy(n.name),
+        "type": named(n.ntype as u64, note::type_name(n.name, n.ntype).map(String::from)),
+        "desc": debug_file::hex(n.desc),
+    })).collect()
+}
+
+pub fn hardening(audit: &Hardening) -> Value {
+    let pie: &str = match audit.pie {
+        Pie::Yes => "yes",
+        Pie::No => "no",
+        Pie::Dso => "dso",
+    };
+    let relro: &str = match audit.relro {
+        Relro::Full => "full",
+        Relro::Partial => "partial",
+        Relro::None => "none",
+    };
+    json!({
+        "pie": pie,
+        "nx": audit.nx,
+        "relro": relro,
+        "bind_now": audit.bind_now,
+        "stack_protector": audit.stack_protector,
+        "fortified": audit.fortified,
+        "rpath": audit.rpath,
+        "runpath": audit.runpath,
+        "textrel": audit.textrel,
+        "ibt": audit.ibt,
+        "shstk": audit.shstk,
+        "bti": audit.bti,
+        "pac": audit.pac,
+        "rwx_segments": audit.rwx_segments,
+    })
+}
@@ -0,0 +1,63 @@
+use crate::elf::Elf;
+use crate::program_header::PHeader;
+use crate::section_header::SHeader;
+use crate::section_header::shdr_enums::SType;
+
+// The bytes of a section or segment as stored in the file, borrowed from
+// it, and the zeros that follow them once loaded: the whole size of a
+// NOBITS section, p_memsz - p_filesz of a segment
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Extract<'a> {
+    pub data: &'a [u8],
+    pub zeros: u64,
+}
+
+impl Extract<'_> {
+    // number of bytes written out, with or without the zero fill
+    pub fn size(&self, zero_fill: bool) -> u64 {
+        match zero_fill {
+            true => self.data.len() as u64 + self.zeros,
+            false => self.data.len() as u64,
+        }
+    }
+}
+
+// Raw bytes of a section, compressed sections are left as they are
+pub fn section(elf: &Elf, idx: usize) -> Result<Extract<'_>, &'static str> {
+    let sheader: &SHeader = elf.sheaders.get(idx).ok_or("No such section.")?;
+    let zeros: u64 = match sheader.stype {
+        SType::NOBITS => sheader.size,
+        _ => 0,
+    };
+    Ok(Extract {
+        data: elf.file_data(sheader)?,
+        zeros,
+    })
+}
+
+pub fn segment(elf: &Elf, idx: usize) -> Result<Extract<'_>, &'static str> {
+    let pheader: &PHeader = elf.pheaders.get(idx).ok_or("No such segment.")?;
+    Ok(Extract {
+        data: elf.segment_data(pheader)?,
+        zeros: pheader.memsz.saturating_sub(pheader.filesz),
+    })
+}
+
+// File names the extracted bytes are written to. The index keeps sections
+// sharing a name apart and the name is made safe for a path.
+pub fn section_file_name(elf: &Elf, idx: usize) -> String {
+    let name: String = match elf.sheaders.get(idx) {
+        Some(sheader) => sheader.sname.trim_start_matches('.').chars()
+            .map(|c| if c.is_ascii_alphanumeric() || "._-".contains(c) { c } else { '_' })
+            .collect(),
+        None => String::new(),
+    };
+    match name.is_empty() {
+        true => format!("section-{idx}.bin"),
+        false => format!("section-{idx}-{name}.bin"),
+    }
+}
+
+pub fn segment_file_name(idx: usize) -> String {
+    format!("segment-{idx}.bin")
+}
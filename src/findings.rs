@@ -0,0 +1,360 @@
+use serde_json::{json, Value};
+use crate::debug_info::lint::{self, Problem};
+use crate::elf::Elf;
+use crate::hardening::Check;
+use crate::policy::{Policy, Report};
+
+// A broken rule, `offset` being a byte offset into the file
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    pub rule: String,
+    pub message: String,
+    pub offset: Option<u64>,
+}
+
+// The rules checked on one file and the ones it breaks, what the SARIF
+// and JUnit writers take. Rule ids are prefixed with where the rule
+// comes from: hardening/pie, policy/build-id, lint/abbrev.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FileFindings {
+    pub path: String,
+    // the file could not be checked
+    pub error: Option<String>,
+    pub rules: Vec<String>,
+    pub findings: Vec<Finding>,
+}
+
+fn policy_rule(rule: &str) -> String {
+    match Check::parse(rule) {
+        Some(_) => format!("hardening/{rule}"),
+        None => format!("policy/{rule}"),
+    }
+}
+
+impl FileFindings {
+    pub fn from_policy(report: &Report, policy: &Policy) -> FileFindings {
+        FileFindings {
+            path: report.path.clone(),
+            error: report.error.clone(),
+            rules: policy.rules().iter().map(|r| policy_rule(r)).collect(),
+            findings: report.violations.iter().map(|v| Finding {
+                rule: policy_rule(&v.rule),
+                message: v.message.clone(),
+                offset: v.offset,
+            }).collect(),
+        }
+    }
+
+    // Lint problems of the debug sections of `elf`. Offsets into
+    // compressed sections have no place in the file.
+    pub fn from_lint(path: &str, elf: &Elf, problems: &[Problem]) -> FileFindings {
+        let offset = |problem: &Problem| -> Option<u64> {
+            let sheader = elf.section(problem.section)?;
+            match elf.is_compressed(sheader) {
+                true => None,
+                false => Some(sheader.offset + problem.offset as u64),
+            }
+        };
+        FileFindings {
+            path: path.to_string(),
+            error: None,
+            rules: lint::CHECKS.iter().map(|c| format!("lint/{c}")).collect(),
+            findings: problems.iter().map(|p| Finding {
+                rule: format!("lint/{0}", p.check),
+                message: format!("{0}+{1:#x}: {2}", p.section, p.offset, p.message),
+                offset: offset(p),
+            }).collect(),
+        }
+    }
+
+    // add the rules and findings of another check of the same file
+    pub fn merge(&mut self, other: FileFindings) {
+        if self.error.is_none() {
+            self.error = other.error;
+        }
+        for rule in other.rules {
+            if !self.rules.contains(&rule) {
+                self.rules.push(rule);
+            }
+        }
+        self.findings.extend(other.findings);
+    }
+}
+
+// a file URI for absolute paths, relative ones are URI references already
+fn uri(path: &str) -> String {
+    let mut encoded: String = String::new();
+    for c in path.chars() {
+        match c {
+            '%' | ' ' | '#' | '?' => encoded.push_str(&format!("%{0:02X}", c as u32)),
+            c => encoded.push(c),
+        }
+    }
+    match encoded.starts_with('/') {
+        true => format!("file://{encoded}"),
+        false => encoded,
+    }
+}
+
+// A SARIF 2.1.0 log with one run holding the findings of every file
+pub fn sarif(files: &[FileFindings]) -> Value {
+    let mut rules: Vec<&str> = Vec::new();
+    for file in files.iter() {
+        for rule in file.rules.iter().chain(file.findings.iter().map(|f| &f.rule)) {
+            if !rules.contains(&rule.as_str()) {
+                rules.push(rule);
+            }
+        }
+    }
+
+    let mut results: Vec<Value> = Vec::new();
+    let mut notifications: Vec<Value> = Vec::new();
+    for file in files.iter() {
+        if let Some(error) = file.error.as_ref() {
+            notifications.push(json!({
+                "level": "error",
+                "message": { "text": format!("{0}: {error}", file.path) },
+            }));
+        }
+        for finding in file.findings.iter() {
+            let mut location: Value = json!({ "artifactLocation": { "uri": uri(&file.path) } });
+            if let Some(offset) = finding.offset {
+                location["region"] = json!({ "byteOffset": offset });
+            }
+            results.push(json!({
+                "ruleId": finding.rule,
+                "ruleIndex": rules.iter().position(|r| *r == finding.rule),
+                "level": "error",
+                "message": { "text": finding.message },
+                "locations": [{ "physicalLocation": location }],
+            }));
+        }
+    }
+
+    json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "dwarf",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules.iter().map(|r| json!({ "id": r })).collect::<Vec<Value>>(),
+                },
+            },
+            "invocations": [{
+                "executionSuccessful": notifications.is_empty(),
+                "toolExecutionNotifications": notifications,
+            }],
+            "results": results,
+        }],
+    })
+}
+
+fn xml_escape(text: &str) -> String {
+    let mut escaped: String = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // not allowed in XML 1.0
+            c if (c as u32) < 0x20 && !matches!(c, '\t' | '\n' | '\r') => escaped.push('?'),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// JUnit XML with a test suite per file and a test case per rule, failed
+// when the file breaks it. A file that cannot be checked only has one
+// test case, in error.
+pub fn junit(files: &[FileFindings]) -> String {
+    let mut suites: String = String::new();
+    let (mut total_tests, mut total_failures, mut total_errors) = (0, 0, 0);
+    for file in files.iter() {
+        let path: String = xml_escape(&file.path);
+        let mut cases: String = String::new();
+        let (mut tests, mut failures, mut errors) = (0, 0, 0);
+        if let Some(error) = file.error.as_ref() {
+            tests += 1;
+            errors += 1;
+            cases.push_str(&format!("    <testcase classname=\"{path}\" name=\"read\">\n      \
+                                     <error message=\"{0}\"/>\n    </testcase>\n",
+                                    xml_escape(error)));
+        }
+        for rule in file.rules.iter().filter(|_| file.error.is_none()) {
+            tests += 1;
+            let found: Vec<&Finding> = file.findings.iter().filter(|f| &f.rule == rule).collect();
+            let name: String = xml_escape(rule);
+            if found.is_empty() {
+                cases.push_str(&format!("    <testcase classname=\"{path}\" name=\"{name}\"/>\n"));
+                continue;
+            }
+            failures += 1;
+            let lines: Vec<String> = found.iter().map(|f| match f.offset {
+                Some(offset) => format!("{offset:#x}: {0}", f.message),
+                None => f.message.clone(),
+            }).collect();
+            cases.push_str(&format!("    <testcase classname=\"{path}\" name=\"{name}\">\n      \
+                                     <failure message=\"{0}\">{1}</failure>\n    </testcase>\n",
+                                    xml_escape(&found[0].message),
+                                    xml_escape(&lines.join("\n"))));
+        }
+        suites.push_str(&format!("  <testsuite name=\"{path}\" tests=\"{tests}\" \
+                                  failures=\"{failures}\" errors=\"{errors}\">\n{cases}  \
+                                  </testsuite>\n"));
+        total_tests += tests;
+        total_failures += failures;
+        total_errors += errors;
+    }
+    format!("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<testsuites name=\"dwarf\" \
+             tests=\"{total_tests}\" failures=\"{total_failures}\" errors=\"{total_errors}\">\n\
+             {suites}</testsuites>\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::policy::Violation;
+    use super::*;
+
+    fn finding(rule: &str, message: &str, offset: Option<u64>) -> Finding {
+        Finding { rule: rule.to_string(), message: message.to_string(), offset }
+    }
+
+    fn files() -> Vec<FileFindings> {
+        vec![
+            FileFindings {
+                path: "/usr/bin/a b".to_string(),
+                error: None,
+                rules: vec!["hardening/pie".to_string(), "policy/build-id".to_string()],
+                findings: vec![finding("policy/build-id", "no build id", None),
+                               finding("lint/abbrev", "bad <abbrev> & \"code\"", Some(0x40))],
+            },
+            FileFindings {
+                path: "lib/c.so".to_string(),
+                error: Some("not an ELF file".to_string()),
+                rules: vec!["hardening/pie".to_string()],
+                findings: Vec::new(),
+            },
+        ]
+    }
+
+    #[test]
+    fn uris() {
+        assert_eq!(uri("/usr/bin/ls"), "file:///usr/bin/ls");
+        assert_eq!(uri("/tmp/a b#1?%"), "file:///tmp/a%20b%231%3F%25");
+        assert_eq!(uri("bin/ls"), "bin/ls");
+    }
+
+    #[test]
+    fn escapes() {
+        assert_eq!(xml_escape("<a href=\"x\">&'</a>"),
+                   "&lt;a href=&quot;x&quot;&gt;&amp;&apos;&lt;/a&gt;");
+        assert_eq!(xml_escape("tab\there\nline\r\x01\x1b"), "tab\there\nline\r??");
+        assert_eq!(xml_escape("plain é"), "plain é");
+    }
+
+    #[test]
+    fn policy_rules() {
+        let policy: Policy = Policy { require: vec![Check::Pie], build_id: true, ..Default::default() };
+        let report: Report = Report {
+            path: "a.out".to_string(),
+            error: None,
+            violations: vec![Violation { rule: "pie".to_string(), message: "not PIE".to_string(),
+                                         offset: Some(0x10) }],
+        };
+        let findings: FileFindings = FileFindings::from_policy(&report, &policy);
+        assert_eq!(findings.rules, ["hardening/pie", "policy/build-id"]);
+        assert_eq!(findings.findings, [finding("hardening/pie", "not PIE", Some(0x10))]);
+    }
+
+    #[test]
+    fn merged() {
+        let mut first: FileFindings = files().remove(0);
+        let mut second: FileFindings = files().remove(1);
+        second.rules.push("lint/abbrev".to_string());
+        second.findings.push(finding("hardening/pie", "not PIE", None));
+        first.merge(second);
+        assert_eq!(first.error.as_deref(), Some("not an ELF file"));
+        assert_eq!(first.rules, ["hardening/pie", "policy/build-id", "lint/abbrev"]);
+        assert_eq!(first.findings.len(), 3);
+
+        // the first error is kept
+        let mut third: FileFindings = FileFindings { error: Some("truncated".to_string()), ..Default::default() };
+        third.merge(files().remove(1));
+        assert_eq!(third.error.as_deref(), Some("truncated"));
+    }
+
+    #[test]
+    fn sarif_log() {
+        let log: Value = sarif(&files());
+        assert_eq!(log["version"], "2.1.0");
+        let run: &Value = &log["runs"][0];
+        // rules checked come first, then the ones only found
+        assert_eq!(run["tool"]["driver"]["rules"],
+                   json!([{ "id": "hardening/pie" }, { "id": "policy/build-id" },
+                          { "id": "lint/abbrev" }]));
+        assert_eq!(run["results"], json!([
+            {
+                "ruleId": "policy/build-id",
+                "ruleIndex": 1,
+                "level": "error",
+                "message": { "text": "no build id" },
+                "locations": [{ "physicalLocation": {
+                    "artifactLocation": { "uri": "file:///usr/bin/a%20b" },
+                } }],
+            },
+            {
+                "ruleId": "lint/abbrev",
+                "ruleIndex": 2,
+                "level": "error",
+                "message": { "text": "bad <abbrev> & \"code\"" },
+                "locations": [{ "physicalLocation": {
+                    "artifactLocation": { "uri": "file:///usr/bin/a%20b" },
+                    "region": { "byteOffset": 0x40 },
+                } }],
+            },
+        ]));
+        let invocation: &Value = &run["invocations"][0];
+        assert_eq!(invocation["executionSuccessful"], false);
+        assert_eq!(invocation["toolExecutionNotifications"],
+                   json!([{ "level": "error", "message": { "text": "lib/c.so: not an ELF file" } }]));
+
+        let clean: Value = sarif(&[]);
+        assert_eq!(clean["runs"][0]["invocations"][0]["executionSuccessful"], true);
+        assert_eq!(clean["runs"][0]["results"], json!([]));
+    }
+
+    #[test]
+    fn junit_xml() {
+        assert_eq!(junit(&files()), "\
+<?xml version=\"1.0\" encoding=\"UTF-8\"?>
+<testsuites name=\"dwarf\" tests=\"3\" failures=\"1\" errors=\"1\">
+  <testsuite name=\"/usr/bin/a b\" tests=\"2\" failures=\"1\" errors=\"0\">
+    <testcase classname=\"/usr/bin/a b\" name=\"hardening/pie\"/>
+    <testcase classname=\"/usr/bin/a b\" name=\"policy/build-id\">
+      <failure message=\"no build id\">no build id</failure>
+    </testcase>
+  </testsuite>
+  <testsuite name=\"lib/c.so\" tests=\"1\" failures=\"0\" errors=\"1\">
+    <testcase classname=\"lib/c.so\" name=\"read\">
+      <error message=\"not an ELF file\"/>
+    </testcase>
+  </testsuite>
+</testsuites>
+");
+
+        // findings of one rule are joined, with their offsets
+        let file: FileFindings = FileFindings {
+            path: "x".to_string(),
+            error: None,
+            rules: vec!["lint/abbrev".to_string()],
+            findings: vec![finding("lint/abbrev", "a<b", Some(0x40)),
+                           finding("lint/abbrev", "c", None)],
+        };
+        assert!(junit(&[file]).contains("<failure message=\"a&lt;b\">0x40: a&lt;b\nc</failure>"));
+    }
+}
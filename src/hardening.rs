@@ -0,0 +1,221 @@
+use serde::Deserialize;
+use crate::debug_info::Reader;
+use crate::dynamic::{self, Dynamic};
+use crate::dynamic::dyn_consts::*;
+use crate::elf::Elf;
+use crate::elf_header::header_enums::{BinType, Machine};
+use crate::note::{self, Property};
+use crate::note::note_consts::*;
+use crate::program_header::phdr_enums::{PType, PF_W, PF_X};
+use crate::section_header::shdr_enums::SType;
+use crate::symbol::{self, Symbol};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Pie {
+    #[default]
+    No,
+    Yes,
+    // a shared library, position independent by nature
+    Dso,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Relro {
+    #[default]
+    None,
+    // PT_GNU_RELRO, the GOT used by the PLT stays writable
+    Partial,
+    // PT_GNU_RELRO with immediate binding
+    Full,
+}
+
+// The hardening features an object was built with, what checksec shows
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Hardening {
+    pub pie: Pie,
+    // PT_GNU_STACK without PF_X
+    pub nx: bool,
+    pub relro: Relro,
+    pub bind_now: bool,
+    pub stack_protector: bool,
+    // the fortified functions (__*_chk) the object calls
+    pub fortified: Vec<String>,
+    pub rpath: Option<String>,
+    pub runpath: Option<String>,
+    pub textrel: bool,
+    // x86 CET
+    pub ibt: bool,
+    pub shstk: bool,
+    // AArch64 branch protection
+    pub bti: bool,
+    pub pac: bool,
+    // indexes of PT_LOAD segments both writable and executable
+    pub rwx_segments: Vec<usize>,
+}
+
+impl Hardening {
+    pub fn fortify(&self) -> bool {
+        !self.fortified.is_empty()
+    }
+
+    // CET or its AArch64 counterpart, depending on the machine
+    pub fn cet(&self) -> bool {
+        (self.ibt && self.shstk) || (self.bti && self.pac)
+    }
+}
+
+// symbols of .symtab and .dynsym, defined or not
+fn all_symbols(elf: &Elf) -> Vec<Symbol> {
+    let mut symbols: Vec<Symbol> = symbol::dynamic_symbols(elf).unwrap_or_default();
+    for sheader in elf.sheaders.iter().filter(|s| s.stype == SType::SYMTAB) {
+        symbols.extend(symbol::parse(elf, sheader, ".symtab").unwrap_or_default());
+    }
+    symbols
+}
+
+// the feature bits of a GNU_PROPERTY_*_FEATURE_1_AND property
+fn feature_bits(elf: &Elf, property: &Property) -> u32 {
+    Reader::new(property.data, 0, elf.header.endian).u32().unwrap_or(0)
+}
+
+pub fn audit(elf: &Elf) -> Result<Hardening, &'static str> {
+    let dynamic: Dynamic = dynamic::parse(elf)?.unwrap_or_default();
+    let mut hardening: Hardening = Hardening::default();
+
+    let has_interp: bool = elf.pheaders.iter().any(|p| p.ptype == PType::INTERP);
+    hardening.pie = match elf.header.bin_type {
+        BinType::DYN if dynamic.flags_1 & DF_1_PIE != 0 => Pie::Yes,
+        // linkers before DF_1_PIE only leave the interpreter to tell, a
+        // library like libc.so.6 can have one too but also has a soname
+        BinType::DYN if has_interp && dynamic.soname.is_none() => Pie::Yes,
+        BinType::DYN => Pie::Dso,
+        _ => Pie::No,
+    };
+
+    hardening.nx = elf.pheaders.iter()
+                      .find(|p| p.ptype == PType::GNU_STACK)
+                      .is_some_and(|p| p.flags & PF_X == 0);
+    hardening.bind_now = dynamic.flags & DF_BIND_NOW != 0
+                         || dynamic.flags_1 & DF_1_NOW != 0
+                         || dynamic.get(DT_BIND_NOW).is_some();
+    hardening.relro = match elf.pheaders.iter().any(|p| p.ptype == PType::GNU_RELRO) {
+        true if hardening.bind_now => Relro::Full,
+        true => Relro::Partial,
+        false => Relro::None,
+    };
+    hardening.textrel = dynamic.flags & DF_TEXTREL != 0 || dynamic.get(DT_TEXTREL).is_some();
+    hardening.rpath = dynamic.rpath.clone();
+    hardening.runpath = dynamic.runpath.clone();
+
+    for sym in all_symbols(elf) {
+        match sym.name.as_str() {
+            "__stack_chk_fail" | "__stack_chk_guard" | "__stack_chk_fail_local" => {
+                hardening.stack_protector = true;
+            },
+            name if name.starts_with("__") && name.ends_with("_chk")
+                    && !hardening.fortified.iter().any(|f| f == name) => {
+                hardening.fortified.push(name.to_string());
+            },
+            _ => (),
+        }
+    }
+    hardening.fortified.sort();
+
+    for note in note::all(elf).iter() {
+        if note.name != b"GNU" || note.ntype != NT_GNU_PROPERTY_TYPE_0 {
+            continue;
+        }
+        let properties: Vec<Property> = match note::properties(note.desc, elf.header.class,
+                                                               elf.header.endian) {
+            Ok(properties) => properties,
+            Err(_) => continue,
+        };
+        for property in properties.iter() {
+            let bits: u32 = feature_bits(elf, property);
+            match (elf.header.machine, property.ptype) {
+                (Machine::AMD64 | Machine::X86, GNU_PROPERTY_X86_FEATURE_1_AND) => {
+                    hardening.ibt = bits & GNU_PROPERTY_X86_FEATURE_1_IBT != 0;
+                    hardening.shstk = bits & GNU_PROPERTY_X86_FEATURE_1_SHSTK != 0;
+                },
+                (Machine::AARCH64, GNU_PROPERTY_AARCH64_FEATURE_1_AND) => {
+                    hardening.bti = bits & GNU_PROPERTY_AARCH64_FEATURE_1_BTI != 0;
+                    hardening.pac = bits & GNU_PROPERTY_AARCH64_FEATURE_1_PAC != 0;
+                },
+                _ => (),
+            }
+        }
+    }
+
+    hardening.rwx_segments = elf.pheaders.iter().enumerate()
+        .filter(|(_, p)| p.ptype == PType::LOAD && p.flags & (PF_W | PF_X) == (PF_W | PF_X))
+        .map(|(idx, _)| idx)
+        .collect();
+    Ok(hardening)
+}
+
+// A requirement of a hardening policy, named as in policy files
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Check {
+    Pie,
+    Nx,
+    PartialRelro,
+    FullRelro,
+    StackProtector,
+    Fortify,
+    NoRpath,
+    NoRunpath,
+    NoTextrel,
+    Cet,
+    NoRwx,
+}
+
+pub const CHECKS: [Check; 11] = [
+    Check::Pie, Check::Nx, Check::PartialRelro, Check::FullRelro, Check::StackProtector,
+    Check::Fortify, Check::NoRpath, Check::NoRunpath, Check::NoTextrel, Check::Cet,
+    Check::NoRwx,
+];
+
+impl Check {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Check::Pie => "pie",
+            Check::Nx => "nx",
+            Check::PartialRelro => "partial-relro",
+            Check::FullRelro => "full-relro",
+            Check::StackProtector => "stack-protector",
+            Check::Fortify => "fortify",
+            Check::NoRpath => "no-rpath",
+            Check::NoRunpath => "no-runpath",
+            Check::NoTextrel => "no-textrel",
+            Check::Cet => "cet",
+            Check::NoRwx => "no-rwx",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Check> {
+        CHECKS.iter().copied().find(|c| c.name() == name)
+    }
+
+    // Shared libraries pass the PIE check, full RELRO implies partial.
+    pub fn passes(&self, hardening: &Hardening) -> bool {
+        match self {
+            Check::Pie => hardening.pie != Pie::No,
+            Check::Nx => hardening.nx,
+            Check::PartialRelro => hardening.relro != Relro::None,
+            Check::FullRelro => hardening.relro == Relro::Full,
+            Check::StackProtector => hardening.stack_protector,
+            Check::Fortify => hardening.fortify(),
+            Check::NoRpath => hardening.rpath.is_none(),
+            Check::NoRunpath => hardening.runpath.is_none(),
+            Check::NoTextrel => !hardening.textrel,
+            Check::Cet => hardening.cet(),
+            Check::NoRwx => hardening.rwx_segments.is_empty(),
+        }
+    }
+}
+
+// the checks of `policy` the object fails
+pub fn failures(hardening: &Hardening, policy: &[Check]) -> Vec<Check> {
+    policy.iter().copied().filter(|c| !c.passes(hardening)).collect()
+}
@@ -0,0 +1,324 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use crate::{Class, Endian};
+use crate::debug_info::Reader;
+use crate::elf_header::Header;
+use crate::elf_header::header_enums::Machine;
+
+pub const OLD_MAGIC: &[u8] = b"ld.so-1.7.0";
+pub const NEW_MAGIC: &[u8] = b"glibc-ld.so.cache1.1";
+
+// the kind of library, low byte of the entry flags
+pub const FLAG_TYPE_MASK: u32 = 0x00ff;
+pub const FLAG_LIBC4: u32 = 0x0000;
+pub const FLAG_ELF: u32 = 0x0001;
+pub const FLAG_ELF_LIBC5: u32 = 0x0002;
+pub const FLAG_ELF_LIBC6: u32 = 0x0003;
+
+// the ABI the library requires, second byte of the entry flags
+pub const FLAG_REQUIRED_MASK: u32 = 0xff00;
+pub const FLAG_SPARC_LIB64: u32 = 0x0100;
+pub const FLAG_IA64_LIB64: u32 = 0x0200;
+pub const FLAG_X8664_LIB64: u32 = 0x0300;
+pub const FLAG_S390_LIB64: u32 = 0x0400;
+pub const FLAG_POWERPC_LIB64: u32 = 0x0500;
+pub const FLAG_MIPS64_LIBN32: u32 = 0x0600;
+pub const FLAG_MIPS64_LIBN64: u32 = 0x0700;
+pub const FLAG_X8664_LIBX32: u32 = 0x0800;
+pub const FLAG_ARM_LIBHF: u32 = 0x0900;
+pub const FLAG_AARCH64_LIB64: u32 = 0x0a00;
+pub const FLAG_ARM_LIBSF: u32 = 0x0b00;
+pub const FLAG_MIPS_LIB32_NAN2008: u32 = 0x0c00;
+pub const FLAG_MIPS64_LIBN32_NAN2008: u32 = 0x0d00;
+pub const FLAG_MIPS64_LIBN64_NAN2008: u32 = 0x0e00;
+pub const FLAG_RISCV_FLOAT_ABI_SOFT: u32 = 0x0f00;
+pub const FLAG_RISCV_FLOAT_ABI_DOUBLE: u32 = 0x1000;
+pub const FLAG_LARCH_FLOAT_ABI_SOFT: u32 = 0x1100;
+pub const FLAG_LARCH_FLOAT_ABI_DOUBLE: u32 = 0x1200;
+
+// hwcap values with these upper bits index the glibc-hwcaps
+// subdirectories of the extension section
+pub const DL_CACHE_HWCAP_EXTENSION: u64 = 1 << 62;
+
+pub const EXTENSION_MAGIC: u32 = 0xeaa42174;
+pub const EXTENSION_TAG_GENERATOR: u32 = 0;
+pub const EXTENSION_TAG_GLIBC_HWCAPS: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    // ld.so-1.7.0 only
+    Old,
+    // glibc-ld.so.cache1.1 only
+    New,
+    // an old table followed by a new one, for old and new loaders
+    Compat,
+}
+
+// One library of the cache: the soname it is found by and its path
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct CacheEntry {
+    pub flags: u32,
+    pub key: String,
+    pub value: String,
+    pub os_version: u32,
+    pub hwcap: u64,
+    // the glibc-hwcaps subdirectory the library is in, as x86-64-v3
+    pub hwcaps: Option<String>,
+}
+
+impl CacheEntry {
+    pub fn kind(&self) -> u32 {
+        self.flags & FLAG_TYPE_MASK
+    }
+
+    pub fn required(&self) -> u32 {
+        self.flags & FLAG_REQUIRED_MASK
+    }
+
+    // the flags as ldconfig -p shows them, as "libc6,x86-64"
+    pub fn describe(&self) -> String {
+        let kind: &str = match self.kind() {
+            FLAG_LIBC4 => "libc4",
+            FLAG_ELF => "ELF",
+            FLAG_ELF_LIBC5 => "libc5",
+            FLAG_ELF_LIBC6 => "libc6",
+            _ => "unknown",
+        };
+        let required: Option<&str> = match self.required() {
+            0 => None,
+            FLAG_SPARC_LIB64 | FLAG_S390_LIB64 | FLAG_POWERPC_LIB64
+            | FLAG_MIPS64_LIBN64 => Some("64bit"),
+            FLAG_IA64_LIB64 => Some("IA-64"),
+            FLAG_X8664_LIB64 => Some("x86-64"),
+            FLAG_MIPS64_LIBN32 => Some("N32"),
+            FLAG_X8664_LIBX32 => Some("x32"),
+            FLAG_ARM_LIBHF => Some("hard-float"),
+            FLAG_AARCH64_LIB64 => Some("AArch64"),
+            FLAG_ARM_LIBSF | FLAG_RISCV_FLOAT_ABI_SOFT
+            | FLAG_LARCH_FLOAT_ABI_SOFT => Some("soft-float"),
+            FLAG_MIPS_LIB32_NAN2008 => Some("nan2008"),
+            FLAG_MIPS64_LIBN32_NAN2008 => Some("N32,nan2008"),
+            FLAG_MIPS64_LIBN64_NAN2008 => Some("64bit,nan2008"),
+            FLAG_RISCV_FLOAT_ABI_DOUBLE | FLAG_LARCH_FLOAT_ABI_DOUBLE => Some("double-float"),
+            _ => Some("unknown"),
+        };
+        match required {
+            Some(required) => format!("{kind},{required}"),
+            None => kind.to_string(),
+        }
+    }
+}
+
+// A parsed ld.so.cache
+#[derive(Debug, Clone, PartialEq)]
+pub struct LdCache {
+    pub format: Format,
+    pub entries: Vec<CacheEntry>,
+    // the ldconfig that wrote the cache, from the extension section
+    pub generator: Option<String>,
+    pub hwcaps: Vec<String>,
+}
+
+// The flags of the cache entries an object can load, the values glibc
+// is built with for each machine. The ABI variants of ARM, RISC-V and
+// LoongArch come from e_flags.
+pub fn accepted_flags(header: &Header) -> Vec<u32> {
+    let is_64: bool = header.class == Class::X64Bit;
+    let required: Vec<u32> = match header.machine {
+        Machine::AMD64 if is_64 => vec![FLAG_X8664_LIB64],
+        Machine::AMD64 => vec![FLAG_X8664_LIBX32],
+        Machine::X86 | Machine::PPC => vec![0],
+        Machine::SPARCV9 => vec![FLAG_SPARC_LIB64],
+        Machine::IA_64 => vec![FLAG_IA64_LIB64],
+        Machine::S390 if is_64 => vec![FLAG_S390_LIB64],
+        Machine::S390 => vec![0],
+        Machine::PPC64 => vec![FLAG_POWERPC_LIB64],
+        Machine::AARCH64 => vec![FLAG_AARCH64_LIB64],
+        // EF_ARM_ABI_FLOAT_HARD
+        Machine::ARM if header.flags & 0x400 != 0 => vec![FLAG_ARM_LIBHF],
+        Machine::ARM => vec![FLAG_ARM_LIBSF],
+        Machine::MIPS if is_64 => vec![FLAG_MIPS64_LIBN64, FLAG_MIPS64_LIBN64_NAN2008],
+        // EF_MIPS_ABI2
+        Machine::MIPS if header.flags & 0x20 != 0 => {
+            vec![FLAG_MIPS64_LIBN32, FLAG_MIPS64_LIBN32_NAN2008]
+        },
+        Machine::MIPS => vec![0, FLAG_MIPS_LIB32_NAN2008],
+        // EF_RISCV_FLOAT_ABI
+        Machine::RISCV => match header.flags & 0x6 {
+            0 => vec![FLAG_RISCV_FLOAT_ABI_SOFT],
+            _ => vec![FLAG_RISCV_FLOAT_ABI_DOUBLE],
+        },
+        // EF_LOONGARCH_ABI_MODIFIER_MASK
+        Machine::LOONGARCH => match header.flags & 0x7 {
+            1 => vec![FLAG_LARCH_FLOAT_ABI_SOFT],
+            _ => vec![FLAG_LARCH_FLOAT_ABI_DOUBLE],
+        },
+        _ => vec![0],
+    };
+    required.into_iter().flat_map(|r| [r | FLAG_ELF_LIBC6, r | FLAG_ELF]).collect()
+}
+
+impl LdCache {
+    // Entries for `soname` an object with this header can load, those
+    // outside a glibc-hwcaps subdirectory first. Which subdirectory the
+    // loader prefers depends on the CPU it runs on.
+    pub fn lookup(&self, soname: &str, header: &Header) -> Vec<&CacheEntry> {
+        let accepted: Vec<u32> = accepted_flags(header);
+        let mut found: Vec<&CacheEntry> = self.entries.iter()
+            .filter(|e| e.key == soname && accepted.contains(&e.flags))
+            .collect();
+        found.sort_by_key(|e| e.hwcaps.is_some());
+        found
+    }
+
+    // every soname with the paths lookup returns for it, the form
+    // loader::Config takes
+    pub fn paths(&self, header: &Header) -> HashMap<String, Vec<PathBuf>> {
+        let accepted: Vec<u32> = accepted_flags(header);
+        let mut entries: Vec<&CacheEntry> = self.entries.iter()
+            .filter(|e| accepted.contains(&e.flags))
+            .collect();
+        entries.sort_by_key(|e| e.hwcaps.is_some());
+        let mut paths: HashMap<String, Vec<PathBuf>> = HashMap::new();
+        for entry in entries {
+            paths.entry(entry.key.clone()).or_default().push(PathBuf::from(&entry.value));
+        }
+        paths
+    }
+}
+
+fn string(data: &[u8], base: usize, offset: u32) -> Result<String, &'static str> {
+    let start: usize = base.checked_add(offset as usize).ok_or("String out of bounds.")?;
+    let name: &[u8] = Reader::new(data, start, Endian::Little).cstr()?;
+    Ok(String::from_utf8_lossy(name).into_owned())
+}
+
+fn native_endian() -> Endian {
+    if cfg!(target_endian = "big") { Endian::Big } else { Endian::Little }
+}
+
+// the table of the old format, strings are relative to its end
+fn parse_old(data: &[u8]) -> Result<Vec<CacheEntry>, &'static str> {
+    let mut reader: Reader = Reader::new(data, 12, native_endian());
+    let count: usize = reader.u32()? as usize;
+    let strings: usize = count.checked_mul(12).and_then(|n| n.checked_add(16))
+                              .ok_or("Cache entries out of bounds.")?;
+    if strings > data.len() {
+        return Err("Cache entries out of bounds.");
+    }
+    let mut entries: Vec<CacheEntry> = Vec::with_capacity(count);
+    for _ in 0..count {
+        let flags: u32 = reader.u32()?;
+        let key: u32 = reader.u32()?;
+        let value: u32 = reader.u32()?;
+        entries.push(CacheEntry {
+            flags,
+            key: string(data, strings, key)?,
+            value: string(data, strings, value)?,
+            ..Default::default()
+        });
+    }
+    Ok(entries)
+}
+
+// The table of the new format starting at `base`, offsets of strings
+// are relative to it. ldconfig writes the offsets of the extension
+// section and of its parts relative to the start of the file, which
+// makes a difference only behind an old table.
+fn parse_new(data: &[u8], base: usize) -> Result<LdCache, &'static str> {
+    let endian: Endian = match data.get(base + 28) {
+        Some(2) => Endian::Little,
+        Some(3) => Endian::Big,
+        Some(0) => native_endian(),
+        _ => return Err("Invalid endianness in ld.so.cache."),
+    };
+    let mut reader: Reader = Reader::new(data, base + NEW_MAGIC.len(), endian);
+    let count: usize = reader.u32()? as usize;
+    let _len_strings: u32 = reader.u32()?;
+    reader.skip(4)?;
+    let extension_offset: u32 = reader.u32()?;
+    reader.skip(12)?;
+
+    let mut cache: LdCache = LdCache {
+        format: Format::New,
+        entries: Vec::new(),
+        generator: None,
+        hwcaps: Vec::new(),
+    };
+
+    // the extension section, present since glibc 2.33
+    let mut ext: Reader = Reader::new(data, extension_offset as usize, endian);
+    if extension_offset != 0 && ext.u32() == Ok(EXTENSION_MAGIC) {
+        let sections: u32 = ext.u32()?;
+        for _ in 0..sections {
+            let tag: u32 = ext.u32()?;
+            let _flags: u32 = ext.u32()?;
+            let offset: usize = ext.u32()? as usize;
+            let size: usize = ext.u32()? as usize;
+            let content: &[u8] = data.get(offset..offset + size)
+                                     .ok_or("Cache extension out of bounds.")?;
+            match tag {
+                EXTENSION_TAG_GENERATOR => {
+                    cache.generator = Some(String::from_utf8_lossy(content).into_owned());
+                },
+                EXTENSION_TAG_GLIBC_HWCAPS => {
+                    let mut names: Reader = Reader::new(content, 0, endian);
+                    while !names.is_empty() {
+                        cache.hwcaps.push(string(data, base, names.u32()?)?);
+                    }
+                },
+                _ => (),
+            }
+        }
+    }
+
+    cache.entries.reserve(count.min(data.len() / 24));
+    for _ in 0..count {
+        let flags: u32 = reader.u32()?;
+        let key: u32 = reader.u32()?;
+        let value: u32 = reader.u32()?;
+        let os_version: u32 = reader.u32()?;
+        let hwcap: u64 = reader.u64()?;
+        let hwcaps: Option<String> = if hwcap >> 32 == DL_CACHE_HWCAP_EXTENSION >> 32 {
+            cache.hwcaps.get((hwcap & 0xffff_ffff) as usize).cloned()
+        }
+        else {
+            None
+        };
+        cache.entries.push(CacheEntry {
+            flags,
+            key: string(data, base, key)?,
+            value: string(data, base, value)?,
+            os_version,
+            hwcap,
+            hwcaps,
+        });
+    }
+    Ok(cache)
+}
+
+// Parse an ld.so.cache in the old, the new or the combined format. The
+// new table is used when there is one, as glibc does.
+pub fn parse(data: &[u8]) -> Result<LdCache, &'static str> {
+    if data.starts_with(NEW_MAGIC) {
+        return parse_new(data, 0);
+    }
+    if !data.starts_with(OLD_MAGIC) {
+        return Err("Not an ld.so.cache file.");
+    }
+    // the new table follows the old one at the next multiple of 8
+    let count: usize = Reader::new(data, 12, native_endian()).u32()? as usize;
+    let base: usize = count.checked_mul(12).and_then(|n| n.checked_add(16 + 7))
+                           .ok_or("Cache entries out of bounds.")? & !7;
+    if data.get(base..).is_some_and(|rest| rest.starts_with(NEW_MAGIC)) {
+        let mut cache: LdCache = parse_new(data, base)?;
+        cache.format = Format::Compat;
+        return Ok(cache);
+    }
+    Ok(LdCache {
+        format: Format::Old,
+        entries: parse_old(data)?,
+        generator: None,
+        hwcaps: Vec::new(),
+    })
+}
@@ -0,0 +1,26 @@
+use byteorder::{BigEndian, LittleEndian, ReadBytesExt};
+pub mod elf_header;
+use crate::elf_header::header_enums::{Class, Endian};
+pub mod program_header;
+pub mod section_header;
+pub mod elf;
+pub mod compression;
+pub mod symbol;
+pub mod dynamic;
+pub mod relocation;
+pub mod loader;
+pub mod ld_cache;
+pub mod resolve;
+pub mod hardening;
+pub mod policy;
+pub mod findings;
+pub mod export;
+pub mod mapping;
+pub mod address;
+pub mod readelf;
+pub mod extract;
+pub mod debug_file;
+pub mod debuginfod;
+pub mod note;
+pub mod core_dump;
+pub mod debug_info;
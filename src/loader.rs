@@ -0,0 +1,629 @@
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::ffi::OsString;
+use std::path::{Component, Path, PathBuf};
+use crate::{Class, Endian};
+use crate::dynamic::{self, Dynamic};
+use crate::dynamic::dyn_consts::*;
+use crate::elf::{self, Elf};
+use crate::elf_header::header_enums::Machine;
+
+// Where the dynamic loader found a library
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Found {
+    // the DT_NEEDED name contains a slash and is used as is
+    Path,
+    Rpath,
+    LdLibraryPath,
+    Runpath,
+    Cache,
+    DefaultDir,
+    // an object with this name was loaded before
+    Loaded,
+}
+
+impl Found {
+    pub fn describe(&self) -> &'static str {
+        match self {
+            Found::Path => "path",
+            Found::Rpath => "RPATH",
+            Found::LdLibraryPath => "LD_LIBRARY_PATH",
+            Found::Runpath => "RUNPATH",
+            Found::Cache => "ld.so.cache",
+            Found::DefaultDir => "default directory",
+            Found::Loaded => "already loaded",
+        }
+    }
+}
+
+// A file the loader opened and skipped while searching for a library
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rejected {
+    pub path: PathBuf,
+    pub reason: &'static str,
+}
+
+// One DT_NEEDED entry and what it resolved to, `object` indexes
+// LoadMap::objects and is None for a missing library
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dependency {
+    pub name: String,
+    pub object: Option<usize>,
+    pub found: Option<Found>,
+    pub rejected: Vec<Rejected>,
+}
+
+// An object loaded into the emulated process. `loader` is the object
+// whose DT_NEEDED entry loaded it, its RPATH is searched as well.
+#[derive(Debug)]
+pub struct Object {
+    pub name: String,
+    pub path: PathBuf,
+    pub elf: Elf,
+    pub dynamic: Dynamic,
+    pub loader: Option<usize>,
+    // how the object was found when it was loaded, None for the executable
+    pub found: Option<Found>,
+    pub needed: Vec<Dependency>,
+}
+
+// The objects of a process in load order, the executable first
+#[derive(Debug, Default)]
+pub struct LoadMap {
+    pub objects: Vec<Object>,
+    pub interpreter: Option<String>,
+    // the loaded interpreter, if it was found
+    pub interpreter_object: Option<usize>,
+}
+
+impl LoadMap {
+    // The global lookup scope: the executable followed by its
+    // dependencies breadth first. The interpreter is where it is first
+    // needed rather than where it was mapped.
+    pub fn scope(&self) -> Vec<usize> {
+        let mut scope: Vec<usize> = vec![0];
+        let mut next: usize = 0;
+        while next < scope.len() {
+            for dependency in self.objects[scope[next]].needed.iter() {
+                if let Some(idx) = dependency.object {
+                    if !scope.contains(&idx) {
+                        scope.push(idx);
+                    }
+                }
+            }
+            next += 1;
+        }
+        scope
+    }
+
+    pub fn missing(&self) -> Vec<&Dependency> {
+        self.objects.iter().flat_map(|o| o.needed.iter())
+            .filter(|d| d.object.is_none())
+            .collect()
+    }
+}
+
+// Settings of the emulated loader. Paths in the objects, the cache and
+// the default directories are taken relative to `sysroot`.
+#[derive(Debug, Default, Clone)]
+pub struct Config {
+    pub sysroot: Option<PathBuf>,
+    // the entries of LD_LIBRARY_PATH
+    pub library_path: Vec<String>,
+    // soname to paths, as listed in ld.so.cache
+    pub cache: HashMap<String, Vec<PathBuf>>,
+    // the trusted directories, derived from the executable when empty
+    pub default_dirs: Vec<PathBuf>,
+    // expansions of $LIB and $PLATFORM, derived when not given
+    pub lib: Option<String>,
+    pub platform: Option<String>,
+}
+
+// the Debian multiarch tuple of a machine
+pub fn triplet(machine: Machine, class: Class, endian: Endian) -> Option<&'static str> {
+    let is_64: bool = class == Class::X64Bit;
+    let little: bool = endian == Endian::Little;
+    match machine {
+        Machine::AMD64 if is_64 => Some("x86_64-linux-gnu"),
+        Machine::AMD64 => Some("x86_64-linux-gnux32"),
+        Machine::X86 => Some("i386-linux-gnu"),
+        Machine::AARCH64 => Some("aarch64-linux-gnu"),
+        Machine::ARM => Some("arm-linux-gnueabihf"),
+        Machine::PPC64 if little => Some("powerpc64le-linux-gnu"),
+        Machine::PPC64 => Some("powerpc64-linux-gnu"),
+        Machine::PPC => Some("powerpc-linux-gnu"),
+        Machine::S390 if is_64 => Some("s390x-linux-gnu"),
+        Machine::RISCV if is_64 => Some("riscv64-linux-gnu"),
+        Machine::MIPS if is_64 && little => Some("mips64el-linux-gnuabi64"),
+        Machine::MIPS if little => Some("mipsel-linux-gnu"),
+        Machine::SPARCV9 => Some("sparc64-linux-gnu"),
+        Machine::LOONGARCH => Some("loongarch64-linux-gnu"),
+        _ => None,
+    }
+}
+
+// AT_PLATFORM as the kernel reports it for a machine
+pub fn platform(machine: Machine, class: Class, endian: Endian) -> Option<&'static str> {
+    match machine {
+        Machine::AMD64 if class == Class::X64Bit => Some("x86_64"),
+        Machine::X86 => Some("i686"),
+        Machine::AARCH64 => Some("aarch64"),
+        Machine::ARM => Some("v7l"),
+        Machine::PPC64 if endian == Endian::Little => Some("ppc64le"),
+        Machine::PPC64 => Some("ppc64"),
+        Machine::S390 => Some("s390x"),
+        Machine::RISCV => Some("riscv64"),
+        Machine::LOONGARCH => Some("loongarch64"),
+        _ => None,
+    }
+}
+
+// Searches libraries the way the glibc dynamic loader does
+struct Loader<'a> {
+    config: &'a Config,
+    class: Class,
+    machine_id: u16,
+    lib: Option<String>,
+    platform: Option<String>,
+    default_dirs: Vec<PathBuf>,
+}
+
+// Candidate file and what it was found by
+type Candidate = (PathBuf, Found);
+
+impl<'a> Loader<'a> {
+    fn new(config: &'a Config, elf: &Elf) -> Loader<'a> {
+        let (machine, class, endian) = (elf.header.machine, elf.header.class, elf.header.endian);
+        let mut loader: Loader = Loader {
+            config,
+            class,
+            machine_id: elf.header.machine_id,
+            lib: config.lib.clone(),
+            platform: config.platform.clone()
+                            .or_else(|| platform(machine, class, endian).map(String::from)),
+            default_dirs: config.default_dirs.clone(),
+        };
+
+        // multiarch systems keep the libraries of a machine below
+        // lib/<triplet>, others in lib64 or lib by class
+        let multiarch: Option<&str> = triplet(machine, class, endian)
+            .filter(|t| loader.rooted(Path::new("/lib").join(t)).is_dir()
+                        || loader.rooted(Path::new("/usr/lib").join(t)).is_dir());
+        let lib: String = match multiarch {
+            Some(triplet) => format!("lib/{triplet}"),
+            None if class == Class::X64Bit
+                    && loader.rooted(PathBuf::from("/lib64")).is_dir() => "lib64".to_string(),
+            None => "lib".to_string(),
+        };
+        if loader.default_dirs.is_empty() {
+            let mut dirs: Vec<String> = Vec::new();
+            if let Some(triplet) = multiarch {
+                dirs.push(format!("/lib/{triplet}"));
+                dirs.push(format!("/usr/lib/{triplet}"));
+            }
+            if class == Class::X64Bit {
+                dirs.push("/lib64".to_string());
+                dirs.push("/usr/lib64".to_string());
+            }
+            dirs.push("/lib".to_string());
+            dirs.push("/usr/lib".to_string());
+            loader.default_dirs = dirs.into_iter().map(PathBuf::from).collect();
+        }
+        if loader.lib.is_none() {
+            loader.lib = Some(lib);
+        }
+        loader
+    }
+
+    // an absolute path inside the sysroot
+    fn rooted(&self, path: PathBuf) -> PathBuf {
+        match (self.config.sysroot.as_ref(), path.strip_prefix("/")) {
+            (Some(root), Ok(relative)) => root.join(relative),
+            _ => path,
+        }
+    }
+
+    // The path with every symlink resolved like fs::canonicalize does,
+    // but inside the sysroot absolute link targets start at its root and
+    // `..` stops there, so a library never resolves to a file of the host
+    fn resolve(&self, path: &Path) -> Option<PathBuf> {
+        let sysroot: &Path = match self.config.sysroot.as_ref() {
+            Some(sysroot) => sysroot,
+            None => return fs::canonicalize(path).ok(),
+        };
+        let root: PathBuf = fs::canonicalize(sysroot).ok()?;
+        let relative: &Path = match path.strip_prefix(sysroot)
+                                        .or_else(|_| path.strip_prefix(&root)) {
+            Ok(relative) => relative,
+            Err(_) => return fs::canonicalize(path).ok(),
+        };
+
+        // components still to walk, the next one last
+        let mut pending: Vec<OsString> = Vec::new();
+        let push = |pending: &mut Vec<OsString>, path: &Path| {
+            for component in path.components().rev() {
+                match component {
+                    Component::Normal(name) => pending.push(name.to_os_string()),
+                    Component::ParentDir => pending.push(OsString::from("..")),
+                    _ => (),
+                }
+            }
+        };
+        push(&mut pending, relative);
+        let mut current: PathBuf = root.clone();
+        let mut links: usize = 0;
+        while let Some(name) = pending.pop() {
+            if name == ".." {
+                if current != root {
+                    current.pop();
+                }
+                continue;
+            }
+            let next: PathBuf = current.join(&name);
+            if !fs::symlink_metadata(&next).ok()?.file_type().is_symlink() {
+                current = next;
+                continue;
+            }
+            // the limit of the kernel, catches loops
+            links += 1;
+            if links > 40 {
+                return None;
+            }
+            let target: PathBuf = fs::read_link(&next).ok()?;
+            if target.is_absolute() {
+                current = root.clone();
+            }
+            push(&mut pending, &target);
+        }
+        Some(current)
+    }
+
+    // directory of an object for $ORIGIN
+    fn origin(&self, path: &Path) -> PathBuf {
+        let dir: &Path = path.parent().filter(|p| !p.as_os_str().is_empty())
+                             .unwrap_or(Path::new("."));
+        self.resolve(dir).unwrap_or(dir.to_path_buf())
+    }
+
+    // Expand $ORIGIN, $LIB and $PLATFORM, also written ${NAME}, in one
+    // search path entry. $ORIGIN is the directory of the object the
+    // entry comes from, as found on this host. An entry that cannot be
+    // expanded is dropped, as glibc does.
+    fn expand(&self, entry: &str, origin: &Path) -> Option<PathBuf> {
+        let mut out: String = String::new();
+        let mut from_origin: bool = false;
+        let mut rest: &str = entry;
+        while let Some(idx) = rest.find('$') {
+            out.push_str(&rest[..idx]);
+            rest = &rest[idx + 1..];
+            let (name, len): (&str, usize) = match rest.strip_prefix('{') {
+                Some(inner) => {
+                    let end: usize = inner.find('}')?;
+                    (&inner[..end], end + 2)
+                },
+                None => {
+                    let end: usize = rest.find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                                         .unwrap_or(rest.len());
+                    (&rest[..end], end)
+                },
+            };
+            match name {
+                "ORIGIN" => {
+                    out.push_str(origin.to_str()?);
+                    from_origin = true;
+                },
+                "LIB" => out.push_str(self.lib.as_deref()?),
+                "PLATFORM" => out.push_str(self.platform.as_deref()?),
+                _ => {
+                    out.push('$');
+                    out.push_str(&rest[..len]);
+                },
+            }
+            rest = &rest[len..];
+        }
+        out.push_str(rest);
+
+        if out.is_empty() {
+            // an empty entry is the current directory
+            return Some(PathBuf::from("."));
+        }
+        let path: PathBuf = PathBuf::from(out);
+        if from_origin { Some(path) } else { Some(self.rooted(path)) }
+    }
+
+    // Directories of a search path. Entries are separated by colons,
+    // LD_LIBRARY_PATH also takes semicolons.
+    fn directories(&self, list: &str, origin: &Path, found: Found) -> Vec<Candidate> {
+        let separators: &[char] = match found {
+            Found::LdLibraryPath => &[':', ';'],
+            _ => &[':'],
+        };
+        list.split(separators)
+            .filter_map(|entry| self.expand(entry, origin))
+            .map(|dir| (dir, found))
+            .collect()
+    }
+
+    // The files tried for DT_NEEDED `name` of `requester`, in the order
+    // of glibc: the RPATH of the requester and of the objects that
+    // loaded it, those without a RUNPATH and only when the requester has
+    // none, LD_LIBRARY_PATH, RUNPATH, the cache and the default
+    // directories. The last two are skipped for requesters marked
+    // DF_1_NODEFLIB.
+    fn candidates(&self, objects: &[Object], requester: usize, name: &str) -> Vec<Candidate> {
+        let object: &Object = &objects[requester];
+        if name.contains('/') {
+            return self.expand(name, &self.origin(&object.path))
+                       .map(|path| vec![(path, Found::Path)])
+                       .unwrap_or_default();
+        }
+
+        let mut dirs: Vec<Candidate> = Vec::new();
+        if object.dynamic.runpath.is_none() {
+            let mut current: Option<usize> = Some(requester);
+            while let Some(idx) = current {
+                // an object with a RUNPATH has its RPATH ignored, also
+                // when searching for the objects it loaded
+                if objects[idx].dynamic.runpath.is_some() {
+                    current = objects[idx].loader;
+                    continue;
+                }
+                if let Some(rpath) = objects[idx].dynamic.rpath.as_ref() {
+                    let origin: PathBuf = self.origin(&objects[idx].path);
+                    dirs.extend(self.directories(rpath, &origin, Found::Rpath));
+                }
+                current = objects[idx].loader;
+            }
+        }
+        let main_origin: PathBuf = self.origin(&objects[0].path);
+        for entry in self.config.library_path.iter() {
+            dirs.extend(self.directories(entry, &main_origin, Found::LdLibraryPath));
+        }
+        if let Some(runpath) = object.dynamic.runpath.as_ref() {
+            dirs.extend(self.directories(runpath, &self.origin(&object.path), Found::Runpath));
+        }
+
+        let mut candidates: Vec<Candidate> = dirs.into_iter()
+                                                 .map(|(dir, found)| (dir.join(name), found))
+                                                 .collect();
+        if object.dynamic.flags_1 & DF_1_NODEFLIB == 0 {
+            if let Some(paths) = self.config.cache.get(name) {
+                candidates.extend(paths.iter().map(|p| (self.rooted(p.clone()), Found::Cache)));
+            }
+            candidates.extend(self.default_dirs.iter()
+                                  .map(|dir| (self.rooted(dir.join(name)), Found::DefaultDir)));
+        }
+        candidates
+    }
+
+    // the object at `path` if it can be loaded into this process
+    fn open(&self, path: &Path) -> Result<Elf, &'static str> {
+        let content: Vec<u8> = fs::read(path).map_err(|_| "cannot be read")?;
+        let elf: Elf = elf::parse(content).map_err(|_| "not an ELF file")?;
+        if elf.header.class != self.class {
+            return Err("wrong ELF class");
+        }
+        if elf.header.machine_id != self.machine_id {
+            return Err("wrong machine");
+        }
+        Ok(elf)
+    }
+}
+
+fn object(name: String, path: PathBuf, elf: Elf, loader: Option<usize>,
+          found: Option<Found>) -> Result<Object, &'static str> {
+    let dynamic: Dynamic = dynamic::parse(&elf)?.unwrap_or_default();
+    Ok(Object {
+        name,
+        path,
+        elf,
+        dynamic,
+        loader,
+        found,
+        needed: Vec::new(),
+    })
+}
+
+// remember the soname and file of a loaded object
+fn register(loader: &Loader, map: &LoadMap, idx: usize, names: &mut HashMap<String, usize>,
+            files: &mut HashMap<PathBuf, usize>) {
+    if let Some(soname) = map.objects[idx].dynamic.soname.as_ref() {
+        names.entry(soname.clone()).or_insert(idx);
+    }
+    let path: &Path = &map.objects[idx].path;
+    files.entry(loader.resolve(path).unwrap_or(path.to_path_buf())).or_insert(idx);
+}
+
+// Load the executable at `path` and, breadth first, every library it
+// needs. Each library is loaded once, later DT_NEEDED entries with the
+// same name or soname refer to the loaded object.
+pub fn load(path: &Path, config: &Config) -> Result<LoadMap, &'static str> {
+    let content: Vec<u8> = fs::read(path).map_err(|_| "Cannot read the executable.")?;
+    let elf: Elf = elf::parse(content)?;
+    let loader: Loader = Loader::new(config, &elf);
+
+    let mut map: LoadMap = LoadMap {
+        interpreter: dynamic::interpreter(&elf),
+        ..Default::default()
+    };
+    map.objects.push(object(path.display().to_string(), path.to_path_buf(), elf, None, None)?);
+
+    // names and files of the loaded objects
+    let mut names: HashMap<String, usize> = HashMap::new();
+    let mut files: HashMap<PathBuf, usize> = HashMap::new();
+    register(&loader, &map, 0, &mut names, &mut files);
+
+    // the interpreter is mapped by the kernel before anything else
+    if let Some(interp) = map.interpreter.clone() {
+        let interp_path: PathBuf = loader.rooted(PathBuf::from(&interp));
+        let opened: Option<Elf> = loader.resolve(&interp_path)
+                                        .and_then(|path| loader.open(&path).ok());
+        if let Some(elf) = opened {
+            let idx: usize = map.objects.len();
+            map.objects.push(object(interp.clone(), interp_path, elf, None, Some(Found::Path))?);
+            names.insert(interp, idx);
+            register(&loader, &map, idx, &mut names, &mut files);
+            map.interpreter_object = Some(idx);
+        }
+    }
+
+    let mut queue: VecDeque<usize> = VecDeque::from([0]);
+    while let Some(requester) = queue.pop_front() {
+        let needed: Vec<String> = map.objects[requester].dynamic.needed.clone();
+        for name in needed {
+            let mut dependency: Dependency = Dependency {
+                name: name.clone(),
+                object: None,
+                found: None,
+                rejected: Vec::new(),
+            };
+            if let Some(idx) = names.get(&name) {
+                dependency.object = Some(*idx);
+                dependency.found = Some(Found::Loaded);
+                map.objects[requester].needed.push(dependency);
+                continue;
+            }
+
+            for (candidate, found) in loader.candidates(&map.objects, requester, &name) {
+                // symlinks are followed inside the sysroot
+                let canonical: PathBuf = match loader.resolve(&candidate) {
+                    Some(path) if path.is_file() => path,
+                    _ => continue,
+                };
+                if let Some(idx) = files.get(&canonical) {
+                    dependency.object = Some(*idx);
+                    dependency.found = Some(found);
+                    break;
+                }
+                match loader.open(&canonical) {
+                    Ok(elf) => {
+                        let idx: usize = map.objects.len();
+                        map.objects.push(object(name.clone(), candidate, elf, Some(requester),
+                                                   Some(found))?);
+                        register(&loader, &map, idx, &mut names, &mut files);
+                        queue.push_back(idx);
+                        dependency.object = Some(idx);
+                        dependency.found = Some(found);
+                        break;
+                    },
+                    Err(reason) => dependency.rejected.push(Rejected {
+                        path: candidate,
+                        reason,
+                    }),
+                }
+            }
+            if let Some(idx) = dependency.object {
+                names.entry(name).or_insert(idx);
+            }
+            map.objects[requester].needed.push(dependency);
+        }
+    }
+    Ok(map)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> Config {
+        Config {
+            library_path: vec!["/env/lib:/env/lib2".to_string()],
+            cache: HashMap::from([("libx.so.1".to_string(), vec![PathBuf::from("/cache/libx.so.1")])]),
+            default_dirs: vec![PathBuf::from("/lib"), PathBuf::from("/usr/lib")],
+            lib: Some("lib64".to_string()),
+            platform: Some("x86_64".to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn object(path: &str, rpath: Option<&str>, runpath: Option<&str>,
+              loader: Option<usize>) -> Object {
+        Object {
+            name: path.rsplit('/').next().unwrap_or(path).to_string(),
+            path: PathBuf::from(path),
+            elf: Elf::default(),
+            dynamic: Dynamic {
+                rpath: rpath.map(String::from),
+                runpath: runpath.map(String::from),
+                ..Default::default()
+            },
+            loader,
+            found: None,
+            needed: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn expansions() {
+        let config: Config = config();
+        let loader: Loader = Loader::new(&config, &Elf::default());
+        let origin: &Path = Path::new("/opt/app/bin");
+        let expand = |entry: &str| loader.expand(entry, origin);
+        assert_eq!(expand("$ORIGIN/../lib"), Some(PathBuf::from("/opt/app/bin/../lib")));
+        assert_eq!(expand("${ORIGIN}/plugins"), Some(PathBuf::from("/opt/app/bin/plugins")));
+        assert_eq!(expand("/usr/$LIB/tls/${PLATFORM}"), Some(PathBuf::from("/usr/lib64/tls/x86_64")));
+        assert_eq!(expand("/opt/$UNKNOWN/lib"), Some(PathBuf::from("/opt/$UNKNOWN/lib")));
+        assert_eq!(expand(""), Some(PathBuf::from(".")));
+        assert_eq!(expand("/opt/${LIB"), None);
+
+        // the sysroot prefixes every directory but those below $ORIGIN
+        let config: Config = Config {
+            sysroot: Some(PathBuf::from("/sysroot")),
+            platform: None,
+            ..config
+        };
+        let loader: Loader = Loader::new(&config, &Elf::default());
+        assert_eq!(loader.expand("/usr/$LIB", origin), Some(PathBuf::from("/sysroot/usr/lib64")));
+        assert_eq!(loader.expand("$ORIGIN/lib", origin), Some(PathBuf::from("/opt/app/bin/lib")));
+        // without a platform the entry is dropped
+        assert_eq!(loader.expand("/usr/lib/$PLATFORM", origin), None);
+    }
+
+    #[test]
+    fn search_order() {
+        let config: Config = config();
+        let loader: Loader = Loader::new(&config, &Elf::default());
+        // main loads libmid, which has both RPATH and RUNPATH, libmid
+        // loads libleaf with an RPATH of its own
+        let mut objects: Vec<Object> = vec![
+            object("/opt/app/bin/main", Some("$ORIGIN/../lib"), None, None),
+            object("/opt/app/lib/libmid.so.1", Some("/mid/rpath"), Some("/mid/runpath"), Some(0)),
+            object("/opt/app/lib/libleaf.so.1", Some("/leaf/rpath"), None, Some(1)),
+        ];
+        let candidates = |objects: &[Object], requester: usize| -> Vec<(String, Found)> {
+            loader.candidates(objects, requester, "libx.so.1").into_iter()
+                  .map(|(path, found)| (path.display().to_string(), found))
+                  .collect()
+        };
+        let expected = |list: &[(&str, Found)]| -> Vec<(String, Found)> {
+            list.iter().map(|(path, found)| (path.to_string(), *found)).collect()
+        };
+
+        // the RPATH of libmid is skipped for its RUNPATH, that of main is not
+        assert_eq!(candidates(&objects, 2), expected(&[
+            ("/leaf/rpath/libx.so.1", Found::Rpath),
+            ("/opt/app/bin/../lib/libx.so.1", Found::Rpath),
+            ("/env/lib/libx.so.1", Found::LdLibraryPath),
+            ("/env/lib2/libx.so.1", Found::LdLibraryPath),
+            ("/cache/libx.so.1", Found::Cache),
+            ("/lib/libx.so.1", Found::DefaultDir),
+            ("/usr/lib/libx.so.1", Found::DefaultDir),
+        ]));
+        // a RUNPATH of the requester drops every RPATH
+        assert_eq!(candidates(&objects, 1), expected(&[
+            ("/env/lib/libx.so.1", Found::LdLibraryPath),
+            ("/env/lib2/libx.so.1", Found::LdLibraryPath),
+            ("/mid/runpath/libx.so.1", Found::Runpath),
+            ("/cache/libx.so.1", Found::Cache),
+            ("/lib/libx.so.1", Found::DefaultDir),
+            ("/usr/lib/libx.so.1", Found::DefaultDir),
+        ]));
+
+        objects[2].dynamic.flags_1 = DF_1_NODEFLIB;
+        assert_eq!(candidates(&objects, 2).last(),
+                   Some(&("/env/lib2/libx.so.1".to_string(), Found::LdLibraryPath)));
+        // a name with a slash is only looked for there
+        assert_eq!(loader.candidates(&objects, 2, "$ORIGIN/libx.so.1"),
+                   [(PathBuf::from("/opt/app/lib/libx.so.1"), Found::Path)]);
+    }
+}
@@ -1,16 +1,97 @@
-use clap::Parser;
+use clap::{CommandFactory, Parser, ValueEnum};
+use clap::error::ErrorKind;
+use serde_json::{Map, Value};
 use std::{error::Error, fs, process};
-use byteorder::{BigEndian, LittleEndian, ReadBytesExt};
-pub mod elf_header;
-use crate::elf_header::header_enums::{Class, Endian};
-use crate::elf_header::Header;
-pub mod program_header;
-pub mod section_header;
+use std::io::{self, BufRead, Read, Write};
+use std::path::{Path, PathBuf};
+use dwarf::address::{AddressMap, Location};
+use dwarf::core_dump::{self, CoreDump, MemoryOnly};
+use dwarf::debug_file::{self, DebugFile, Resolver};
+use dwarf::debuginfod::Client;
+use dwarf::debug_info::{self, DwarfSections};
+use dwarf::debug_info::expression::{self, Context, NoProvider, Provider};
+use dwarf::debug_info::dw_consts;
+use dwarf::debug_info::lint::{self, Problem};
+use dwarf::debug_info::line::{self, LineHeader};
+use dwarf::debug_info::locals::{self, Frame};
+use dwarf::debug_info::macros::{self, MacroEntry, UnitMacros};
+use dwarf::debug_info::names::{self, NameEntry, NameIndex};
+use dwarf::debug_info::producer::{self, UnitSummary};
+use dwarf::debug_info::ranges;
+use dwarf::debug_info::split::{self, DwoFiles, Package};
+use dwarf::debug_info::unit::{self, Unit};
+use dwarf::dynamic::{self, Dynamic};
+use dwarf::elf::{self, Elf};
+use dwarf::export;
+use dwarf::extract::{self, Extract};
+use dwarf::findings::{self, FileFindings};
+use dwarf::hardening::{self, Check, Hardening, Pie, Relro, CHECKS};
+use dwarf::ld_cache::{self, LdCache};
+use dwarf::loader::{self, Config, LoadMap};
+use dwarf::note;
+use dwarf::policy::{self, Policy};
+use dwarf::readelf;
+use dwarf::relocation::{self, Relocation};
+use dwarf::resolve::{self, DynamicSymbols, Interposition, LinkReport, Report, Resolution};
+use dwarf::resolve::VersionStatus;
+use dwarf::symbol::{self, Symbol, SymbolTable};
+use dwarf::symbol::hash::{self, HashCheck, HashTables};
+use dwarf::symbol::sym_consts::STB_WEAK;
+use dwarf::program_header::PHeader;
+use dwarf::program_header::phdr_enums::{PF_R, PF_W, PF_X};
+use dwarf::section_header::shdr_enums::SType;
+
+// how results meant for other programs are written
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+enum Format {
+    Text,
+    Json,
+    Yaml,
+    // findings of --checksec, --policy and --lint only
+    Sarif,
+    Junit,
+}
 
 struct CliArgs {
     file_path: String,
+    files: Vec<String>,
+    format: Format,
     program_header: bool,
     section_header: bool,
+    symbols: bool,
+    relocs: bool,
+    dynamic: bool,
+    notes: bool,
+    hex_dump: Vec<String>,
+    string_dump: Vec<String>,
+    extract_section: Vec<String>,
+    extract_segment: Vec<usize>,
+    output_dir: String,
+    zero_fill: bool,
+    locals: Option<Option<String>>,
+    core: Option<String>,
+    lookup: Vec<String>,
+    lint: bool,
+    units: bool,
+    macros: bool,
+    symbol: Vec<String>,
+    whatis: Vec<String>,
+    offsets: bool,
+    debug_root: Vec<String>,
+    debuginfod: Vec<String>,
+    ldd: bool,
+    sysroot: Option<String>,
+    library_path: Option<String>,
+    ld_cache: Option<String>,
+    check_symbols: bool,
+    interposition: bool,
+    needed: bool,
+    hash: bool,
+    hash_lookup: Vec<String>,
+    checksec: bool,
+    require: Vec<String>,
+    // the path of --policy and the policy read from it
+    policy: Option<(String, Policy)>,
 }
 
 fn parse_args() -> CliArgs {
@@ -19,102 +100,1234 @@ fn parse_args() -> CliArgs {
     struct Args {
         #[arg(long, short)]
         file_path: String,
+        /// More files checked against --policy
+        files: Vec<String>,
+        /// Output format. json and yaml write one document with the
+        /// header, the tables asked for, --checksec and --policy results,
+        /// sarif and junit the findings of --checksec, --policy and --lint,
+        /// the other modes only print text
+        #[arg(long, value_enum, default_value = "text")]
+        format: Format,
         #[arg(long, short, action)]
         program_header: bool,
         #[arg(long, short, action)]
         section_header: bool,
+        /// Show the symbols of .symtab and .dynsym
+        #[arg(long, action)]
+        symbols: bool,
+        /// Show the entries of the relocation sections
+        #[arg(long, action)]
+        relocs: bool,
+        /// Show the entries of the dynamic section
+        #[arg(long, action)]
+        dynamic: bool,
+        /// Show the notes of the note sections or PT_NOTE segments
+        #[arg(long, action)]
+        notes: bool,
+        /// Dump the bytes of a section in hex, by name or index
+        #[arg(long, short = 'x', value_name = "SECTION")]
+        hex_dump: Vec<String>,
+        /// Dump the strings of a section, by name or index
+        #[arg(long, value_name = "SECTION")]
+        string_dump: Vec<String>,
+        /// Write the raw bytes of a section, by name or index, to a file
+        /// in --output-dir
+        #[arg(long, value_name = "SECTION")]
+        extract_section: Vec<String>,
+        /// Write the bytes of a segment, by index, to a file in --output-dir
+        #[arg(long, value_name = "INDEX")]
+        extract_segment: Vec<usize>,
+        /// Directory --extract-section and --extract-segment write to
+        #[arg(long, value_name = "DIR", default_value = ".")]
+        output_dir: String,
+        /// Add the zeros NOBITS sections and the memsz - filesz tail of
+        /// segments take in memory to the extracted bytes
+        #[arg(long, action)]
+        zero_fill: bool,
+        /// Show the parameters and local variables at a link time address,
+        /// defaults to the crash address of --core
+        #[arg(long, value_name = "PC")]
+        locals: Option<Option<String>>,
+        /// Core dump used to read memory for --locals, and registers when
+        /// --locals is at the crash address
+        #[arg(long, value_name = "FILE")]
+        core: Option<String>,
+        /// Find functions, variables and types by name using the name
+        /// index of the file
+        #[arg(long, value_name = "NAME")]
+        lookup: Vec<String>,
+        /// Check the debug information for structural problems, exits
+        /// with status 1 when any is found
+        #[arg(long, action)]
+        lint: bool,
+        /// List the compilation units with their source file, directory,
+        /// language, compiler and compiler options
+        #[arg(long, action)]
+        units: bool,
+        /// Show the macros recorded for each compilation unit (-g3)
+        #[arg(long, action)]
+        macros: bool,
+        /// Name the symbol containing an address, using .symtab, .dynsym
+        /// and the MiniDebugInfo of stripped binaries
+        #[arg(long, value_name = "ADDR")]
+        symbol: Vec<String>,
+        /// Show the segment, permissions, section, symbol and source line
+        /// of an address, - reads the addresses from stdin one per line
+        #[arg(long, value_name = "ADDR")]
+        whatis: Vec<String>,
+        /// Take the values of --whatis as file offsets
+        #[arg(long, action)]
+        offsets: bool,
+        /// Directory searched for separate debug files by build id and
+        /// .gnu_debuglink, /usr/lib/debug when none is given
+        #[arg(long, value_name = "DIR")]
+        debug_root: Vec<String>,
+        /// debuginfod server asked for debug files that are not found
+        /// locally, defaults to the servers of $DEBUGINFOD_URLS
+        #[arg(long, value_name = "URL")]
+        debuginfod: Vec<String>,
+        /// Show the libraries the dynamic loader would load, without
+        /// running the file, exits with status 1 when one is missing
+        #[arg(long, action)]
+        ldd: bool,
+        /// Root directory the libraries of --ldd are looked up in
+        #[arg(long, value_name = "DIR")]
+        sysroot: Option<String>,
+        /// Search path used by --ldd in place of $LD_LIBRARY_PATH
+        #[arg(long, value_name = "PATHS")]
+        library_path: Option<String>,
+        /// ld.so.cache used by --ldd, defaults to /etc/ld.so.cache in
+        /// the sysroot
+        #[arg(long, value_name = "FILE")]
+        ld_cache: Option<String>,
+        /// Check that every undefined dynamic symbol and needed version
+        /// is defined by a library --ldd finds, exits with status 1 when
+        /// one is missing
+        #[arg(long, action)]
+        check_symbols: bool,
+        /// List the dynamic symbols exported by more than one object
+        /// --ldd finds and which definition the loader picks
+        #[arg(long, action)]
+        interposition: bool,
+        /// Find DT_NEEDED libraries the executable uses no symbol of and
+        /// symbols it only gets through indirect dependencies, exits with
+        /// status 1 when any is found
+        #[arg(long, action)]
+        needed: bool,
+        /// Show the chain statistics of .hash and .gnu.hash and check that
+        /// every exported symbol can be found through them, exits with
+        /// status 1 when one cannot
+        #[arg(long, action)]
+        hash: bool,
+        /// Find a dynamic symbol by name through the hash tables
+        #[arg(long, value_name = "NAME")]
+        hash_lookup: Vec<String>,
+        /// Show the hardening features of the file: PIE, NX, RELRO, stack
+        /// protector, FORTIFY_SOURCE, RPATH/RUNPATH, CET and RWX segments
+        #[arg(long, action)]
+        checksec: bool,
+        /// Hardening checks --checksec requires, exits with status 1 when
+        /// one fails: pie, nx, partial-relro, full-relro, stack-protector,
+        /// fortify, no-rpath, no-runpath, no-textrel, cet, no-rwx
+        #[arg(long, value_name = "CHECKS", value_delimiter = ',')]
+        require: Vec<String>,
+        /// TOML policy file every file is checked against, exits with
+        /// status 1 when one breaks a rule and 2 when one cannot be read
+        /// or the policy itself is invalid
+        #[arg(long, value_name = "FILE")]
+        policy: Option<String>,
     }
     let args: Args = Args::parse();
 
+    // these modes only print text, which would break the json, yaml, sarif
+    // and junit documents, --lint has findings for sarif and junit
+    let text_only: [(&str, bool); 15] = [
+        ("--hex-dump", !args.hex_dump.is_empty()),
+        ("--string-dump", !args.string_dump.is_empty()),
+        ("--locals", args.locals.is_some()),
+        ("--lookup", !args.lookup.is_empty()),
+        ("--lint", args.lint && matches!(args.format, Format::Json | Format::Yaml)),
+        ("--units", args.units),
+        ("--macros", args.macros),
+        ("--symbol", !args.symbol.is_empty()),
+        ("--whatis", !args.whatis.is_empty()),
+        ("--ldd", args.ldd),
+        ("--check-symbols", args.check_symbols),
+        ("--interposition", args.interposition),
+        ("--needed", args.needed),
+        ("--hash", args.hash),
+        ("--hash-lookup", !args.hash_lookup.is_empty()),
+    ];
+    if args.format != Format::Text {
+        if let Some((flag, _)) = text_only.iter().find(|(_, used)| *used) {
+            let format: String = format!("{:?}", args.format).to_lowercase();
+            Args::command().error(ErrorKind::ArgumentConflict,
+                                  format!("{flag} has no {format} output, use --format text"))
+                           .exit();
+        }
+    }
+
+    // a broken policy is a usage error, exiting with status 2 before any
+    // file is looked at
+    let policy: Option<(String, Policy)> = args.policy.map(|path| {
+        match Policy::load(Path::new(&path)) {
+            Ok(policy) => (path, policy),
+            Err(e) => Args::command().error(ErrorKind::InvalidValue,
+                                            format!("invalid policy {path}: {e}"))
+                                     .exit(),
+        }
+    });
+
     CliArgs {
         file_path: args.file_path,
+        files: args.files,
+        format: args.format,
         program_header: args.program_header,
         section_header: args.section_header,
-    } 
+        symbols: args.symbols,
+        relocs: args.relocs,
+        dynamic: args.dynamic,
+        notes: args.notes,
+        hex_dump: args.hex_dump,
+        string_dump: args.string_dump,
+        extract_section: args.extract_section,
+        extract_segment: args.extract_segment,
+        output_dir: args.output_dir,
+        zero_fill: args.zero_fill,
+        locals: args.locals,
+        core: args.core,
+        lookup: args.lookup,
+        lint: args.lint,
+        units: args.units,
+        macros: args.macros,
+        symbol: args.symbol,
+        whatis: args.whatis,
+        offsets: args.offsets,
+        debug_root: args.debug_root,
+        debuginfod: args.debuginfod,
+        ldd: args.ldd,
+        sysroot: args.sysroot,
+        library_path: args.library_path,
+        ld_cache: args.ld_cache,
+        check_symbols: args.check_symbols,
+        interposition: args.interposition,
+        needed: args.needed,
+        hash: args.hash,
+        hash_lookup: args.hash_lookup,
+        checksec: args.checksec,
+        require: args.require,
+        policy,
+    }
 }
 
 fn main() {
     // get the command line arguments like the binary file name
     let cli_args: CliArgs = parse_args();
-    println!("The file to be parsed is: {0}", cli_args.file_path);
+    if cli_args.format == Format::Text {
+        println!("The file to be parsed is: {0}", cli_args.file_path);
+    }
 
     // Now we need to read the binary ELF file
-    if let Err(e) = run(cli_args) {
-        println!("Error: {e}");
-        process::exit(1);
+    match run(cli_args) {
+        Ok(status) => process::exit(status),
+        Err(e) => {
+            println!("Error: {e}");
+            process::exit(1);
+        },
     }
 }
 
-fn run(cli_args: CliArgs) -> Result<(), Box<dyn Error>> {
-    let content: Vec<u8> = fs::read(cli_args.file_path)?;
+fn parse_address(value: &str) -> Result<u64, Box<dyn Error>> {
+    let value: &str = value.trim();
+    match value.strip_prefix("0x").or_else(|| value.strip_prefix("0X")) {
+        Some(hex) => Ok(u64::from_str_radix(hex, 16)?),
+        None => Ok(value.parse::<u64>()?),
+    }
+}
 
-    // we have the contents 
-    // in a byte array, time to start parsing ELF header
-    
-    let mut header: Header = Header {
+fn print_locals(frame: &Frame, context: &Context,
+                provider: &dyn Provider) {
+    println!("Function {0} at {1:#x}", frame.function, frame.pc);
+    if let Some(expr) = frame.frame_base.as_ref().and_then(|fb| fb.at(frame.pc)) {
+        let text: String = expression::disassemble(expr, frame.encoding)
+                                      .unwrap_or_else(|e| e.to_string());
+        match frame.frame_base(context, provider) {
+            Ok(base) => println!("  frame base: {text} = {base:#x}"),
+            Err(_) => println!("  frame base: {text}"),
+        }
+    }
+
+    for variable in frame.variables.iter() {
+        let kind: &str = if variable.is_parameter { "parameter" } else { "variable" };
+        let location: String = match variable.location.as_ref()
+                                             .and_then(|l| l.at(frame.pc)) {
+            Some(expr) => expression::disassemble(expr, frame.encoding)
+                                     .unwrap_or_else(|e| e.to_string()),
+            None if variable.const_value.is_some() => String::from("constant"),
+            None => String::from("<optimized out>"),
+        };
+        let value: String = match frame.read(variable, context, provider) {
+            Ok(bytes) => {
+                let hex: Vec<String> = bytes.iter().map(|b| format!("{b:02x}")).collect();
+                if bytes.len() <= 8 {
+                    let number: u64 = expression::bytes_to_value(&bytes,
+                                                                 frame.encoding.endian);
+                    format!("{0} ({number})", hex.join(" "))
+                }
+                else {
+                    hex.join(" ")
+                }
+            },
+            Err(e) => format!("<{e}>"),
+        };
+        println!("  {kind} {0}: {location} = {value}", variable.name);
+    }
+}
+
+fn print_name(sections: &DwarfSections, dwos: &[DwarfSections],
+              package: Option<&Package>,
+              entry: &NameEntry) -> Result<(), Box<dyn Error>> {
+    let (unit, idx) = match names::resolve(sections, dwos, package, entry)? {
+        Some(found) => found,
+        None => {
+            println!("{0}: {1:?} in unit {2:#x}, entry not found", entry.name,
+                     entry.kind, entry.unit_offset);
+            return Ok(());
+        },
+    };
+    let die = &unit.dies[idx];
+    let tag: String = dw_consts::tag_name(die.tag).map(String::from)
+                                                  .unwrap_or_else(|| format!("{:#x}", die.tag));
+    let ranges: Vec<String> = ranges::die_ranges(&unit, die)?.iter()
+        .map(|r| format!("[{0:#x}, {1:#x})", r.begin, r.end))
+        .collect();
+    println!("{0}: {tag} in unit {1:#x} at {2:#x} {3}", entry.name,
+             entry.unit_offset, die.offset, ranges.join(" "));
+    Ok(())
+}
+
+fn print_summary(summary: &UnitSummary) {
+    println!("Unit {0:#x}: {1}", summary.offset,
+             summary.name.as_deref().unwrap_or("<unnamed>"));
+    if let Some(dir) = summary.comp_dir.as_ref() {
+        println!("  directory: {dir}");
+    }
+    if let Some(language) = summary.language {
+        let name: String = summary.language_name().map(String::from)
+                                  .unwrap_or_else(|| format!("{language:#x}"));
+        println!("  language: {name}");
+    }
+    if let Some(compiler) = summary.compiler() {
+        println!("  compiler: {compiler}");
+    }
+    let flags: Vec<&str> = summary.flags();
+    if !flags.is_empty() {
+        println!("  flags: {0}", flags.join(" "));
+    }
+}
+
+fn print_macros(unit: &Unit, macros: &UnitMacros) {
+    println!("Unit {0:#x}: {1}+{2:#x} version {3}", unit.header.offset, macros.section,
+             macros.offset, macros.version);
+    // file numbers refer to the line table of the unit
+    let header: Option<LineHeader> = macros.line_offset.and_then(|offset| {
+        line::parse_header(&unit.sections, offset as usize).ok()
+    });
+    let mut depth: usize = 1;
+    for entry in macros.entries.iter() {
+        let indent: String = "  ".repeat(depth);
+        match &entry.entry {
+            MacroEntry::Define { line, text } => println!("{indent}{line}: #define {text}"),
+            MacroEntry::Undef { line, text } => println!("{indent}{line}: #undef {text}"),
+            MacroEntry::StartFile { line, file } => {
+                let path: String = header.as_ref().and_then(|h| h.file_path(*file))
+                                         .unwrap_or_else(|| format!("file {file}"));
+                println!("{indent}{line}: start {path}");
+                depth += 1;
+            },
+            MacroEntry::EndFile => depth = depth.saturating_sub(1).max(1),
+            MacroEntry::Import { offset } => println!("{indent}import {offset:#x}"),
+            MacroEntry::DefineSup { line, offset } => {
+                println!("{indent}{line}: #define <supplementary string {offset:#x}>");
+            },
+            MacroEntry::UndefSup { line, offset } => {
+                println!("{indent}{line}: #undef <supplementary string {offset:#x}>");
+            },
+            MacroEntry::ImportSup { offset } => {
+                println!("{indent}import supplementary {offset:#x}");
+            },
+            MacroEntry::Vendor { opcode, constant, text } => {
+                println!("{indent}vendor {opcode:#x}: {constant} {text}");
+            },
+        }
+    }
+}
+
+fn symbol_text(symbols: &SymbolTable, addr: u64) -> String {
+    match symbols.lookup(addr) {
+        Some((symbol, 0)) => format!("{0} [{1}]", symbol.name, symbol.table),
+        Some((symbol, offset)) => format!("{0}+{offset:#x} [{1}]", symbol.name,
+                                          symbol.table),
+        None => String::from("??"),
+    }
+}
+
+fn print_whatis(elf: &Elf, symbols: &SymbolTable, sections: &DwarfSections, query: &str,
+                location: &Location) {
+    println!("{query}:");
+    match location.vaddr {
+        Some(vaddr) => println!("  address: {vaddr:#x}"),
+        None => println!("  address: not loaded"),
+    }
+    match location.offset {
+        Some(offset) => println!("  file offset: {offset:#x}"),
+        None => println!("  file offset: none, zero filled"),
+    }
+    // a segment ending past the last address is not mapped
+    let segment = location.segment.and_then(|idx| {
+        let p: &PHeader = elf.pheaders.get(idx)?;
+        Some((idx, p, p.vaddr.checked_add(p.memsz)?))
+    });
+    if let Some((idx, p, end)) = segment {
+        println!("  segment: {idx} LOAD [{0:#x}, {end:#x})", p.vaddr);
+        let permissions: String = [(PF_R, 'r'), (PF_W, 'w'), (PF_X, 'x')].iter()
+            .map(|(bit, key)| if p.flags & bit != 0 { *key } else { '-' })
+            .collect();
+        println!("  permissions: {permissions}");
+    }
+    if let Some(s) = location.section.and_then(|idx| elf.sheaders.get(idx)) {
+        println!("  section: {0}", s.sname);
+    }
+    if let Some(vaddr) = location.vaddr {
+        println!("  symbol: {0}", symbol_text(symbols, vaddr));
+        if let Ok(Some(source)) = line::source_line(sections, vaddr) {
+            match source.column {
+                0 => println!("  source: {0}:{1}", source.path, source.line),
+                column => println!("  source: {0}:{1}:{column}", source.path, source.line),
+            }
+        }
+    }
+}
+
+fn print_hardening(audit: &Hardening) {
+    let yes_no = |value: bool| if value { "yes" } else { "no" };
+    let pie: &str = match audit.pie {
+        Pie::Yes => "yes",
+        Pie::No => "no",
+        Pie::Dso => "shared object",
+    };
+    let relro: &str = match audit.relro {
+        Relro::Full => "full",
+        Relro::Partial => "partial",
+        Relro::None => "none",
+    };
+    println!("PIE: {pie}");
+    println!("NX: {0}", yes_no(audit.nx));
+    println!("RELRO: {relro}");
+    println!("Stack protector: {0}", yes_no(audit.stack_protector));
+    match audit.fortified.len() {
+        0 => println!("FORTIFY_SOURCE: no"),
+        count => println!("FORTIFY_SOURCE: yes ({count} fortified functions)"),
+    }
+    println!("RPATH: {0}", audit.rpath.as_deref().unwrap_or("none"));
+    println!("RUNPATH: {0}", audit.runpath.as_deref().unwrap_or("none"));
+    println!("TEXTREL: {0}", yes_no(audit.textrel));
+    let mut cet: Vec<&str> = Vec::new();
+    for (enabled, name) in [(audit.ibt, "IBT"), (audit.shstk, "SHSTK"),
+                            (audit.bti, "BTI"), (audit.pac, "PAC")] {
+        if enabled {
+            cet.push(name);
+        }
+    }
+    println!("CET: {0}", if cet.is_empty() { "none".to_string() } else { cet.join(", ") });
+    match audit.rwx_segments.is_empty() {
+        true => println!("RWX segments: none"),
+        false => {
+            let indexes: Vec<String> = audit.rwx_segments.iter().map(|i| i.to_string()).collect();
+            println!("RWX segments: {0}", indexes.join(", "));
+        },
+    }
+}
+
+fn print_hash_check(check: &HashCheck, dynsym: &[Symbol]) {
+    println!("{0}: {1} buckets, {2} symbols", check.table, check.buckets(), check.symbol_count);
+    println!("    longest chain {0}, {1:.2} compares per successful lookup",
+             check.max_chain(), check.average_chain());
+    for (length, count) in check.histogram().iter().enumerate() {
+        println!("    chains of length {length}: {count}");
+    }
+    if check.unreachable.is_empty() {
+        println!("    every exported symbol is reachable");
+    }
+    for idx in check.unreachable.iter() {
+        println!("    unreachable: .dynsym[{idx}] {0}", dynsym[*idx].name);
+    }
+}
+
+// the objects the dynamic loader would load for the executable
+fn load_map(cli_args: &CliArgs, elf: &Elf) -> Result<LoadMap, Box<dyn Error>> {
+    let mut config: Config = Config {
+        sysroot: cli_args.sysroot.as_ref().map(PathBuf::from),
+        library_path: cli_args.library_path.clone()
+                              .or_else(|| std::env::var("LD_LIBRARY_PATH").ok())
+                              .into_iter().collect(),
         ..Default::default()
     };
-    
-    elf_header::parse(&content, &mut header)?;
-    
-    if cli_args.program_header == true {
-        
-        let mut phdr_offset: u64 = header.phdr_offset;
-        for _ in 0..header.phdr_entries {
-            program_header::parse(&content, phdr_offset,
-                              header.class, header.endian)?;
-            phdr_offset += header.phdr_entry_sz as u64;
-        }
-    }
-
-    if cli_args.section_header == true {
-        
-        let mut shdr_offset: u64 = header.shdr_offset;
-        let strtab_header_start: u64 = header.shdr_offset
-                            + ((header.shstr_idx) as u64
-                            * header.shdr_entry_sz as u64);
-        let strtab_start: u64;
-        if header.class == Class::X32Bit {
-            if header.endian == Endian::Little {
-                let mut buff: &[u8];
-                buff = &content[(strtab_header_start + 0x10) as usize
-                                ..(strtab_header_start + 0x14)
-                                as usize];
-                strtab_start = buff.read_u32::<LittleEndian>().unwrap() as u64;
-            }
-            else {
-                let mut buff: &[u8];
-                buff = &content[(strtab_header_start + 0x10) as usize
-                                ..(strtab_header_start + 0x14)
-                                as usize];
-                strtab_start = buff.read_u32::<BigEndian>().unwrap() as u64;
+    let cache_path: PathBuf = match cli_args.ld_cache.as_ref() {
+        Some(path) => PathBuf::from(path),
+        None => config.sysroot.clone().unwrap_or(PathBuf::from("/"))
+                      .join("etc/ld.so.cache"),
+    };
+    // a system without a cache only searches the other directories
+    if cli_args.ld_cache.is_some() || cache_path.is_file() {
+        let cache: LdCache = ld_cache::parse(&fs::read(&cache_path)?)?;
+        config.cache = cache.paths(&elf.header);
+    }
+    Ok(loader::load(Path::new(&cli_args.file_path), &config)?)
+}
+
+fn print_bindings(map: &LoadMap, symbols: &[DynamicSymbols], report: &Report) {
+    println!("Undefined symbols of {0}:", map.objects[report.object].name);
+    for binding in report.bindings.iter() {
+        let name: String = match binding.version.as_ref() {
+            Some(version) => format!("{0}@{version}", binding.name),
+            None => binding.name.clone(),
+        };
+        let weak: &str = if binding.weak { " (weak)" } else { "" };
+        match binding.resolution {
+            Resolution::Defined { object, symbol } => {
+                let definition: &Symbol = &symbols[object].symbols[symbol];
+                let kind: &str = if definition.bind == STB_WEAK { " weak" } else { "" };
+                println!("    {name}{weak} => {0}{kind}", map.objects[object].path.display());
+            },
+            Resolution::WeakUndefined => println!("    {name}{weak} => unresolved, reads as 0"),
+            Resolution::Missing => println!("    {name}{weak} => not found"),
+        }
+    }
+    for check in report.versions.iter().filter(|v| v.status != VersionStatus::Found) {
+        let weak: &str = if check.weak { " (weak)" } else { "" };
+        let problem: &str = match check.status {
+            VersionStatus::Missing => "not found",
+            VersionStatus::NoVersionInfo => "no version information available",
+            VersionStatus::NotLoaded => "library not found",
+            VersionStatus::Found => "found",
+        };
+        println!("    version {0}{weak} of {1}: {problem}", check.version, check.file);
+    }
+    println!("{0} symbol or version references cannot be resolved.", report.failures());
+}
+
+fn print_interpositions(map: &LoadMap, found: &[Interposition]) {
+    println!("Symbols defined by more than one object:");
+    for interposition in found.iter() {
+        println!("    {0}", interposition.name);
+        for (idx, definition) in interposition.definitions.iter().enumerate() {
+            let mut notes: Vec<String> = Vec::new();
+            if interposition.winner == Some(idx) {
+                notes.push("wins".to_string());
+            }
+            if let Some(version) = definition.version.as_ref() {
+                let at: &str = if definition.hidden { "@" } else { "@@" };
+                notes.push(format!("{at}{version}"));
+            }
+            if definition.protected {
+                notes.push("protected".to_string());
             }
+            if definition.weak {
+                notes.push("weak".to_string());
+            }
+            if interposition.winner != Some(idx) && !interposition.differs(definition) {
+                notes.push("shadowed".to_string());
+            }
+            println!("        {0} {1}", map.objects[definition.object].path.display(),
+                     notes.join(", "));
         }
-        else {
-             if header.endian == Endian::Little {
-                let mut buff: &[u8];
-                buff = &content[(strtab_header_start + 0x18) as usize
-                                ..(strtab_header_start + 0x20)
-                                as usize];
-                strtab_start = buff.read_u64::<LittleEndian>().unwrap();
-            }
-            else {
-                let mut buff: &[u8];
-                buff = &content[(strtab_header_start + 0x10) as usize
-                                ..(strtab_header_start + 0x14)
-                                as usize];
-                strtab_start = buff.read_u64::<BigEndian>().unwrap();
-            }           
-        }
-        println!("{strtab_start}");
-        for _ in 0..header.shdr_entries { 
-            section_header::parse(&content, shdr_offset,
-                          header.class, header.endian,
-                          strtab_start)?;     
-            shdr_offset += header.shdr_entry_sz as u64;
-        }
-    }
-    
+    }
+    println!("{0} symbols are defined more than once.", found.len());
+}
+
+fn print_link_usage(map: &LoadMap, usage: &LinkReport) {
+    println!("DT_NEEDED entries of {0}:", map.objects[usage.object].name);
+    for needed in usage.needed.iter() {
+        match (needed.object, needed.symbols.len()) {
+            (None, _) => println!("    {0}: not found", needed.name),
+            (Some(_), 0) => {
+                println!("    {0}: unused, can be dropped with --as-needed", needed.name)
+            },
+            (Some(_), count) => println!("    {0}: {count} symbols used", needed.name),
+        }
+    }
+    if usage.underlinked.is_empty() {
+        return;
+    }
+    println!("Symbols found only through indirect dependencies:");
+    for underlinked in usage.underlinked.iter() {
+        let name: String = match underlinked.version.as_ref() {
+            Some(version) => format!("{0}@{version}", underlinked.name),
+            None => underlinked.name.clone(),
+        };
+        let lib: &str = map.objects[underlinked.object].name.as_str();
+        match underlinked.through {
+            Some(through) => {
+                println!("    {name} from {lib}, loaded for {0}", map.objects[through].name)
+            },
+            None => println!("    {name} from {lib}"),
+        }
+    }
+}
+
+// the dependency tree of the executable with how each library was found
+// when it was loaded, each object is expanded where it first appears
+fn print_load_map(map: &LoadMap) {
+    match map.interpreter.as_ref() {
+        Some(interp) if map.interpreter_object.is_some() => println!("Interpreter: {interp}"),
+        Some(interp) => println!("Interpreter: {interp} (not found)"),
+        None => println!("Interpreter: none"),
+    }
+    if map.objects[0].dynamic.needed.is_empty() {
+        println!("No DT_NEEDED entries.");
+        return;
+    }
+    println!("{0}", map.objects[0].name);
+    let mut shown: Vec<bool> = vec![false; map.objects.len()];
+    shown[0] = true;
+    print_dependencies(map, 0, 1, &mut shown);
+}
+
+fn print_dependencies(map: &LoadMap, idx: usize, depth: usize, shown: &mut Vec<bool>) {
+    let indent: String = "    ".repeat(depth);
+    for dependency in map.objects[idx].needed.iter() {
+        match dependency.object.map(|idx| &map.objects[idx]) {
+            Some(object) => {
+                let found: &str = object.found.map_or("executable", |f| f.describe());
+                println!("{indent}{0} => {1} ({found})", dependency.name, object.path.display());
+            },
+            None => println!("{indent}{0} => not found", dependency.name),
+        }
+        for rejected in dependency.rejected.iter() {
+            println!("{indent}    skipped {0}: {1}", rejected.path.display(), rejected.reason);
+        }
+        if let Some(object) = dependency.object {
+            if !shown[object] {
+                shown[object] = true;
+                print_dependencies(map, object, depth + 1, shown);
+            }
+        }
+    }
+}
+
+// add the findings of one check, those of a file already checked are
+// merged
+fn add_findings(all: &mut Vec<FileFindings>, file: FileFindings) {
+    match all.iter_mut().find(|f| f.path == file.path) {
+        Some(known) => known.merge(file),
+        None => all.push(file),
+    }
+}
+
+// write the document of the json and yaml formats or the findings of
+// sarif and junit, text is printed as it goes
+fn print_output(format: Format, document: &Map<String, Value>,
+                all: &[FileFindings]) -> Result<(), Box<dyn Error>> {
+    match format {
+        Format::Json => println!("{0}", serde_json::to_string_pretty(document)?),
+        Format::Yaml => print!("{0}", serde_yaml::to_string(document)?),
+        Format::Sarif => println!("{0}", serde_json::to_string_pretty(&findings::sarif(all))?),
+        Format::Junit => print!("{0}", findings::junit(all)),
+        Format::Text => (),
+    }
+    Ok(())
+}
+
+// Hex and string dumps of sections, in section order like readelf,
+// false when a section asked for does not exist
+fn print_dumps(elf: &Elf, hex: &[String],
+               strings: &[String]) -> Result<bool, Box<dyn Error>> {
+    let mut found: bool = true;
+    let mut select = |specs: &[String]| -> Vec<usize> {
+        let mut selected: Vec<usize> = Vec::new();
+        for spec in specs.iter() {
+            let indexes: Vec<usize> = elf.select_sections(spec);
+            if indexes.is_empty() {
+                eprintln!("Section '{spec}' was not dumped because it does not exist");
+                found = false;
+            }
+            selected.extend(indexes);
+        }
+        selected
+    };
+    let hex: Vec<usize> = select(hex);
+    let strings: Vec<usize> = select(strings);
+    let mut stdout = io::stdout().lock();
+    for idx in 0..elf.sheaders.len() {
+        if hex.contains(&idx) {
+            stdout.write_all(readelf::hex_dump(elf, idx)?.as_bytes())?;
+        }
+        if strings.contains(&idx) {
+            stdout.write_all(&readelf::string_dump(elf, idx)?)?;
+        }
+    }
+    Ok(found)
+}
+
+// Write the extracted sections and segments to files, false when one
+// asked for does not exist
+fn write_extracts(elf: &Elf, cli_args: &CliArgs, text: bool) -> Result<bool, Box<dyn Error>> {
+    let mut found: bool = true;
+    let mut files: Vec<(String, Extract, String)> = Vec::new();
+    for spec in cli_args.extract_section.iter() {
+        let indexes: Vec<usize> = elf.select_sections(spec);
+        if indexes.is_empty() {
+            eprintln!("Section '{spec}' was not extracted because it does not exist");
+            found = false;
+        }
+        for idx in indexes {
+            files.push((format!("section '{0}'", elf.sheaders[idx].sname),
+                        extract::section(elf, idx)?, extract::section_file_name(elf, idx)));
+        }
+    }
+    for idx in cli_args.extract_segment.iter().copied() {
+        if idx >= elf.pheaders.len() {
+            eprintln!("Segment {idx} was not extracted because it does not exist");
+            found = false;
+            continue;
+        }
+        files.push((format!("segment {idx}"), extract::segment(elf, idx)?,
+                    extract::segment_file_name(idx)));
+    }
+    for (what, extract, name) in files.iter() {
+        let path: PathBuf = Path::new(&cli_args.output_dir).join(name);
+        let mut file: fs::File = fs::File::create(&path)?;
+        file.write_all(extract.data)?;
+        if cli_args.zero_fill {
+            io::copy(&mut io::repeat(0).take(extract.zeros), &mut file)?;
+        }
+        if text {
+            println!("Wrote {0} bytes of {what} to {1}", extract.size(cli_args.zero_fill),
+                     path.display());
+        }
+    }
+    Ok(found)
+}
+
+fn print_symbols(elf: &Elf) -> Result<(), Box<dyn Error>> {
+    let mut symbols: Vec<Symbol> = Vec::new();
+    for sheader in elf.sheaders.iter().filter(|s| s.stype == SType::SYMTAB) {
+        symbols.extend(symbol::parse(elf, sheader, ".symtab")?);
+    }
+    symbols.extend(symbol::dynamic_symbols(elf)?);
+    let mut idx: usize = 0;
+    for (pos, sym) in symbols.iter().enumerate() {
+        // numbered within each table
+        if pos > 0 && symbols[pos - 1].table != sym.table {
+            idx = 0;
+        }
+        println!("{0}[{idx}] {1:#018x} {2:6} {3:7} {4:6} {5:9} {6:5} {7}", sym.table, sym.value,
+                 sym.size, symbol::type_name(sym.stype).unwrap_or("?"),
+                 symbol::bind_name(sym.bind).unwrap_or("?"),
+                 symbol::visibility_name(sym.visibility).unwrap_or("?"), sym.shndx, sym.name);
+        idx += 1;
+    }
     Ok(())
 }
 
+fn print_relocations(elf: &Elf) -> Result<(), Box<dyn Error>> {
+    for sheader in elf.sheaders.iter().filter(|s| matches!(s.stype, SType::REL | SType::RELA)) {
+        let entries: Vec<Relocation> = relocation::parse(elf, sheader)?;
+        println!("Relocation section {0}, {1} entries:", sheader.sname, entries.len());
+        for r in entries.iter() {
+            let rtype: String = match relocation::type_name(elf.header.machine, r.rtype) {
+                Some(name) => name.to_string(),
+                None => format!("{0:#x}", r.rtype),
+            };
+            match r.addend {
+                Some(addend) if addend < 0 => println!("    {0:#018x} {rtype:24} {1}-{2:#x}",
+                                                       r.offset, r.symbol, -(addend as i128)),
+                Some(addend) => println!("    {0:#018x} {rtype:24} {1}+{addend:#x}",
+                                         r.offset, r.symbol),
+                None => println!("    {0:#018x} {rtype:24} {1}", r.offset, r.symbol),
+            }
+        }
+    }
+    Ok(())
+}
+
+fn print_dynamic(elf: &Elf) -> Result<(), Box<dyn Error>> {
+    let dynamic: Dynamic = match dynamic::parse(elf)? {
+        Some(dynamic) => dynamic,
+        None => {
+            println!("No dynamic section.");
+            return Ok(());
+        },
+    };
+    let strtab: &[u8] = dynamic::strtab(elf, &dynamic)?;
+    for entry in dynamic.entries.iter() {
+        let tag: String = match dynamic::tag_name(entry.tag) {
+            Some(name) => name.to_string(),
+            None => format!("{0:#x}", entry.tag),
+        };
+        match dynamic::is_string_tag(entry.tag) {
+            true => {
+                let value: &[u8] = strtab.get(entry.value as usize..).unwrap_or(&[])
+                                         .split(|b| *b == 0).next().unwrap_or(&[]);
+                println!("{tag:20} {0}", String::from_utf8_lossy(value));
+            },
+            false => println!("{tag:20} {0:#x}", entry.value),
+        }
+    }
+    Ok(())
+}
+
+fn print_notes(elf: &Elf) {
+    for note in note::all(elf).iter() {
+        let ntype: String = match note::type_name(note.name, note.ntype) {
+            Some(name) => name.to_string(),
+            None => format!("{0:#x}", note.ntype),
+        };
+        println!("{0:8} {ntype:28} {1}", String::from_utf8_lossy(note.name),
+                 debug_file::hex(note.desc));
+    }
+}
+
+fn print_policy(reports: &[policy::Report]) {
+    for report in reports.iter() {
+        match (&report.error, report.passed()) {
+            (Some(e), _) => println!("{0}: ERROR {e}", report.path),
+            (None, true) => println!("{0}: pass", report.path),
+            (None, false) => println!("{0}: FAIL", report.path),
+        }
+        for violation in report.violations.iter() {
+            println!("    {0}: {1}", violation.rule, violation.message);
+        }
+    }
+}
+
+// returns the exit status of the program
+fn run(cli_args: CliArgs) -> Result<i32, Box<dyn Error>> {
+    let content: Vec<u8> = fs::read(&cli_args.file_path)?;
+
+    // we have the contents
+    // in a byte array, time to start parsing ELF header
+    let elf: Elf = elf::parse(content)?;
+    // the json and yaml document, text is printed as it goes
+    let text: bool = cli_args.format == Format::Text;
+    let mut document: Map<String, Value> = Map::new();
+    if text {
+        println!("Valid ELF binary.");
+        print!("{0}", readelf::render(&elf, true, cli_args.section_header,
+                                      cli_args.program_header));
+    }
+    else {
+        document.insert("schema_version".to_string(), Value::from(export::SCHEMA_VERSION));
+        document.insert("file".to_string(), Value::from(cli_args.file_path.clone()));
+        document.insert("header".to_string(), export::header(&elf.header));
+    }
+
+    if cli_args.program_header && !text {
+        document.insert("program_headers".to_string(), export::program_headers(&elf));
+    }
+
+    if cli_args.section_header && !text {
+        document.insert("section_headers".to_string(), export::section_headers(&elf));
+    }
+
+    match text {
+        true => {
+            if cli_args.symbols {
+                print_symbols(&elf)?;
+            }
+            if cli_args.relocs {
+                print_relocations(&elf)?;
+            }
+            if cli_args.dynamic {
+                print_dynamic(&elf)?;
+            }
+            if cli_args.notes {
+                print_notes(&elf);
+            }
+        },
+        false => {
+            if cli_args.symbols {
+                document.insert("symbols".to_string(), export::symbols(&elf)?);
+            }
+            if cli_args.relocs {
+                document.insert("relocations".to_string(), export::relocations(&elf)?);
+            }
+            if cli_args.dynamic {
+                document.insert("dynamic".to_string(), export::dynamic(&elf)?);
+            }
+            if cli_args.notes {
+                document.insert("notes".to_string(), export::notes(&elf));
+            }
+        },
+    }
+
+    let mut status: i32 = 0;
+    if !print_dumps(&elf, &cli_args.hex_dump, &cli_args.string_dump)? {
+        status = 1;
+    }
+    if !write_extracts(&elf, &cli_args, text)? {
+        status = 1;
+    }
+    let findings_output: bool = matches!(cli_args.format, Format::Sarif | Format::Junit);
+    let mut all_findings: Vec<FileFindings> = Vec::new();
+    if cli_args.checksec || !cli_args.require.is_empty() {
+        let mut required: Vec<Check> = Vec::new();
+        for name in cli_args.require.iter() {
+            required.push(Check::parse(name).ok_or(format!("Unknown hardening check {name}."))?);
+        }
+        let audit: Hardening = hardening::audit(&elf)?;
+        let failed: Vec<Check> = hardening::failures(&audit, &required);
+        if findings_output {
+            // every check is reported when none is required
+            let checks: Policy = Policy {
+                require: if required.is_empty() { CHECKS.to_vec() } else { required.clone() },
+                ..Default::default()
+            };
+            let report: policy::Report = policy::Report {
+                path: cli_args.file_path.clone(),
+                error: None,
+                violations: policy::check(&elf, &checks)?,
+            };
+            add_findings(&mut all_findings, FileFindings::from_policy(&report, &checks));
+        }
+        else if !text {
+            let mut value: Value = export::hardening(&audit);
+            value["failed"] = failed.iter().map(|c| c.name()).collect();
+            document.insert("hardening".to_string(), value);
+        }
+        else {
+            print_hardening(&audit);
+            if !required.is_empty() {
+                match failed.is_empty() {
+                    true => println!("Policy: pass"),
+                    false => {
+                        let names: Vec<&str> = failed.iter().map(|c| c.name()).collect();
+                        println!("Policy: FAIL ({0})", names.join(", "));
+                    },
+                }
+            }
+        }
+        if !failed.is_empty() {
+            status = 1;
+        }
+    }
+
+    if let Some((path, policy)) = cli_args.policy.as_ref() {
+        let reports: Vec<policy::Report> = std::iter::once(&cli_args.file_path)
+            .chain(cli_args.files.iter())
+            .map(|file| policy::evaluate(Path::new(file), policy))
+            .collect();
+        let passed: bool = reports.iter().all(|r| r.passed());
+        match cli_args.format {
+            Format::Text => print_policy(&reports),
+            Format::Json | Format::Yaml => {
+                let results: Value = serde_json::json!({
+                    "path": path,
+                    "passed": passed,
+                    "files": reports,
+                });
+                document.insert("policy".to_string(), results);
+            },
+            Format::Sarif | Format::Junit => {
+                for report in reports.iter() {
+                    add_findings(&mut all_findings, FileFindings::from_policy(report, policy));
+                }
+            },
+        }
+        if reports.iter().any(|r| r.error.is_some()) {
+            status = 2;
+        } else if !passed {
+            status = 1;
+        }
+    }
+
+    if cli_args.hash || !cli_args.hash_lookup.is_empty() {
+        let tables: HashTables = HashTables::load(&elf)?;
+        let dynsym: Vec<Symbol> = symbol::dynamic_symbols(&elf)?;
+        if cli_args.hash {
+            let checks: Vec<HashCheck> = hash::check(&tables, &dynsym);
+            if checks.is_empty() {
+                println!("No hash tables.");
+            }
+            for check in checks.iter() {
+                print_hash_check(check, &dynsym);
+                if !check.unreachable.is_empty() {
+                    status = 1;
+                }
+            }
+        }
+        for name in cli_args.hash_lookup.iter() {
+            match tables.lookup(name, &dynsym) {
+                Some((idx, sym)) => println!("{name}: .dynsym[{idx}] value {0:#x} size {1}",
+                                             sym.value, sym.size),
+                None => println!("{name}: not found"),
+            }
+        }
+    }
+
+    if cli_args.ldd || cli_args.check_symbols || cli_args.interposition || cli_args.needed {
+        let map: LoadMap = load_map(&cli_args, &elf)?;
+        if cli_args.ldd {
+            print_load_map(&map);
+            if !map.missing().is_empty() {
+                status = 1;
+            }
+        }
+        if cli_args.check_symbols {
+            let symbols: Vec<DynamicSymbols> = resolve::load_symbols(&map)?;
+            let report: Report = resolve::check(&map, &symbols, 0);
+            print_bindings(&map, &symbols, &report);
+            if report.failures() > 0 {
+                status = 1;
+            }
+        }
+        if cli_args.interposition {
+            let symbols: Vec<DynamicSymbols> = resolve::load_symbols(&map)?;
+            print_interpositions(&map, &resolve::interpositions(&map, &symbols));
+        }
+        if cli_args.needed {
+            let symbols: Vec<DynamicSymbols> = resolve::load_symbols(&map)?;
+            let usage: LinkReport = resolve::link_usage(&map, &symbols, 0);
+            print_link_usage(&map, &usage);
+            if !usage.unused().is_empty() || !usage.underlinked.is_empty() {
+                status = 1;
+            }
+        }
+    }
+
+    if cli_args.locals.is_none() && cli_args.lookup.is_empty() && !cli_args.lint
+       && !cli_args.units && !cli_args.macros && cli_args.symbol.is_empty()
+       && cli_args.whatis.is_empty() {
+        print_output(cli_args.format, &document, &all_findings)?;
+        return Ok(status);
+    }
+
+    // stripped objects keep their debug information in a separate file,
+    // which may in turn share strings with a dwz supplementary file
+    let path: &Path = Path::new(&cli_args.file_path);
+    let mut resolver: Resolver = if cli_args.debug_root.is_empty() {
+        Resolver::default()
+    }
+    else {
+        Resolver::new(cli_args.debug_root.iter().map(PathBuf::from).collect())
+    };
+    resolver.debuginfod = if cli_args.debuginfod.is_empty() {
+        Client::from_env()
+    }
+    else {
+        Client::default_cache_dir().map(|dir| Client::new(cli_args.debuginfod.clone(), dir))
+    };
+    let debug: Option<DebugFile> = if elf.section(".debug_info").is_none() {
+        resolver.find_debug_file(&elf, path)?
+    }
+    else {
+        None
+    };
+    if let Some(debug) = debug.as_ref().filter(|_| !findings_output) {
+        println!("Debug file: {0} (found by {1:?})", debug.path.display(), debug.method);
+    }
+    let alt: Option<DebugFile> = match debug.as_ref() {
+        Some(debug) => resolver.find_alt_file(&debug.elf, &debug.path)?,
+        None => resolver.find_alt_file(&elf, path)?,
+    };
+    if let Some(alt) = alt.as_ref().filter(|_| !findings_output) {
+        println!("Supplementary file: {0}", alt.path.display());
+    }
+
+    let mut symbols: SymbolTable = SymbolTable::load(&elf)?;
+    if let Some(debug) = debug.as_ref() {
+        symbols.merge(SymbolTable::load(&debug.elf)?.symbols);
+    }
+    for addr in cli_args.symbol.iter() {
+        let addr: u64 = parse_address(addr)?;
+        println!("{addr:#x}: {0}", symbol_text(&symbols, addr));
+    }
+
+    let mut sections: DwarfSections = match debug.as_ref() {
+        Some(debug) => {
+            // the debug file has no loaded contents, the unwind tables
+            // stay in the object
+            let original: DwarfSections = debug_info::load(&elf);
+            DwarfSections {
+                eh_frame: original.eh_frame,
+                eh_frame_addr: original.eh_frame_addr,
+                ..debug_info::load(&debug.elf)
+            }
+        },
+        None => debug_info::load(&elf),
+    };
+    if let Some(alt) = alt.as_ref() {
+        sections.sup_str = debug_info::load(&alt.elf).str;
+    }
+    if !cli_args.whatis.is_empty() {
+        let map: AddressMap = AddressMap::new(&elf);
+        let mut queries: Vec<String> = Vec::new();
+        for value in cli_args.whatis.iter() {
+            match value.as_str() {
+                "-" => {
+                    for line in io::stdin().lock().lines() {
+                        let line: String = line?;
+                        if !line.trim().is_empty() {
+                            queries.push(line.trim().to_string());
+                        }
+                    }
+                },
+                value => queries.push(value.to_string()),
+            }
+        }
+        for query in queries.iter() {
+            let value: u64 = match parse_address(query) {
+                Ok(value) => value,
+                Err(_) => {
+                    println!("{query}: not an address");
+                    status = 1;
+                    continue;
+                },
+            };
+            let location: Option<Location> = match cli_args.offsets {
+                true => map.lookup_offset(value),
+                false => map.lookup(value),
+            };
+            match location {
+                Some(location) => print_whatis(&elf, &symbols, &sections, query, &location),
+                None if cli_args.offsets => println!("{query}: not in any segment or section \
+                                                      of the file"),
+                None => println!("{query}: not in any segment or section"),
+            }
+        }
+    }
+    if cli_args.lint {
+        let problems: Vec<Problem> = lint::check(&sections);
+        if findings_output {
+            // the problems are in the debug file when there is one
+            let file: FileFindings = match debug.as_ref() {
+                Some(debug) => FileFindings::from_lint(&debug.path.display().to_string(),
+                                                       &debug.elf, &problems),
+                None => FileFindings::from_lint(&cli_args.file_path, &elf, &problems),
+            };
+            add_findings(&mut all_findings, file);
+        }
+        else {
+            for problem in problems.iter() {
+                println!("{0}+{1:#x}: [{2}] {3}", problem.section, problem.offset,
+                         problem.check, problem.message);
+            }
+            println!("{0} problems found.", problems.len());
+        }
+        if !problems.is_empty() {
+            status = 1;
+        }
+    }
+
+    // split DWARF keeps the entries in .dwo files or a .dwp package,
+    // both are also looked for next to the executable
+    let search_dirs: Vec<PathBuf> = path.parent().map(Path::to_path_buf)
+                                        .into_iter().collect();
+    let dwo_files: DwoFiles = split::load_dwos(&sections, &search_dirs)?;
+    for invalid in dwo_files.invalid.iter() {
+        eprintln!("Warning: skipped {0}: {1}", invalid.path.display(), invalid.reason);
+    }
+    let package: Option<Elf> = match fs::read(split::package_path(path)) {
+        Ok(content) => Some(elf::parse(content)?),
+        Err(_) => None,
+    };
+    let dwos: Vec<DwarfSections> = dwo_files.files.iter().map(debug_info::load).collect();
+    let package_sections: Option<Package> = match package.as_ref() {
+        Some(package) => Some(Package::load(debug_info::load(package))?),
+        None => None,
+    };
+
+    if !cli_args.lookup.is_empty() {
+        let index: NameIndex = names::load(&sections, &dwos, package_sections.as_ref())?;
+        println!("Name index: {0}", index.source());
+        for name in cli_args.lookup.iter() {
+            let entries: Vec<NameEntry> = index.lookup(name)?;
+            if entries.is_empty() {
+                println!("{name}: not found");
+            }
+            for entry in entries.iter() {
+                print_name(&sections, &dwos, package_sections.as_ref(), entry)?;
+            }
+        }
+    }
+
+    if cli_args.units || cli_args.macros {
+        // split units leave some attributes, like the directory, in
+        // their skeleton
+        let skeletons: Vec<Unit> = unit::parse_units(&sections)?;
+        for unit in split::units(&sections, &dwos, package_sections.as_ref())?.iter() {
+            if cli_args.units {
+                let mut summary: UnitSummary = producer::summarize(unit);
+                let skeleton: Option<&Unit> = unit.skeleton_offset.and_then(|offset| {
+                    skeletons.iter().find(|s| s.header.offset == offset)
+                });
+                if let Some(skeleton) = skeleton {
+                    let outer: UnitSummary = producer::summarize(skeleton);
+                    summary.offset = outer.offset;
+                    summary.name = summary.name.or(outer.name);
+                    summary.comp_dir = summary.comp_dir.or(outer.comp_dir);
+                    summary.producer = summary.producer.or(outer.producer);
+                    summary.language = summary.language.or(outer.language);
+                }
+                print_summary(&summary);
+            }
+            if cli_args.macros {
+                // a unit with broken macro information does not keep the
+                // others from being shown
+                match macros::unit_macros(unit) {
+                    Ok(Some(unit_macros)) => print_macros(unit, &unit_macros),
+                    Ok(None) => (),
+                    Err(e) => println!("Unit {0:#x}: {e}", unit.header.offset),
+                }
+            }
+        }
+    }
+
+    if let Some(pc) = cli_args.locals {
+        let units: Vec<Unit> = split::units(&sections, &dwos, package_sections.as_ref())?;
+        let core: Option<CoreDump> = match cli_args.core {
+            Some(path) => Some(core_dump::parse(fs::read(path)?)?),
+            None => None,
+        };
+        let context: Context = Context {
+            load_bias: core.as_ref()
+                           .map_or(0, |c| c.load_bias(elf.header.entry_point)),
+            ..Default::default()
+        };
+        let pc: u64 = match (pc, core.as_ref()) {
+            (Some(pc), _) => parse_address(&pc)?,
+            (None, Some(core)) => {
+                core.pc().ok_or("Core dump has no program counter.")?
+                    .wrapping_sub(context.load_bias)
+            },
+            (None, None) => return Err("--locals needs an address or --core.".into()),
+        };
+        // the registers of the core belong to the crashed frame only
+        let crash_pc: Option<u64> = core.as_ref().and_then(|c| c.pc())
+                                        .map(|c| c.wrapping_sub(context.load_bias));
+        let memory: Option<MemoryOnly> = core.as_ref().map(MemoryOnly);
+        let provider: &dyn Provider = match (core.as_ref(), memory.as_ref()) {
+            (Some(core), _) if crash_pc == Some(pc) => core,
+            (_, Some(memory)) => {
+                eprintln!("Warning: {pc:#x} is not the crash address of the core dump, \
+                           frames are not unwound so values kept in registers are not shown.");
+                memory
+            },
+            _ => &NoProvider,
+        };
+
+        match locals::frame_at(&units, pc)? {
+            Some(frame) => print_locals(&frame, &context, provider),
+            None => {
+                // stripped binaries may still name the function through
+                // their symbol tables
+                println!("No function with debug information at {pc:#x} ({0}).",
+                         symbol_text(&symbols, pc));
+            },
+        }
+    }
+
+    print_output(cli_args.format, &document, &all_findings)?;
+    Ok(status)
+}
@@ -0,0 +1,158 @@
+use crate::elf::Elf;
+use crate::program_header::PHeader;
+use crate::program_header::phdr_enums::PType;
+use crate::section_header::SHeader;
+use crate::section_header::shdr_enums::{SType, SHF_ALLOC, SHF_TLS};
+
+// p_type range of the PT_GNU_MBIND segments, one per memory policy
+const PT_GNU_MBIND_LO: u32 = 0x6474e555;
+const PT_GNU_MBIND_HI: u32 = 0x6474f554;
+
+// Whether a section lies in a segment, by file offset for sections with
+// data and by virtual address for SHF_ALLOC ones. The rules are those of
+// readelf's section to segment mapping (ELF_SECTION_IN_SEGMENT_STRICT of
+// binutils): a .tbss is only part of PT_TLS, it takes no room in the
+// PT_LOAD the thread data is copied from, PT_PHDR has no section and
+// empty sections at the very start or end of PT_DYNAMIC and PT_NOTE are
+// left out.
+pub fn section_in_segment(sheader: &SHeader, pheader: &PHeader) -> bool {
+    let tls: bool = sheader.flags & SHF_TLS != 0;
+    let alloc: bool = sheader.flags & SHF_ALLOC != 0;
+    let nobits: bool = sheader.stype == SType::NOBITS;
+    let ptype: PType = pheader.ptype;
+    if tls && nobits && ptype != PType::TLS {
+        return false;
+    }
+    let size: u64 = sheader.size;
+
+    // only PT_LOAD, PT_GNU_RELRO and PT_TLS hold SHF_TLS sections and
+    // PT_TLS nothing else
+    let kind: bool = match tls {
+        true => matches!(ptype, PType::TLS | PType::GNU_RELRO | PType::LOAD),
+        false => ptype != PType::TLS && ptype != PType::PHDR,
+    };
+    // these only hold SHF_ALLOC sections
+    let loaded: bool = matches!(ptype, PType::LOAD | PType::DYNAMIC | PType::GNU_EH_FRAME
+                                       | PType::GNU_STACK | PType::GNU_RELRO | PType::GNU_SFRAME)
+                       || (PT_GNU_MBIND_LO..=PT_GNU_MBIND_HI).contains(&pheader.type_id);
+    if !kind || (!alloc && loaded) {
+        return false;
+    }
+
+    // the bytes of the section lie in the file part of the segment, an
+    // empty segment only holds empty sections at its offset
+    let in_file: bool = nobits
+        || (sheader.offset >= pheader.offset
+            && sheader.offset - pheader.offset <= pheader.filesz.wrapping_sub(1)
+            && (sheader.offset - pheader.offset).wrapping_add(size) <= pheader.filesz);
+    let in_memory: bool = !alloc
+        || (sheader.vaddr >= pheader.vaddr
+            && sheader.vaddr - pheader.vaddr <= pheader.memsz.wrapping_sub(1)
+            && (sheader.vaddr - pheader.vaddr).wrapping_add(size) <= pheader.memsz);
+    if !in_file || !in_memory {
+        return false;
+    }
+
+    if (ptype != PType::DYNAMIC && ptype != PType::NOTE) || size != 0 || pheader.memsz == 0 {
+        return true;
+    }
+    (nobits || (sheader.offset > pheader.offset
+                && sheader.offset - pheader.offset < pheader.filesz))
+        && (!alloc || (sheader.vaddr > pheader.vaddr
+                       && sheader.vaddr - pheader.vaddr < pheader.memsz))
+}
+
+// The indexes of the sections in each segment, in program header order.
+// The null section at index 0 is in none.
+pub fn segment_sections(elf: &Elf) -> Vec<Vec<usize>> {
+    elf.pheaders.iter().map(|pheader| {
+        elf.sheaders.iter().enumerate().skip(1)
+           .filter(|(_, sheader)| section_in_segment(sheader, pheader))
+           .map(|(idx, _)| idx)
+           .collect()
+    }).collect()
+}
+
+// the indexes of the segments holding the section at `section`
+pub fn section_segments(elf: &Elf, section: usize) -> Vec<usize> {
+    let sheader: &SHeader = match elf.sheaders.get(section) {
+        Some(sheader) if section != 0 => sheader,
+        _ => return Vec::new(),
+    };
+    elf.pheaders.iter().enumerate()
+       .filter(|(_, pheader)| section_in_segment(sheader, pheader))
+       .map(|(idx, _)| idx)
+       .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn section(stype: SType, flags: u64, addr: u64, offset: u64, size: u64) -> SHeader {
+        SHeader { stype, flags, vaddr: addr, offset, size, ..Default::default() }
+    }
+
+    fn segment(ptype: PType, offset: u64, vaddr: u64, filesz: u64, memsz: u64) -> PHeader {
+        PHeader { ptype, offset, vaddr, filesz, memsz, ..Default::default() }
+    }
+
+    #[test]
+    fn file_and_memory_ranges() {
+        let load: PHeader = segment(PType::LOAD, 0x1000, 0x401000, 0x100, 0x200);
+        let text: SHeader = section(SType::PROGBITS, SHF_ALLOC, 0x401000, 0x1000, 0x100);
+        assert!(section_in_segment(&text, &load));
+        // one byte past the file part, or in memory at another address
+        assert!(!section_in_segment(&SHeader { size: 0x101, ..text.clone() }, &load));
+        assert!(!section_in_segment(&SHeader { vaddr: 0x402000, ..text.clone() }, &load));
+        // .bss only takes room in memory
+        let bss: SHeader = section(SType::NOBITS, SHF_ALLOC, 0x401100, 0x1100, 0x100);
+        assert!(section_in_segment(&bss, &load));
+        assert!(!section_in_segment(&SHeader { size: 0x101, ..bss }, &load));
+        // sections that are not loaded are in no loadable segment
+        let comment: SHeader = section(SType::PROGBITS, 0, 0, 0x1000, 0x10);
+        assert!(!section_in_segment(&comment, &load));
+        assert!(section_in_segment(&comment, &segment(PType::NOTE, 0x1000, 0, 0x10, 0)));
+        // PT_PHDR holds no section
+        assert!(!section_in_segment(&text, &segment(PType::PHDR, 0x1000, 0x401000, 0x100, 0x100)));
+        // nor PT_GNU_MBIND when the section is not loaded
+        let mbind: PHeader = PHeader { type_id: PT_GNU_MBIND_LO + 1,
+                                       ..segment(PType::NONE, 0x1000, 0x401000, 0x100, 0x100) };
+        assert!(section_in_segment(&text, &mbind));
+        assert!(!section_in_segment(&comment, &mbind));
+    }
+
+    #[test]
+    fn thread_local_sections() {
+        let load: PHeader = segment(PType::LOAD, 0x2ffc, 0x403ffc, 4, 12);
+        let tls: PHeader = segment(PType::TLS, 0x2ffc, 0x403ffc, 4, 8);
+        let relro: PHeader = segment(PType::GNU_RELRO, 0x2ffc, 0x403ffc, 4, 4);
+        let tdata: SHeader = section(SType::PROGBITS, SHF_ALLOC | SHF_TLS, 0x403ffc, 0x2ffc, 4);
+        let tbss: SHeader = section(SType::NOBITS, SHF_ALLOC | SHF_TLS, 0x404000, 0x3000, 4);
+        let bss: SHeader = section(SType::NOBITS, SHF_ALLOC, 0x404000, 0x3000, 8);
+        assert!(section_in_segment(&tdata, &load));
+        assert!(section_in_segment(&tdata, &tls));
+        assert!(section_in_segment(&tdata, &relro));
+        assert!(!section_in_segment(&tbss, &load));
+        assert!(section_in_segment(&tbss, &tls));
+        assert!(!section_in_segment(&tbss, &relro));
+        assert!(section_in_segment(&bss, &load));
+        assert!(!section_in_segment(&bss, &tls));
+    }
+
+    #[test]
+    fn empty_sections() {
+        let dynamic: PHeader = segment(PType::DYNAMIC, 0x2e00, 0x3e00, 0x100, 0x100);
+        let empty = |offset: u64| section(SType::PROGBITS, SHF_ALLOC, offset + 0x1000, offset, 0);
+        // left out at the start and end of PT_DYNAMIC and PT_NOTE only
+        assert!(!section_in_segment(&empty(0x2e00), &dynamic));
+        assert!(section_in_segment(&empty(0x2e80), &dynamic));
+        assert!(!section_in_segment(&empty(0x2f00), &dynamic));
+        assert!(section_in_segment(&empty(0x2e00),
+                                   &segment(PType::LOAD, 0x2e00, 0x3e00, 0x100, 0x100)));
+        // an empty segment holds the empty sections at its offset
+        let nothing: PHeader = segment(PType::LOAD, 0x2e00, 0x3e00, 0, 0);
+        assert!(section_in_segment(&empty(0x2e00), &nothing));
+        assert!(!section_in_segment(&empty(0x2e01), &nothing));
+    }
+}
@@ -0,0 +1,122 @@
+use crate::{Class, Endian};
+use crate::debug_info::Reader;
+use crate::elf::Elf;
+use crate::program_header::phdr_enums::PType;
+use crate::section_header::shdr_enums::SType;
+pub mod note_consts;
+use self::note_consts::*;
+
+// One entry of a SHT_NOTE section or PT_NOTE segment
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Note<'a> {
+    pub name: &'a [u8],
+    pub ntype: u32,
+    pub desc: &'a [u8],
+}
+
+// the name of a note type, which depends on the owner of the note
+pub fn type_name(owner: &[u8], ntype: u32) -> Option<&'static str> {
+    let name: &str = match (owner, ntype) {
+        (b"GNU", 1) => "NT_GNU_ABI_TAG",
+        (b"GNU", 2) => "NT_GNU_HWCAP",
+        (b"GNU", NT_GNU_BUILD_ID) => "NT_GNU_BUILD_ID",
+        (b"GNU", 4) => "NT_GNU_GOLD_VERSION",
+        (b"GNU", NT_GNU_PROPERTY_TYPE_0) => "NT_GNU_PROPERTY_TYPE_0",
+        (b"CORE", NT_PRSTATUS) => "NT_PRSTATUS",
+        (b"CORE", 2) => "NT_PRFPREG",
+        (b"CORE", 3) => "NT_PRPSINFO",
+        (b"CORE", NT_AUXV) => "NT_AUXV",
+        (b"CORE", NT_FILE) => "NT_FILE",
+        (b"CORE", 0x53494749) => "NT_SIGINFO",
+        (b"stapsdt", 3) => "NT_STAPSDT",
+        (b"FDO", 0xcafe1a7e) => "NT_FDO_PACKAGING_METADATA",
+        _ => return None,
+    };
+    Some(name)
+}
+
+fn align_up(value: usize, align: usize) -> usize {
+    (value + align - 1) & !(align - 1)
+}
+
+// parse the notes in a note section or segment, `align` is its alignment
+// (4 for most notes, 8 for .note.gnu.property on 64 bit objects)
+pub fn parse(data: &[u8], endian: Endian,
+             align: u64) -> Result<Vec<Note<'_>>, &'static str> {
+    let align: usize = if align == 8 { 8 } else { 4 };
+    let mut reader: Reader = Reader::new(data, 0, endian);
+    let mut notes: Vec<Note> = Vec::new();
+
+    while reader.offset + 12 <= data.len() {
+        let namesz: u32 = reader.u32()?;
+        let descsz: u32 = reader.u32()?;
+        let ntype: u32 = reader.u32()?;
+
+        let mut name: &[u8] = reader.bytes(namesz as usize)?;
+        // the name is NUL terminated
+        if let Some((0x00, rest)) = name.split_last() {
+            name = rest;
+        }
+        reader.offset = align_up(reader.offset, align);
+        let desc: &[u8] = reader.bytes(descsz as usize)?;
+        reader.offset = align_up(reader.offset, align);
+
+        notes.push(Note {
+            name,
+            ntype,
+            desc,
+        });
+    }
+
+    Ok(notes)
+}
+
+// The notes of an object, from its note sections or, for objects
+// without section headers, its PT_NOTE segments. Blocks that do not
+// parse are skipped.
+pub fn all(elf: &Elf) -> Vec<Note<'_>> {
+    let mut blocks: Vec<(&[u8], u64)> = Vec::new();
+    for sheader in elf.sheaders.iter().filter(|s| s.stype == SType::NOTE) {
+        if let Ok(data) = elf.section_data(sheader) {
+            blocks.push((data, sheader.align));
+        }
+    }
+    if blocks.is_empty() {
+        for pheader in elf.pheaders.iter().filter(|p| p.ptype == PType::NOTE) {
+            if let Ok(data) = elf.segment_data(pheader) {
+                blocks.push((data, pheader.align));
+            }
+        }
+    }
+    blocks.into_iter()
+          .filter_map(|(data, align)| parse(data, elf.header.endian, align).ok())
+          .flatten()
+          .collect()
+}
+
+// One property of a NT_GNU_PROPERTY_TYPE_0 note
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Property<'a> {
+    pub ptype: u32,
+    pub data: &'a [u8],
+}
+
+// the properties in the descriptor of a NT_GNU_PROPERTY_TYPE_0 note,
+// each padded to 8 bytes on 64 bit objects and 4 on 32 bit ones
+pub fn properties(desc: &[u8], class: Class,
+                  endian: Endian) -> Result<Vec<Property<'_>>, &'static str> {
+    let align: usize = if class == Class::X32Bit { 4 } else { 8 };
+    let mut reader: Reader = Reader::new(desc, 0, endian);
+    let mut found: Vec<Property> = Vec::new();
+    while reader.offset + 8 <= desc.len() {
+        let ptype: u32 = reader.u32()?;
+        let size: u32 = reader.u32()?;
+        let data: &[u8] = reader.bytes(size as usize)?;
+        reader.offset = align_up(reader.offset, align).min(desc.len());
+        found.push(Property {
+            ptype,
+            data,
+        });
+    }
+    Ok(found)
+}
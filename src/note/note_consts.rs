@@ -0,0 +1,20 @@
+// note types used by the "CORE" owner in core dumps
+pub const NT_PRSTATUS: u32 = 1;
+pub const NT_AUXV: u32 = 6;
+pub const NT_FILE: u32 = 0x46494c45;
+
+// auxiliary vector entries
+pub const AT_NULL: u64 = 0;
+pub const AT_ENTRY: u64 = 9;
+
+// note types used by the "GNU" owner
+pub const NT_GNU_BUILD_ID: u32 = 3;
+pub const NT_GNU_PROPERTY_TYPE_0: u32 = 5;
+
+// program properties in NT_GNU_PROPERTY_TYPE_0 notes
+pub const GNU_PROPERTY_AARCH64_FEATURE_1_AND: u32 = 0xc0000000;
+pub const GNU_PROPERTY_AARCH64_FEATURE_1_BTI: u32 = 0x1;
+pub const GNU_PROPERTY_AARCH64_FEATURE_1_PAC: u32 = 0x2;
+pub const GNU_PROPERTY_X86_FEATURE_1_AND: u32 = 0xc0000002;
+pub const GNU_PROPERTY_X86_FEATURE_1_IBT: u32 = 0x1;
+pub const GNU_PROPERTY_X86_FEATURE_1_SHSTK: u32 = 0x2;
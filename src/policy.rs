@@ -0,0 +1,348 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use crate::debug_file;
+use crate::dynamic::{self, Dynamic};
+use crate::dynamic::dyn_consts::*;
+use crate::elf::{self, Elf};
+use crate::hardening::{self, Check, Hardening, Relro};
+use crate::program_header::phdr_enums::PType;
+use crate::section_header::shdr_enums::SType;
+use crate::symbol::version::{self, Versions};
+
+// A policy file, the rules every checked file has to follow:
+//
+//     require = ["pie", "nx", "full-relro", "no-rwx", "no-textrel", "no-rpath"]
+//     build-id = true
+//     absent-sections = [".debug_*", ".comment"]
+//
+//     [max-version]
+//     GLIBC = "2.28"
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Policy {
+    // hardening checks, named as for --require
+    pub require: Vec<Check>,
+    // a NT_GNU_BUILD_ID note
+    pub build_id: bool,
+    // the highest version needed from each version namespace, a prefix
+    // like GLIBC or GLIBCXX
+    pub max_version: BTreeMap<String, String>,
+    // sections that must not be present, * and ? match like in a shell
+    pub absent_sections: Vec<String>,
+}
+
+impl Policy {
+    // Read a policy file and check the values serde cannot, so that a
+    // broken policy is refused before any file is checked
+    pub fn load(path: &Path) -> Result<Policy, String> {
+        let text: String = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let policy: Policy = toml::from_str(&text).map_err(|e| e.to_string())?;
+        for (prefix, max) in policy.max_version.iter() {
+            if prefix.is_empty() || version_numbers(max).is_none() {
+                return Err(format!("invalid max-version {prefix} = \"{max}\""));
+            }
+        }
+        if let Some(pattern) = policy.absent_sections.iter().find(|p| p.is_empty()) {
+            return Err(format!("invalid absent-sections pattern \"{pattern}\""));
+        }
+        Ok(policy)
+    }
+
+    // the names of the rules the policy checks
+    pub fn rules(&self) -> Vec<String> {
+        let mut rules: Vec<String> = self.require.iter().map(|c| c.name().to_string()).collect();
+        if self.build_id {
+            rules.push("build-id".to_string());
+        }
+        if !self.max_version.is_empty() {
+            rules.push("max-version".to_string());
+        }
+        if !self.absent_sections.is_empty() {
+            rules.push("absent-sections".to_string());
+        }
+        rules
+    }
+}
+
+// A rule one file breaks, `rule` being a hardening check name, build-id,
+// max-version or absent-sections. `offset` is where in the file the
+// cause is declared: the ELF header, a program or section header entry,
+// a dynamic entry or a section.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Violation {
+    pub rule: String,
+    pub message: String,
+    pub offset: Option<u64>,
+}
+
+// The result of checking one file. Files that cannot be read or parsed
+// have an error and no violations.
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct Report {
+    pub path: String,
+    pub error: Option<String>,
+    pub violations: Vec<Violation>,
+}
+
+impl Report {
+    pub fn passed(&self) -> bool {
+        self.error.is_none() && self.violations.is_empty()
+    }
+}
+
+// the numbers of a version like 2.28, None when it has others
+fn version_numbers(version: &str) -> Option<Vec<u32>> {
+    version.split('.').map(|n| n.parse::<u32>().ok()).collect()
+}
+
+// Match a shell pattern: on a mismatch after a '*', the star takes one
+// more byte and matching resumes behind it. Only the last star matters,
+// earlier ones would only retry what it already covers.
+fn glob_match(pattern: &[u8], name: &[u8]) -> bool {
+    let (mut p, mut n): (usize, usize) = (0, 0);
+    // the position after the last star and the name byte it stopped at
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some(b'*') => {
+                star = Some((p + 1, n));
+                p += 1;
+            },
+            Some(c) if *c == b'?' || *c == name[n] => {
+                p += 1;
+                n += 1;
+            },
+            _ => match star {
+                Some((after, from)) => {
+                    p = after;
+                    n = from + 1;
+                    star = Some((after, from + 1));
+                },
+                None => return false,
+            },
+        }
+    }
+    pattern[p.min(pattern.len())..].iter().all(|c| *c == b'*')
+}
+
+// the maximum of `policy` a needed version like GLIBC_2.34 exceeds.
+// Versions without a number, like GLIBC_PRIVATE, exceed none.
+fn exceeded<'a>(policy: &'a Policy, version: &str) -> Option<&'a str> {
+    let (prefix, number) = version.rsplit_once('_')?;
+    let max: &str = policy.max_version.get(prefix)?;
+    match (version_numbers(number), version_numbers(max)) {
+        (Some(number), Some(limit)) if number > limit => Some(max),
+        _ => None,
+    }
+}
+
+// file offset of a program header entry
+fn pheader_offset(elf: &Elf, idx: usize) -> u64 {
+    elf.header.phdr_offset + idx as u64 * elf.header.phdr_entry_sz as u64
+}
+
+// file offset of a section header entry
+fn sheader_offset(elf: &Elf, idx: usize) -> u64 {
+    elf.header.shdr_offset + idx as u64 * elf.header.shdr_entry_sz as u64
+}
+
+// where the cause of a failed check is declared, if in one place
+fn locate(check: Check, elf: &Elf, audit: &Hardening, dynamic: &Dynamic) -> Option<u64> {
+    let segment = |ptype: PType| -> Option<u64> {
+        elf.pheaders.iter().position(|p| p.ptype == ptype).map(|idx| pheader_offset(elf, idx))
+    };
+    match check {
+        // e_type
+        Check::Pie => Some(0x10),
+        Check::Nx => segment(PType::GNU_STACK),
+        Check::PartialRelro | Check::FullRelro => segment(PType::GNU_RELRO),
+        Check::NoRpath => dynamic.entry_offset(DT_RPATH),
+        Check::NoRunpath => dynamic.entry_offset(DT_RUNPATH),
+        Check::NoTextrel => dynamic.entry_offset(DT_TEXTREL)
+                                   .or_else(|| dynamic.entry_offset(DT_FLAGS)),
+        Check::Cet => elf.section(".note.gnu.property").map(|s| s.offset),
+        Check::NoRwx => audit.rwx_segments.first().map(|idx| pheader_offset(elf, *idx)),
+        Check::StackProtector | Check::Fortify => None,
+    }
+}
+
+fn describe(check: Check, audit: &Hardening) -> String {
+    let relro: &str = match audit.relro {
+        Relro::Full => "full",
+        Relro::Partial => "partial",
+        Relro::None => "none",
+    };
+    match check {
+        Check::Pie => "not position independent".to_string(),
+        Check::Nx => "the stack is executable".to_string(),
+        Check::PartialRelro | Check::FullRelro => format!("RELRO is {relro}"),
+        Check::StackProtector => "no stack protector".to_string(),
+        Check::Fortify => "no fortified function is called".to_string(),
+        Check::NoRpath => format!("DT_RPATH is {0}", audit.rpath.as_deref().unwrap_or("")),
+        Check::NoRunpath => format!("DT_RUNPATH is {0}", audit.runpath.as_deref().unwrap_or("")),
+        Check::NoTextrel => "has text relocations".to_string(),
+        Check::Cet => "not built for CET or branch protection".to_string(),
+        Check::NoRwx => {
+            let indexes: Vec<String> = audit.rwx_segments.iter().map(|i| i.to_string()).collect();
+            format!("writable and executable segments {0}", indexes.join(", "))
+        },
+    }
+}
+
+// Check one parsed object against the policy
+pub fn check(elf: &Elf, policy: &Policy) -> Result<Vec<Violation>, &'static str> {
+    let mut violations: Vec<Violation> = Vec::new();
+
+    if !policy.require.is_empty() {
+        let audit: Hardening = hardening::audit(elf)?;
+        let dynamic: Dynamic = dynamic::parse(elf)?.unwrap_or_default();
+        for check in hardening::failures(&audit, &policy.require) {
+            violations.push(Violation {
+                rule: check.name().to_string(),
+                message: describe(check, &audit),
+                offset: locate(check, elf, &audit, &dynamic),
+            });
+        }
+    }
+
+    if policy.build_id && debug_file::build_id(elf).is_none() {
+        violations.push(Violation {
+            rule: "build-id".to_string(),
+            message: "no build id note".to_string(),
+            offset: None,
+        });
+    }
+
+    if !policy.max_version.is_empty() {
+        let versions: Versions = version::parse(elf)?;
+        let verneed: Option<u64> = elf.sheaders.iter()
+                                      .find(|s| s.stype == SType::GNU_VERNEED)
+                                      .map(|s| s.offset);
+        for need in versions.needs.iter() {
+            for aux in need.versions.iter() {
+                if let Some(max) = exceeded(policy, &aux.name) {
+                    let prefix: &str = aux.name.rsplit_once('_').map_or("", |(p, _)| p);
+                    violations.push(Violation {
+                        rule: "max-version".to_string(),
+                        message: format!("needs {0} from {1}, newer than {prefix}_{max}",
+                                         aux.name, need.file),
+                        offset: verneed,
+                    });
+                }
+            }
+        }
+    }
+
+    for (idx, sheader) in elf.sheaders.iter().enumerate() {
+        let pattern: Option<&String> = policy.absent_sections.iter()
+            .find(|p| glob_match(p.as_bytes(), sheader.sname.as_bytes()));
+        if let Some(pattern) = pattern {
+            violations.push(Violation {
+                rule: "absent-sections".to_string(),
+                message: format!("has section {0} matching {pattern}", sheader.sname),
+                offset: Some(sheader_offset(elf, idx)),
+            });
+        }
+    }
+    Ok(violations)
+}
+
+// Read, parse and check the file at `path`
+pub fn evaluate(path: &Path, policy: &Policy) -> Report {
+    let mut report: Report = Report {
+        path: path.display().to_string(),
+        ..Default::default()
+    };
+    let content: Vec<u8> = match fs::read(path) {
+        Ok(content) => content,
+        Err(e) => {
+            report.error = Some(e.to_string());
+            return report;
+        },
+    };
+    match elf::parse(content).and_then(|elf| check(&elf, policy)) {
+        Ok(violations) => report.violations = violations,
+        Err(e) => report.error = Some(e.to_string()),
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+    use super::*;
+
+    fn load(text: &str) -> Result<Policy, String> {
+        let dir: tempfile::TempDir = tempfile::tempdir().unwrap();
+        let path: std::path::PathBuf = dir.path().join("policy.toml");
+        fs::write(&path, text).unwrap();
+        Policy::load(&path)
+    }
+
+    #[test]
+    fn rules() {
+        let policy: Policy = load("require = [\"pie\", \"full-relro\"]\n\
+                                   build-id = true\n\
+                                   absent-sections = [\".debug_*\"]\n\
+                                   [max-version]\n\
+                                   GLIBC = \"2.28\"\n").unwrap();
+        assert_eq!(policy.require, [Check::Pie, Check::FullRelro]);
+        assert_eq!(policy.rules(), ["pie", "full-relro", "build-id", "max-version",
+                                    "absent-sections"]);
+        assert_eq!(load("").unwrap(), Policy::default());
+        assert!(load("").unwrap().rules().is_empty());
+
+        // refused when loaded rather than when a file is checked
+        assert!(load("require = [\"canary\"]").is_err());
+        assert!(load("build_id = true").is_err());
+        assert!(load("[max-version]\nGLIBC = 2.28").is_err());
+        assert_eq!(load("[max-version]\nGLIBC = \"2.x\""),
+                   Err("invalid max-version GLIBC = \"2.x\"".to_string()));
+        assert!(load("absent-sections = [\"\"]").is_err());
+        assert!(Policy::load(Path::new("/nonexistent/policy.toml")).is_err());
+    }
+
+    #[test]
+    fn glob() {
+        let matches = |pattern: &str, name: &str| glob_match(pattern.as_bytes(), name.as_bytes());
+        assert!(matches(".debug_*", ".debug_info"));
+        assert!(matches(".debug_*", ".debug_"));
+        assert!(!matches(".debug_*", ".zdebug_info"));
+        assert!(matches("*", ""));
+        assert!(matches("", ""));
+        assert!(!matches("", ".text"));
+        assert!(matches(".rela.?ebug*", ".rela.debug_line"));
+        assert!(!matches("?", ""));
+        assert!(matches("*.*.*", ".rela.dyn"));
+        assert!(matches("*info", ".debug_info.dwo.info"));
+        assert!(!matches("*info", ".debug_info.dwo"));
+        assert!(matches(".comment", ".comment"));
+        assert!(!matches(".comment", ".comments"));
+
+        // many stars against a long name that almost matches stay linear
+        let start: Instant = Instant::now();
+        assert!(!matches(&"*a".repeat(30), &format!("{0}b", "a".repeat(29))));
+        assert!(matches(&"*a".repeat(30), &"a".repeat(10000)));
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn max_version() {
+        let policy: Policy = Policy {
+            max_version: BTreeMap::from([("GLIBC".to_string(), "2.28".to_string()),
+                                         ("GLIBCXX".to_string(), "3.4.25".to_string())]),
+            ..Default::default()
+        };
+        assert_eq!(exceeded(&policy, "GLIBC_2.34"), Some("2.28"));
+        assert_eq!(exceeded(&policy, "GLIBC_2.28"), None);
+        // numbers compare one by one, not as text
+        assert_eq!(exceeded(&policy, "GLIBC_2.3"), None);
+        assert_eq!(exceeded(&policy, "GLIBC_2.28.1"), Some("2.28"));
+        assert_eq!(exceeded(&policy, "GLIBCXX_3.4.30"), Some("3.4.25"));
+        assert_eq!(exceeded(&policy, "GLIBC_PRIVATE"), None);
+        assert_eq!(exceeded(&policy, "CXXABI_1.3.13"), None);
+        assert_eq!(exceeded(&policy, "GLIBC"), None);
+    }
+}
@@ -3,34 +3,42 @@ use crate::{BigEndian, LittleEndian, ReadBytesExt};
 pub mod phdr_enums;
 use self::phdr_enums::{PType, PFlags};
 
-#[derive(Debug, Default)]
-struct PHeader {
-    ptype: PType,
-    pflags: PFlags,
-    offset: u64,
-    vaddr: u64,
-    paddr: u64,
-    filesz: u64,
-    memsz: u64,
-    align: u64,
+#[derive(Debug, Default, Clone)]
+pub struct PHeader {
+    pub ptype: PType,
+    // p_type as stored
+    pub type_id: u32,
+    pub pflags: PFlags,
+    // p_flags as stored, PF_X 1, PF_W 2 and PF_R 4
+    pub flags: u32,
+    pub offset: u64,
+    pub vaddr: u64,
+    pub paddr: u64,
+    pub filesz: u64,
+    pub memsz: u64,
+    pub align: u64,
 }
 
-pub fn parse(content: &Vec<u8>,
+pub fn parse(content: &[u8],
              phdr_offset: u64,
              class: Class,
              endian: Endian
-             ) -> Result<(), &'static str> {
+             ) -> Result<PHeader, &'static str> {
     
     // cursor
     let mut cursor: usize = phdr_offset as usize;
-    let width: usize;
-    let mut buff: &[u8];
-
-    if class == Class::X32Bit {
-        width = 0x04;
+    let width: usize = if class == Class::X32Bit {
+        0x04
     }
     else {
-        width = 0x08;
+        0x08
+    };
+    let mut buff: &[u8];
+
+    let entry_sz: usize = if class == Class::X32Bit { 0x20 } else { 0x38 };
+    let end: usize = cursor.checked_add(entry_sz).ok_or("Program header out of bounds.")?;
+    if end > content.len() {
+        return Err("Program header out of bounds.");
     }
 
     let mut p_header: PHeader = PHeader {
@@ -65,6 +73,7 @@ pub fn parse(content: &Vec<u8>,
        0x6474e554 => PType::GNU_SFRAME,
        _ => PType::NONE,
     };
+    p_header.type_id = ptype;
     cursor += 0x04;
 
     // Flags
@@ -78,6 +87,7 @@ pub fn parse(content: &Vec<u8>,
             _ => return Err("Undefined Endianess.")
         };
 
+        p_header.flags = pflags;
         p_header.pflags = match pflags {
             0x01 => PFlags::X,
             0x02 => PFlags::W,
@@ -92,51 +102,111 @@ pub fn parse(content: &Vec<u8>,
 
     // offset
     buff = &content[cursor..(cursor + width)];
-    p_header.offset = match endian {
-        Endian::Little => buff.read_u32::<LittleEndian>().unwrap()
-            as u64,
-        Endian::Big => buff.read_u32::<BigEndian>().unwrap() as u64,
-        _ => return Err("Undefined Endianess.")
+    p_header.offset = match class {
+        Class::X64Bit => {
+            match endian {
+                Endian::Little => buff.read_u64::<LittleEndian>().unwrap(),
+                Endian::Big => buff.read_u64::<BigEndian>().unwrap(),
+                _ => return Err("Undefined Endianess."),
+            }
+        },
+        Class::X32Bit => {
+            match endian {
+                Endian::Little => buff.read_u32::<LittleEndian>().unwrap()
+                as u64,
+                Endian::Big => buff.read_u32::<BigEndian>().unwrap() as u64,
+                _ => return Err("Undefined Endianess."),
+            }
+        },
+        _ => return Err("Unknown binary type."),
     };
     cursor += width;
 
     // vaddr
     buff = &content[cursor..(cursor + width)];
-    p_header.vaddr = match endian {
-        Endian::Little => buff.read_u32::<LittleEndian>().unwrap()
-            as u64,
-        Endian::Big => buff.read_u32::<BigEndian>().unwrap() as u64,
-        _ => return Err("Undefined endianess.")
+    p_header.vaddr = match class {
+        Class::X64Bit => {
+            match endian {
+                Endian::Little => buff.read_u64::<LittleEndian>().unwrap(),
+                Endian::Big => buff.read_u64::<BigEndian>().unwrap(),
+                _ => return Err("Undefined Endianess."),
+            }
+        },
+        Class::X32Bit => {
+            match endian {
+                Endian::Little => buff.read_u32::<LittleEndian>().unwrap()
+                as u64,
+                Endian::Big => buff.read_u32::<BigEndian>().unwrap() as u64,
+                _ => return Err("Undefined Endianess."),
+            }
+        },
+        _ => return Err("Unknown binary type."),
     };
     cursor += width;
 
-   // paddr
+    // paddr
     buff = &content[cursor..(cursor + width)];
-    p_header.paddr = match endian {
-        Endian::Little => buff.read_u32::<LittleEndian>().unwrap()
-            as u64,
-        Endian::Big => buff.read_u32::<BigEndian>().unwrap() as u64,
-        _ => return Err("Undefined endianess.")
+    p_header.paddr = match class {
+        Class::X64Bit => {
+            match endian {
+                Endian::Little => buff.read_u64::<LittleEndian>().unwrap(),
+                Endian::Big => buff.read_u64::<BigEndian>().unwrap(),
+                _ => return Err("Undefined Endianess."),
+            }
+        },
+        Class::X32Bit => {
+            match endian {
+                Endian::Little => buff.read_u32::<LittleEndian>().unwrap()
+                as u64,
+                Endian::Big => buff.read_u32::<BigEndian>().unwrap() as u64,
+                _ => return Err("Undefined Endianess."),
+            }
+        },
+        _ => return Err("Unknown binary type."),
     };
     cursor += width;
 
     // filesz
     buff = &content[cursor..(cursor + width)];
-    p_header.filesz = match endian {
-        Endian::Little => buff.read_u32::<LittleEndian>().unwrap()
-            as u64,
-        Endian::Big => buff.read_u32::<BigEndian>().unwrap() as u64,
-        _ => return Err("TBD")
+    p_header.filesz = match class {
+        Class::X64Bit => {
+            match endian {
+                Endian::Little => buff.read_u64::<LittleEndian>().unwrap(),
+                Endian::Big => buff.read_u64::<BigEndian>().unwrap(),
+                _ => return Err("Undefined Endianess."),
+            }
+        },
+        Class::X32Bit => {
+            match endian {
+                Endian::Little => buff.read_u32::<LittleEndian>().unwrap()
+                as u64,
+                Endian::Big => buff.read_u32::<BigEndian>().unwrap() as u64,
+                _ => return Err("Undefined Endianess."),
+            }
+        },
+        _ => return Err("Unknown binary type."),
     };
     cursor += width;
 
     // memsz
     buff = &content[cursor..(cursor + width)];
-    p_header.memsz = match endian {
-        Endian::Little => buff.read_u32::<LittleEndian>().unwrap() as
-            u64,
-        Endian::Big => buff.read_u32::<BigEndian>().unwrap() as u64,
-        _ => return Err("TBD")
+    p_header.memsz = match class {
+        Class::X64Bit => {
+            match endian {
+                Endian::Little => buff.read_u64::<LittleEndian>().unwrap(),
+                Endian::Big => buff.read_u64::<BigEndian>().unwrap(),
+                _ => return Err("Undefined Endianess."),
+            }
+        },
+        Class::X32Bit => {
+            match endian {
+                Endian::Little => buff.read_u32::<LittleEndian>().unwrap()
+                as u64,
+                Endian::Big => buff.read_u32::<BigEndian>().unwrap() as u64,
+                _ => return Err("Undefined Endianess."),
+            }
+        },
+        _ => return Err("Unknown binary type."),
     };
     cursor += width;
 
@@ -151,6 +221,7 @@ pub fn parse(content: &Vec<u8>,
             _ => return Err("TBD")
         };
 
+        p_header.flags = pflags;
         p_header.pflags = match pflags {
             0x01 => PFlags::X,
             0x02 => PFlags::W,
@@ -165,15 +236,25 @@ pub fn parse(content: &Vec<u8>,
 
     // align
     buff = &content[cursor..(cursor + width)];
-    p_header.align = match endian {
-        Endian::Little => buff.read_u32::<LittleEndian>().unwrap()
-            as u64,
-        Endian::Big => buff.read_u32::<BigEndian>().unwrap() as u64,
-        _ => return Err("TBD")
+    p_header.align = match class {
+        Class::X64Bit => {
+            match endian {
+                Endian::Little => buff.read_u64::<LittleEndian>().unwrap(),
+                Endian::Big => buff.read_u64::<BigEndian>().unwrap(),
+                _ => return Err("Undefined Endianess."),
+            }
+        },
+        Class::X32Bit => {
+            match endian {
+                Endian::Little => buff.read_u32::<LittleEndian>().unwrap()
+                as u64,
+                Endian::Big => buff.read_u32::<BigEndian>().unwrap() as u64,
+                _ => return Err("Undefined Endianess."),
+            }
+        },
+        _ => return Err("Unknown binary type."),
     };
-    cursor += width;
 
     // end of program header
-    dbg!(p_header);
-    Ok(())
+    Ok(p_header)
 }
@@ -1,4 +1,7 @@
-#[derive(Default, Debug)]
+// Variant names mirror the constants from the ELF specification.
+#![allow(non_camel_case_types)]
+
+#[derive(Default, Debug, PartialEq, Copy, Clone)]
 pub enum PType {
     #[default]
     NONE,
@@ -21,7 +24,7 @@ pub enum PType {
     GNU_SFRAME,
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, PartialEq, Copy, Clone)]
 pub enum PFlags {
     #[default]
     NONE,
@@ -33,3 +36,8 @@ pub enum PFlags {
     RWX,
 }
 
+
+// p_flags bits
+pub const PF_X: u32 = 0x1;
+pub const PF_W: u32 = 0x2;
+pub const PF_R: u32 = 0x4;
@@ -0,0 +1,545 @@
+use crate::Class;
+use crate::dynamic;
+use crate::dynamic::dyn_consts::DF_1_PIE;
+use crate::elf::Elf;
+use crate::elf_header::header_enums::{Endian, Machine};
+use crate::mapping;
+use crate::program_header::phdr_enums::{PF_R, PF_W, PF_X};
+use crate::section_header::SHeader;
+use crate::section_header::shdr_enums::SType;
+
+// The ELF header, section headers and program headers laid out the way
+// GNU readelf -h, -S and -l print them, so that scripts reading readelf
+// output can read ours. The flag names readelf appends to e_flags for
+// some machines are left out, the number is the same.
+
+// %#x of printf, 0 has no prefix
+fn alt_hex(value: u64) -> String {
+    match value {
+        0 => "0".to_string(),
+        value => format!("{value:#x}"),
+    }
+}
+
+fn osabi_name(abi: u8) -> String {
+    let name: &str = match abi {
+        0 => "UNIX - System V",
+        1 => "UNIX - HP-UX",
+        2 => "UNIX - NetBSD",
+        3 => "UNIX - GNU",
+        6 => "UNIX - Solaris",
+        7 => "UNIX - AIX",
+        8 => "UNIX - IRIX",
+        9 => "UNIX - FreeBSD",
+        10 => "UNIX - TRU64",
+        11 => "Novell - Modesto",
+        12 => "UNIX - OpenBSD",
+        13 => "VMS - OpenVMS",
+        14 => "HP - Non-Stop Kernel",
+        15 => "AROS",
+        16 => "FenixOS",
+        17 => "Nuxi CloudABI",
+        18 => "Stratus Technologies OpenVOS",
+        _ => return format!("<unknown: {abi:x}>"),
+    };
+    name.to_string()
+}
+
+fn file_type(elf: &Elf) -> String {
+    let name: &str = match elf.header.type_id {
+        0 => "NONE (None)",
+        1 => "REL (Relocatable file)",
+        2 => "EXEC (Executable file)",
+        3 => {
+            let flags_1: u64 = dynamic::parse(elf).ok().flatten().map(|d| d.flags_1)
+                                                  .unwrap_or(0);
+            match flags_1 & DF_1_PIE != 0 {
+                true => "DYN (Position-Independent Executable file)",
+                false => "DYN (Shared object file)",
+            }
+        },
+        4 => "CORE (Core file)",
+        etype if etype >= 0xff00 => return format!("Processor Specific: ({etype:x})"),
+        etype if etype >= 0xfe00 => return format!("OS Specific: ({etype:x})"),
+        etype => return format!("<unknown>: {etype:x}"),
+    };
+    name.to_string()
+}
+
+fn machine_name(machine: u16) -> String {
+    let name: &str = match machine {
+        0 => "None",
+        2 => "Sparc",
+        3 => "Intel 80386",
+        8 => "MIPS R3000",
+        20 => "PowerPC",
+        21 => "PowerPC64",
+        22 => "IBM S/390",
+        40 => "ARM",
+        43 => "Sparc v9",
+        50 => "Intel IA-64",
+        62 => "Advanced Micro Devices X86-64",
+        183 => "AArch64",
+        243 => "RISC-V",
+        258 => "LoongArch",
+        _ => return format!("<unknown>: {machine:#x}"),
+    };
+    name.to_string()
+}
+
+fn segment_type(elf: &Elf, ptype: u32) -> String {
+    let name: &str = match (elf.header.machine, ptype) {
+        (_, 0) => "NULL",
+        (_, 1) => "LOAD",
+        (_, 2) => "DYNAMIC",
+        (_, 3) => "INTERP",
+        (_, 4) => "NOTE",
+        (_, 5) => "SHLIB",
+        (_, 6) => "PHDR",
+        (_, 7) => "TLS",
+        (_, 0x6474e550) => "GNU_EH_FRAME",
+        (_, 0x6474e551) => "GNU_STACK",
+        (_, 0x6474e552) => "GNU_RELRO",
+        (_, 0x6474e553) => "GNU_PROPERTY",
+        (_, 0x6474e554) => "GNU_SFRAME",
+        (Machine::ARM, 0x70000001) => "EXIDX",
+        (Machine::AARCH64, 0x70000002) => "AARCH64_MEMTAG_MTE",
+        (Machine::RISCV, 0x70000003) => "RISCV_ATTRIBUTE",
+        (_, 0x70000000..=0x7fffffff) => return format!("LOPROC+{0}", alt_hex(ptype as u64 - 0x70000000)),
+        (_, 0x60000000..=0x6fffffff) => return format!("LOOS+{0}", alt_hex(ptype as u64 - 0x60000000)),
+        _ => return format!("<unknown>: {ptype:x}"),
+    };
+    name.to_string()
+}
+
+fn section_type(elf: &Elf, stype: u32) -> String {
+    let name: &str = match (elf.header.machine, stype) {
+        (_, 0) => "NULL",
+        (_, 1) => "PROGBITS",
+        (_, 2) => "SYMTAB",
+        (_, 3) => "STRTAB",
+        (_, 4) => "RELA",
+        (_, 5) => "HASH",
+        (_, 6) => "DYNAMIC",
+        (_, 7) => "NOTE",
+        (_, 8) => "NOBITS",
+        (_, 9) => "REL",
+        (_, 10) => "SHLIB",
+        (_, 11) => "DYNSYM",
+        (_, 14) => "INIT_ARRAY",
+        (_, 15) => "FINI_ARRAY",
+        (_, 16) => "PREINIT_ARRAY",
+        (_, 17) => "GROUP",
+        (_, 18) => "SYMTAB SECTION INDICES",
+        (_, 19) => "RELR",
+        (_, 0x6ffffff4) => "GNU_SFRAME",
+        (_, 0x6ffffff5) => "GNU_ATTRIBUTES",
+        (_, 0x6ffffff6) => "GNU_HASH",
+        (_, 0x6ffffff7) => "GNU_LIBLIST",
+        (_, 0x6ffffffd) => "VERDEF",
+        (_, 0x6ffffffe) => "VERNEED",
+        (_, 0x6fffffff) => "VERSYM",
+        (Machine::AMD64, 0x70000001) => "X86_64_UNWIND",
+        (Machine::ARM, 0x70000001) => "ARM_EXIDX",
+        (Machine::ARM, 0x70000002) => "ARM_PREEMPTMAP",
+        (Machine::ARM, 0x70000003) => "ARM_ATTRIBUTES",
+        (Machine::AARCH64, 0x70000003) => "AARCH64_ATTRIBUTES",
+        (Machine::RISCV, 0x70000003) => "RISCV_ATTRIBUTES",
+        (_, 0x70000000..=0x7fffffff) => return format!("LOPROC+{0}", alt_hex(stype as u64 - 0x70000000)),
+        (_, 0x60000000..=0x6fffffff) => return format!("LOOS+{0}", alt_hex(stype as u64 - 0x60000000)),
+        (_, 0x80000000..=0xffffffff) => return format!("LOUSER+{0}", alt_hex(stype as u64 - 0x80000000)),
+        _ => return format!("{stype:08x}: <unknown>"),
+    };
+    name.to_string()
+}
+
+// SHF_GNU_MBIND is only known under these OS/ABIs, SHF_GNU_RETAIN under
+// the last two
+fn gnu_abi(elf: &Elf) -> bool {
+    matches!(elf.header.abi_id, 0 | 3 | 9)
+}
+
+fn gnu_retain(elf: &Elf) -> bool {
+    matches!(elf.header.abi_id, 3 | 9)
+}
+
+// the one letter flag keys, the OS and processor specific ranges give
+// one letter however many of their bits are set
+fn section_flags(elf: &Elf, flags: u64) -> String {
+    const MASKOS: u64 = 0x0ff00000;
+    const MASKPROC: u64 = 0xf0000000;
+    let mut keys: String = String::new();
+    let mut unknown: bool = false;
+    let mut rest: u64 = flags;
+    while rest != 0 {
+        let flag: u64 = rest & rest.wrapping_neg();
+        rest &= !flag;
+        let key: Option<char> = match flag {
+            0x1 => Some('W'),
+            0x2 => Some('A'),
+            0x4 => Some('X'),
+            0x10 => Some('M'),
+            0x20 => Some('S'),
+            0x40 => Some('I'),
+            0x80 => Some('L'),
+            0x100 => Some('O'),
+            0x200 => Some('G'),
+            0x400 => Some('T'),
+            0x800 => Some('C'),
+            0x8000_0000 => Some('E'),
+            0x20_0000 if gnu_retain(elf) => Some('R'),
+            0x100_0000 if gnu_abi(elf) => Some('D'),
+            0x1000_0000 if elf.header.machine == Machine::AMD64 => Some('l'),
+            0x2000_0000 if elf.header.machine == Machine::ARM => Some('y'),
+            _ => None,
+        };
+        match key {
+            Some(key) => keys.push(key),
+            None if flag & MASKOS != 0 => {
+                keys.push('o');
+                rest &= !MASKOS;
+            },
+            None if flag & MASKPROC != 0 => {
+                keys.push('p');
+                rest &= !MASKPROC;
+            },
+            None => unknown = true,
+        }
+    }
+    if unknown {
+        keys.push('x');
+    }
+    keys
+}
+
+// readelf shows control characters as ^X
+fn printable(name: &str) -> String {
+    let mut printable: String = String::new();
+    for c in name.chars() {
+        match c {
+            c if (c as u32) < 0x20 => {
+                printable.push('^');
+                printable.push(((c as u8) + 0x40) as char);
+            },
+            '\x7f' => printable.push_str("^?"),
+            c => printable.push(c),
+        }
+    }
+    printable
+}
+
+// names longer than the column are cut, ending with [...]
+fn section_name(name: &str) -> String {
+    let name: String = printable(name);
+    match name.chars().count() > 17 {
+        true => format!("{0}[...]", name.chars().take(12).collect::<String>()),
+        false => name,
+    }
+}
+
+// readelf -h
+pub fn file_header(elf: &Elf) -> String {
+    let header = &elf.header;
+    let magic: String = elf.content.iter().take(16).map(|b| format!("{b:02x} ")).collect();
+    let class: &str = match header.class {
+        Class::X32Bit => "ELF32",
+        Class::X64Bit => "ELF64",
+        Class::NONE => "none",
+    };
+    let data: &str = match header.endian {
+        Endian::Little => "2's complement, little endian",
+        Endian::Big => "2's complement, big endian",
+        Endian::NONE => "none",
+    };
+    let version: &str = match header.version {
+        0 => "",
+        1 => " (current)",
+        _ => " <unknown>",
+    };
+
+    let mut text: String = String::from("ELF Header:\n");
+    text.push_str(&format!("  Magic:   {magic}\n"));
+    text.push_str(&format!("  Class:                             {class}\n"));
+    text.push_str(&format!("  Data:                              {data}\n"));
+    text.push_str(&format!("  Version:                           {0}{version}\n", header.version));
+    text.push_str(&format!("  OS/ABI:                            {0}\n", osabi_name(header.abi_id)));
+    text.push_str(&format!("  ABI Version:                       {0}\n", header.abi_version));
+    text.push_str(&format!("  Type:                              {0}\n", file_type(elf)));
+    text.push_str(&format!("  Machine:                           {0}\n",
+                           machine_name(header.machine_id)));
+    text.push_str(&format!("  Version:                           {0:#x}\n", header.misc_version));
+    text.push_str(&format!("  Entry point address:               {0:#x}\n", header.entry_point));
+    text.push_str(&format!("  Start of program headers:          {0} (bytes into file)\n",
+                           header.phdr_offset));
+    text.push_str(&format!("  Start of section headers:          {0} (bytes into file)\n",
+                           header.shdr_offset));
+    text.push_str(&format!("  Flags:                             {0:#x}\n", header.flags));
+    text.push_str(&format!("  Size of this header:               {0} (bytes)\n", header.hdr_sz));
+    text.push_str(&format!("  Size of program headers:           {0} (bytes)\n",
+                           header.phdr_entry_sz));
+    text.push_str(&format!("  Number of program headers:         {0}\n", header.phdr_entries));
+    text.push_str(&format!("  Size of section headers:           {0} (bytes)\n",
+                           header.shdr_entry_sz));
+    text.push_str(&format!("  Number of section headers:         {0}\n", header.shdr_entries));
+    text.push_str(&format!("  Section header string table index: {0}\n", header.shstr_idx));
+    text
+}
+
+// readelf -S, `with_header` when the ELF header is printed before it and
+// the line saying where the headers are is left out
+pub fn section_headers(elf: &Elf, with_header: bool) -> String {
+    let mut text: String = String::new();
+    let count: usize = elf.sheaders.len();
+    if count == 0 {
+        text.push_str("\nThere are no sections in this file.\n");
+        return text;
+    }
+    if !with_header {
+        let there: String = match count {
+            1 => "There is 1 section header".to_string(),
+            _ => format!("There are {count} section headers"),
+        };
+        text.push_str(&format!("{there}, starting at offset {0:#x}:\n", elf.header.shdr_offset));
+    }
+    text.push_str(match count {
+        1 => "\nSection Header:\n",
+        _ => "\nSection Headers:\n",
+    });
+
+    let is_64: bool = elf.header.class != Class::X32Bit;
+    match is_64 {
+        true => text.push_str("  [Nr] Name              Type             Address           Offset\n       \
+                               Size              EntSize          Flags  Link  Info  Align\n"),
+        false => text.push_str("  [Nr] Name              Type            Addr     Off    Size   ES \
+                                Flg Lk Inf Al\n"),
+    }
+    for (idx, s) in elf.sheaders.iter().enumerate() {
+        let name: String = section_name(&s.sname);
+        let stype: String = section_type(elf, s.type_id);
+        let flags: String = section_flags(elf, s.flags);
+        match is_64 {
+            true => text.push_str(&format!(
+                "  [{idx:2}] {name:<17} {stype:<15.15}  {0:016x}  {1:08x}\n       \
+                 {2:016x}  {3:016x} {flags:>3}      {4:2}   {5:3}     {6}\n",
+                s.vaddr, s.offset, s.size, s.entsize, s.link, s.info, s.align)),
+            false => text.push_str(&format!(
+                "  [{idx:2}] {name:<17} {stype:<15.15} {0:08x} {1:06x} {2:06x} {3:02x} {flags:>3} \
+                 {4:2} {5:3} {6:2}\n",
+                s.vaddr, s.offset, s.size, s.entsize, s.link, s.info, s.align)),
+        }
+    }
+
+    text.push_str("Key to Flags:\n  \
+                   W (write), A (alloc), X (execute), M (merge), S (strings), I (info),\n  \
+                   L (link order), O (extra OS processing required), G (group), T (TLS),\n  \
+                   C (compressed), x (unknown), o (OS specific), E (exclude),\n  ");
+    if gnu_retain(elf) {
+        text.push_str("R (retain), ");
+    }
+    if gnu_abi(elf) {
+        text.push_str("D (mbind), ");
+    }
+    match elf.header.machine {
+        Machine::AMD64 => text.push_str("l (large), "),
+        Machine::ARM => text.push_str("y (purecode), "),
+        Machine::PPC | Machine::PPC64 => text.push_str("v (VLE), "),
+        _ => (),
+    }
+    text.push_str("p (processor specific)\n");
+    text
+}
+
+// readelf -l with the section to segment mapping, `with_header` as for
+// section_headers
+pub fn program_headers(elf: &Elf, with_header: bool) -> String {
+    let mut text: String = String::new();
+    if elf.pheaders.is_empty() {
+        text.push_str("\nThere are no program headers in this file.\n");
+        return text;
+    }
+    if !with_header {
+        text.push_str(&format!("\nElf file type is {0}\nEntry point {1:#x}\n\
+                                There are {2} program headers, starting at offset {3}\n",
+                               file_type(elf), elf.header.entry_point, elf.pheaders.len(),
+                               elf.header.phdr_offset));
+    }
+
+    let is_64: bool = elf.header.class != Class::X32Bit;
+    match is_64 {
+        true => {
+            text.push_str("\nProgram Headers:\n");
+            text.push_str("  Type           Offset             VirtAddr           PhysAddr\n");
+            text.push_str("                 FileSiz            MemSiz              Flags  Align\n");
+        },
+        false => text.push_str("\nProgram Headers:\n  \
+                                Type           Offset   VirtAddr   PhysAddr   FileSiz MemSiz  Flg \
+                                Align\n"),
+    }
+    for p in elf.pheaders.iter() {
+        let ptype: String = segment_type(elf, p.type_id);
+        let flags: String = [(PF_R, 'R'), (PF_W, 'W'), (PF_X, 'E')].iter()
+            .map(|(bit, key)| if p.flags & bit != 0 { *key } else { ' ' })
+            .collect();
+        match is_64 {
+            true => text.push_str(&format!(
+                "  {ptype:<14.14} 0x{0:016x} 0x{1:016x} 0x{2:016x}\n                 \
+                 0x{3:016x} 0x{4:016x}  {flags}    {5:#x}\n",
+                p.offset, p.vaddr, p.paddr, p.filesz, p.memsz, p.align)),
+            false => text.push_str(&format!(
+                "  {ptype:<14.14} 0x{0:06x} 0x{1:08x} 0x{2:08x} 0x{3:05x} 0x{4:05x} {flags} {5}\n",
+                p.offset, p.vaddr, p.paddr, p.filesz, p.memsz, alt_hex(p.align))),
+        }
+        // readelf gives up on an empty PT_INTERP, as in separate debug files
+        if p.type_id == 3 && p.filesz != 0 {
+            if let Ok(data) = elf.segment_data(p) {
+                let name: &[u8] = data.split(|b| *b == 0).next().unwrap_or_default();
+                text.push_str(&format!("      [Requesting program interpreter: {0}]\n",
+                                       String::from_utf8_lossy(name)));
+            }
+        }
+    }
+
+    if elf.sheaders.is_empty() {
+        return text;
+    }
+    text.push_str("\n Section to Segment mapping:\n  Segment Sections...\n");
+    for (idx, sections) in mapping::segment_sections(elf).iter().enumerate() {
+        text.push_str(&format!("   {idx:02}     "));
+        for s in sections.iter() {
+            text.push_str(&format!("{0} ", printable(&elf.sheaders[*s].sname)));
+        }
+        text.push('\n');
+    }
+    text
+}
+
+// whether a relocation section applies to the section at `idx`
+fn has_relocations(elf: &Elf, idx: usize) -> bool {
+    let count: usize = elf.sheaders.len();
+    elf.sheaders.iter().any(|s| {
+        matches!(s.stype, SType::REL | SType::RELA) && s.info as usize == idx && s.size != 0
+            && (s.link as usize) < count
+    })
+}
+
+// the bytes of a section to dump, None when it has none
+fn dump_data<'a>(elf: &'a Elf, sheader: &SHeader) -> Result<Option<&'a [u8]>, &'static str> {
+    if sheader.size == 0 || sheader.stype == SType::NOBITS {
+        return Ok(None);
+    }
+    elf.section_data(sheader).map(Some)
+}
+
+// readelf -x of the section at `idx`, compressed sections are dumped
+// decompressed as with -z
+pub fn hex_dump(elf: &Elf, idx: usize) -> Result<String, &'static str> {
+    let sheader: &SHeader = elf.sheaders.get(idx).ok_or("No such section.")?;
+    let name: String = printable(&sheader.sname);
+    let data: &[u8] = match dump_data(elf, sheader)? {
+        Some(data) => data,
+        None => return Ok(format!("Section '{name}' has no data to dump.\n")),
+    };
+    let mut text: String = format!("\nHex dump of section '{name}':\n");
+    if has_relocations(elf, idx) {
+        text.push_str(" NOTE: This section has relocations against it, but these have NOT \
+                       been applied to this dump.\n");
+    }
+    let mut addr: u64 = sheader.vaddr;
+    for chunk in data.chunks(16) {
+        text.push_str(&format!("  0x{addr:08x} "));
+        for j in 0..16 {
+            match chunk.get(j) {
+                Some(byte) => text.push_str(&format!("{byte:02x}")),
+                None => text.push_str("  "),
+            }
+            if j % 4 == 3 {
+                text.push(' ');
+            }
+        }
+        for byte in chunk.iter() {
+            match byte {
+                0x20..=0x7e => text.push(*byte as char),
+                _ => text.push('.'),
+            }
+        }
+        text.push('\n');
+        addr = addr.wrapping_add(chunk.len() as u64);
+    }
+    text.push('\n');
+    Ok(text)
+}
+
+// readelf -p of the section at `idx`: the runs of characters starting
+// with a printable one, by offset. Bytes past ASCII are written as they
+// are, so the dump is bytes and not text.
+pub fn string_dump(elf: &Elf, idx: usize) -> Result<Vec<u8>, &'static str> {
+    let sheader: &SHeader = elf.sheaders.get(idx).ok_or("No such section.")?;
+    let name: String = printable(&sheader.sname);
+    let data: &[u8] = match dump_data(elf, sheader)? {
+        Some(data) => data,
+        None => return Ok(format!("Section '{name}' has no data to dump.\n").into_bytes()),
+    };
+    let mut dump: Vec<u8> = format!("\nString dump of section '{name}':\n").into_bytes();
+    if has_relocations(elf, idx) {
+        dump.extend_from_slice(b"  Note: This section has relocations against it, but these have \
+                                 NOT been applied to this dump.\n");
+    }
+
+    let mut pos: usize = 0;
+    let mut shown: bool = false;
+    // the string goes on after a new line
+    let mut continuing: bool = false;
+    while pos < data.len() {
+        while pos < data.len() && !(0x20..=0x7e).contains(&data[pos]) {
+            pos += 1;
+        }
+        if pos >= data.len() {
+            break;
+        }
+        match continuing {
+            true => dump.extend_from_slice(b"            "),
+            false => dump.extend_from_slice(format!("  [{pos:6x}]  ").as_bytes()),
+        }
+        continuing = false;
+        shown = true;
+        let mut last: u8 = 0;
+        while pos < data.len() {
+            last = data[pos];
+            pos += 1;
+            match last {
+                0 => break,
+                b'\n' => {
+                    dump.extend_from_slice(b"\\n\n");
+                    continuing = data.get(pos).is_some_and(|b| *b != 0);
+                    break;
+                },
+                // control characters as ^X
+                0..=0x1f | 0x7f => dump.extend_from_slice(&[b'^', last.wrapping_add(0x40)]),
+                byte => dump.push(byte),
+            }
+        }
+        if last != b'\n' {
+            dump.push(b'\n');
+        }
+    }
+    if !shown {
+        dump.extend_from_slice(b"  No strings found in this section.");
+    }
+    dump.push(b'\n');
+    Ok(dump)
+}
+
+// What readelf prints for any of -h, -S and -l, in its order
+pub fn render(elf: &Elf, header: bool, sections: bool, segments: bool) -> String {
+    let mut text: String = String::new();
+    if header {
+        text.push_str(&file_header(elf));
+    }
+    if sections {
+        text.push_str(&section_headers(elf, header));
+    }
+    if segments {
+        text.push_str(&program_headers(elf, header));
+    }
+    text
+}
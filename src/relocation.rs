@@ -0,0 +1,209 @@
+use crate::{Class, Endian};
+use crate::debug_info::Reader;
+use crate::elf::Elf;
+use crate::elf_header::header_enums::Machine;
+use crate::section_header::SHeader;
+use crate::section_header::shdr_enums::SType;
+use crate::symbol::{self, Symbol};
+use crate::symbol::sym_consts::STT_SECTION;
+
+// One entry of a SHT_REL or SHT_RELA section. `sym` indexes the symbol
+// table in sh_link of the section, 0 for relocations without a symbol.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Relocation {
+    pub offset: u64,
+    pub rtype: u32,
+    pub sym: u32,
+    pub symbol: String,
+    // None for SHT_REL, the addend is then stored at the target
+    pub addend: Option<i64>,
+}
+
+// section symbols have no name of their own, they go by their section
+fn symbol_name(elf: &Elf, sym: &Symbol) -> String {
+    match elf.sheaders.get(sym.shndx as usize) {
+        Some(sheader) if sym.stype == STT_SECTION && sym.name.is_empty() => sheader.sname.clone(),
+        _ => sym.name.clone(),
+    }
+}
+
+// Parse the relocations of a SHT_REL or SHT_RELA section
+pub fn parse(elf: &Elf, sheader: &SHeader) -> Result<Vec<Relocation>, &'static str> {
+    let has_addend: bool = match sheader.stype {
+        SType::RELA => true,
+        SType::REL => false,
+        _ => return Err("Not a relocation section."),
+    };
+    let data: &[u8] = elf.section_data(sheader)?;
+    let symbols: Vec<Symbol> = match elf.sheaders.get(sheader.link as usize) {
+        Some(symtab) if symtab.stype == SType::SYMTAB => symbol::parse(elf, symtab, ".symtab")?,
+        Some(symtab) if symtab.stype == SType::DYNSYM => symbol::parse(elf, symtab, ".dynsym")?,
+        _ => Vec::new(),
+    };
+
+    let is_64: bool = elf.header.class != Class::X32Bit;
+    let entry_size: usize = match (is_64, has_addend) {
+        (true, true) => 24,
+        (true, false) => 16,
+        (false, true) => 12,
+        (false, false) => 8,
+    };
+    let mut relocations: Vec<Relocation> = Vec::with_capacity(data.len() / entry_size);
+    let mut reader: Reader = Reader::new(data, 0, elf.header.endian);
+    while reader.offset + entry_size <= data.len() {
+        let (offset, sym, rtype, addend): (u64, u32, u32, Option<i64>) = if is_64 {
+            let offset: u64 = reader.u64()?;
+            let info: u64 = reader.u64()?;
+            let addend: Option<i64> = if has_addend { Some(reader.u64()? as i64) } else { None };
+            (offset, (info >> 32) as u32, info as u32, addend)
+        }
+        else {
+            let offset: u64 = reader.u32()? as u64;
+            let info: u32 = reader.u32()?;
+            let addend: Option<i64> = if has_addend {
+                Some(reader.u32()? as i32 as i64)
+            }
+            else {
+                None
+            };
+            (offset, info >> 8, info & 0xff, addend)
+        };
+        relocations.push(Relocation {
+            offset,
+            rtype,
+            sym,
+            symbol: symbols.get(sym as usize).map(|s| symbol_name(elf, s)).unwrap_or_default(),
+            addend,
+        });
+    }
+    Ok(relocations)
+}
+
+// the name of a relocation type, for the machines with tables here
+pub fn type_name(machine: Machine, rtype: u32) -> Option<&'static str> {
+    let name: &str = match (machine, rtype) {
+        (Machine::AMD64, 0) => "R_X86_64_NONE",
+        (Machine::AMD64, 1) => "R_X86_64_64",
+        (Machine::AMD64, 2) => "R_X86_64_PC32",
+        (Machine::AMD64, 3) => "R_X86_64_GOT32",
+        (Machine::AMD64, 4) => "R_X86_64_PLT32",
+        (Machine::AMD64, 5) => "R_X86_64_COPY",
+        (Machine::AMD64, 6) => "R_X86_64_GLOB_DAT",
+        (Machine::AMD64, 7) => "R_X86_64_JUMP_SLOT",
+        (Machine::AMD64, 8) => "R_X86_64_RELATIVE",
+        (Machine::AMD64, 9) => "R_X86_64_GOTPCREL",
+        (Machine::AMD64, 10) => "R_X86_64_32",
+        (Machine::AMD64, 11) => "R_X86_64_32S",
+        (Machine::AMD64, 12) => "R_X86_64_16",
+        (Machine::AMD64, 13) => "R_X86_64_PC16",
+        (Machine::AMD64, 14) => "R_X86_64_8",
+        (Machine::AMD64, 15) => "R_X86_64_PC8",
+        (Machine::AMD64, 16) => "R_X86_64_DTPMOD64",
+        (Machine::AMD64, 17) => "R_X86_64_DTPOFF64",
+        (Machine::AMD64, 18) => "R_X86_64_TPOFF64",
+        (Machine::AMD64, 19) => "R_X86_64_TLSGD",
+        (Machine::AMD64, 20) => "R_X86_64_TLSLD",
+        (Machine::AMD64, 21) => "R_X86_64_DTPOFF32",
+        (Machine::AMD64, 22) => "R_X86_64_GOTTPOFF",
+        (Machine::AMD64, 23) => "R_X86_64_TPOFF32",
+        (Machine::AMD64, 24) => "R_X86_64_PC64",
+        (Machine::AMD64, 25) => "R_X86_64_GOTOFF64",
+        (Machine::AMD64, 26) => "R_X86_64_GOTPC32",
+        (Machine::AMD64, 32) => "R_X86_64_SIZE32",
+        (Machine::AMD64, 33) => "R_X86_64_SIZE64",
+        (Machine::AMD64, 34) => "R_X86_64_GOTPC32_TLSDESC",
+        (Machine::AMD64, 35) => "R_X86_64_TLSDESC_CALL",
+        (Machine::AMD64, 36) => "R_X86_64_TLSDESC",
+        (Machine::AMD64, 37) => "R_X86_64_IRELATIVE",
+        (Machine::AMD64, 38) => "R_X86_64_RELATIVE64",
+        (Machine::AMD64, 41) => "R_X86_64_GOTPCRELX",
+        (Machine::AMD64, 42) => "R_X86_64_REX_GOTPCRELX",
+        (Machine::X86, 0) => "R_386_NONE",
+        (Machine::X86, 1) => "R_386_32",
+        (Machine::X86, 2) => "R_386_PC32",
+        (Machine::X86, 3) => "R_386_GOT32",
+        (Machine::X86, 4) => "R_386_PLT32",
+        (Machine::X86, 5) => "R_386_COPY",
+        (Machine::X86, 6) => "R_386_GLOB_DAT",
+        (Machine::X86, 7) => "R_386_JUMP_SLOT",
+        (Machine::X86, 8) => "R_386_RELATIVE",
+        (Machine::X86, 9) => "R_386_GOTOFF",
+        (Machine::X86, 10) => "R_386_GOTPC",
+        (Machine::X86, 14) => "R_386_TLS_TPOFF",
+        (Machine::X86, 35) => "R_386_TLS_DTPMOD32",
+        (Machine::X86, 36) => "R_386_TLS_DTPOFF32",
+        (Machine::X86, 37) => "R_386_TLS_TPOFF32",
+        (Machine::X86, 42) => "R_386_IRELATIVE",
+        (Machine::X86, 43) => "R_386_GOT32X",
+        (Machine::AARCH64, 0) => "R_AARCH64_NONE",
+        (Machine::AARCH64, 257) => "R_AARCH64_ABS64",
+        (Machine::AARCH64, 258) => "R_AARCH64_ABS32",
+        (Machine::AARCH64, 259) => "R_AARCH64_ABS16",
+        (Machine::AARCH64, 260) => "R_AARCH64_PREL64",
+        (Machine::AARCH64, 261) => "R_AARCH64_PREL32",
+        (Machine::AARCH64, 262) => "R_AARCH64_PREL16",
+        (Machine::AARCH64, 275) => "R_AARCH64_ADR_PREL_PG_HI21",
+        (Machine::AARCH64, 277) => "R_AARCH64_ADD_ABS_LO12_NC",
+        (Machine::AARCH64, 282) => "R_AARCH64_JUMP26",
+        (Machine::AARCH64, 283) => "R_AARCH64_CALL26",
+        (Machine::AARCH64, 311) => "R_AARCH64_ADR_GOT_PAGE",
+        (Machine::AARCH64, 312) => "R_AARCH64_LD64_GOT_LO12_NC",
+        (Machine::AARCH64, 1024) => "R_AARCH64_COPY",
+        (Machine::AARCH64, 1025) => "R_AARCH64_GLOB_DAT",
+        (Machine::AARCH64, 1026) => "R_AARCH64_JUMP_SLOT",
+        (Machine::AARCH64, 1027) => "R_AARCH64_RELATIVE",
+        (Machine::AARCH64, 1028) => "R_AARCH64_TLS_DTPMOD",
+        (Machine::AARCH64, 1029) => "R_AARCH64_TLS_DTPREL",
+        (Machine::AARCH64, 1030) => "R_AARCH64_TLS_TPREL",
+        (Machine::AARCH64, 1031) => "R_AARCH64_TLSDESC",
+        (Machine::AARCH64, 1032) => "R_AARCH64_IRELATIVE",
+        _ => return None,
+    };
+    Some(name)
+}
+
+// Apply the relocations against the section at `target` to its bytes in
+// `data`, as a linker would with the sections all placed at address 0.
+// Only the absolute types found in debug sections are handled, other
+// entries are left alone.
+pub fn apply(elf: &Elf, target: usize, data: &mut [u8]) -> Result<(), &'static str> {
+    let endian: Endian = elf.header.endian;
+    for sheader in elf.sheaders.iter() {
+        if !matches!(sheader.stype, SType::REL | SType::RELA) || sheader.info as usize != target {
+            continue;
+        }
+        let symbols: Vec<Symbol> = match elf.sheaders.get(sheader.link as usize) {
+            Some(symtab) if symtab.stype == SType::SYMTAB => symbol::parse(elf, symtab, ".symtab")?,
+            _ => return Err("Relocation section without a symbol table."),
+        };
+        for r in parse(elf, sheader)? {
+            let size: usize = match (elf.header.machine, r.rtype) {
+                (Machine::AMD64, 1) | (Machine::AARCH64, 257) => 8,
+                (Machine::AMD64, 10) | (Machine::AMD64, 11) | (Machine::X86, 1)
+                    | (Machine::AARCH64, 258) => 4,
+                _ => continue,
+            };
+            let start: usize = r.offset as usize;
+            let field: &mut [u8] = start.checked_add(size).and_then(|end| data.get_mut(start..end))
+                                        .ok_or("Relocation out of bounds.")?;
+            let value: u64 = match symbols.get(r.sym as usize) {
+                Some(symbol) => symbol.value,
+                None if r.sym == 0 => 0,
+                None => return Err("Relocation symbol out of bounds."),
+            };
+            // SHT_REL keeps the addend in the field itself
+            let addend: i64 = match r.addend {
+                Some(addend) => addend,
+                None => Reader::new(field, 0, endian).sized(size as u8)? as i64,
+            };
+            let value: u64 = value.wrapping_add(addend as u64);
+            match (size, endian) {
+                (8, Endian::Big) => field.copy_from_slice(&value.to_be_bytes()),
+                (8, _) => field.copy_from_slice(&value.to_le_bytes()),
+                (_, Endian::Big) => field.copy_from_slice(&(value as u32).to_be_bytes()),
+                (_, _) => field.copy_from_slice(&(value as u32).to_le_bytes()),
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,654 @@
+use std::collections::HashMap;
+use crate::loader::{LoadMap, Object};
+use crate::symbol::{self, Symbol};
+use crate::symbol::sym_consts::*;
+use crate::symbol::version::{self, Version, Versions};
+
+// The .dynsym of an object with its versions, indexed by name
+#[derive(Debug, Default, Clone)]
+pub struct DynamicSymbols {
+    pub symbols: Vec<Symbol>,
+    pub versions: Versions,
+    by_name: HashMap<String, Vec<usize>>,
+}
+
+impl DynamicSymbols {
+    pub fn load(object: &Object) -> Result<DynamicSymbols, &'static str> {
+        let elf = &object.elf;
+        Ok(DynamicSymbols::new(symbol::dynamic_symbols(elf)?, version::parse(elf)?))
+    }
+
+    pub fn new(symbols: Vec<Symbol>, versions: Versions) -> DynamicSymbols {
+        let mut by_name: HashMap<String, Vec<usize>> = HashMap::new();
+        for (idx, sym) in symbols.iter().enumerate().filter(|(_, s)| !s.name.is_empty()) {
+            by_name.entry(sym.name.clone()).or_default().push(idx);
+        }
+        DynamicSymbols {
+            symbols,
+            versions,
+            by_name,
+        }
+    }
+
+    // indexes of the symbols named `name`
+    pub fn named(&self, name: &str) -> &[usize] {
+        self.by_name.get(name).map_or(&[], |v| v.as_slice())
+    }
+
+    // Whether the symbol at `idx` satisfies a reference to its name with
+    // the required `version`, following check_match of glibc. A versioned
+    // reference binds to the definition of that version or to an
+    // unversioned one, an unversioned reference to any default version.
+    pub fn exports(&self, idx: usize, version: Option<&str>) -> bool {
+        let sym: &Symbol = &self.symbols[idx];
+        if !sym.is_defined() || !matches!(sym.bind, STB_GLOBAL | STB_WEAK | STB_GNU_UNIQUE)
+           || matches!(sym.stype, STT_SECTION | STT_FILE)
+           || matches!(sym.visibility, STV_HIDDEN | STV_INTERNAL) {
+            return false;
+        }
+        match (version, self.versions.of(idx)) {
+            (Some(wanted), Version::Defined { name, .. }) => wanted == name,
+            (_, Version::Defined { hidden, .. }) => !hidden,
+            (_, Version::Needed { .. }) => false,
+            (_, Version::None) => true,
+        }
+    }
+
+    // whether the symbol at `idx` is visible to other objects under any
+    // version
+    pub fn exports_any(&self, idx: usize) -> bool {
+        match self.versions.of(idx) {
+            Version::Defined { name, .. } => self.exports(idx, Some(name)),
+            _ => self.exports(idx, None),
+        }
+    }
+
+    // the first symbol satisfying a reference to `name`
+    pub fn find(&self, name: &str, version: Option<&str>) -> Option<usize> {
+        self.named(name).iter().copied().find(|idx| self.exports(*idx, version))
+    }
+
+    pub fn defines_version(&self, name: &str) -> bool {
+        self.versions.defs.iter().any(|d| d.name == name)
+    }
+}
+
+// What an undefined symbol binds to at run time
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Resolution {
+    // defined by an object of the load map
+    Defined { object: usize, symbol: usize },
+    // a weak reference without a definition, it reads as zero
+    WeakUndefined,
+    // a "symbol lookup error"
+    Missing,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Binding {
+    pub name: String,
+    pub version: Option<String>,
+    pub weak: bool,
+    pub resolution: Resolution,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VersionStatus {
+    Found,
+    Missing,
+    // the library defines no versions at all, glibc only warns
+    NoVersionInfo,
+    // the library itself was not found
+    NotLoaded,
+}
+
+// A version of a dependency required through .gnu.version_r
+#[derive(Debug, Clone, PartialEq)]
+pub struct VersionCheck {
+    pub file: String,
+    pub version: String,
+    pub weak: bool,
+    pub status: VersionStatus,
+}
+
+// How the undefined dynamic symbols of one object bind
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Report {
+    pub object: usize,
+    pub bindings: Vec<Binding>,
+    pub versions: Vec<VersionCheck>,
+}
+
+impl Report {
+    // the references and versions that stop the program from loading
+    pub fn failures(&self) -> usize {
+        self.bindings.iter().filter(|b| b.resolution == Resolution::Missing).count()
+            + self.versions.iter()
+                  .filter(|v| v.status == VersionStatus::Missing && !v.weak)
+                  .count()
+    }
+}
+
+// the dynamic symbols of every object of the load map
+pub fn load_symbols(map: &LoadMap) -> Result<Vec<DynamicSymbols>, &'static str> {
+    map.objects.iter().map(DynamicSymbols::load).collect()
+}
+
+// Resolve the undefined dynamic symbols of `object` against the global
+// scope of `map` and check the versions it needs from its dependencies
+pub fn check(map: &LoadMap, symbols: &[DynamicSymbols],
+             object: usize) -> Report {
+    let scope: Vec<usize> = map.scope();
+    let own: &DynamicSymbols = &symbols[object];
+    let mut report: Report = Report {
+        object,
+        ..Default::default()
+    };
+
+    for (idx, sym) in own.symbols.iter().enumerate() {
+        if sym.is_defined() || sym.name.is_empty() || sym.bind == STB_LOCAL {
+            continue;
+        }
+        let version: Option<&str> = match own.versions.of(idx) {
+            Version::Needed { name, .. } | Version::Defined { name, .. } => Some(name),
+            Version::None => None,
+        };
+        let weak: bool = sym.bind == STB_WEAK;
+        let found: Option<Resolution> = scope.iter().find_map(|candidate| {
+            symbols[*candidate].find(&sym.name, version)
+                .map(|symbol| Resolution::Defined { object: *candidate, symbol })
+        });
+        report.bindings.push(Binding {
+            name: sym.name.clone(),
+            version: version.map(String::from),
+            weak,
+            resolution: match found {
+                Some(resolution) => resolution,
+                None if weak => Resolution::WeakUndefined,
+                None => Resolution::Missing,
+            },
+        });
+    }
+
+    for need in own.versions.needs.iter() {
+        let target: Option<usize> = map.objects[object].needed.iter()
+            .find(|d| d.name == need.file)
+            .and_then(|d| d.object);
+        for aux in need.versions.iter() {
+            let status: VersionStatus = match target.map(|t| &symbols[t]) {
+                None => VersionStatus::NotLoaded,
+                Some(lib) if lib.versions.defs.is_empty() => VersionStatus::NoVersionInfo,
+                Some(lib) if lib.defines_version(&aux.name) => VersionStatus::Found,
+                Some(_) => VersionStatus::Missing,
+            };
+            report.versions.push(VersionCheck {
+                file: need.file.clone(),
+                version: aux.name.clone(),
+                weak: aux.flags & version::VER_FLG_WEAK != 0,
+                status,
+            });
+        }
+    }
+    report
+}
+
+// One definition of a symbol exported by several objects
+#[derive(Debug, Clone, PartialEq)]
+pub struct Definition {
+    pub object: usize,
+    pub symbol: usize,
+    pub version: Option<String>,
+    // a non-default version, name@VERSION
+    pub hidden: bool,
+    // STV_PROTECTED, references from inside its object bind to it even
+    // when it loses
+    pub protected: bool,
+    pub weak: bool,
+}
+
+// A symbol name exported by more than one object. `definitions` are in
+// lookup order and `winner` indexes the one unversioned references bind
+// to, None when every definition is a non-default version.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Interposition {
+    pub name: String,
+    pub definitions: Vec<Definition>,
+    pub winner: Option<usize>,
+}
+
+impl Interposition {
+    // the losing definitions that differ from the winner in visibility or
+    // version, they are not simply shadowed
+    pub fn differs(&self, definition: &Definition) -> bool {
+        let winner: Option<&Definition> = self.winner.map(|w| &self.definitions[w]);
+        definition.protected || definition.hidden
+            || winner.is_some_and(|w| w.version != definition.version)
+    }
+}
+
+// Every exported dynamic symbol defined by more than one object of the
+// global scope, walking it in breadth first load order
+pub fn interpositions(map: &LoadMap, symbols: &[DynamicSymbols]) -> Vec<Interposition> {
+    let mut by_name: HashMap<&str, Vec<Definition>> = HashMap::new();
+    let mut names: Vec<&str> = Vec::new();
+    for object in map.scope() {
+        let own: &DynamicSymbols = &symbols[object];
+        for (idx, sym) in own.symbols.iter().enumerate() {
+            if sym.name.is_empty() || !own.exports_any(idx) {
+                continue;
+            }
+            // the absolute symbols naming the versions an object defines
+            if sym.shndx == SHN_ABS && own.defines_version(&sym.name) {
+                continue;
+            }
+            let (version, hidden): (Option<String>, bool) = match own.versions.of(idx) {
+                Version::Defined { name, hidden } => (Some(name.to_string()), hidden),
+                _ => (None, false),
+            };
+            let definitions: &mut Vec<Definition> = by_name.entry(&sym.name).or_insert_with(|| {
+                names.push(&sym.name);
+                Vec::new()
+            });
+            let definition: Definition = Definition {
+                object,
+                symbol: idx,
+                version,
+                hidden,
+                protected: sym.visibility == STV_PROTECTED,
+                weak: sym.bind == STB_WEAK,
+            };
+            // several versions of a name in one object are not interposition,
+            // the default version stands for them
+            match definitions.last_mut() {
+                Some(last) if last.object == object => {
+                    if last.hidden && !definition.hidden {
+                        *last = definition;
+                    }
+                },
+                _ => definitions.push(definition),
+            }
+        }
+    }
+
+    let mut found: Vec<Interposition> = Vec::new();
+    for name in names {
+        let definitions: Vec<Definition> = by_name.remove(name).unwrap_or_default();
+        if definitions.len() < 2 {
+            continue;
+        }
+        let winner: Option<usize> = definitions.iter().position(|d| {
+            symbols[d.object].exports(d.symbol, None)
+        });
+        found.push(Interposition {
+            name: name.to_string(),
+            definitions,
+            winner,
+        });
+    }
+    found
+}
+
+// A DT_NEEDED entry and the undefined symbols of the requester that
+// bind to its library
+#[derive(Debug, Clone, PartialEq)]
+pub struct NeededUse {
+    pub name: String,
+    pub object: Option<usize>,
+    pub symbols: Vec<String>,
+}
+
+// An undefined symbol that binds to a library the requester does not
+// list in DT_NEEDED. `through` is the direct dependency that loaded it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Underlinked {
+    pub name: String,
+    pub version: Option<String>,
+    pub object: usize,
+    pub through: Option<usize>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct LinkReport {
+    pub object: usize,
+    pub needed: Vec<NeededUse>,
+    pub underlinked: Vec<Underlinked>,
+}
+
+impl LinkReport {
+    // libraries none of the symbols of the requester bind to, the ones
+    // ld --as-needed would leave out
+    pub fn unused(&self) -> Vec<&NeededUse> {
+        self.needed.iter().filter(|n| n.object.is_some() && n.symbols.is_empty()).collect()
+    }
+}
+
+// Cross-reference the DT_NEEDED libraries of `object` with its undefined
+// symbols: which symbols each of them could satisfy, and which symbols
+// only bind through a library loaded on behalf of another one
+pub fn link_usage(map: &LoadMap, symbols: &[DynamicSymbols], object: usize) -> LinkReport {
+    let report: Report = check(map, symbols, object);
+    let direct: Vec<usize> = map.objects[object].needed.iter()
+                                .filter_map(|d| d.object)
+                                .collect();
+    let mut usage: LinkReport = LinkReport {
+        object,
+        ..Default::default()
+    };
+
+    for dependency in map.objects[object].needed.iter() {
+        let used: Vec<String> = match dependency.object {
+            // a library defining a symbol that binds elsewhere is not used
+            Some(lib) => report.bindings.iter()
+                .filter(|b| matches!(b.resolution,
+                                     Resolution::Defined { object, .. } if object == lib))
+                .map(|b| b.name.clone())
+                .collect(),
+            None => Vec::new(),
+        };
+        usage.needed.push(NeededUse {
+            name: dependency.name.clone(),
+            object: dependency.object,
+            symbols: used,
+        });
+    }
+
+    for binding in report.bindings.iter() {
+        let lib: usize = match binding.resolution {
+            Resolution::Defined { object: lib, .. } => lib,
+            _ => continue,
+        };
+        if lib == object || direct.contains(&lib) {
+            continue;
+        }
+        // up the chain of loaders to a direct dependency
+        let mut through: Option<usize> = map.objects[lib].loader;
+        while let Some(idx) = through {
+            if direct.contains(&idx) || map.objects[idx].loader.is_none() {
+                break;
+            }
+            through = map.objects[idx].loader;
+        }
+        usage.underlinked.push(Underlinked {
+            name: binding.name.clone(),
+            version: binding.version.clone(),
+            object: lib,
+            through: through.filter(|idx| direct.contains(idx)),
+        });
+    }
+    usage
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use super::*;
+    use crate::dynamic::Dynamic;
+    use crate::elf::Elf;
+    use crate::loader::Dependency;
+    use crate::symbol::version::{VerDef, VerNeed, VerNeedAux};
+
+    // an object needing the objects at `needed` of the map
+    fn object(name: &str, needed: &[(&str, usize)]) -> Object {
+        Object {
+            name: name.to_string(),
+            path: PathBuf::from("/lib").join(name),
+            elf: Elf::default(),
+            dynamic: Dynamic::default(),
+            loader: None,
+            found: None,
+            needed: needed.iter().map(|(name, idx)| Dependency {
+                name: name.to_string(),
+                object: Some(*idx),
+                found: None,
+                rejected: Vec::new(),
+            }).collect(),
+        }
+    }
+
+    fn symbol(name: &str, defined: bool, bind: u8) -> Symbol {
+        Symbol {
+            name: name.to_string(),
+            bind,
+            stype: STT_FUNC,
+            shndx: if defined { 1 } else { SHN_UNDEF },
+            table: ".dynsym",
+            ..Default::default()
+        }
+    }
+
+    // (name, defined) pairs of global symbols without versions
+    fn unversioned(names: &[(&str, bool)]) -> DynamicSymbols {
+        let symbols: Vec<Symbol> = names.iter()
+            .map(|(name, defined)| symbol(name, *defined, STB_GLOBAL))
+            .collect();
+        DynamicSymbols::new(symbols, Versions::default())
+    }
+
+    // symbols of an object defining the versions `defs`, numbered from 1
+    // with the first one as the base version, and needing `needs`
+    fn versioned(symbols: Vec<(Symbol, u16)>, defs: &[&str], needs: Vec<VerNeed>)
+                 -> DynamicSymbols {
+        let versions: Versions = Versions {
+            versym: symbols.iter().map(|(_, versym)| *versym).collect(),
+            defs: defs.iter().enumerate().map(|(idx, name)| VerDef {
+                index: idx as u16 + 1,
+                flags: if idx == 0 { version::VER_FLG_BASE } else { 0 },
+                name: name.to_string(),
+                parents: Vec::new(),
+            }).collect(),
+            needs,
+        };
+        DynamicSymbols::new(symbols.into_iter().map(|(sym, _)| sym).collect(), versions)
+    }
+
+    fn need(file: &str, versions: &[(u16, &str, bool)]) -> VerNeed {
+        VerNeed {
+            file: file.to_string(),
+            versions: versions.iter().map(|(index, name, weak)| VerNeedAux {
+                index: *index,
+                flags: if *weak { version::VER_FLG_WEAK } else { 0 },
+                name: name.to_string(),
+            }).collect(),
+        }
+    }
+
+    // libc.so.6 with memcpy@GLIBC_2.2.5 and memcpy@@GLIBC_2.14
+    fn libc() -> DynamicSymbols {
+        let hidden: Symbol = Symbol { visibility: STV_HIDDEN, ..symbol("internal", true, STB_GLOBAL) };
+        let local: Symbol = symbol("local", true, STB_LOCAL);
+        versioned(vec![(Symbol::default(), 0),
+                       (symbol("memcpy", true, STB_GLOBAL), 2 | version::VERSYM_HIDDEN),
+                       (symbol("memcpy", true, STB_GLOBAL), 3),
+                       (symbol("puts", true, STB_GLOBAL), 2),
+                       (symbol("environ", true, STB_WEAK), 2),
+                       (hidden, 3),
+                       (local, 0),
+                       (symbol("free", true, STB_GLOBAL), 1)],
+                  &["libc.so.6", "GLIBC_2.2.5", "GLIBC_2.14"], Vec::new())
+    }
+
+    #[test]
+    fn exported_versions() {
+        let libc: DynamicSymbols = libc();
+        // the old memcpy only binds to references asking for it
+        assert!(libc.exports(1, Some("GLIBC_2.2.5")));
+        assert!(!libc.exports(1, None));
+        assert!(!libc.exports(1, Some("GLIBC_2.14")));
+        assert!(libc.exports(2, None));
+        assert!(libc.exports(2, Some("GLIBC_2.14")));
+        assert!(libc.exports_any(1));
+        assert_eq!(libc.find("memcpy", None), Some(2));
+        assert_eq!(libc.find("memcpy", Some("GLIBC_2.2.5")), Some(1));
+        assert_eq!(libc.find("memcpy", Some("GLIBC_2.0")), None);
+        // weak definitions count, hidden and local ones do not
+        assert_eq!(libc.find("environ", None), Some(4));
+        assert_eq!(libc.find("internal", None), None);
+        assert!(!libc.exports_any(5));
+        assert_eq!(libc.find("local", None), None);
+        // an unversioned definition satisfies any version
+        assert_eq!(libc.find("free", Some("GLIBC_2.14")), Some(7));
+        assert_eq!(unversioned(&[("puts", true)]).find("puts", Some("GLIBC_2.2.5")), Some(0));
+        assert_eq!(unversioned(&[("puts", false)]).find("puts", None), None);
+        assert!(libc.defines_version("GLIBC_2.14"));
+        assert!(!libc.defines_version("GLIBC_2.34"));
+    }
+
+    #[test]
+    fn undefined_symbols_and_versions() {
+        // main needs libc, libm without versions and libz, not found
+        let mut map: LoadMap = LoadMap {
+            objects: vec![object("main", &[("libc.so.6", 1), ("libm.so.6", 2), ("libz.so.1", 0)]),
+                          object("libc.so.6", &[]),
+                          object("libm.so.6", &[])],
+            ..Default::default()
+        };
+        map.objects[0].needed[2].object = None;
+        let main: DynamicSymbols = versioned(
+            vec![(Symbol::default(), 0),
+                 (symbol("memcpy", false, STB_GLOBAL), 2),
+                 (symbol("memcpy", false, STB_GLOBAL), 3),
+                 (symbol("puts", false, STB_GLOBAL), 0),
+                 (symbol("sin", false, STB_GLOBAL), 4),
+                 (symbol("internal", false, STB_GLOBAL), 0),
+                 (symbol("__gmon_start__", false, STB_WEAK), 0),
+                 (symbol("main", true, STB_GLOBAL), 1)],
+            &["main"],
+            vec![need("libc.so.6", &[(2, "GLIBC_2.2.5", false), (3, "GLIBC_2.34", false),
+                                     (5, "GLIBC_2.99", true)]),
+                 need("libm.so.6", &[(4, "GLIBC_2.29", false)]),
+                 need("libz.so.1", &[(6, "ZLIB_1.2.9", false)])]);
+        let symbols: Vec<DynamicSymbols> = vec![main, libc(), unversioned(&[("sin", true)])];
+
+        let report: Report = check(&map, &symbols, 0);
+        let bindings: Vec<(&str, Option<&str>, Resolution)> = report.bindings.iter()
+            .map(|b| (b.name.as_str(), b.version.as_deref(), b.resolution))
+            .collect();
+        assert_eq!(bindings, [
+            ("memcpy", Some("GLIBC_2.2.5"), Resolution::Defined { object: 1, symbol: 1 }),
+            // the version main needs is not the one libc defines
+            ("memcpy", Some("GLIBC_2.34"), Resolution::Missing),
+            ("puts", None, Resolution::Defined { object: 1, symbol: 3 }),
+            ("sin", Some("GLIBC_2.29"), Resolution::Defined { object: 2, symbol: 0 }),
+            ("internal", None, Resolution::Missing),
+            ("__gmon_start__", None, Resolution::WeakUndefined),
+        ]);
+        assert!(report.bindings[5].weak);
+
+        let versions: Vec<(&str, &str, VersionStatus)> = report.versions.iter()
+            .map(|v| (v.file.as_str(), v.version.as_str(), v.status))
+            .collect();
+        assert_eq!(versions, [
+            ("libc.so.6", "GLIBC_2.2.5", VersionStatus::Found),
+            ("libc.so.6", "GLIBC_2.34", VersionStatus::Missing),
+            ("libc.so.6", "GLIBC_2.99", VersionStatus::Missing),
+            ("libm.so.6", "GLIBC_2.29", VersionStatus::NoVersionInfo),
+            ("libz.so.1", "ZLIB_1.2.9", VersionStatus::NotLoaded),
+        ]);
+        // two missing symbols and GLIBC_2.34, the weak GLIBC_2.99 is not
+        assert_eq!(report.failures(), 3);
+    }
+
+    #[test]
+    fn interposed_symbols() {
+        // main needs liba and libd, liba needs libc
+        let map: LoadMap = LoadMap {
+            objects: vec![object("main", &[("liba.so.1", 1), ("libd.so.1", 3)]),
+                          object("liba.so.1", &[("libc.so.6", 2)]),
+                          object("libc.so.6", &[]),
+                          object("libd.so.1", &[])],
+            ..Default::default()
+        };
+        let protected: Symbol = Symbol { visibility: STV_PROTECTED, ..symbol("puts", true, STB_GLOBAL) };
+        let internal: Symbol = Symbol { visibility: STV_HIDDEN, ..symbol("internal", true, STB_GLOBAL) };
+        let version: Symbol = Symbol { shndx: SHN_ABS, stype: STT_OBJECT,
+                                       ..symbol("GLIBC_2.14", true, STB_GLOBAL) };
+        let symbols: Vec<DynamicSymbols> = vec![
+            unversioned(&[("environ", true), ("memcpy", false)]),
+            versioned(vec![(Symbol::default(), 0),
+                           (symbol("memcpy", true, STB_WEAK), 0),
+                           (protected, 0),
+                           (internal, 0),
+                           (symbol("a_only", true, STB_GLOBAL), 0),
+                           (version.clone(), 2)],
+                      &["liba.so.1", "GLIBC_2.14"], Vec::new()),
+            versioned(vec![(Symbol::default(), 0),
+                           (symbol("memcpy", true, STB_GLOBAL), 2 | version::VERSYM_HIDDEN),
+                           (symbol("memcpy", true, STB_GLOBAL), 3),
+                           (symbol("puts", true, STB_GLOBAL), 2),
+                           (symbol("environ", true, STB_WEAK), 2),
+                           (symbol("internal", true, STB_GLOBAL), 3),
+                           (symbol("old", true, STB_GLOBAL), 2 | version::VERSYM_HIDDEN),
+                           (version, 3)],
+                      &["libc.so.6", "GLIBC_2.2.5", "GLIBC_2.14"], Vec::new()),
+            versioned(vec![(Symbol::default(), 0),
+                           (symbol("old", true, STB_GLOBAL), 2 | version::VERSYM_HIDDEN),
+                           (symbol("memcpy", true, STB_GLOBAL), 0)],
+                      &["libd.so.1", "D_1"], Vec::new()),
+        ];
+
+        let found: Vec<Interposition> = interpositions(&map, &symbols);
+        let names: Vec<(&str, Option<usize>)> = found.iter()
+            .map(|i| (i.name.as_str(), i.winner))
+            .collect();
+        let definitions = |i: &Interposition| -> Vec<(usize, Option<String>)> {
+            i.definitions.iter().map(|d| (d.object, d.version.clone())).collect()
+        };
+        // lookup order is main, liba, libd, libc and the names come in the
+        // order they are first defined. The absolute symbols naming the
+        // versions of liba and libc are left out, internal is hidden in liba.
+        assert_eq!(names, [("environ", Some(0)), ("memcpy", Some(0)), ("puts", Some(0)),
+                           ("old", None)]);
+        assert_eq!(definitions(&found[0]), [(0, None), (2, Some("GLIBC_2.2.5".to_string()))]);
+        assert_eq!(definitions(&found[1]), [(1, None), (3, None),
+                                            (2, Some("GLIBC_2.14".to_string()))]);
+        assert_eq!(definitions(&found[2]), [(1, None), (2, Some("GLIBC_2.2.5".to_string()))]);
+        assert_eq!(definitions(&found[3]), [(3, Some("D_1".to_string())),
+                                            (2, Some("GLIBC_2.2.5".to_string()))]);
+
+        let memcpy: &Interposition = &found[1];
+        assert!(memcpy.definitions[0].weak);
+        // the default version of libc stands for its two memcpy
+        assert_eq!(memcpy.definitions[2].symbol, 2);
+        assert!(!memcpy.definitions[2].hidden);
+        assert!(!memcpy.differs(&memcpy.definitions[1]));
+        assert!(memcpy.differs(&memcpy.definitions[2]));
+        let puts: &Interposition = &found[2];
+        assert!(puts.definitions[0].protected);
+        assert!(puts.differs(&puts.definitions[0]));
+        let old: &Interposition = &found[3];
+        assert!(old.definitions.iter().all(|d| d.hidden && old.differs(d)));
+    }
+
+    #[test]
+    fn needed_libraries_in_use() {
+        // main needs liba and libb, both define dup, only liba is the one
+        // it binds to. libc comes with libb.
+        let mut map: LoadMap = LoadMap {
+            objects: vec![object("main", &[("liba.so.1", 1), ("libb.so.1", 2)]),
+                          object("liba.so.1", &[]),
+                          object("libb.so.1", &[("libc.so.6", 3)]),
+                          object("libc.so.6", &[])],
+            ..Default::default()
+        };
+        map.objects[3].loader = Some(2);
+        let symbols: Vec<DynamicSymbols> = vec![
+            unversioned(&[("dup", false), ("puts", false)]),
+            unversioned(&[("dup", true)]),
+            unversioned(&[("dup", true), ("b_only", true)]),
+            unversioned(&[("puts", true)]),
+        ];
+        let usage: LinkReport = link_usage(&map, &symbols, 0);
+        let used: Vec<(&str, Vec<String>)> = usage.needed.iter()
+            .map(|n| (n.name.as_str(), n.symbols.clone()))
+            .collect();
+        assert_eq!(used, [("liba.so.1", vec!["dup".to_string()]), ("libb.so.1", vec![])]);
+        assert_eq!(usage.unused().len(), 1);
+        assert_eq!(usage.unused()[0].name, "libb.so.1");
+        assert_eq!(usage.underlinked, [Underlinked {
+            name: "puts".to_string(),
+            version: None,
+            object: 3,
+            through: Some(2),
+        }]);
+    }
+}
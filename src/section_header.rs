@@ -3,37 +3,46 @@ use crate::{BigEndian, LittleEndian, ReadBytesExt};
 pub mod shdr_enums;
 use self::shdr_enums::{SType, SFlags};
 
-#[derive(Debug, Default)]
-struct SHeader {
-    sname: String,
-    stype: SType,
-    sflags: SFlags,
-    vaddr: u64,
-    offset: u64,
-    size: u64,
-    link: u32,
-    info: u32,
-    align: u64,
-    entsize: u64,
+#[derive(Debug, Default, Clone)]
+pub struct SHeader {
+    pub sname: String,
+    pub stype: SType,
+    // sh_type as stored
+    pub type_id: u32,
+    pub sflags: SFlags,
+    // sh_flags as stored, sflags only names a few single flags and
+    // combinations
+    pub flags: u64,
+    pub vaddr: u64,
+    pub offset: u64,
+    pub size: u64,
+    pub link: u32,
+    pub info: u32,
+    pub align: u64,
+    pub entsize: u64,
 }
 
-pub fn parse(content: &Vec<u8>,
+pub fn parse(content: &[u8],
              shdr_offset: u64,
              class: Class,
              endian: Endian,
              strtab_start: u64,
-             ) -> Result<(), &'static str> {
+             ) -> Result<SHeader, &'static str> {
     
     // cursor
     let mut cursor: usize = shdr_offset as usize;
-    let width: usize;
-    let mut buff: &[u8];
-
-    if class == Class::X32Bit {
-        width = 0x04;
+    let width: usize = if class == Class::X32Bit {
+        0x04
     }
     else {
-        width = 0x08;
+        0x08
+    };
+    let mut buff: &[u8];
+
+    let entry_sz: usize = if class == Class::X32Bit { 0x28 } else { 0x40 };
+    let end: usize = cursor.checked_add(entry_sz).ok_or("Section header out of bounds.")?;
+    if end > content.len() {
+        return Err("Section header out of bounds.");
     }
 
     let mut s_header: SHeader = SHeader {
@@ -48,16 +57,14 @@ pub fn parse(content: &Vec<u8>,
         _ => return Err("Unkown error"),
     };
     
-    let mut byte: u8 = content[(strtab_start + name_offset as u64)
-                               as usize];
-    let mut i: usize = 1;
+    let mut i: usize = strtab_start.checked_add(name_offset as u64)
+                                   .ok_or("Section name out of bounds.")? as usize;
     let mut section_name: String = String::from("");
-    while byte != 0x00 {
-        section_name.push(byte as char);
-        byte = content[(strtab_start + name_offset as u64) as usize + i];
+    while i < content.len() && content[i] != 0x00 {
+        section_name.push(content[i] as char);
         i += 1;
     }
-    s_header.sname = section_name; 
+    s_header.sname = section_name;
     cursor += 0x04;
     
     // section type
@@ -87,28 +94,32 @@ pub fn parse(content: &Vec<u8>,
        0x11 => SType::GROUP,
        0x12 => SType::SYMTAB_SHNDX,
        0x13 => SType::NUM,
+       0x6ffffff6 => SType::GNU_HASH,
+       0x6ffffffd => SType::GNU_VERDEF,
+       0x6ffffffe => SType::GNU_VERNEED,
+       0x6fffffff => SType::GNU_VERSYM,
        _ => SType::NONE,
     };
+    s_header.type_id = stype;
     cursor += 0x04;
 
     // Flags
     buff = &content[cursor..(cursor + width)];
-    let sflags: u64;
-    if class == Class::X64Bit {
-        sflags = match endian {
+    let sflags: u64 = if class == Class::X64Bit {
+        match endian {
             Endian::Little => buff.read_u64::<LittleEndian>().unwrap(),
             Endian::Big => buff.read_u64::<BigEndian>().unwrap(),
             _ => return Err("TBD"),
-        };
+        }
     }
     else {
-        sflags = match endian {
+        match endian {
             Endian::Little => buff.read_u32::<LittleEndian>().unwrap()
                 as u64,
             Endian::Big => buff.read_u32::<BigEndian>().unwrap() as u64,
             _ => return Err("TBD"),
-        };
-    }
+        }
+    };
     s_header.sflags = match sflags {
         0x01 => SFlags::WRITE,
         0x02 => SFlags::ALLOC,
@@ -120,6 +131,7 @@ pub fn parse(content: &Vec<u8>,
         0x100 => SFlags::OS_NONCONFORMING,
         0x200 => SFlags::GROUP,
         0x400 => SFlags::TLS,
+        0x800 => SFlags::COMPRESSED,
         0x0ff00000 => SFlags::MASKOS,
         0xf0000000 => SFlags::MASKPROC,
         0x4000000 => SFlags::ORDERED,
@@ -129,6 +141,7 @@ pub fn parse(content: &Vec<u8>,
         0x30 => SFlags::MERGE_STRINGS,
         _ => SFlags::NONE,
     };
+    s_header.flags = sflags;
     cursor += width;
 
     // vaddr
@@ -257,9 +270,7 @@ pub fn parse(content: &Vec<u8>,
         },
         _ => return Err("TBD"),
     };
-    cursor += width;
 
-    // end of program header
-    dbg!(s_header);
-    Ok(())
+    // end of section header
+    Ok(s_header)
 }
@@ -1,4 +1,7 @@
-#[derive(Debug, Default)]
+// Variant names mirror the constants from the ELF specification.
+#![allow(non_camel_case_types)]
+
+#[derive(Debug, Default, PartialEq, Copy, Clone)]
 pub enum SType {
     #[default]
     NULL,
@@ -19,10 +22,21 @@ pub enum SType {
     GROUP,
     SYMTAB_SHNDX,
     NUM,
+    GNU_HASH,
+    GNU_VERDEF,
+    GNU_VERNEED,
+    GNU_VERSYM,
     NONE,
 }
 
-#[derive(Debug, Default)]
+// sh_flags bit of sections taking memory when the object is loaded
+pub const SHF_ALLOC: u64 = 0x2;
+// sh_flags bit of thread local data, .tdata and .tbss
+pub const SHF_TLS: u64 = 0x400;
+// sh_flags bit of sections whose data starts with a compression header
+pub const SHF_COMPRESSED: u64 = 0x800;
+
+#[derive(Debug, Default, PartialEq, Copy, Clone)]
 pub enum SFlags {
     #[default]
     NULL,
@@ -39,6 +53,7 @@ pub enum SFlags {
     OS_NONCONFORMING,
     GROUP,
     TLS,
+    COMPRESSED,
     MASKOS,
     MASKPROC,
     ORDERED,
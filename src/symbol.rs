@@ -0,0 +1,307 @@
+use crate::Class;
+use crate::debug_info::Reader;
+use crate::dynamic::{self, Dynamic};
+use crate::dynamic::dyn_consts::DT_SYMTAB;
+use crate::elf::{self, Elf};
+use crate::section_header::SHeader;
+use crate::section_header::shdr_enums::{SType, SHF_ALLOC};
+pub mod sym_consts;
+pub mod version;
+pub mod hash;
+use self::sym_consts::*;
+use self::hash::HashTables;
+
+// One entry of a symbol table. `table` names the table it was read
+// from, .symtab, .dynsym or .gnu_debugdata for MiniDebugInfo.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Symbol {
+    pub name: String,
+    pub value: u64,
+    pub size: u64,
+    pub bind: u8,
+    pub stype: u8,
+    pub visibility: u8,
+    pub shndx: u16,
+    pub table: &'static str,
+}
+
+impl Symbol {
+    pub fn is_defined(&self) -> bool {
+        self.shndx != SHN_UNDEF
+    }
+
+    // symbols an address can be attributed to
+    pub fn is_code_or_data(&self) -> bool {
+        matches!(self.stype, STT_FUNC | STT_OBJECT | STT_GNU_IFUNC | STT_NOTYPE)
+            && self.is_defined() && self.shndx != SHN_ABS && !self.name.is_empty()
+    }
+}
+
+pub fn bind_name(bind: u8) -> Option<&'static str> {
+    match bind {
+        STB_LOCAL => Some("LOCAL"),
+        STB_GLOBAL => Some("GLOBAL"),
+        STB_WEAK => Some("WEAK"),
+        STB_GNU_UNIQUE => Some("UNIQUE"),
+        _ => None,
+    }
+}
+
+pub fn type_name(stype: u8) -> Option<&'static str> {
+    match stype {
+        STT_NOTYPE => Some("NOTYPE"),
+        STT_OBJECT => Some("OBJECT"),
+        STT_FUNC => Some("FUNC"),
+        STT_SECTION => Some("SECTION"),
+        STT_FILE => Some("FILE"),
+        STT_COMMON => Some("COMMON"),
+        STT_TLS => Some("TLS"),
+        STT_GNU_IFUNC => Some("IFUNC"),
+        _ => None,
+    }
+}
+
+pub fn visibility_name(visibility: u8) -> Option<&'static str> {
+    match visibility {
+        STV_DEFAULT => Some("DEFAULT"),
+        STV_INTERNAL => Some("INTERNAL"),
+        STV_HIDDEN => Some("HIDDEN"),
+        STV_PROTECTED => Some("PROTECTED"),
+        _ => None,
+    }
+}
+
+// Parse the symbols of a SHT_SYMTAB or SHT_DYNSYM section, names come
+// from the string table in its sh_link
+pub fn parse(elf: &Elf, sheader: &SHeader,
+             table: &'static str) -> Result<Vec<Symbol>, &'static str> {
+    let data: &[u8] = elf.section_data(sheader)?;
+    let strtab: &[u8] = match elf.sheaders.get(sheader.link as usize) {
+        Some(strtab) => elf.section_data(strtab)?,
+        None => &[],
+    };
+    parse_table(elf, data, strtab, table)
+}
+
+// symbols of a symbol table in `data` with names from `strtab`
+fn parse_table(elf: &Elf, data: &[u8], strtab: &[u8],
+               table: &'static str) -> Result<Vec<Symbol>, &'static str> {
+    let is_64: bool = elf.header.class != Class::X32Bit;
+    let entry_size: usize = if is_64 { 24 } else { 16 };
+
+    let mut symbols: Vec<Symbol> = Vec::with_capacity(data.len() / entry_size);
+    let mut reader: Reader = Reader::new(data, 0, elf.header.endian);
+    while reader.offset + entry_size <= data.len() {
+        let name_offset: u32 = reader.u32()?;
+        let (value, size, info, other, shndx): (u64, u64, u8, u8, u16) = if is_64 {
+            let info: u8 = reader.u8()?;
+            let other: u8 = reader.u8()?;
+            let shndx: u16 = reader.u16()?;
+            (reader.u64()?, reader.u64()?, info, other, shndx)
+        }
+        else {
+            let value: u64 = reader.u32()? as u64;
+            let size: u64 = reader.u32()? as u64;
+            (value, size, reader.u8()?, reader.u8()?, reader.u16()?)
+        };
+        let name: &[u8] = Reader::new(strtab, name_offset as usize, elf.header.endian)
+                                 .cstr().unwrap_or(&[]);
+        symbols.push(Symbol {
+            name: String::from_utf8_lossy(name).into_owned(),
+            value,
+            size,
+            bind: info >> 4,
+            stype: info & 0xf,
+            visibility: other & 0x3,
+            shndx,
+            table,
+        });
+    }
+    Ok(symbols)
+}
+
+// The symbols of .dynsym. Without section headers the table is found
+// through DT_SYMTAB and its size through the hash tables.
+pub fn dynamic_symbols(elf: &Elf) -> Result<Vec<Symbol>, &'static str> {
+    if let Some(sheader) = elf.sheaders.iter().find(|s| s.stype == SType::DYNSYM) {
+        return parse(elf, sheader, ".dynsym");
+    }
+    let dynamic: Dynamic = match dynamic::parse(elf)? {
+        Some(dynamic) => dynamic,
+        None => return Ok(Vec::new()),
+    };
+    let count: usize = match HashTables::load(elf)?.symbol_count() {
+        Some(count) => count,
+        None => return Ok(Vec::new()),
+    };
+    let entry_size: usize = if elf.header.class == Class::X32Bit { 16 } else { 24 };
+    let data: &[u8] = dynamic.get(DT_SYMTAB)
+                             .and_then(|addr| dynamic::data_at(elf, addr))
+                             .ok_or("DT_SYMTAB outside the loaded segments.")?;
+    let data: &[u8] = data.get(..count * entry_size).ok_or("Dynamic symbols out of bounds.")?;
+    parse_table(elf, data, dynamic::strtab(elf, &dynamic)?, ".dynsym")
+}
+
+// The ELF file embedded in .gnu_debugdata (MiniDebugInfo), an xz
+// compressed object holding the .symtab removed from a stripped binary
+pub fn mini_debuginfo(elf: &Elf) -> Result<Option<Elf>, &'static str> {
+    let sheader: &SHeader = match elf.section(".gnu_debugdata") {
+        Some(sheader) => sheader,
+        None => return Ok(None),
+    };
+    let mut data: &[u8] = elf.section_data(sheader)?;
+    let mut content: Vec<u8> = Vec::new();
+    lzma_rs::xz_decompress(&mut data, &mut content)
+        .map_err(|_| "Invalid xz data in .gnu_debugdata.")?;
+    Ok(Some(elf::parse(content)?))
+}
+
+// Symbols of an object sorted by address for lookups
+#[derive(Debug, Default, Clone)]
+pub struct SymbolTable {
+    pub symbols: Vec<Symbol>,
+    // address ranges of the allocated sections, sorted, bounding the
+    // symbols without a size
+    pub sections: Vec<(u64, u64)>,
+}
+
+impl SymbolTable {
+    // Build from the .symtab and .dynsym of `elf` and the .symtab of its
+    // MiniDebugInfo when present. A symbol already known at the same
+    // address under the same name is not added twice.
+    pub fn load(elf: &Elf) -> Result<SymbolTable, &'static str> {
+        let mut symbols: Vec<Symbol> = Vec::new();
+        for sheader in elf.sheaders.iter() {
+            match sheader.stype {
+                SType::SYMTAB => symbols.extend(parse(elf, sheader, ".symtab")?),
+                SType::DYNSYM => symbols.extend(parse(elf, sheader, ".dynsym")?),
+                _ => (),
+            }
+        }
+        if let Some(mini) = mini_debuginfo(elf)? {
+            for sheader in mini.sheaders.iter().filter(|s| s.stype == SType::SYMTAB) {
+                symbols.extend(parse(&mini, sheader, ".gnu_debugdata")?);
+            }
+        }
+
+        let mut sections: Vec<(u64, u64)> = elf.sheaders.iter()
+            .filter(|s| s.flags & SHF_ALLOC != 0 && s.size != 0)
+            .map(|s| (s.vaddr, s.vaddr.saturating_add(s.size)))
+            .collect();
+        sections.sort();
+        let mut table: SymbolTable = SymbolTable {
+            symbols: Vec::new(),
+            sections,
+        };
+        table.merge(symbols);
+        Ok(table)
+    }
+
+    // add symbols, for instance those of a separate debug file
+    pub fn merge(&mut self, symbols: Vec<Symbol>) {
+        self.symbols.extend(symbols.into_iter().filter(|s| s.is_code_or_data()));
+        self.symbols.sort_by(|a, b| a.value.cmp(&b.value).then_with(|| a.name.cmp(&b.name)));
+        self.symbols.dedup_by(|a, b| a.value == b.value && a.name == b.name);
+    }
+
+    // The symbol containing `addr` and the offset of `addr` into it.
+    // Symbols without a size cover the addresses up to the next symbol
+    // and never past the end of their section, only their own address
+    // when no section holds them. Functions are preferred over other
+    // symbols at the same address.
+    pub fn lookup(&self, addr: u64) -> Option<(&Symbol, u64)> {
+        let end: usize = self.symbols.partition_point(|s| s.value <= addr);
+        let value: u64 = self.symbols[..end].last()?.value;
+        let start: usize = self.symbols[..end].partition_point(|s| s.value < value);
+        let candidates: &[Symbol] = &self.symbols[start..end];
+        // the next symbol starts past `addr`, so only the section is left
+        // to bound a symbol without a size
+        let section_end: Option<u64> = self.sections.iter()
+            .filter(|(first, last)| *first <= value && value < *last)
+            .map(|(_, last)| *last)
+            .min();
+        let best: &Symbol = candidates.iter()
+            .filter(|s| match s.size {
+                0 => addr == s.value || section_end.is_some_and(|last| addr < last),
+                size => addr < s.value.wrapping_add(size),
+            })
+            .max_by_key(|s| (s.stype == STT_FUNC, s.bind != STB_LOCAL, s.size))?;
+        Some((best, addr - best.value))
+    }
+
+    pub fn by_name(&self, name: &str) -> Vec<&Symbol> {
+        self.symbols.iter().filter(|s| s.name == name).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn symbol(name: &str, value: u64, size: u64, stype: u8) -> Symbol {
+        Symbol {
+            name: name.to_string(),
+            value,
+            size,
+            bind: STB_GLOBAL,
+            stype,
+            shndx: 1,
+            table: ".symtab",
+            ..Default::default()
+        }
+    }
+
+    fn table() -> SymbolTable {
+        let mut table: SymbolTable = SymbolTable {
+            symbols: Vec::new(),
+            sections: vec![(0x1000, 0x1100), (0x2000, 0x2010)],
+        };
+        table.merge(vec![symbol("main", 0x1000, 0x20, STT_FUNC),
+                         symbol("main_label", 0x1000, 0, STT_NOTYPE),
+                         symbol("_start", 0x1040, 0, STT_NOTYPE),
+                         symbol("tail", 0x1080, 0, STT_NOTYPE),
+                         symbol("counter", 0x2000, 8, STT_OBJECT),
+                         symbol("outside", 0x3000, 0, STT_NOTYPE)]);
+        table
+    }
+
+    fn lookup(table: &SymbolTable, addr: u64) -> Option<(&str, u64)> {
+        table.lookup(addr).map(|(s, offset)| (s.name.as_str(), offset))
+    }
+
+    #[test]
+    fn sized_symbols() {
+        let table: SymbolTable = table();
+        // the function wins over the label at the same address
+        assert_eq!(lookup(&table, 0x1000), Some(("main", 0)));
+        assert_eq!(lookup(&table, 0x101f), Some(("main", 0x1f)));
+        assert_eq!(lookup(&table, 0x2007), Some(("counter", 7)));
+        // past the size the label without one still covers the address
+        assert_eq!(lookup(&table, 0x1020), Some(("main_label", 0x20)));
+        assert_eq!(lookup(&table, 0x2008), None);
+    }
+
+    #[test]
+    fn symbols_without_a_size() {
+        let table: SymbolTable = table();
+        assert_eq!(lookup(&table, 0x1040), Some(("_start", 0)));
+        // up to the next symbol
+        assert_eq!(lookup(&table, 0x107f), Some(("_start", 0x3f)));
+        // the last one up to the end of its section
+        assert_eq!(lookup(&table, 0x10ff), Some(("tail", 0x7f)));
+        assert_eq!(lookup(&table, 0x1100), None);
+        assert_eq!(lookup(&table, 0x1fff), None);
+        // outside every section only its own address
+        assert_eq!(lookup(&table, 0x3000), Some(("outside", 0)));
+        assert_eq!(lookup(&table, 0x3001), None);
+    }
+
+    #[test]
+    fn addresses_out_of_range() {
+        let table: SymbolTable = table();
+        assert_eq!(lookup(&table, 0), None);
+        assert_eq!(lookup(&table, 0xfff), None);
+        assert_eq!(lookup(&table, u64::MAX), None);
+        assert_eq!(lookup(&SymbolTable::default(), 0x1000), None);
+    }
+}
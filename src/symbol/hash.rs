@@ -0,0 +1,466 @@
+use crate::Class;
+use crate::debug_info::Reader;
+use crate::dynamic::{self, Dynamic};
+use crate::dynamic::dyn_consts::*;
+use crate::elf::Elf;
+use crate::section_header::shdr_enums::SType;
+use super::Symbol;
+use super::sym_consts::*;
+
+// the hash function of the SysV .hash table
+pub fn sysv_hash(name: &[u8]) -> u32 {
+    let mut h: u32 = 0;
+    for c in name {
+        h = (h << 4).wrapping_add(*c as u32);
+        let g: u32 = h & 0xf000_0000;
+        if g != 0 {
+            h ^= g >> 24;
+        }
+        h &= !g;
+    }
+    h
+}
+
+// the hash function of .gnu.hash (Bernstein's djb2)
+pub fn gnu_hash(name: &[u8]) -> u32 {
+    name.iter().fold(5381u32, |h, c| h.wrapping_mul(33).wrapping_add(*c as u32))
+}
+
+// The SysV hash table: every symbol of .dynsym is in the chain of the
+// bucket of its hash
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SysvHash {
+    pub buckets: Vec<u32>,
+    pub chains: Vec<u32>,
+}
+
+impl SysvHash {
+    pub fn parse(data: &[u8], elf: &Elf) -> Result<SysvHash, &'static str> {
+        let mut reader: Reader = Reader::new(data, 0, elf.header.endian);
+        let nbucket: usize = reader.u32()? as usize;
+        let nchain: usize = reader.u32()? as usize;
+        if nbucket.saturating_add(nchain).saturating_mul(4) > data.len() {
+            return Err("Hash table out of bounds.");
+        }
+        let mut table: SysvHash = SysvHash {
+            buckets: Vec::with_capacity(nbucket),
+            chains: Vec::with_capacity(nchain),
+        };
+        for _ in 0..nbucket {
+            table.buckets.push(reader.u32()?);
+        }
+        for _ in 0..nchain {
+            table.chains.push(reader.u32()?);
+        }
+        Ok(table)
+    }
+
+    // nchain, the number of symbols in .dynsym
+    pub fn symbol_count(&self) -> usize {
+        self.chains.len()
+    }
+
+    // the symbol indexes in the chain of one bucket
+    fn walk(&self, bucket: usize) -> Vec<usize> {
+        let mut indexes: Vec<usize> = Vec::new();
+        let mut idx: usize = self.buckets[bucket] as usize;
+        // a cycle would loop forever, no chain is longer than nchain
+        while idx != 0 && idx < self.chains.len() && indexes.len() < self.chains.len() {
+            indexes.push(idx);
+            idx = self.chains[idx] as usize;
+        }
+        indexes
+    }
+
+    // the candidates for `name`, the symbols in the chain of its bucket
+    pub fn chain(&self, name: &str) -> Vec<usize> {
+        if self.buckets.is_empty() {
+            return Vec::new();
+        }
+        self.walk(sysv_hash(name.as_bytes()) as usize % self.buckets.len())
+    }
+
+    pub fn chain_lengths(&self) -> Vec<usize> {
+        (0..self.buckets.len()).map(|b| self.walk(b).len()).collect()
+    }
+}
+
+// The GNU hash table. Symbols from `symoffset` on are sorted by bucket,
+// each with its hash in `chain` whose lowest bit ends a bucket. A bloom
+// filter rejects most names that are not defined before the buckets
+// are looked at.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct GnuHash {
+    pub symoffset: u32,
+    pub bloom_shift: u32,
+    // bits per bloom word, the ELF class size
+    pub word_bits: u32,
+    pub bloom: Vec<u64>,
+    pub buckets: Vec<u32>,
+    pub chain: Vec<u32>,
+}
+
+impl GnuHash {
+    pub fn parse(data: &[u8], elf: &Elf) -> Result<GnuHash, &'static str> {
+        let mut reader: Reader = Reader::new(data, 0, elf.header.endian);
+        let nbuckets: usize = reader.u32()? as usize;
+        let symoffset: u32 = reader.u32()?;
+        let bloom_size: usize = reader.u32()? as usize;
+        let bloom_shift: u32 = reader.u32()?;
+        let word_bits: u32 = if elf.header.class == Class::X32Bit { 32 } else { 64 };
+        if (word_bits as usize / 8).saturating_mul(bloom_size)
+                                   .saturating_add(nbuckets.saturating_mul(4)) > data.len() {
+            return Err("GNU hash table out of bounds.");
+        }
+
+        let mut table: GnuHash = GnuHash {
+            symoffset,
+            bloom_shift,
+            word_bits,
+            ..Default::default()
+        };
+        for _ in 0..bloom_size {
+            table.bloom.push(if word_bits == 32 { reader.u32()? as u64 } else { reader.u64()? });
+        }
+        for _ in 0..nbuckets {
+            table.buckets.push(reader.u32()?);
+        }
+        // the chain has no stored length, it ends with the last entry of
+        // the bucket starting at the highest index
+        if let Some(last) = table.buckets.iter().copied().filter(|b| *b >= symoffset).max() {
+            let mut idx: u32 = symoffset;
+            loop {
+                let hash: u32 = reader.u32().map_err(|_| "GNU hash chain without end.")?;
+                table.chain.push(hash);
+                if idx >= last && hash & 1 != 0 {
+                    break;
+                }
+                idx += 1;
+            }
+        }
+        Ok(table)
+    }
+
+    // symbols before symoffset and those in the chain
+    pub fn symbol_count(&self) -> usize {
+        self.symoffset as usize + self.chain.len()
+    }
+
+    // whether the bloom filter lets a hash through
+    pub fn bloom_accepts(&self, hash: u32) -> bool {
+        if self.bloom.is_empty() {
+            return false;
+        }
+        let bits: u32 = self.word_bits;
+        let word: u64 = self.bloom[(hash / bits) as usize % self.bloom.len()];
+        let second: u32 = hash.checked_shr(self.bloom_shift).unwrap_or(0);
+        let mask: u64 = (1u64 << (hash % bits)) | (1u64 << (second % bits));
+        word & mask == mask
+    }
+
+    // the symbol indexes of one bucket and their hashes
+    fn walk(&self, bucket: usize) -> Vec<(usize, u32)> {
+        let mut entries: Vec<(usize, u32)> = Vec::new();
+        let mut idx: usize = self.buckets[bucket] as usize;
+        if idx < self.symoffset as usize {
+            return entries;
+        }
+        while let Some(hash) = self.chain.get(idx - self.symoffset as usize) {
+            entries.push((idx, *hash));
+            if hash & 1 != 0 {
+                break;
+            }
+            idx += 1;
+        }
+        entries
+    }
+
+    // the candidates for `name`: the symbols of its bucket with the same
+    // hash, none when the bloom filter rejects it
+    pub fn chain(&self, name: &str) -> Vec<usize> {
+        let hash: u32 = gnu_hash(name.as_bytes());
+        if self.buckets.is_empty() || !self.bloom_accepts(hash) {
+            return Vec::new();
+        }
+        self.walk(hash as usize % self.buckets.len()).into_iter()
+            .filter(|(_, h)| (h | 1) == (hash | 1))
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+
+    pub fn chain_lengths(&self) -> Vec<usize> {
+        (0..self.buckets.len()).map(|b| self.walk(b).len()).collect()
+    }
+}
+
+// The hash tables of an object, from the sections or, without section
+// headers, from DT_HASH and DT_GNU_HASH
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct HashTables {
+    pub sysv: Option<SysvHash>,
+    pub gnu: Option<GnuHash>,
+}
+
+impl HashTables {
+    pub fn load(elf: &Elf) -> Result<HashTables, &'static str> {
+        let mut tables: HashTables = HashTables::default();
+        for sheader in elf.sheaders.iter() {
+            match sheader.stype {
+                SType::HASH => tables.sysv = Some(SysvHash::parse(elf.section_data(sheader)?, elf)?),
+                SType::GNU_HASH => {
+                    tables.gnu = Some(GnuHash::parse(elf.section_data(sheader)?, elf)?)
+                },
+                _ => (),
+            }
+        }
+        if tables.sysv.is_some() || tables.gnu.is_some() {
+            return Ok(tables);
+        }
+
+        let dynamic: Dynamic = match dynamic::parse(elf)? {
+            Some(dynamic) => dynamic,
+            None => return Ok(tables),
+        };
+        if let Some(data) = dynamic.get(DT_HASH).and_then(|addr| dynamic::data_at(elf, addr)) {
+            tables.sysv = Some(SysvHash::parse(data, elf)?);
+        }
+        if let Some(data) = dynamic.get(DT_GNU_HASH).and_then(|addr| dynamic::data_at(elf, addr)) {
+            tables.gnu = Some(GnuHash::parse(data, elf)?);
+        }
+        Ok(tables)
+    }
+
+    // the number of .dynsym symbols, which both tables imply
+    pub fn symbol_count(&self) -> Option<usize> {
+        match (self.sysv.as_ref(), self.gnu.as_ref()) {
+            (Some(sysv), _) => Some(sysv.symbol_count()),
+            (None, Some(gnu)) => Some(gnu.symbol_count()),
+            (None, None) => None,
+        }
+    }
+
+    // The .dynsym symbol called `name` through the GNU table, or else the
+    // SysV one. Non-default versions are found as well.
+    pub fn lookup<'a>(&self, name: &str, symbols: &'a [Symbol]) -> Option<(usize, &'a Symbol)> {
+        let candidates: Vec<usize> = match (self.gnu.as_ref(), self.sysv.as_ref()) {
+            (Some(gnu), _) => gnu.chain(name),
+            (None, Some(sysv)) => sysv.chain(name),
+            (None, None) => return None,
+        };
+        candidates.into_iter()
+                  .filter_map(|idx| symbols.get(idx).map(|sym| (idx, sym)))
+                  .find(|(_, sym)| sym.name == name)
+    }
+}
+
+// Chain statistics of one hash table and the exported symbols it does
+// not lead to
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct HashCheck {
+    pub table: &'static str,
+    pub symbol_count: usize,
+    pub chain_lengths: Vec<usize>,
+    pub unreachable: Vec<usize>,
+}
+
+impl HashCheck {
+    pub fn buckets(&self) -> usize {
+        self.chain_lengths.len()
+    }
+
+    pub fn max_chain(&self) -> usize {
+        self.chain_lengths.iter().copied().max().unwrap_or(0)
+    }
+
+    // the average number of symbols compared per successful lookup
+    pub fn average_chain(&self) -> f64 {
+        let symbols: usize = self.chain_lengths.iter().sum();
+        let probes: usize = self.chain_lengths.iter().map(|l| l * (l + 1) / 2).sum();
+        if symbols == 0 { 0.0 } else { probes as f64 / symbols as f64 }
+    }
+
+    // the number of buckets per chain length, as readelf --histogram
+    pub fn histogram(&self) -> Vec<usize> {
+        let mut counts: Vec<usize> = vec![0; self.max_chain() + 1];
+        for length in self.chain_lengths.iter() {
+            counts[*length] += 1;
+        }
+        counts
+    }
+}
+
+// exported symbols, the ones other objects look up
+fn exported(sym: &Symbol) -> bool {
+    sym.is_defined() && sym.bind != STB_LOCAL && !sym.name.is_empty()
+        && !matches!(sym.visibility, STV_HIDDEN | STV_INTERNAL)
+}
+
+// Check that every exported symbol of `symbols` (.dynsym) is reachable
+// through each hash table of the object
+pub fn check(tables: &HashTables, symbols: &[Symbol]) -> Vec<HashCheck> {
+    let mut checks: Vec<HashCheck> = Vec::new();
+    let unreachable = |chain: &dyn Fn(&str) -> Vec<usize>| -> Vec<usize> {
+        symbols.iter().enumerate()
+               .filter(|(idx, sym)| exported(sym) && !chain(&sym.name).contains(idx))
+               .map(|(idx, _)| idx)
+               .collect()
+    };
+    if let Some(sysv) = tables.sysv.as_ref() {
+        checks.push(HashCheck {
+            table: ".hash",
+            symbol_count: sysv.symbol_count(),
+            chain_lengths: sysv.chain_lengths(),
+            unreachable: unreachable(&|name| sysv.chain(name)),
+        });
+    }
+    if let Some(gnu) = tables.gnu.as_ref() {
+        checks.push(HashCheck {
+            table: ".gnu.hash",
+            symbol_count: gnu.symbol_count(),
+            chain_lengths: gnu.chain_lengths(),
+            unreachable: unreachable(&|name| gnu.chain(name)),
+        });
+    }
+    checks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::elf;
+
+    // the ELF header of an empty little endian ELF64 file, the tables
+    // only take the class and byte order from it
+    fn elf64() -> Elf {
+        let mut content: Vec<u8> = vec![0; 64];
+        content[..7].copy_from_slice(&[0x7f, b'E', b'L', b'F', 2, 1, 1]);
+        content[16] = 3;
+        content[52] = 64;
+        elf::parse(content).unwrap()
+    }
+
+    fn symbols(names: &[&str]) -> Vec<Symbol> {
+        let mut symbols: Vec<Symbol> = vec![Symbol::default()];
+        symbols.extend(names.iter().map(|name| Symbol {
+            name: name.to_string(),
+            bind: STB_GLOBAL,
+            stype: STT_FUNC,
+            shndx: 1,
+            ..Default::default()
+        }));
+        symbols
+    }
+
+    // .hash with `nbucket` buckets over the symbols, chained as ld does
+    fn sysv_table(symbols: &[Symbol], nbucket: u32) -> Vec<u8> {
+        let mut buckets: Vec<u32> = vec![0; nbucket as usize];
+        let mut chains: Vec<u32> = vec![0; symbols.len()];
+        for (idx, sym) in symbols.iter().enumerate().skip(1) {
+            let bucket: usize = (sysv_hash(sym.name.as_bytes()) % nbucket) as usize;
+            chains[idx] = buckets[bucket];
+            buckets[bucket] = idx as u32;
+        }
+        [nbucket, chains.len() as u32].iter().chain(buckets.iter()).chain(chains.iter())
+            .flat_map(|v| v.to_le_bytes()).collect()
+    }
+
+    // .gnu.hash over the symbols from `symoffset` on, which must be
+    // sorted by bucket, with a bloom filter of one word
+    fn gnu_table(symbols: &[Symbol], symoffset: u32, nbuckets: u32) -> Vec<u8> {
+        let hashes: Vec<u32> = symbols[symoffset as usize..].iter()
+            .map(|sym| gnu_hash(sym.name.as_bytes())).collect();
+        let mut bloom: u64 = 0;
+        let mut buckets: Vec<u32> = vec![0; nbuckets as usize];
+        let mut chain: Vec<u32> = Vec::new();
+        for (pos, hash) in hashes.iter().enumerate() {
+            bloom |= (1u64 << (hash % 64)) | (1u64 << ((hash >> 6) % 64));
+            let bucket: usize = (hash % nbuckets) as usize;
+            if buckets[bucket] == 0 {
+                buckets[bucket] = symoffset + pos as u32;
+            }
+            let last: bool = hashes.get(pos + 1).is_none_or(|next| next % nbuckets != hash % nbuckets);
+            chain.push(if last { hash | 1 } else { hash & !1 });
+        }
+        let mut out: Vec<u8> = [nbuckets, symoffset, 1, 6].iter()
+            .flat_map(|v| v.to_le_bytes()).collect();
+        out.extend_from_slice(&bloom.to_le_bytes());
+        out.extend(buckets.iter().chain(chain.iter()).flat_map(|v| v.to_le_bytes()));
+        out
+    }
+
+    #[test]
+    fn sysv_lookups() {
+        let elf: Elf = elf64();
+        let symbols: Vec<Symbol> = symbols(&["printf", "puts", "malloc", "free", "exit"]);
+        let sysv: SysvHash = SysvHash::parse(&sysv_table(&symbols, 3), &elf).unwrap();
+        assert_eq!(sysv.symbol_count(), 6);
+        assert_eq!(sysv.chain_lengths().iter().sum::<usize>(), 5);
+        let tables: HashTables = HashTables {
+            sysv: Some(sysv.clone()),
+            gnu: None,
+        };
+        assert_eq!(tables.symbol_count(), Some(6));
+        assert_eq!(tables.lookup("malloc", &symbols).map(|(idx, _)| idx), Some(3));
+        assert_eq!(tables.lookup("realloc", &symbols), None);
+        assert_eq!(check(&tables, &symbols)[0].unreachable, Vec::<usize>::new());
+
+        // a chain cut short hides the symbols behind it
+        let mut broken: SysvHash = sysv;
+        let bucket: usize = (sysv_hash(b"exit") % 3) as usize;
+        broken.buckets[bucket] = 0;
+        let tables: HashTables = HashTables {
+            sysv: Some(broken),
+            gnu: None,
+        };
+        assert!(check(&tables, &symbols)[0].unreachable.contains(&5));
+        assert!(SysvHash::parse(&sysv_table(&symbols, 3)[..20], &elf).is_err());
+    }
+
+    #[test]
+    fn gnu_lookups() {
+        let elf: Elf = elf64();
+        let mut names: Vec<&str> = vec!["printf", "puts", "malloc", "free", "exit"];
+        names.sort_by_key(|name| gnu_hash(name.as_bytes()) % 2);
+        // the first symbol after the null one is not hashed, as an
+        // undefined symbol would not be
+        let mut symbols: Vec<Symbol> = symbols(&names);
+        symbols.insert(1, Symbol {
+            name: "abort".to_string(),
+            ..Default::default()
+        });
+        let gnu: GnuHash = GnuHash::parse(&gnu_table(&symbols, 2, 2), &elf).unwrap();
+        // the symbol count follows from the end of the last chain
+        assert_eq!(gnu.symbol_count(), 7);
+        assert_eq!(gnu.chain.len(), 5);
+        let tables: HashTables = HashTables {
+            sysv: None,
+            gnu: Some(gnu.clone()),
+        };
+        for (idx, sym) in symbols.iter().enumerate().skip(2) {
+            assert_eq!(tables.lookup(&sym.name, &symbols).map(|(i, _)| i), Some(idx));
+        }
+        assert_eq!(tables.lookup("abort", &symbols), None);
+        assert_eq!(check(&tables, &symbols)[0].unreachable, Vec::<usize>::new());
+
+        // names the bloom filter rejects never reach the buckets
+        let rejected: Vec<String> = (0..200).map(|n| format!("missing{n}"))
+            .filter(|name| !gnu.bloom_accepts(gnu_hash(name.as_bytes())))
+            .collect();
+        assert!(!rejected.is_empty());
+        for name in rejected.iter() {
+            assert_eq!(gnu.chain(name), Vec::<usize>::new());
+        }
+        // an empty filter rejects everything
+        let empty: GnuHash = GnuHash {
+            bloom: vec![0],
+            ..gnu.clone()
+        };
+        assert_eq!(empty.chain("puts"), Vec::<usize>::new());
+
+        // a chain without its end bit runs off the table
+        let mut data: Vec<u8> = gnu_table(&symbols, 2, 2);
+        let len: usize = data.len();
+        data[len - 4] &= !1;
+        assert_eq!(GnuHash::parse(&data, &elf), Err("GNU hash chain without end."));
+    }
+}
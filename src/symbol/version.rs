@@ -0,0 +1,213 @@
+use crate::debug_info::Reader;
+use crate::elf::Elf;
+use crate::section_header::SHeader;
+use crate::section_header::shdr_enums::SType;
+
+// special .gnu.version values
+pub const VER_NDX_LOCAL: u16 = 0;
+pub const VER_NDX_GLOBAL: u16 = 1;
+// the symbol is not the default version of its name
+pub const VERSYM_HIDDEN: u16 = 0x8000;
+pub const VERSYM_VERSION: u16 = 0x7fff;
+
+// vd_flags and vna_flags
+pub const VER_FLG_BASE: u16 = 0x1;
+pub const VER_FLG_WEAK: u16 = 0x2;
+
+// A version defined by an object (.gnu.version_d). `parents` are the
+// versions it inherits from.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct VerDef {
+    pub index: u16,
+    pub flags: u16,
+    pub name: String,
+    pub parents: Vec<String>,
+}
+
+// A version an object needs from one of its dependencies
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct VerNeedAux {
+    pub index: u16,
+    pub flags: u16,
+    pub name: String,
+}
+
+// The versions needed from one file (.gnu.version_r)
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct VerNeed {
+    pub file: String,
+    pub versions: Vec<VerNeedAux>,
+}
+
+// The symbol versioning sections of an object. `versym` has one entry
+// per .dynsym symbol and is empty for objects without versions.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Versions {
+    pub versym: Vec<u16>,
+    pub defs: Vec<VerDef>,
+    pub needs: Vec<VerNeed>,
+}
+
+// A version as a symbol refers to it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Version<'a> {
+    // the symbol has no version or is local
+    None,
+    // defined by this object, `hidden` when it is not the default
+    // version (name@VERSION rather than name@@VERSION)
+    Defined { name: &'a str, hidden: bool },
+    // required from the dependency `file`
+    Needed { name: &'a str, file: &'a str, weak: bool },
+}
+
+impl Versions {
+    pub fn is_empty(&self) -> bool {
+        self.versym.is_empty()
+    }
+
+    // the version of the .dynsym symbol at `index`
+    pub fn of(&self, index: usize) -> Version<'_> {
+        let raw: u16 = match self.versym.get(index) {
+            Some(raw) => *raw,
+            None => return Version::None,
+        };
+        let ndx: u16 = raw & VERSYM_VERSION;
+        if ndx == VER_NDX_LOCAL || ndx == VER_NDX_GLOBAL {
+            return Version::None;
+        }
+        if let Some(def) = self.defs.iter().find(|d| d.index == ndx) {
+            return Version::Defined {
+                name: &def.name,
+                hidden: raw & VERSYM_HIDDEN != 0,
+            };
+        }
+        for need in self.needs.iter() {
+            if let Some(aux) = need.versions.iter().find(|v| v.index == ndx) {
+                return Version::Needed {
+                    name: &aux.name,
+                    file: &need.file,
+                    weak: aux.flags & VER_FLG_WEAK != 0,
+                };
+            }
+        }
+        Version::None
+    }
+
+    // the name of the object itself, from the base version definition
+    pub fn base(&self) -> Option<&str> {
+        self.defs.iter().find(|d| d.flags & VER_FLG_BASE != 0).map(|d| d.name.as_str())
+    }
+}
+
+fn string(elf: &Elf, sheader: &SHeader, offset: u32) -> Result<String, &'static str> {
+    let strtab: &[u8] = match elf.sheaders.get(sheader.link as usize) {
+        Some(strtab) => elf.section_data(strtab)?,
+        None => &[],
+    };
+    let name: &[u8] = Reader::new(strtab, offset as usize, elf.header.endian).cstr()?;
+    Ok(String::from_utf8_lossy(name).into_owned())
+}
+
+fn parse_defs(elf: &Elf, sheader: &SHeader) -> Result<Vec<VerDef>, &'static str> {
+    let data: &[u8] = elf.section_data(sheader)?;
+    let mut defs: Vec<VerDef> = Vec::new();
+    let mut offset: usize = 0;
+    // sh_info holds the number of entries, vd_next chains them
+    for _ in 0..sheader.info.max(1) {
+        let mut reader: Reader = Reader::new(data, offset, elf.header.endian);
+        let _version: u16 = reader.u16()?;
+        let flags: u16 = reader.u16()?;
+        let index: u16 = reader.u16()?;
+        let count: u16 = reader.u16()?;
+        let _hash: u32 = reader.u32()?;
+        let aux: u32 = reader.u32()?;
+        let next: u32 = reader.u32()?;
+
+        let mut names: Vec<String> = Vec::new();
+        let mut aux_offset: usize = offset + aux as usize;
+        for _ in 0..count {
+            let mut reader: Reader = Reader::new(data, aux_offset, elf.header.endian);
+            names.push(string(elf, sheader, reader.u32()?)?);
+            let aux_next: u32 = reader.u32()?;
+            if aux_next == 0 {
+                break;
+            }
+            aux_offset += aux_next as usize;
+        }
+        let mut names = names.into_iter();
+        defs.push(VerDef {
+            index,
+            flags,
+            name: names.next().unwrap_or_default(),
+            parents: names.collect(),
+        });
+        if next == 0 {
+            break;
+        }
+        offset += next as usize;
+    }
+    Ok(defs)
+}
+
+fn parse_needs(elf: &Elf, sheader: &SHeader) -> Result<Vec<VerNeed>, &'static str> {
+    let data: &[u8] = elf.section_data(sheader)?;
+    let mut needs: Vec<VerNeed> = Vec::new();
+    let mut offset: usize = 0;
+    for _ in 0..sheader.info.max(1) {
+        let mut reader: Reader = Reader::new(data, offset, elf.header.endian);
+        let _version: u16 = reader.u16()?;
+        let count: u16 = reader.u16()?;
+        let file: u32 = reader.u32()?;
+        let aux: u32 = reader.u32()?;
+        let next: u32 = reader.u32()?;
+
+        let mut need: VerNeed = VerNeed {
+            file: string(elf, sheader, file)?,
+            versions: Vec::new(),
+        };
+        let mut aux_offset: usize = offset + aux as usize;
+        for _ in 0..count {
+            let mut reader: Reader = Reader::new(data, aux_offset, elf.header.endian);
+            let _hash: u32 = reader.u32()?;
+            let flags: u16 = reader.u16()?;
+            let index: u16 = reader.u16()?;
+            let name: u32 = reader.u32()?;
+            let aux_next: u32 = reader.u32()?;
+            need.versions.push(VerNeedAux {
+                index,
+                flags,
+                name: string(elf, sheader, name)?,
+            });
+            if aux_next == 0 {
+                break;
+            }
+            aux_offset += aux_next as usize;
+        }
+        needs.push(need);
+        if next == 0 {
+            break;
+        }
+        offset += next as usize;
+    }
+    Ok(needs)
+}
+
+// Parse .gnu.version, .gnu.version_d and .gnu.version_r
+pub fn parse(elf: &Elf) -> Result<Versions, &'static str> {
+    let mut versions: Versions = Versions::default();
+    for sheader in elf.sheaders.iter() {
+        match sheader.stype {
+            SType::GNU_VERSYM => {
+                let data: &[u8] = elf.section_data(sheader)?;
+                let mut reader: Reader = Reader::new(data, 0, elf.header.endian);
+                while reader.offset + 2 <= data.len() {
+                    versions.versym.push(reader.u16()?);
+                }
+            },
+            SType::GNU_VERDEF => versions.defs = parse_defs(elf, sheader)?,
+            SType::GNU_VERNEED => versions.needs = parse_needs(elf, sheader)?,
+            _ => (),
+        }
+    }
+    Ok(versions)
+}
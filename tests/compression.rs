@@ -0,0 +1,50 @@
+use std::io::Write;
+use dwarf::compression::{self, ELFCOMPRESS_ZLIB, ELFCOMPRESS_ZSTD};
+use dwarf::elf_header::header_enums::{Class, Endian};
+
+// 64 KiB of zeros compressed by zstd -19
+const ZSTD_ZEROS: [u8; 22] = [
+    0x28, 0xb5, 0x2f, 0xfd, 0x04, 0x68, 0x4d, 0x00, 0x00, 0x08, 0x00, 0x01,
+    0x00, 0xfc, 0x7f, 0x1d, 0x08, 0x01, 0xa4, 0x15, 0x57, 0xf1,
+];
+
+fn zlib_zeros() -> Vec<u8> {
+    let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::best());
+    encoder.write_all(&[0; 65536]).unwrap();
+    encoder.finish().unwrap()
+}
+
+// an Elf64_Chdr in front of a compressed stream
+fn section(ctype: u32, size: u64, stream: &[u8]) -> Vec<u8> {
+    let mut data: Vec<u8> = Vec::new();
+    data.extend_from_slice(&ctype.to_le_bytes());
+    data.extend_from_slice(&0u32.to_le_bytes());
+    data.extend_from_slice(&size.to_le_bytes());
+    data.extend_from_slice(&1u64.to_le_bytes());
+    data.extend_from_slice(stream);
+    data
+}
+
+#[test]
+fn sizes_from_the_headers() {
+    let streams: [(u32, Vec<u8>); 2] = [(ELFCOMPRESS_ZLIB, zlib_zeros()),
+                                        (ELFCOMPRESS_ZSTD, ZSTD_ZEROS.to_vec())];
+    for (ctype, stream) in streams {
+        let data: Vec<u8> = section(ctype, 65536, &stream);
+        assert_eq!(compression::decompress(&data, Class::X64Bit, Endian::Little),
+                   Ok(vec![0; 65536]));
+        // a stream inflating past the header size is refused, as is one
+        // ending short of it
+        for size in [16, 65537] {
+            let data: Vec<u8> = section(ctype, size, &stream);
+            assert!(compression::decompress(&data, Class::X64Bit, Endian::Little).is_err());
+        }
+    }
+
+    let mut zdebug: Vec<u8> = b"ZLIB".to_vec();
+    zdebug.extend_from_slice(&16u64.to_be_bytes());
+    zdebug.extend_from_slice(&zlib_zeros());
+    assert!(compression::decompress_zdebug(&zdebug).is_err());
+    zdebug[4..12].copy_from_slice(&65536u64.to_be_bytes());
+    assert_eq!(compression::decompress_zdebug(&zdebug), Ok(vec![0; 65536]));
+}
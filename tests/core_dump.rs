@@ -0,0 +1,106 @@
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+use dwarf::core_dump::{self, CoreDump, MemoryOnly};
+use dwarf::debug_info::{self, DwarfSections};
+use dwarf::debug_info::expression::{Context, Provider};
+use dwarf::debug_info::locals::{self, Frame, Variable};
+use dwarf::debug_info::unit::{self, Unit};
+use dwarf::elf::{self, Elf};
+
+fn path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join("data").join("core").join(name)
+}
+
+fn read(name: &str) -> Vec<u8> {
+    fs::read(path(name)).unwrap()
+}
+
+fn variable<'a>(frame: &'a Frame, name: &str) -> &'a Variable<'a> {
+    frame.variables.iter().find(|v| v.name == name).unwrap()
+}
+
+fn integer(bytes: &[u8]) -> u64 {
+    let mut value: [u8; 8] = [0; 8];
+    value[..bytes.len()].copy_from_slice(bytes);
+    u64::from_le_bytes(value)
+}
+
+#[test]
+fn registers_and_memory() {
+    let elf: Elf = elf::parse(read("crash")).unwrap();
+    let core: CoreDump = core_dump::parse(read("core")).unwrap();
+    let bias: u64 = core.load_bias(elf.header.entry_point);
+    assert_eq!(bias & 0xfff, 0);
+    assert_eq!(core.pc().map(|pc| pc - bias), Some(0x1154));
+    assert_eq!(core.registers.len(), 17);
+    // the stack pointer is in a saved segment, the null pointer is not
+    let sp: u64 = core.register(7).unwrap();
+    assert_eq!(core.read_memory(sp, 8).map(|b| b.len()), Some(8));
+    assert!(core.read_memory(0, 4).is_none());
+    assert!(core.read_memory(u64::MAX - 1, 4).is_none());
+    // memory only, without the registers of the crash
+    assert!(MemoryOnly(&core).register(7).is_none());
+    assert_eq!(MemoryOnly(&core).read_memory(sp, 8), core.read_memory(sp, 8));
+
+    assert_eq!(core_dump::parse(read("crash")).err(), Some("Not a core dump."));
+}
+
+#[test]
+fn locals_at_the_crash() {
+    let elf: Elf = elf::parse(read("crash")).unwrap();
+    let core: CoreDump = core_dump::parse(read("core")).unwrap();
+    let context: Context = Context {
+        load_bias: core.load_bias(elf.header.entry_point),
+        ..Default::default()
+    };
+    let sections: DwarfSections = debug_info::load(&elf);
+    let units: Vec<Unit> = unit::parse_units(&sections).unwrap();
+    let pc: u64 = core.pc().unwrap() - context.load_bias;
+    let frame: Frame = locals::frame_at(&units, pc).unwrap().unwrap();
+    assert_eq!(frame.function, "crash");
+    let names: Vec<(&str, bool)> = frame.variables.iter()
+                                        .map(|v| (v.name.as_str(), v.is_parameter)).collect();
+    assert_eq!(names, [("pair", true), ("count", true), ("local", false), ("null", false)]);
+
+    // the frame base is the CFA, computed from the registers and .eh_frame
+    let cfa: u64 = frame.frame_base(&context, &core).unwrap();
+    assert!(cfa > core.register(7).unwrap());
+    let value = |name: &str| integer(&frame.read(variable(&frame, name), &context, &core).unwrap());
+    assert_eq!(value("count"), 5);
+    assert_eq!(value("local"), 35);
+    assert_eq!(value("null"), 0);
+    let pair: Vec<u8> = core.read_memory(value("pair"), 16).unwrap();
+    assert_eq!((integer(&pair[..4]), integer(&pair[8..])), (11, 0x1122334455667788));
+
+    // in main, without unwinding, only memory of the dump can be read
+    let frame: Frame = locals::frame_at(&units, 0x1170).unwrap().unwrap();
+    assert_eq!(frame.function, "main");
+    let memory: MemoryOnly = MemoryOnly(&core);
+    assert!(frame.read(variable(&frame, "pair"), &context, &memory).is_err());
+    assert!(locals::frame_at(&units, 0x10).unwrap().is_none());
+}
+
+#[test]
+fn locals_mode() {
+    let locals = |pc: Option<&str>| -> Output {
+        let mut command: Command = Command::new(env!("CARGO_BIN_EXE_dwarf"));
+        command.args(["-f", path("crash").to_str().unwrap(),
+                      "--core", path("core").to_str().unwrap(), "--locals"]);
+        command.args(pc).output().unwrap()
+    };
+    let output: Output = locals(None);
+    assert_eq!(output.status.code(), Some(0));
+    let stdout: String = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Function crash at 0x1154\n"), "{stdout}");
+    assert!(stdout.contains("  parameter count: DW_OP_fbreg: -44 = 05 00 00 00 (5)\n"));
+    assert!(stdout.contains("  variable local: DW_OP_fbreg: -28 = 23 00 00 00 (35)\n"));
+    assert!(output.stderr.is_empty());
+
+    // another frame is only read from memory, with a warning
+    let output: Output = locals(Some("0x1170"));
+    let stdout: String = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Function main at 0x1170\n"), "{stdout}");
+    assert!(stdout.contains("  variable pair: DW_OP_fbreg: -32 = <Frame base is not available.>"));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("not the crash address"));
+}
@@ -0,0 +1,20 @@
+#include <stdlib.h>
+
+struct pair {
+	int first;
+	long second;
+};
+
+__attribute__((noinline)) int crash(struct pair *pair, int count)
+{
+	volatile int local = count * 7;
+	int *null = (int *)(long)(pair->first - 11);
+	return *null + local;
+}
+
+int main(int argc, char **argv)
+{
+	struct pair pair = { 11, 0x1122334455667788 };
+	(void)argv;
+	return crash(&pair, argc + 4);
+}
@@ -0,0 +1 @@
+int common_value = 42;
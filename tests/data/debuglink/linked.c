@@ -0,0 +1,12 @@
+#include <stdio.h>
+
+__attribute__((noinline)) static int twice(int value)
+{
+	return value * 2;
+}
+
+int main(int argc, char **argv)
+{
+	printf("%d\n", twice(argc));
+	return 0;
+}
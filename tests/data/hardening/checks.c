@@ -0,0 +1,12 @@
+#include <stdio.h>
+#include <string.h>
+#ifdef RWX
+__asm__(".section .rwx,\"awx\",@progbits\n.byte 0xc3\n.previous");
+#endif
+int main(int argc, char **argv) {
+    char buffer[64];
+    snprintf(buffer, sizeof buffer, "%s", argv[argc - 1]);
+    strcpy(buffer + 32, argv[0] + 1);
+    puts(buffer);
+    return 0;
+}
@@ -0,0 +1,4 @@
+int counter;
+int next(void) {
+    return ++counter;
+}
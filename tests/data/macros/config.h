@@ -0,0 +1,2 @@
+#define CONFIG_SIZE 16
+#define CONFIG_NAME "sample"
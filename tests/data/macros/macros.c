@@ -0,0 +1,12 @@
+#include "config.h"
+
+#define SQUARE(x) ((x) * (x))
+#define LIMIT 100
+#undef LIMIT
+#define LIMIT 200
+
+int main(int argc, char **argv)
+{
+	(void)argv;
+	return SQUARE(argc) + CONFIG_SIZE + LIMIT;
+}
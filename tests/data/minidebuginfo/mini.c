@@ -0,0 +1,19 @@
+#include <stdio.h>
+
+static int counter;
+
+__attribute__((noinline)) static int step(int value)
+{
+	return value * 3 + counter++;
+}
+
+__attribute__((noinline)) int compute(int value)
+{
+	return step(value) + step(value + 1);
+}
+
+int main(int argc, char **argv)
+{
+	printf("%d\n", compute(argc));
+	return 0;
+}
@@ -0,0 +1,17 @@
+struct point {
+	int x;
+	int y;
+};
+
+typedef struct point point_t;
+
+int counter = 1;
+
+int scale(point_t *p, int factor);
+
+int main(int argc, char **argv)
+{
+	point_t p = { argc, 2 };
+	int total = scale(&p, 3);
+	return total + counter;
+}
@@ -0,0 +1,12 @@
+struct point {
+	int x;
+	int y;
+};
+
+static int limit = 100;
+
+int scale(struct point *p, int factor)
+{
+	int sum = p->x * factor + p->y * factor;
+	return sum < limit ? sum : limit;
+}
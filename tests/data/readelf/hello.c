@@ -0,0 +1,9 @@
+#include <stdio.h>
+static int counter;
+int answer = 42;
+__attribute__((section(".data.a_very_long_section_name"))) int tagged = 1;
+int main(void) {
+    counter++;
+    printf("hello %d %d %d\n", answer, counter, tagged);
+    return 0;
+}
@@ -0,0 +1,6 @@
+int value = 3;
+int zero;
+void _start(void) {
+    zero = value;
+    __asm__ volatile("mov $1, %eax\n xor %ebx, %ebx\n int $0x80");
+}
@@ -0,0 +1,7 @@
+__thread int tls_init = 7;
+__thread int tls_zero;
+int bss_value;
+void _start(void) {
+    tls_zero = tls_init + bss_value;
+    __asm__ volatile("mov $60, %eax\n xor %edi, %edi\n syscall");
+}
@@ -0,0 +1,4 @@
+extern int shared_counter;
+int twice(int value) {
+    return 2 * value + shared_counter;
+}
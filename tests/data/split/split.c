@@ -0,0 +1,7 @@
+int shared_counter;
+int twice(int value);
+int main(int argc, char **argv) {
+    (void)argv;
+    shared_counter = argc;
+    return twice(argc);
+}
@@ -0,0 +1,149 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use dwarf::debug_file::{self, AltLink, DebugFile, DebugLink, Method, Resolver};
+use dwarf::elf::{self, Elf};
+
+const LINKED_ID: &str = "ca3827fdcd362b36a552a7caeaa2a9164921c976";
+const COMMON_ID: &str = "bf314e41a97947dcdc446f9012066b98879adfaf";
+
+fn sample(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join("data").join("debuglink")
+        .join(name)
+}
+
+fn load(path: &Path) -> Elf {
+    elf::parse(fs::read(path).unwrap()).unwrap()
+}
+
+// copy the sample `name` to `to`, creating its directory
+fn copy(name: &str, to: &Path) -> PathBuf {
+    fs::create_dir_all(to.parent().unwrap()).unwrap();
+    fs::copy(sample(name), to).unwrap();
+    to.to_path_buf()
+}
+
+fn find(resolver: &Resolver, object: &Path) -> Option<DebugFile> {
+    resolver.find_debug_file(&load(object), object).unwrap()
+}
+
+#[test]
+fn links() {
+    let linked: Elf = load(&sample("linked"));
+    let debug: Elf = load(&sample("linked.debug"));
+    assert_eq!(debug_file::build_id(&linked).map(|id| debug_file::hex(&id)).as_deref(),
+               Some(LINKED_ID));
+    assert_eq!(debug_file::build_id(&debug), debug_file::build_id(&linked));
+    let crc: u32 = crc32fast::hash(&fs::read(sample("linked.debug")).unwrap());
+    assert_eq!(debug_file::debug_link(&linked).unwrap(),
+               Some(DebugLink { name: "linked.debug".to_string(), crc }));
+    let common: Elf = load(&sample("common.debug"));
+    assert_eq!(debug_file::alt_link(&debug).unwrap(),
+               Some(AltLink { name: "common.debug".to_string(),
+                              build_id: debug_file::build_id(&common).unwrap() }));
+    assert_eq!(debug_file::alt_link(&linked).unwrap(), None);
+    assert_eq!(debug_file::debug_link(&debug).unwrap(), None);
+}
+
+#[test]
+fn build_id_paths() {
+    let resolver: Resolver = Resolver::new(vec![PathBuf::from("/a"), PathBuf::from("/b")]);
+    assert_eq!(resolver.build_id_paths(&[0xab, 0xcd, 0xef]),
+               [PathBuf::from("/a/.build-id/ab/cdef.debug"),
+                PathBuf::from("/b/.build-id/ab/cdef.debug")]);
+    assert!(resolver.build_id_paths(&[0xab]).is_empty());
+}
+
+#[test]
+fn found_by_build_id() {
+    let dir: tempfile::TempDir = tempfile::tempdir().unwrap();
+    let object: PathBuf = copy("linked", &dir.path().join("bin").join("linked"));
+    let root: PathBuf = dir.path().join("debug");
+    let resolver: Resolver = Resolver::new(vec![root.clone()]);
+    assert!(find(&resolver, &object).is_none());
+
+    // a file of another build id at the path is not taken
+    let path: PathBuf = root.join(".build-id").join(&LINKED_ID[..2])
+                            .join(format!("{0}.debug", &LINKED_ID[2..]));
+    copy("common.debug", &path);
+    assert!(find(&resolver, &object).is_none());
+    copy("linked.debug", &path);
+    let found: DebugFile = find(&resolver, &object).unwrap();
+    assert_eq!((found.path, found.method), (path, Method::BuildId));
+    assert!(found.elf.section(".debug_info").is_some());
+}
+
+#[test]
+fn found_by_debug_link() {
+    let dir: tempfile::TempDir = tempfile::tempdir().unwrap();
+    let bin: PathBuf = fs::canonicalize(dir.path()).unwrap().join("bin");
+    let object: PathBuf = copy("linked", &bin.join("linked"));
+    let root: PathBuf = dir.path().join("debug");
+    let resolver: Resolver = Resolver::new(vec![root.clone()]);
+
+    // below the root under the directory of the object
+    let path: PathBuf = root.join(bin.strip_prefix("/").unwrap()).join("linked.debug");
+    copy("linked.debug", &path);
+    let found: DebugFile = find(&resolver, &object).unwrap();
+    assert_eq!((found.path, found.method), (path, Method::DebugLink));
+
+    // in the .debug directory next to it, looked at first
+    let path: PathBuf = copy("linked.debug", &bin.join(".debug").join("linked.debug"));
+    assert_eq!(find(&resolver, &object).unwrap().path, path);
+
+    // next to it, but only with the CRC of the link
+    let path: PathBuf = copy("common.debug", &bin.join("linked.debug"));
+    assert_eq!(find(&resolver, &object).unwrap().path, bin.join(".debug").join("linked.debug"));
+    copy("linked.debug", &path);
+    assert_eq!(find(&resolver, &object).unwrap().path, path);
+}
+
+#[test]
+fn supplementary_file() {
+    let dir: tempfile::TempDir = tempfile::tempdir().unwrap();
+    let debug_path: PathBuf = copy("linked.debug", &dir.path().join("linked.debug"));
+    let debug: Elf = load(&debug_path);
+    let root: PathBuf = dir.path().join("debug");
+    let resolver: Resolver = Resolver::new(vec![root.clone()]);
+    assert!(resolver.find_alt_file(&debug, &debug_path).unwrap().is_none());
+
+    // by build id when it is not next to the debug file
+    let by_id: PathBuf = root.join(".build-id").join(&COMMON_ID[..2])
+                             .join(format!("{0}.debug", &COMMON_ID[2..]));
+    copy("common.debug", &by_id);
+    let found: DebugFile = resolver.find_alt_file(&debug, &debug_path).unwrap().unwrap();
+    assert_eq!((found.path, found.method), (by_id.clone(), Method::AltLink));
+
+    // the name in the link is relative to the debug file, and must have
+    // the build id of the link
+    let named: PathBuf = copy("linked.debug", &dir.path().join("common.debug"));
+    let found: DebugFile = resolver.find_alt_file(&debug, &debug_path).unwrap().unwrap();
+    assert_eq!(found.path, by_id);
+    copy("common.debug", &named);
+    let found: DebugFile = resolver.find_alt_file(&debug, &debug_path).unwrap().unwrap();
+    assert_eq!(found.path, named);
+
+    // objects without the link have no supplementary file
+    let linked: Elf = load(&sample("linked"));
+    assert!(resolver.find_alt_file(&linked, &sample("linked")).unwrap().is_none());
+}
+
+#[test]
+fn symbols_and_units_of_the_debug_file() {
+    let dir: tempfile::TempDir = tempfile::tempdir().unwrap();
+    let object: PathBuf = copy("linked", &dir.path().join("linked"));
+    copy("linked.debug", &dir.path().join("linked.debug"));
+    copy("common.debug", &dir.path().join("common.debug"));
+    let output: Output = Command::new(env!("CARGO_BIN_EXE_dwarf"))
+        .args(["-f", object.to_str().unwrap(), "--debug-root", dir.path().to_str().unwrap(),
+               "--symbol", "0x1160", "--units"])
+        .env_remove("DEBUGINFOD_URLS")
+        .output().unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    let stdout: String = String::from_utf8(output.stdout).unwrap();
+    let dir: String = fs::canonicalize(dir.path()).unwrap().display().to_string();
+    assert!(stdout.contains(&format!("Debug file: {dir}/linked.debug (found by DebugLink)\n\
+                                      Supplementary file: {dir}/common.debug\n\
+                                      0x1160: twice [.symtab]\n\
+                                      Unit 0x0: linked.c\n")), "{stdout}");
+}
@@ -0,0 +1,227 @@
+use std::fs;
+use std::path::PathBuf;
+use dwarf::debug_info::{self, DwarfSections};
+use dwarf::debug_info::dw_consts::*;
+use dwarf::debug_info::lint::{self, Problem};
+use dwarf::debug_info::macros::{self, MacroEntry, UnitMacros};
+use dwarf::debug_info::names::{self, NameEntry, NameIndex, NameKind};
+use dwarf::debug_info::producer::{self, UnitSummary};
+use dwarf::debug_info::split::{self, DwoFiles, Package};
+use dwarf::debug_info::unit::{self, Unit};
+use dwarf::elf::{self, Elf};
+
+fn data_dir(dir: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join("data").join(dir)
+}
+
+fn load(name: &str) -> Elf {
+    elf::parse(fs::read(data_dir("readelf").join(name)).unwrap()).unwrap()
+}
+
+fn load_split(name: &str) -> Elf {
+    elf::parse(fs::read(data_dir("split").join(name)).unwrap()).unwrap()
+}
+
+// names of the units, which must all be split units
+fn split_names(units: &[Unit]) -> Vec<String> {
+    units.iter().map(|unit| {
+        assert!(unit.is_split());
+        producer::summarize(unit).name.unwrap_or_default()
+    }).collect()
+}
+
+#[test]
+fn relocated_object_units() {
+    // every string of hello.o is reached through a relocation against
+    // .debug_str or .debug_line_str
+    let elf: Elf = load("hello.o");
+    let sections: DwarfSections = debug_info::load(&elf);
+    let units: Vec<Unit> = unit::parse_units(&sections).unwrap();
+    assert_eq!(units.len(), 1);
+    let summary: UnitSummary = producer::summarize(&units[0]);
+    assert_eq!(summary.name.as_deref(), Some("hello.c"));
+    assert_eq!(summary.comp_dir.as_deref(), Some("/tmp/samples"));
+    assert_eq!(summary.producer.as_deref(),
+               Some("GNU C17 12.2.0 -mtune=generic -march=x86-64 -g -gz -O2 \
+                     -fasynchronous-unwind-tables"));
+    assert_eq!(summary.compiler(), Some("GNU C17 12.2.0"));
+
+    // the hex dumps still show the bytes as stored
+    let info = elf.section(".debug_info").unwrap();
+    assert_ne!(elf.section_data(info).unwrap(), elf.debug_data(info).unwrap());
+}
+
+#[test]
+fn relocated_object_lints_clean() {
+    let elf: Elf = load("hello.o");
+    let sections: DwarfSections = debug_info::load(&elf);
+    assert_eq!(lint::check(&sections), []);
+
+    // unrelocated, DW_AT_ranges of the unit points at the header of
+    // .debug_rnglists
+    let info = elf.section(".debug_info").unwrap();
+    let unrelocated: DwarfSections = DwarfSections {
+        info: elf.section_data(info).unwrap(),
+        ..sections
+    };
+    let problems: Vec<Problem> = lint::check(&unrelocated);
+    assert!(problems.iter().any(|p| p.check == "range-offset"));
+}
+
+#[test]
+fn dwo_files() {
+    // the .dwo files are not in the recorded directory, which is gone,
+    // but next to the executable
+    let elf: Elf = load_split("split");
+    let sections: DwarfSections = debug_info::load(&elf);
+    let dwo_files: DwoFiles = split::load_dwos(&sections, &[data_dir("split")]).unwrap();
+    assert_eq!((dwo_files.files.len(), dwo_files.invalid.len()), (2, 0));
+    let dwos: Vec<DwarfSections> = dwo_files.files.iter().map(debug_info::load).collect();
+    let units: Vec<Unit> = split::units(&sections, &dwos, None).unwrap();
+    assert_eq!(split_names(&units), ["split.c", "other.c"]);
+
+    // an invalid file is skipped for the next one with the same name
+    let dir: tempfile::TempDir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("split.dwo"), b"not an ELF file").unwrap();
+    let dwo_files: DwoFiles = split::load_dwos(&sections, &[dir.path().to_path_buf(),
+                                                          data_dir("split")]).unwrap();
+    assert_eq!(dwo_files.files.len(), 2);
+    assert_eq!(dwo_files.invalid.len(), 1);
+    assert_eq!(dwo_files.invalid[0].path, dir.path().join("split.dwo"));
+
+    // without them the skeleton units are all there is
+    let dwo_files: DwoFiles = split::load_dwos(&sections, &[dir.path().to_path_buf()]).unwrap();
+    assert!(dwo_files.files.is_empty());
+    let units: Vec<Unit> = split::units(&sections, &[], None).unwrap();
+    assert!(units.iter().all(|unit| unit.is_skeleton()));
+}
+
+#[test]
+fn dwp_package() {
+    let elf: Elf = load_split("split");
+    let sections: DwarfSections = debug_info::load(&elf);
+    assert_eq!(split::package_path(&data_dir("split").join("split")),
+               data_dir("split").join("split.dwp"));
+    let dwp: Elf = load_split("split.dwp");
+    let package: Package = Package::load(debug_info::load(&dwp)).unwrap();
+    let index = package.cu_index.as_ref().unwrap();
+    assert_eq!((index.version, index.entries.len()), (2, 2));
+    let units: Vec<Unit> = split::units(&sections, &[], Some(&package)).unwrap();
+    assert_eq!(split_names(&units), ["split.c", "other.c"]);
+    for (unit, skeleton) in units.iter().zip(unit::parse_units(&sections).unwrap()) {
+        assert_eq!(unit.dwo_id(), skeleton.dwo_id());
+    }
+}
+
+// the unit offset, DIE offset and kind of the definitions of `name`
+fn definitions(sections: &DwarfSections, dwos: &[DwarfSections], index: &NameIndex,
+               name: &str) -> Vec<(usize, usize, NameKind)> {
+    let mut found: Vec<(usize, usize, NameKind)> = Vec::new();
+    for entry in index.lookup(name).unwrap() {
+        let (unit, idx) = names::resolve(sections, dwos, None, &entry).unwrap().unwrap();
+        let die = &unit.dies[idx];
+        assert_eq!(unit.sections.string(&die.attr(DW_AT_NAME).unwrap()), Some(name.as_bytes()));
+        if die.attr(DW_AT_DECLARATION).is_none() {
+            found.push((entry.unit_offset, die.offset, NameKind::from_tag(die.tag)));
+        }
+    }
+    found
+}
+
+#[test]
+fn name_indexes() {
+    for (name, source) in [("names-gdb-index", ".gdb_index"), ("names-pubnames", ".debug_pubnames"),
+                           ("names", "in-memory index")] {
+        let elf: Elf = elf::parse(fs::read(data_dir("names").join(name)).unwrap()).unwrap();
+        let sections: DwarfSections = debug_info::load(&elf);
+        let index: NameIndex = names::load(&sections, &[], None).unwrap();
+        assert_eq!(index.source(), source);
+        assert_eq!(definitions(&sections, &[], &index, "scale"),
+                   [(0x12a, 0x18b, NameKind::Function)], "{name}");
+        assert_eq!(definitions(&sections, &[], &index, "main"),
+                   [(0x0, 0x97, NameKind::Function)], "{name}");
+        assert_eq!(definitions(&sections, &[], &index, "point_t"),
+                   [(0x0, 0x55, NameKind::Type)], "{name}");
+        assert_eq!(definitions(&sections, &[], &index, "counter"),
+                   [(0x0, 0x61, NameKind::Variable)], "{name}");
+        // file scope statics are indexed, locals are not
+        assert_eq!(definitions(&sections, &[], &index, "limit"),
+                   [(0x12a, 0x17f, NameKind::Variable)], "{name}");
+        assert!(index.lookup("sum").unwrap().is_empty(), "{name}");
+        assert!(index.lookup("factor").unwrap().is_empty(), "{name}");
+        assert!(index.lookup("missing").unwrap().is_empty(), "{name}");
+    }
+
+    // .gdb_index records the unit only, the GNU pubnames the kind
+    let elf: Elf = elf::parse(fs::read(data_dir("names").join("names-gdb-index")).unwrap()).unwrap();
+    let sections: DwarfSections = debug_info::load(&elf);
+    let entries: Vec<NameEntry> = names::load(&sections, &[], None).unwrap().lookup("scale").unwrap();
+    assert!(entries.iter().all(|e| e.die_offset.is_none() && e.kind == NameKind::Function));
+}
+
+#[test]
+fn split_unit_names() {
+    // gcc writes GNU pubnames for split units, naming the skeleton
+    let elf: Elf = load_split("split");
+    let sections: DwarfSections = debug_info::load(&elf);
+    let dwo_files: DwoFiles = split::load_dwos(&sections, &[data_dir("split")]).unwrap();
+    let dwos: Vec<DwarfSections> = dwo_files.files.iter().map(debug_info::load).collect();
+    let index: NameIndex = names::load(&sections, &dwos, None).unwrap();
+    assert_eq!(index.source(), ".debug_pubnames");
+    let skeletons: Vec<usize> = unit::parse_units(&sections).unwrap().iter()
+                                     .map(|u| u.header.offset).collect();
+    // gcc lists shared_counter twice in the pubnames of split.c
+    for (name, skeleton) in [("main", skeletons[0]), ("shared_counter", skeletons[0]),
+                             ("twice", skeletons[1])] {
+        let entries: Vec<NameEntry> = index.lookup(name).unwrap();
+        assert_eq!(entries.len(), 1, "{name}");
+        assert_eq!(entries[0].unit_offset, skeleton, "{name}");
+        let (unit, _) = names::resolve(&sections, &dwos, None, &entries[0]).unwrap().unwrap();
+        assert!(unit.is_split());
+        assert_eq!(definitions(&sections, &dwos, &index, name).len(), 1, "{name}");
+    }
+    // the split unit cannot be read without its .dwo
+    let entry: NameEntry = index.lookup("twice").unwrap().remove(0);
+    assert!(names::resolve(&sections, &[], None, &entry).unwrap().is_none());
+}
+
+#[test]
+fn unit_macros() {
+    for (name, section, version) in [("macros", ".debug_macro", 5),
+                                     ("macros-macinfo", ".debug_macinfo", 4)] {
+        let elf: Elf = elf::parse(fs::read(data_dir("macros").join(name)).unwrap()).unwrap();
+        let sections: DwarfSections = debug_info::load(&elf);
+        let units: Vec<Unit> = unit::parse_units(&sections).unwrap();
+        let found: UnitMacros = macros::unit_macros(&units[0]).unwrap().unwrap();
+        assert_eq!((found.section, found.offset, found.version), (section, 0, version), "{name}");
+        assert_eq!(found.line_offset, Some(0), "{name}");
+
+        // the predefined macros come first, on line 0
+        let defines: usize = found.entries.iter().take_while(|m| {
+            matches!(m.entry, MacroEntry::Define { line: 0, .. })
+        }).count();
+        assert!(defines > 100, "{name}");
+        assert!(found.entries.iter().all(|m| !matches!(m.entry, MacroEntry::Import { .. })));
+        let rest: Vec<String> = found.entries[defines..].iter().map(|m| match &m.entry {
+            MacroEntry::Define { line, text } => format!("{line}: #define {text}"),
+            MacroEntry::Undef { line, text } => format!("{line}: #undef {text}"),
+            MacroEntry::StartFile { line, file } => format!("{line}: start {file}"),
+            MacroEntry::EndFile => "end".to_string(),
+            entry => format!("{entry:?}"),
+        }).filter(|text| !text.contains("#define _")).collect();
+        // stdc-predef.h only defines names starting with _
+        assert_eq!(rest, ["0: start 1".to_string(), "0: start 2".to_string(), "end".to_string(),
+                          "1: start 3".to_string(),
+                          "1: #define CONFIG_SIZE 16".to_string(),
+                          "2: #define CONFIG_NAME \"sample\"".to_string(), "end".to_string(),
+                          "3: #define SQUARE(x) ((x) * (x))".to_string(),
+                          "4: #define LIMIT 100".to_string(), "5: #undef LIMIT".to_string(),
+                          "6: #define LIMIT 200".to_string(), "end".to_string()], "{name}");
+    }
+
+    // units without -g3 have none
+    let elf: Elf = load("hello.o");
+    let sections: DwarfSections = debug_info::load(&elf);
+    let units: Vec<Unit> = unit::parse_units(&sections).unwrap();
+    assert!(macros::unit_macros(&units[0]).unwrap().is_none());
+}
@@ -0,0 +1,109 @@
+use std::path::PathBuf;
+use std::process::{Command, Output};
+use serde_json::{json, Value};
+
+fn sample(name: &str) -> String {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join("data").join("readelf")
+        .join(name).to_string_lossy().into_owned()
+}
+
+fn dwarf(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_dwarf")).args(args).output().unwrap()
+}
+
+fn document(args: &[&str]) -> Value {
+    let output: Output = dwarf(args);
+    assert_eq!(output.status.code(), Some(0), "{0}", String::from_utf8_lossy(&output.stderr));
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+#[test]
+fn header_and_tables() {
+    let path: String = sample("hello-pie");
+    let doc: Value = document(&["-f", &path, "--format", "json", "-p", "-s", "--symbols",
+                                "--relocs", "--dynamic", "--notes"]);
+    assert_eq!(doc["schema_version"], 1);
+    assert_eq!(doc["file"], path.as_str());
+    assert_eq!(doc["header"]["class"], json!({ "value": 2, "name": "ELF64" }));
+    assert_eq!(doc["header"]["type"], json!({ "value": 3, "name": "DYN" }));
+    assert_eq!(doc["header"]["machine"], json!({ "value": 62, "name": "AMD64" }));
+    assert_eq!(doc["header"]["phnum"], 13);
+    assert_eq!(doc["header"]["shnum"], 31);
+
+    assert_eq!(doc["program_headers"][1], json!({
+        "index": 1, "type": { "value": 3, "name": "INTERP" },
+        "flags": { "value": 4, "names": ["R"] },
+        "offset": 0x318, "vaddr": 0x318, "paddr": 0x318, "filesz": 28, "memsz": 28, "align": 1,
+        "sections": [".interp"],
+    }));
+    assert_eq!(doc["section_headers"][1], json!({
+        "index": 1, "name": ".interp", "type": { "value": 1, "name": "PROGBITS" },
+        "flags": { "value": 2, "names": ["ALLOC"] },
+        "addr": 0x318, "offset": 0x318, "size": 28, "link": 0, "info": 0, "addralign": 1,
+        "entsize": 0,
+    }));
+
+    let main: Vec<&Value> = doc["symbols"].as_array().unwrap().iter()
+                                          .filter(|s| s["name"] == "main").collect();
+    assert_eq!(main, [&json!({
+        "table": ".symtab", "index": 33, "name": "main", "value": 0x1050, "size": 52,
+        "type": { "value": 2, "name": "FUNC" }, "bind": { "value": 1, "name": "GLOBAL" },
+        "visibility": { "value": 0, "name": "DEFAULT" }, "shndx": 15,
+    })]);
+    assert_eq!(doc["relocations"][0]["section"], ".rela.dyn");
+    assert_eq!(doc["relocations"][0]["entries"][0]["type"],
+               json!({ "value": 8, "name": "R_X86_64_RELATIVE" }));
+    assert_eq!(doc["dynamic"][0], json!({
+        "tag": { "value": 1, "name": "DT_NEEDED" }, "value": 41, "string": "libc.so.6",
+    }));
+    assert_eq!(doc["dynamic"][1]["string"], Value::Null);
+    assert!(doc["notes"].as_array().unwrap().contains(&json!({
+        "owner": "GNU", "type": { "value": 3, "name": "NT_GNU_BUILD_ID" },
+        "desc": "e3deeec1d94689471c114db07ec81a8cbec4a415",
+    })));
+}
+
+#[test]
+fn only_the_tables_asked_for() {
+    let doc: Value = document(&["-f", &sample("tiny32"), "--format", "json", "--relocs"]);
+    let mut keys: Vec<&str> = doc.as_object().unwrap().keys().map(|k| k.as_str()).collect();
+    keys.sort();
+    assert_eq!(keys, ["file", "header", "relocations", "schema_version"]);
+    assert_eq!(doc["header"]["class"], json!({ "value": 1, "name": "ELF32" }));
+    assert_eq!(doc["relocations"], json!([]));
+}
+
+#[test]
+fn relocation_addends() {
+    let doc: Value = document(&["-f", &sample("hello.o"), "--format", "json", "--relocs"]);
+    assert_eq!(doc["relocations"][0]["section"], ".rela.text.startup");
+    assert_eq!(doc["relocations"][0]["entries"][0], json!({
+        "offset": 6, "type": { "value": 2, "name": "R_X86_64_PC32" }, "symbol_index": 2,
+        "symbol": ".bss", "addend": -4,
+    }));
+}
+
+#[test]
+fn yaml_matches_json() {
+    let path: String = sample("hello-pie");
+    let json: Value = document(&["-f", &path, "--format", "json", "-p", "-s", "--dynamic"]);
+    let output: Output = dwarf(&["-f", &path, "--format", "yaml", "-p", "-s", "--dynamic"]);
+    assert_eq!(output.status.code(), Some(0));
+    let yaml: Value = serde_yaml::from_slice(&output.stdout).unwrap();
+    assert_eq!(yaml, json);
+}
+
+#[test]
+fn text_only_modes_are_refused() {
+    for (format, mode) in [("json", "--hash"), ("yaml", "--units"), ("sarif", "--ldd"),
+                           ("junit", "--macros"), ("json", "--lint")] {
+        let output: Output = dwarf(&["-f", &sample("hello-pie"), "--format", format, mode]);
+        assert_eq!(output.status.code(), Some(2), "{format} {mode}");
+        assert!(output.stdout.is_empty(), "{format} {mode}");
+        let stderr: String = String::from_utf8_lossy(&output.stderr).into_owned();
+        assert!(stderr.contains(&format!("{mode} has no {format} output, use --format text")),
+                "{stderr}");
+    }
+    let output: Output = dwarf(&["-f", &sample("hello-pie"), "--format", "json", "-x", ".interp"]);
+    assert_eq!(output.status.code(), Some(2));
+}
@@ -0,0 +1,31 @@
+use std::fs;
+use std::path::PathBuf;
+use dwarf::elf::{self, Elf};
+use dwarf::extract::{self, Extract};
+
+fn load(name: &str) -> Elf {
+    let path: PathBuf = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests").join("data").join("readelf").join(name);
+    elf::parse(fs::read(path).unwrap()).unwrap()
+}
+
+#[test]
+fn sections_and_segments() {
+    let elf: Elf = load("tls-static");
+    let tdata: usize = elf.select_sections(".tdata")[0];
+    let bss: usize = elf.select_sections(".bss")[0];
+
+    let extract: Extract = extract::section(&elf, tdata).unwrap();
+    assert_eq!(extract.data, &elf.content[0x2ffc..0x3000]);
+    assert_eq!(extract.zeros, 0);
+    let extract: Extract = extract::section(&elf, bss).unwrap();
+    assert!(extract.data.is_empty());
+    assert_eq!((extract.size(false), extract.size(true)), (0, 8));
+    assert_eq!(extract::section_file_name(&elf, bss), "section-6-bss.bin");
+
+    // the RW PT_LOAD holds .tdata in the file and .bss only in memory
+    let extract: Extract = extract::segment(&elf, 3).unwrap();
+    assert_eq!(extract.data, &elf.content[0x2ffc..0x3000]);
+    assert_eq!((extract.size(false), extract.size(true)), (4, 12));
+    assert!(extract::segment(&elf, elf.pheaders.len()).is_err());
+}
@@ -0,0 +1,86 @@
+use std::fs;
+use std::path::PathBuf;
+use dwarf::elf::{self, Elf};
+use dwarf::hardening::{self, Check, Hardening, Pie, Relro, CHECKS};
+
+fn audit(name: &str) -> Hardening {
+    let path: PathBuf = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests").join("data").join("hardening").join(name);
+    let elf: Elf = elf::parse(fs::read(path).unwrap()).unwrap();
+    hardening::audit(&elf).unwrap()
+}
+
+#[test]
+fn hardened() {
+    let audit: Hardening = audit("hardened");
+    assert_eq!(audit.pie, Pie::Yes);
+    assert!(audit.nx);
+    assert_eq!(audit.relro, Relro::Full);
+    assert!(audit.bind_now);
+    assert!(audit.stack_protector);
+    assert_eq!(audit.fortified, ["__strcpy_chk"]);
+    assert!(audit.ibt && audit.shstk && audit.cet());
+    assert_eq!((audit.rpath.as_deref(), audit.runpath.as_deref()), (None, None));
+    assert!(!audit.textrel);
+    assert!(audit.rwx_segments.is_empty());
+    assert_eq!(hardening::failures(&audit, &CHECKS), []);
+}
+
+#[test]
+fn weak() {
+    let audit: Hardening = audit("weak");
+    assert_eq!(audit.pie, Pie::No);
+    assert!(!audit.nx);
+    assert_eq!(audit.relro, Relro::None);
+    assert!(!audit.stack_protector);
+    assert!(!audit.fortify());
+    assert!(!audit.cet());
+    assert_eq!(audit.rpath.as_deref(), Some("/opt/old/lib"));
+    assert_eq!(audit.runpath, None);
+    assert!(!audit.rwx_segments.is_empty());
+    assert_eq!(hardening::failures(&audit, &CHECKS),
+               [Check::Pie, Check::Nx, Check::PartialRelro, Check::FullRelro,
+                Check::StackProtector, Check::Fortify, Check::NoRpath, Check::Cet,
+                Check::NoRwx]);
+}
+
+#[test]
+fn partial_relro_and_runpath() {
+    let audit: Hardening = audit("partial");
+    assert_eq!(audit.pie, Pie::Yes);
+    assert_eq!(audit.relro, Relro::Partial);
+    assert!(!audit.bind_now);
+    assert_eq!(audit.runpath.as_deref(), Some("$ORIGIN/../lib"));
+    assert_eq!(hardening::failures(&audit, &[Check::PartialRelro, Check::FullRelro,
+                                            Check::NoRpath, Check::NoRunpath]),
+               [Check::FullRelro, Check::NoRunpath]);
+}
+
+#[test]
+fn text_relocations() {
+    let audit: Hardening = audit("libtextrel.so.1");
+    // a library passes the PIE check
+    assert_eq!(audit.pie, Pie::Dso);
+    assert!(audit.textrel);
+    assert_eq!(hardening::failures(&audit, &[Check::Pie, Check::NoTextrel]),
+               [Check::NoTextrel]);
+}
+
+#[test]
+fn check_names() {
+    for check in CHECKS {
+        assert_eq!(Check::parse(check.name()), Some(check));
+    }
+    assert_eq!(Check::parse("full-relro"), Some(Check::FullRelro));
+    assert_eq!(Check::parse("relro"), None);
+    assert_eq!(Check::parse("PIE"), None);
+    assert_eq!(Check::parse(""), None);
+
+    // AArch64 branch protection counts as CET, but only with both parts
+    let bti: Hardening = Hardening {
+        bti: true,
+        ..Default::default()
+    };
+    assert!(!Check::Cet.passes(&bti));
+    assert!(Check::Cet.passes(&Hardening { pac: true, ..bti }));
+}
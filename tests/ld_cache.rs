@@ -0,0 +1,194 @@
+use dwarf::elf_header::Header;
+use dwarf::elf_header::header_enums::{Class, Machine};
+use dwarf::ld_cache::{self, CacheEntry, Format, LdCache};
+use dwarf::ld_cache::{DL_CACHE_HWCAP_EXTENSION, EXTENSION_MAGIC, FLAG_ELF, FLAG_ELF_LIBC6,
+                      FLAG_X8664_LIB64, NEW_MAGIC, OLD_MAGIC};
+
+const LIBC6_X8664: u32 = FLAG_ELF_LIBC6 | FLAG_X8664_LIB64;
+
+fn push_u32(out: &mut Vec<u8>, value: u32) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+// strings of a cache and their offsets from `base`
+struct Strings {
+    base: usize,
+    data: Vec<u8>,
+}
+
+impl Strings {
+    fn add(&mut self, name: &str) -> u32 {
+        let offset: usize = self.base + self.data.len();
+        self.data.extend_from_slice(name.as_bytes());
+        self.data.push(0);
+        offset as u32
+    }
+}
+
+// (flags, soname, path, hwcap)
+type Entry<'a> = (u32, &'a str, &'a str, u64);
+
+// A little endian table of the new format, to be placed at `base` of the
+// file. With `hwcaps` it ends in an extension section naming the
+// glibc-hwcaps subdirectories and the generator.
+fn new_table(base: usize, entries: &[Entry], hwcaps: &[&str]) -> Vec<u8> {
+    let strings_start: usize = 48 + 24 * entries.len();
+    let mut strings: Strings = Strings { base: strings_start, data: Vec::new() };
+    let names: Vec<(u32, u32)> = entries.iter()
+        .map(|(_, key, value, _)| (strings.add(key), strings.add(value))).collect();
+    let subdirs: Vec<u32> = hwcaps.iter().map(|name| strings.add(name)).collect();
+    let generator: &[u8] = b"ldconfig (GNU libc) 2.40";
+    while !strings.data.len().is_multiple_of(4) {
+        strings.data.push(0);
+    }
+    // the extension section, its offsets count from the start of the file
+    let extension: usize = base + strings_start + strings.data.len();
+
+    let mut out: Vec<u8> = NEW_MAGIC.to_vec();
+    push_u32(&mut out, entries.len() as u32);
+    push_u32(&mut out, strings.data.len() as u32);
+    out.extend_from_slice(&[2, 0, 0, 0]);
+    push_u32(&mut out, if hwcaps.is_empty() { 0 } else { extension as u32 });
+    out.extend_from_slice(&[0; 12]);
+    for ((flags, _, _, hwcap), (key, value)) in entries.iter().zip(names) {
+        push_u32(&mut out, *flags);
+        push_u32(&mut out, key);
+        push_u32(&mut out, value);
+        push_u32(&mut out, 0);
+        out.extend_from_slice(&hwcap.to_le_bytes());
+    }
+    out.extend_from_slice(&strings.data);
+    if !hwcaps.is_empty() {
+        let generator_at: usize = extension + 8 + 2 * 16;
+        let hwcaps_at: usize = generator_at + generator.len();
+        push_u32(&mut out, EXTENSION_MAGIC);
+        push_u32(&mut out, 2);
+        for (tag, offset, size) in [(0, generator_at, generator.len()),
+                                    (1, hwcaps_at, 4 * subdirs.len())] {
+            push_u32(&mut out, tag);
+            push_u32(&mut out, 0);
+            push_u32(&mut out, offset as u32);
+            push_u32(&mut out, size as u32);
+        }
+        out.extend_from_slice(generator);
+        for offset in subdirs {
+            push_u32(&mut out, offset);
+        }
+    }
+    out
+}
+
+// A table of the old format, strings follow the entries
+fn old_table(entries: &[Entry]) -> Vec<u8> {
+    let mut strings: Strings = Strings { base: 0, data: Vec::new() };
+    let mut out: Vec<u8> = OLD_MAGIC.to_vec();
+    out.push(0);
+    push_u32(&mut out, entries.len() as u32);
+    for (flags, key, value, _) in entries.iter() {
+        push_u32(&mut out, *flags);
+        push_u32(&mut out, strings.add(key));
+        push_u32(&mut out, strings.add(value));
+    }
+    out.extend_from_slice(&strings.data);
+    out
+}
+
+fn header(class: Class, machine: Machine, flags: u32) -> Header {
+    Header {
+        class,
+        machine,
+        flags,
+        ..Default::default()
+    }
+}
+
+const ENTRIES: [Entry; 4] = [
+    (LIBC6_X8664, "libfoo.so.1", "/usr/lib/glibc-hwcaps/x86-64-v3/libfoo.so.1",
+     DL_CACHE_HWCAP_EXTENSION),
+    (LIBC6_X8664, "libfoo.so.1", "/usr/lib/libfoo.so.1", 0),
+    (LIBC6_X8664, "libc.so.6", "/lib/x86_64-linux-gnu/libc.so.6", 0),
+    (FLAG_ELF_LIBC6, "libc.so.6", "/lib/i386-linux-gnu/libc.so.6", 0),
+];
+
+fn paths<'a>(entries: &[&'a CacheEntry]) -> Vec<&'a str> {
+    entries.iter().map(|e| e.value.as_str()).collect()
+}
+
+#[test]
+fn new_format() {
+    let cache: LdCache = ld_cache::parse(&new_table(0, &ENTRIES, &["x86-64-v3"])).unwrap();
+    assert_eq!(cache.format, Format::New);
+    assert_eq!(cache.generator.as_deref(), Some("ldconfig (GNU libc) 2.40"));
+    assert_eq!(cache.hwcaps, ["x86-64-v3"]);
+    assert_eq!(cache.entries.len(), 4);
+    assert_eq!(cache.entries[0].hwcaps.as_deref(), Some("x86-64-v3"));
+    assert_eq!(cache.entries[1].hwcaps, None);
+    assert_eq!(cache.entries[2].key, "libc.so.6");
+    assert_eq!(cache.entries[2].describe(), "libc6,x86-64");
+    assert_eq!(cache.entries[3].describe(), "libc6");
+
+    let amd64: Header = header(Class::X64Bit, Machine::AMD64, 0);
+    let i386: Header = header(Class::X32Bit, Machine::X86, 0);
+    let x32: Header = header(Class::X32Bit, Machine::AMD64, 0);
+    assert_eq!(paths(&cache.lookup("libc.so.6", &amd64)), ["/lib/x86_64-linux-gnu/libc.so.6"]);
+    assert_eq!(paths(&cache.lookup("libc.so.6", &i386)), ["/lib/i386-linux-gnu/libc.so.6"]);
+    assert!(cache.lookup("libc.so.6", &x32).is_empty());
+    assert!(cache.lookup("libbar.so.1", &amd64).is_empty());
+    // the library outside glibc-hwcaps comes first
+    assert_eq!(paths(&cache.lookup("libfoo.so.1", &amd64)),
+               ["/usr/lib/libfoo.so.1", "/usr/lib/glibc-hwcaps/x86-64-v3/libfoo.so.1"]);
+    assert_eq!(cache.paths(&amd64)["libfoo.so.1"].len(), 2);
+    assert!(!cache.paths(&amd64).contains_key("libbar.so.1"));
+
+    // without the extension section
+    let cache: LdCache = ld_cache::parse(&new_table(0, &ENTRIES[1..], &[])).unwrap();
+    assert_eq!((cache.generator, cache.hwcaps.len(), cache.entries.len()), (None, 0, 3));
+}
+
+#[test]
+fn old_and_compat_formats() {
+    let old: Vec<u8> = old_table(&ENTRIES[1..]);
+    let cache: LdCache = ld_cache::parse(&old).unwrap();
+    assert_eq!(cache.format, Format::Old);
+    let names: Vec<(&str, &str)> = cache.entries.iter()
+        .map(|e| (e.key.as_str(), e.value.as_str())).collect();
+    assert_eq!(names, [("libfoo.so.1", "/usr/lib/libfoo.so.1"),
+                       ("libc.so.6", "/lib/x86_64-linux-gnu/libc.so.6"),
+                       ("libc.so.6", "/lib/i386-linux-gnu/libc.so.6")]);
+    let amd64: Header = header(Class::X64Bit, Machine::AMD64, 0);
+    assert_eq!(paths(&cache.lookup("libc.so.6", &amd64)), ["/lib/x86_64-linux-gnu/libc.so.6"]);
+
+    // the new table behind the old one, at the next multiple of 8, wins
+    let old_entry: Entry = (FLAG_ELF_LIBC6, "libold.so.1", "/lib/libold.so.1", 0);
+    let mut compat: Vec<u8> = old_table(&[old_entry]);
+    compat.truncate(16 + 12);
+    compat.resize(32, 0);
+    compat.extend_from_slice(&new_table(32, &ENTRIES, &["x86-64-v3"]));
+    let cache: LdCache = ld_cache::parse(&compat).unwrap();
+    assert_eq!(cache.format, Format::Compat);
+    assert_eq!(cache.entries, ld_cache::parse(&new_table(0, &ENTRIES, &["x86-64-v3"]))
+                                  .unwrap().entries);
+    assert_eq!(cache.generator.as_deref(), Some("ldconfig (GNU libc) 2.40"));
+
+    assert_eq!(ld_cache::parse(b"not a cache"), Err("Not an ld.so.cache file."));
+    let mut truncated: Vec<u8> = new_table(0, &ENTRIES, &[]);
+    truncated.truncate(48 + 24);
+    assert!(ld_cache::parse(&truncated).is_err());
+}
+
+#[test]
+fn accepted_flags() {
+    let flags = |class: Class, machine: Machine, e_flags: u32| -> Vec<u32> {
+        ld_cache::accepted_flags(&header(class, machine, e_flags))
+    };
+    assert_eq!(flags(Class::X64Bit, Machine::AMD64, 0),
+               [LIBC6_X8664, FLAG_ELF | FLAG_X8664_LIB64]);
+    assert_eq!(flags(Class::X32Bit, Machine::AMD64, 0), [0x0803, 0x0801]);
+    assert_eq!(flags(Class::X32Bit, Machine::X86, 0), [FLAG_ELF_LIBC6, FLAG_ELF]);
+    assert_eq!(flags(Class::X64Bit, Machine::AARCH64, 0), [0x0a03, 0x0a01]);
+    // EF_ARM_ABI_FLOAT_HARD picks hard-float libraries
+    assert_eq!(flags(Class::X32Bit, Machine::ARM, 0x400), [0x0903, 0x0901]);
+    assert_eq!(flags(Class::X32Bit, Machine::ARM, 0), [0x0b03, 0x0b01]);
+    // 32 bit MIPS takes both NaN encodings
+    assert_eq!(flags(Class::X32Bit, Machine::MIPS, 0), [0x0003, 0x0001, 0x0c03, 0x0c01]);
+}
@@ -0,0 +1,49 @@
+use std::fs;
+use std::path::PathBuf;
+use dwarf::elf::{self, Elf};
+use dwarf::mapping;
+
+fn data_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join("data").join("readelf")
+}
+
+fn load(name: &str) -> Elf {
+    elf::parse(fs::read(data_dir().join(name)).unwrap()).unwrap()
+}
+
+// the section names of each segment in what readelf -l printed
+fn readelf_mapping(name: &str) -> Vec<Vec<String>> {
+    let text: String = fs::read_to_string(data_dir().join(format!("{name}.l"))).unwrap();
+    text.lines().skip_while(|l| !l.contains("Section to Segment mapping")).skip(2)
+        .map(|l| l.split_whitespace().skip(1).map(String::from).collect())
+        .collect()
+}
+
+#[test]
+fn same_as_readelf() {
+    for name in ["hello-pie", "tls-static", "tiny32"] {
+        let elf: Elf = load(name);
+        let mapping: Vec<Vec<String>> = mapping::segment_sections(&elf).iter().map(|sections| {
+            sections.iter().map(|idx| elf.sheaders[*idx].sname.clone()).collect()
+        }).collect();
+        assert_eq!(mapping, readelf_mapping(name), "{name}");
+    }
+}
+
+#[test]
+fn segments_of_a_section() {
+    let elf: Elf = load("tls-static");
+    let index = |name: &str| elf.select_sections(name)[0];
+    assert_eq!(mapping::section_segments(&elf, index(".tdata")), [3, 5, 7]);
+    assert_eq!(mapping::section_segments(&elf, index(".tbss")), [5]);
+    assert_eq!(mapping::section_segments(&elf, index(".bss")), [3]);
+    assert_eq!(mapping::section_segments(&elf, index(".note.gnu.build-id")), [0, 4]);
+    assert!(mapping::section_segments(&elf, index(".symtab")).is_empty());
+    assert!(mapping::section_segments(&elf, 0).is_empty());
+    assert!(mapping::section_segments(&elf, elf.sheaders.len()).is_empty());
+
+    // a relocatable object has no segments
+    let object: Elf = load("hello.o");
+    assert!(mapping::segment_sections(&object).is_empty());
+    assert!(mapping::section_segments(&object, 1).is_empty());
+}
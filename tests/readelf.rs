@@ -0,0 +1,71 @@
+use std::fs;
+use std::path::PathBuf;
+use dwarf::elf::{self, Elf};
+use dwarf::readelf;
+
+const SAMPLES: [&str; 4] = ["hello-pie", "hello.o", "tls-static", "tiny32"];
+
+fn data_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join("data").join("readelf")
+}
+
+fn load(name: &str) -> Elf {
+    let content: Vec<u8> = fs::read(data_dir().join(name)).unwrap();
+    elf::parse(content).unwrap()
+}
+
+fn expected(name: &str, options: &str) -> String {
+    fs::read_to_string(data_dir().join(format!("{name}.{options}"))).unwrap()
+}
+
+#[test]
+fn header_program_and_section_headers() {
+    for name in SAMPLES.iter() {
+        let elf: Elf = load(name);
+        assert_eq!(readelf::render(&elf, true, true, true), expected(name, "hlS"), "{name}");
+    }
+}
+
+#[test]
+fn program_headers_alone() {
+    for name in SAMPLES.iter() {
+        let elf: Elf = load(name);
+        assert_eq!(readelf::render(&elf, false, false, true), expected(name, "l"), "{name}");
+    }
+}
+
+#[test]
+fn section_headers_alone() {
+    for name in SAMPLES.iter() {
+        let elf: Elf = load(name);
+        assert_eq!(readelf::render(&elf, false, true, false), expected(name, "S"), "{name}");
+    }
+}
+
+#[test]
+fn hex_and_string_dumps() {
+    let elf: Elf = load("hello.o");
+    let mut dump: Vec<u8> = Vec::new();
+    for idx in 0..elf.sheaders.len() {
+        let name: &str = &elf.sheaders[idx].sname;
+        if [".data", ".text.startup", ".bss"].contains(&name) {
+            dump.extend(readelf::hex_dump(&elf, idx).unwrap().into_bytes());
+        }
+        if [".rodata.str1.1", ".debug_str"].contains(&name) {
+            dump.extend(readelf::string_dump(&elf, idx).unwrap());
+        }
+    }
+    assert_eq!(String::from_utf8(dump).unwrap(), expected("hello.o", "xp"));
+}
+
+#[test]
+fn header_offsets_near_the_end_of_the_address_space() {
+    let content: Vec<u8> = fs::read(data_dir().join("hello-pie")).unwrap();
+    // e_phoff, e_shoff and the sh_offset of .shstrtab
+    let shstrtab: usize = 0x3700 + 30 * 0x40 + 0x18;
+    for at in [0x20, 0x28, shstrtab] {
+        let mut crafted: Vec<u8> = content.clone();
+        crafted[at..at + 8].copy_from_slice(&0xfffffffffffffff0u64.to_le_bytes());
+        assert!(elf::parse(crafted).is_err(), "{at:#x}");
+    }
+}
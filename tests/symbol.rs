@@ -0,0 +1,70 @@
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+use dwarf::elf::{self, Elf};
+use dwarf::section_header::shdr_enums::SType;
+use dwarf::symbol::{self, Symbol, SymbolTable};
+
+fn path(dir: &str, name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join("data").join(dir).join(name)
+}
+
+fn load(dir: &str, name: &str) -> Elf {
+    elf::parse(fs::read(path(dir, name)).unwrap()).unwrap()
+}
+
+#[test]
+fn embedded_symbol_table() {
+    let elf: Elf = load("minidebuginfo", "mini");
+    assert!(elf.sheaders.iter().all(|s| s.stype != SType::SYMTAB));
+    let mini: Elf = symbol::mini_debuginfo(&elf).unwrap().unwrap();
+    let symtab = mini.sheaders.iter().find(|s| s.stype == SType::SYMTAB).unwrap();
+    let names: Vec<String> = symbol::parse(&mini, symtab, ".symtab").unwrap()
+                                   .into_iter().map(|s| s.name).collect();
+    assert!(names.iter().any(|n| n == "step"));
+    assert!(names.iter().any(|n| n == "compute"));
+
+    // nothing to decompress in binaries without MiniDebugInfo
+    assert!(symbol::mini_debuginfo(&load("readelf", "hello-pie")).unwrap().is_none());
+}
+
+#[test]
+fn lookups_in_stripped_binaries() {
+    let table: SymbolTable = SymbolTable::load(&load("minidebuginfo", "mini")).unwrap();
+    let (step, offset): (&Symbol, u64) = table.lookup(0x1165).unwrap();
+    assert_eq!((step.name.as_str(), offset, step.table), ("step", 5, ".gnu_debugdata"));
+    let (compute, offset): (&Symbol, u64) = table.lookup(0x1190).unwrap();
+    assert_eq!((compute.name.as_str(), offset), ("compute", 0x10));
+    assert_eq!(table.by_name("main").len(), 1);
+    assert_eq!(table.by_name("main")[0].value, 0x1050);
+    assert!(table.lookup(0x10).is_none());
+
+    // an unstripped binary has no symbol twice
+    let table: SymbolTable = SymbolTable::load(&load("readelf", "hello-pie")).unwrap();
+    assert_eq!(table.by_name("main").len(), 1);
+}
+
+#[test]
+fn corrupt_embedded_file() {
+    let mut content: Vec<u8> = fs::read(path("minidebuginfo", "mini")).unwrap();
+    let elf: Elf = elf::parse(content.clone()).unwrap();
+    let offset: usize = elf.section(".gnu_debugdata").unwrap().offset as usize;
+    // past the xz stream header, into the compressed block
+    content[offset + 0x40] ^= 0xff;
+    let elf: Elf = elf::parse(content).unwrap();
+    assert!(symbol::mini_debuginfo(&elf).is_err());
+    assert!(SymbolTable::load(&elf).is_err());
+}
+
+#[test]
+fn symbol_mode() {
+    let output: Output = Command::new(env!("CARGO_BIN_EXE_dwarf"))
+        .args(["-f", path("minidebuginfo", "mini").to_str().unwrap(),
+               "--symbol", "0x1165", "--symbol", "0x1180", "--symbol", "0x10"])
+        .output().unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    let stdout: String = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.ends_with("0x1165: step+0x5 [.gnu_debugdata]\n\
+                              0x1180: compute [.gnu_debugdata]\n\
+                              0x10: ??\n"), "{stdout}");
+}